@@ -0,0 +1,61 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Benchmarks of the hot paths of the estimation: the search of code
+//! parameters, the frontier of the elliptic curve discrete logarithm, and the
+//! counting of the Q# adder.
+
+use std::rc::Rc;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use qsharp_alice_bob_resource_estimator::{
+    testing, CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+use resource_estimator::estimates::{ErrorCorrection, PhysicalResourceEstimation};
+
+fn compute_code_parameter(c: &mut Criterion) {
+    let qubit = CatQubit::new();
+    c.bench_function("compute_code_parameter", |b| {
+        b.iter(|| {
+            // a new code for each search, not to measure the cache only
+            RepetitionCode::new().compute_code_parameter(&qubit, 1e-12)
+        });
+    });
+    // the same code for every search, as in a frontier
+    let code = RepetitionCode::new();
+    c.bench_function("compute_code_parameter_cached", |b| {
+        b.iter(|| code.compute_code_parameter(&qubit, 1e-12));
+    });
+}
+
+fn build_frontier(c: &mut Criterion) {
+    let estimation = PhysicalResourceEstimation::new(
+        RepetitionCode::new(),
+        Rc::new(CatQubit::new()),
+        ToffoliBuilder::default(),
+        Rc::new(testing::ecc_256_counts()),
+        testing::ecc_256_budget(),
+    );
+    let mut group = c.benchmark_group("ecc_256");
+    group.sample_size(10);
+    group.bench_function("build_frontier", |b| b.iter(|| estimation.build_frontier()));
+    group.finish();
+}
+
+fn count_qsharp(c: &mut Criterion) {
+    let path = testing::adder_path();
+    let mut group = c.benchmark_group("adder");
+    group.sample_size(10);
+    group.bench_function("count_qsharp", |b| {
+        b.iter(|| LogicalCounts::from_qsharp(&path));
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    compute_code_parameter,
+    build_frontier,
+    count_qsharp
+);
+criterion_main!(benches);
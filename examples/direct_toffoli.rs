@@ -0,0 +1,67 @@
+// Copyright (c) Alice & Bob
+// Licensed under the MIT License.
+
+#![warn(missing_docs)]
+//! Compare the execution of the Toffoli gates with magic state factories and
+//! directly on the logical qubits, on the same logical counts.
+//!
+//! Both estimates are reported with the same accounting: the vertical routing
+//! qubits of the layout of arXiv:2302.06639 are added to the physical qubits,
+//! the direct gates taking no factory in the row.
+
+use std::rc::Rc;
+
+use qsharp_alice_bob_resource_estimator::{
+    factories::direct::DirectToffoliBuilder,
+    layout::{LayoutPolicy, PaperLayout},
+    units::TimeSpan,
+    AliceAndBobEstimates, CatQubit, LogicalCounts, RepetitionCode, TimeUnit, ToffoliBuilder,
+};
+use resource_estimator::estimates::{ErrorBudget, Overhead, PhysicalResourceEstimation};
+
+/// Print the resources of one approach.
+fn report(approach: &str, physical_qubits: u64, runtime: TimeSpan, code_distance: u64) {
+    println!("{approach}:");
+    println!("  physical qubits: {physical_qubits}");
+    println!("  runtime:         {}", runtime.format(TimeUnit::default()));
+    println!("  code distance:   {code_distance}");
+}
+
+/// Estimate resources with both approaches.
+fn main() -> Result<(), anyhow::Error> {
+    let qubit = Rc::new(CatQubit::new());
+    let count = Rc::new(LogicalCounts::new(1000, 1_000_000, 100_000));
+    let budget = ErrorBudget::new(0.333 * 0.5, 0.333 * 0.5, 0.0);
+
+    let estimation = PhysicalResourceEstimation::new(
+        RepetitionCode::new(),
+        qubit.clone(),
+        ToffoliBuilder::default(),
+        count.clone(),
+        budget,
+    );
+    let result = AliceAndBobEstimates::estimate(&estimation).map_err(anyhow::Error::msg)?;
+    report(
+        "With Toffoli magic state factories",
+        result.physical_qubits(),
+        result.runtime_duration(),
+        result.logical_patch().code_parameter().distance(),
+    );
+
+    let estimation = PhysicalResourceEstimation::new(
+        RepetitionCode::new(),
+        qubit,
+        DirectToffoliBuilder::default(),
+        count.clone(),
+        budget,
+    );
+    let result = estimation.estimate()?;
+    report(
+        "With direct Toffoli gates",
+        result.physical_qubits() + PaperLayout.vertical_routing_qubits(count.logical_qubits(), 0),
+        TimeSpan::from_ns(result.runtime()),
+        result.logical_patch().code_parameter().distance(),
+    );
+
+    Ok(())
+}
@@ -25,7 +25,7 @@ fn main() -> Result<(), anyhow::Error> {
 
     let estimation =
         PhysicalResourceEstimation::new(qec, Rc::new(qubit), builder, Rc::new(count), budget);
-    let result: AliceAndBobEstimates = estimation.estimate()?.into();
+    let result = AliceAndBobEstimates::estimate(&estimation).map_err(anyhow::Error::msg)?;
     println!("Resource estimate from Q# code (ripple-carry adder):");
     println!("{result}");
 
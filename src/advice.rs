@@ -0,0 +1,298 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Suggestions to reduce the resources of an algorithm.
+//!
+//! The estimate is recomputed with each input changed by a factor 2 (half the
+//! CCX, half the CX, half the logical qubits, twice the error budget), which
+//! gives the sensitivity of the physical qubits and runtime to this input,
+//! code distance changes included. The [`Advice`] ranks these changes by the
+//! runtime or qubits they save, with the share of the logical depth due to each
+//! gate and the share of the qubits used by the factories.
+
+use std::fmt::Display;
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::ErrorBudget;
+
+use crate::{
+    counter::{CCX_CYCLES, CX_CYCLES},
+    scan::estimate,
+    CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+
+/// Share of the factories in the physical qubits (in %) above which the
+/// estimate is considered factory-bound.
+const FACTORY_BOUND_PERCENT: f64 = 30.0;
+
+/// Effect of changing one input of the estimation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sensitivity {
+    /// Changed input.
+    pub change: &'static str,
+    /// Factor applied to the input.
+    pub factor: f64,
+    /// Ratio of the runtime before and after the change (above 1 when the
+    /// change shortens the runtime).
+    pub runtime_gain: f64,
+    /// Ratio of the physical qubits before and after the change.
+    pub qubits_gain: f64,
+}
+
+impl Sensitivity {
+    /// Elasticity of the runtime, d ln(runtime) / d ln(input), estimated from
+    /// the change of the input.
+    #[must_use]
+    pub fn runtime_elasticity(&self) -> f64 {
+        -self.runtime_gain.ln() / self.factor.ln()
+    }
+
+    /// Elasticity of the physical qubits, d ln(qubits) / d ln(input).
+    #[must_use]
+    pub fn qubits_elasticity(&self) -> f64 {
+        -self.qubits_gain.ln() / self.factor.ln()
+    }
+}
+
+/// Analysis of an estimate, with the sensitivities to its inputs.
+pub struct Advice {
+    /// Share of the logical depth due to the CCX gates, in %.
+    pub ccx_depth_percent: f64,
+    /// Share of the physical qubits used by the factories, in %.
+    pub factory_percent: f64,
+    /// Sensitivities, from the largest runtime gain to the smallest.
+    pub sensitivities: Vec<Sensitivity>,
+}
+
+/// Ratio of two quantities of the estimates.
+fn gain(before: u64, after: u64) -> f64 {
+    match (before.to_f64(), after.to_f64()) {
+        (Some(before), Some(after)) if after > 0.0 => before / after,
+        _ => 1.0,
+    }
+}
+
+impl Advice {
+    /// Analyse the estimate of `counts`, by estimating again with each input
+    /// changed by a factor 2.
+    #[allow(clippy::similar_names)]
+    pub fn new(
+        qubit: &CatQubit,
+        code: &RepetitionCode,
+        builder: &ToffoliBuilder,
+        counts: &LogicalCounts,
+        budget: &ErrorBudget,
+    ) -> Result<Self, String> {
+        let baseline = estimate(qubit, code, builder, counts.clone(), budget)?;
+
+        let sensitivity = |change, factor, counts, budget: &ErrorBudget| {
+            let altered = estimate(qubit, code, builder, counts, budget)?;
+            Ok::<_, String>(Sensitivity {
+                change,
+                factor,
+                runtime_gain: gain(baseline.runtime(), altered.runtime()),
+                qubits_gain: gain(baseline.physical_qubits(), altered.physical_qubits()),
+            })
+        };
+
+        let half = |count: u64| count.div_ceil(2);
+        let (qubits, cx, ccx) = (counts.qubit_count, counts.cx_count, counts.ccx_count);
+        let mut sensitivities = vec![];
+        for (change, halved) in [
+            ("half the CCX", (qubits, cx, half(ccx))),
+            ("half the CX", (qubits, half(cx), ccx)),
+            ("half the logical qubits", (half(qubits), cx, ccx)),
+        ] {
+            // an input may already be at its minimum (e.g. no CX)
+            if halved != (qubits, cx, ccx) {
+                let (qubits, cx, ccx) = halved;
+                // a depth given by the user is kept, only the factories change
+                let altered = counts.with_counts(qubits, cx, ccx);
+                sensitivities.push(sensitivity(change, 0.5, altered, budget)?);
+            }
+        }
+        let doubled = ErrorBudget::new(
+            budget.logical() * 2.0,
+            budget.magic_states() * 2.0,
+            budget.rotations() * 2.0,
+        );
+        sensitivities.push(sensitivity(
+            "twice the error budget",
+            2.0,
+            counts.clone(),
+            &doubled,
+        )?);
+        sensitivities.sort_by(|a, b| b.runtime_gain.total_cmp(&a.runtime_gain));
+
+        let cx_depth = cx.to_f64().unwrap_or_default() * CX_CYCLES;
+        let ccx_depth = ccx.to_f64().unwrap_or_default() * CCX_CYCLES;
+        let ccx_depth_percent = if cx_depth + ccx_depth > 0.0 {
+            100.0 * ccx_depth / (cx_depth + ccx_depth)
+        } else {
+            0.0
+        };
+
+        Ok(Self {
+            ccx_depth_percent,
+            factory_percent: baseline.factory_fraction(),
+            sensitivities,
+        })
+    }
+
+    /// Sensitivity to a change, if computed.
+    #[must_use]
+    pub fn sensitivity(&self, change: &str) -> Option<&Sensitivity> {
+        self.sensitivities.iter().find(|s| s.change == change)
+    }
+
+    /// Concrete suggestions, from the most to the least effective.
+    #[must_use]
+    pub fn suggestions(&self) -> Vec<String> {
+        let mut suggestions = vec![];
+
+        let (dominant, change) = if self.ccx_depth_percent >= 50.0 {
+            ("CCX", "half the CCX")
+        } else {
+            ("CX", "half the CX")
+        };
+        let share = if dominant == "CCX" {
+            self.ccx_depth_percent
+        } else {
+            100.0 - self.ccx_depth_percent
+        };
+        if let Some(sensitivity) = self.sensitivity(change) {
+            suggestions.push(format!(
+                "{dominant}-dominated ({share:.0}% of the logical depth): reducing the {dominant} \
+                 gates by 2x would cut the runtime {:.2}x and the qubits {:.2}x",
+                sensitivity.runtime_gain, sensitivity.qubits_gain
+            ));
+        }
+
+        if self.factory_percent >= FACTORY_BOUND_PERCENT {
+            let suggestion = format!(
+                "factory-bound: factories use {:.0}% of the physical qubits, giving a larger \
+                 share of the error budget to the magic states allows smaller factories",
+                self.factory_percent
+            );
+            suggestions.push(match self.sensitivity("half the CCX") {
+                Some(sensitivity) => format!(
+                    "{suggestion}, and halving the CCX gates would cut the qubits {:.2}x",
+                    sensitivity.qubits_gain
+                ),
+                None => suggestion,
+            });
+        }
+
+        if let Some(sensitivity) = self.sensitivity("half the logical qubits") {
+            suggestions.push(format!(
+                "halving the logical qubits would cut the physical qubits {:.2}x",
+                sensitivity.qubits_gain
+            ));
+        }
+
+        if let Some(sensitivity) = self.sensitivity("twice the error budget") {
+            if sensitivity.qubits_gain < 1.05 && sensitivity.runtime_gain < 1.05 {
+                suggestions.push(
+                    "the estimate is insensitive to the error budget, tightening it is cheap"
+                        .into(),
+                );
+            } else {
+                suggestions.push(format!(
+                    "doubling the error budget would cut the qubits {:.2}x and the runtime {:.2}x",
+                    sensitivity.qubits_gain, sensitivity.runtime_gain
+                ));
+            }
+        }
+
+        suggestions
+    }
+}
+
+impl Display for Advice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Suggestions:")?;
+        for suggestion in self.suggestions() {
+            writeln!(f, "- {suggestion}")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Sensitivities (runtime gain, qubits gain):")?;
+        for sensitivity in &self.sensitivities {
+            writeln!(
+                f,
+                "  {:<24}{:>6.2}x {:>6.2}x",
+                sensitivity.change, sensitivity.runtime_gain, sensitivity.qubits_gain
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gain, Advice, Sensitivity};
+
+    fn sensitivity(change: &'static str, factor: f64, runtime_gain: f64) -> Sensitivity {
+        Sensitivity {
+            change,
+            factor,
+            runtime_gain,
+            qubits_gain: 1.0,
+        }
+    }
+
+    fn advice(ccx_depth_percent: f64, factory_percent: f64) -> Advice {
+        Advice {
+            ccx_depth_percent,
+            factory_percent,
+            sensitivities: vec![
+                sensitivity("half the CCX", 0.5, 1.8),
+                sensitivity("half the CX", 0.5, 1.1),
+                sensitivity("twice the error budget", 2.0, 1.01),
+            ],
+        }
+    }
+
+    #[test]
+    fn gains_are_ratios_of_the_resources() {
+        assert!((gain(200, 100) - 2.0).abs() < 1e-12);
+        assert!((gain(100, 200) - 0.5).abs() < 1e-12);
+        // nothing left to compare with
+        assert!((gain(100, 0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn elasticities_follow_the_factor() {
+        // halving the CCX halves the runtime: elasticity 1
+        let halved = sensitivity("half the CCX", 0.5, 2.0);
+        assert!((halved.runtime_elasticity() - 1.0).abs() < 1e-12);
+        assert!(halved.qubits_elasticity().abs() < 1e-12);
+        // doubling the budget shortens the runtime by 1.25x
+        let doubled = sensitivity("twice the error budget", 2.0, 1.25);
+        assert!((doubled.runtime_elasticity() + 1.25_f64.log2()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn suggestions_follow_the_dominant_gate() {
+        let suggestions = advice(80.0, 10.0).suggestions();
+        assert!(suggestions[0].starts_with("CCX-dominated (80% of the logical depth)"));
+        assert!(suggestions[0].contains("runtime 1.80x"));
+        assert!(!suggestions.iter().any(|s| s.starts_with("factory-bound")));
+        assert!(suggestions
+            .last()
+            .is_some_and(|s| s.contains("insensitive to the error budget")));
+
+        let suggestions = advice(20.0, 40.0).suggestions();
+        assert!(suggestions[0].starts_with("CX-dominated (80% of the logical depth)"));
+        assert!(suggestions[1].starts_with("factory-bound: factories use 40%"));
+    }
+
+    #[test]
+    fn sensitivities_are_found_by_change() {
+        let advice = advice(50.0, 0.0);
+        assert!(advice.sensitivity("half the CX").is_some());
+        assert!(advice.sensitivity("half the logical qubits").is_none());
+        assert!(advice
+            .to_string()
+            .contains("  half the CCX              1.80x   1.00x"));
+    }
+}
@@ -0,0 +1,545 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Validation of error budgets.
+//!
+//! [`ErrorBudget`] accepts any value, while a budget above 1, negative or
+//! without room for the topological or magic state errors makes the search of
+//! code parameters fail with obscure messages (or succeed with meaningless
+//! estimates). The functions of this module build budgets from user input and
+//! reject such values with messages telling what to change.
+//!
+//! The generic rotations of Q# programs are synthesized into T gates, each
+//! one failing with the precision of the synthesis: [`charge_rotation_synthesis`]
+//! charges this error to the rotation part of the budget, taking it from the
+//! topological part when no rotation budget is given. Every estimate charges
+//! its counts through [`budget_for_counts`].
+//!
+//! When only a total error target is given, [`optimal_error_budget`] searches
+//! the split between the topological and magic state errors which minimizes a
+//! cost, e.g. the space-time volume of the estimate.
+//!
+//! When several types of magic states are consumed (e.g. Toffoli and T states),
+//! [`split_magic_state_budget`] shares the magic state part of the budget
+//! between them, instead of giving each type the same share.
+//!
+//! Once estimated, [`BudgetConsumption`] tells how much of each component of
+//! the budget the chosen parameters actually use, the discrete code distances
+//! and factories leaving some slack that could be given to the other
+//! component. Conversely, [`BudgetExcess`] flags the estimates whose total
+//! error lands above the budget (e.g. after rounding), with the factor by which
+//! to tighten the budget.
+
+use std::fmt::Display;
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::ErrorBudget;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    optimize::{Dimension, GridRefine, SearchSpace},
+    LogicalCounts,
+};
+
+/// Parse a probability, in decimal (`0.001`) or scientific (`1e-3`) notation.
+///
+/// Values too small to be represented as a positive `f64` (e.g. `1e-400`)
+/// are rejected rather than silently rounded to 0.
+pub fn parse_probability(s: &str) -> Result<f64, String> {
+    let value: f64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{s}' is not a probability (e.g. 0.001 or 1e-3)"))?;
+    let mantissa = s.split(['e', 'E']).next().unwrap_or_default();
+    if value == 0.0 && mantissa.chars().any(|c| ('1'..='9').contains(&c)) {
+        return Err(format!(
+            "'{s}' is too small to be represented (smallest positive value is {:e})",
+            f64::MIN_POSITIVE
+        ));
+    }
+    if !(0.0..=1.0).contains(&value) {
+        return Err(format!(
+            "'{s}' is not a probability (expected between 0 and 1)"
+        ));
+    }
+    Ok(value)
+}
+
+/// Error budget with the given probabilities, checked for consistency.
+///
+/// The topological and magic state parts must be positive and the parts must
+/// sum to at most 1.
+pub fn error_budget(
+    logical: f64,
+    magic_states: f64,
+    rotations: f64,
+) -> Result<ErrorBudget, String> {
+    for (name, value) in [
+        ("topological", logical),
+        ("magic state", magic_states),
+        ("rotation", rotations),
+    ] {
+        if !(0.0..=1.0).contains(&value) {
+            return Err(format!(
+                "{name} error budget {value:e} is not a probability (expected between 0 and 1)"
+            ));
+        }
+    }
+    if logical == 0.0 {
+        return Err("topological error budget is 0, no code can reach it (give e.g. 1e-3)".into());
+    }
+    if magic_states == 0.0 {
+        return Err(
+            "magic state error budget is 0, no factory can reach it (give e.g. 1e-3)".into(),
+        );
+    }
+    let total = logical + magic_states + rotations;
+    if total > 1.0 {
+        return Err(format!(
+            "error budget sums to {total:.3} > 1 (the parts are probabilities of failure of the \
+             same run, lower them)"
+        ));
+    }
+    Ok(ErrorBudget::new(logical, magic_states, rotations))
+}
+
+/// Error budget of an estimate of `counts`: `budget` charged with the error
+/// of the synthesis of their rotations, see [`charge_rotation_synthesis`].
+pub fn budget_for_counts(
+    budget: &ErrorBudget,
+    counts: &LogicalCounts,
+) -> Result<ErrorBudget, String> {
+    charge_rotation_synthesis(budget, counts.rotation_synthesis_error())
+}
+
+/// Error budget covering the error `synthesis_error` of the synthesis of the
+/// rotations (see
+/// [`LogicalCounts::rotation_synthesis_error`](crate::LogicalCounts::rotation_synthesis_error))
+/// with its
+/// rotation part.
+///
+/// A budget without rotation part gives the synthesis error from its
+/// topological part, while a rotation part smaller than the synthesis error
+/// is rejected (the precision of the synthesis must be raised).
+pub fn charge_rotation_synthesis(
+    budget: &ErrorBudget,
+    synthesis_error: f64,
+) -> Result<ErrorBudget, String> {
+    let (logical, magic_states, rotations) =
+        (budget.logical(), budget.magic_states(), budget.rotations());
+    if synthesis_error <= rotations {
+        if synthesis_error == 0.0 && rotations > 0.0 {
+            tracing::warn!(
+                rotations,
+                "no rotation is synthesized, the rotation error budget is unused (give 0 to \
+                 spend it on the topological and magic state errors)"
+            );
+        }
+        return Ok(ErrorBudget::new(logical, magic_states, rotations));
+    }
+    if rotations > 0.0 {
+        return Err(format!(
+            "rotation synthesis error {synthesis_error:.3e} exceeds the rotation error budget \
+             {rotations:.3e} (lower --rotation-precision or raise the rotation budget)"
+        ));
+    }
+    if synthesis_error >= logical {
+        return Err(format!(
+            "rotation synthesis error {synthesis_error:.3e} exceeds the topological error budget \
+             {logical:.3e} (lower --rotation-precision)"
+        ));
+    }
+    Ok(ErrorBudget::new(
+        logical - synthesis_error,
+        magic_states,
+        synthesis_error,
+    ))
+}
+
+/// Error budget splitting `total` equally between the topological and magic
+/// state errors.
+pub fn split_error_budget(total: f64) -> Result<ErrorBudget, String> {
+    fractional_error_budget(total, 0.5, 0.0)
+}
+
+/// Error budget giving the fraction `magic_fraction` of `total` to the magic
+/// state errors, the fraction `rotation_fraction` to the rotations, and the
+/// rest to the topological errors.
+pub fn fractional_error_budget(
+    total: f64,
+    magic_fraction: f64,
+    rotation_fraction: f64,
+) -> Result<ErrorBudget, String> {
+    for (name, fraction) in [("magic", magic_fraction), ("rotation", rotation_fraction)] {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(format!("{name} fraction {fraction} is not between 0 and 1"));
+        }
+    }
+    let logical_fraction = 1.0 - magic_fraction - rotation_fraction;
+    if logical_fraction <= 0.0 {
+        return Err(format!(
+            "magic and rotation fractions sum to {}, leaving nothing for the topological errors",
+            magic_fraction + rotation_fraction
+        ));
+    }
+    error_budget(
+        total * logical_fraction,
+        total * magic_fraction,
+        total * rotation_fraction,
+    )
+    .map_err(|err| format!("total error budget {total:e}: {err}"))
+}
+
+/// Errors reached by an estimate, compared with the components of its error
+/// budget.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BudgetConsumption {
+    /// Budget of the topological errors.
+    pub topological_budget: f64,
+    /// Topological error of the estimate (computation part).
+    pub topological_error: f64,
+    /// Budget of the magic state errors.
+    pub magic_budget: f64,
+    /// Magic state error of the estimate (factories).
+    pub magic_error: f64,
+}
+
+impl BudgetConsumption {
+    /// Consumption of `budget` by the `topological_error` and `magic_error`
+    /// of an estimate.
+    #[must_use]
+    pub fn new(budget: &ErrorBudget, topological_error: f64, magic_error: f64) -> Self {
+        Self {
+            topological_budget: budget.logical(),
+            topological_error,
+            magic_budget: budget.magic_states(),
+            magic_error,
+        }
+    }
+
+    /// Fraction of the topological budget used.
+    #[must_use]
+    pub fn topological_fraction(&self) -> f64 {
+        used_fraction(self.topological_error, self.topological_budget)
+    }
+
+    /// Fraction of the magic state budget used.
+    #[must_use]
+    pub fn magic_fraction(&self) -> f64 {
+        used_fraction(self.magic_error, self.magic_budget)
+    }
+
+    /// Unused part of the topological and magic state budgets.
+    #[must_use]
+    pub fn slack(&self) -> f64 {
+        (self.topological_budget - self.topological_error).max(0.0)
+            + (self.magic_budget - self.magic_error).max(0.0)
+    }
+}
+
+/// `error / budget`, 0 without error and infinite for an error without budget.
+fn used_fraction(error: f64, budget: f64) -> f64 {
+    if error <= 0.0 {
+        0.0
+    } else if budget > 0.0 {
+        error / budget
+    } else {
+        f64::INFINITY
+    }
+}
+
+impl Display for BudgetConsumption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "topological: {:.0}% of budget used, magic: {:.0}% (slack {:.3e})",
+            100.0 * self.topological_fraction(),
+            100.0 * self.magic_fraction(),
+            self.slack()
+        )
+    }
+}
+
+/// Total error of an estimate above its requested error budget.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BudgetExcess {
+    /// Total error of the estimate.
+    pub total_error: f64,
+    /// Total of the requested error budget.
+    pub budget: f64,
+}
+
+impl BudgetExcess {
+    /// Excess of `total_error` over the total of `budget`, [`None`] if it is
+    /// within the budget.
+    #[must_use]
+    pub fn check(budget: &ErrorBudget, total_error: f64) -> Option<Self> {
+        let budget = budget.logical() + budget.magic_states() + budget.rotations();
+        (total_error > budget).then_some(Self {
+            total_error,
+            budget,
+        })
+    }
+
+    /// Factor by which the total error exceeds the budget.
+    #[must_use]
+    pub fn factor(&self) -> f64 {
+        self.total_error / self.budget
+    }
+
+    /// Total budget to request so that the total error meets the requested
+    /// one, assuming the error scales with the budget.
+    #[must_use]
+    pub fn corrected_budget(&self) -> f64 {
+        self.budget / self.factor()
+    }
+}
+
+impl Display for BudgetExcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "total error {:.3e} exceeds the budget {:.3e} by a factor {:.3}, request a total of {:.3e} to meet it",
+            self.total_error,
+            self.budget,
+            self.factor(),
+            self.corrected_budget()
+        )
+    }
+}
+
+/// Fractions of the total error given to the magic state errors, tried by
+/// [`optimal_error_budget`].
+const MAGIC_FRACTIONS: [f64; 19] = [
+    0.05, 0.1, 0.15, 0.2, 0.25, 0.3, 0.35, 0.4, 0.45, 0.5, 0.55, 0.6, 0.65, 0.7, 0.75, 0.8, 0.85,
+    0.9, 0.95,
+];
+
+/// Error budget splitting the total error target `total` between the
+/// topological and magic state errors so as to minimize `cost`, evaluated for
+/// each split (e.g. the space-time volume of the estimate obtained with it,
+/// [`None`] if no estimate meets the budget).
+///
+/// The splits giving 5% to 95% of `total` to the magic state errors, by steps
+/// of 5%, are all evaluated: the cost changes by steps with the discrete code
+/// parameters and factories, and may be feasible for a narrow band of splits
+/// only, so that a coarse grid refined by local moves (see
+/// [`crate::optimize`]) can miss the best split. The first of the cheapest
+/// splits is returned.
+pub fn optimal_error_budget(
+    total: f64,
+    mut cost: impl FnMut(&ErrorBudget) -> Option<f64>,
+) -> Result<ErrorBudget, String> {
+    // reject an invalid total before the search
+    fractional_error_budget(total, 0.5, 0.0)?;
+    let space = SearchSpace::new(vec![Dimension::new("magic fraction", MAGIC_FRACTIONS)]);
+    // a grid of stride 1 is the exhaustive scan of the splits
+    let optimum = GridRefine::new(1)?.minimize(&space, |point: &[f64]| {
+        let magic_fraction = point[0];
+        let budget = fractional_error_budget(total, magic_fraction, 0.0).ok()?;
+        let split_cost = cost(&budget)?;
+        tracing::debug!(magic_fraction, cost = split_cost, "error budget split");
+        Some(split_cost)
+    });
+    match optimum {
+        Some(optimum) => {
+            tracing::debug!(
+                evaluations = optimum.evaluations,
+                "error budget split search"
+            );
+            fractional_error_budget(total, optimum.point[0], 0.0)
+        }
+        None => Err(format!("no split of the total error {total:e} can be met")),
+    }
+}
+
+/// Demand for one type of magic states.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MagicStateDemand {
+    /// Number of magic states of this type consumed by the algorithm.
+    pub count: u64,
+    /// Relative cost of the factories of this type per decade of output error
+    /// (e.g. the increase of their space-time volume when dividing their
+    /// output error by 10). Give the same cost to all the types to split the
+    /// budget proportionally to the counts.
+    pub cost: f64,
+}
+
+impl MagicStateDemand {
+    /// Demand of `count` states, with the same cost as the other types.
+    #[must_use]
+    pub fn new(count: u64) -> Self {
+        Self { count, cost: 1.0 }
+    }
+
+    /// Set the relative cost of the factories of this type.
+    #[must_use]
+    pub fn with_cost(self, cost: f64) -> Self {
+        Self { cost, ..self }
+    }
+}
+
+/// Split the magic state error budget `magic_states` between types of magic
+/// states, returning the error probability allowed for all the states of each
+/// type.
+///
+/// Each type gets a share proportional to count × cost. When the factory
+/// volumes grow as cost × log(1/ε), this split minimizes the total volume of
+/// the factories: the error allowed per state is then proportional to the cost
+/// of its factories, and is the same for all the types when they have the same
+/// cost. Types without states get no budget.
+pub fn split_magic_state_budget(
+    magic_states: f64,
+    demands: &[MagicStateDemand],
+) -> Result<Vec<f64>, String> {
+    if let Some(demand) = demands
+        .iter()
+        .find(|demand| !demand.cost.is_finite() || demand.cost <= 0.0)
+    {
+        return Err(format!(
+            "magic state cost {} is not a positive number",
+            demand.cost
+        ));
+    }
+    let weight =
+        |demand: &MagicStateDemand| demand.count.to_f64().unwrap_or(f64::MAX) * demand.cost;
+    let total: f64 = demands.iter().map(weight).sum();
+    if total == 0.0 {
+        return Ok(vec![0.0; demands.len()]);
+    }
+    Ok(demands
+        .iter()
+        .map(|demand| magic_states * weight(demand) / total)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use resource_estimator::estimates::ErrorBudget;
+
+    use super::{
+        charge_rotation_synthesis, fractional_error_budget, optimal_error_budget,
+        parse_probability, BudgetConsumption, BudgetExcess,
+    };
+
+    #[test]
+    fn optimal_split_is_the_cheapest_one() {
+        // cheapest at 30% of the total for the magic states
+        let budget = optimal_error_budget(1e-3, |budget| {
+            Some((budget.magic_states() / 1e-3 - 0.3).abs())
+        });
+        assert!(budget.is_ok_and(|budget| (budget.magic_states() - 3e-4).abs() < 1e-12));
+    }
+
+    #[test]
+    fn optimal_split_finds_narrow_feasible_bands() {
+        // only the split giving 65% to the magic states is feasible
+        let budget = optimal_error_budget(1e-3, |budget| {
+            ((budget.magic_states() / 1e-3 - 0.65).abs() < 1e-9).then_some(1.0)
+        });
+        assert!(budget.is_ok_and(|budget| (budget.magic_states() - 6.5e-4).abs() < 1e-12));
+    }
+
+    #[test]
+    fn optimal_split_keeps_the_first_of_a_plateau() {
+        let budget = optimal_error_budget(1e-3, |_| Some(1.0));
+        let first = fractional_error_budget(1e-3, 0.05, 0.0);
+        assert!(budget.is_ok_and(|budget| first
+            .is_ok_and(|first| (budget.magic_states() - first.magic_states()).abs() < 1e-15)));
+    }
+
+    #[test]
+    fn optimal_split_fails_without_feasible_split() {
+        assert!(optimal_error_budget(1e-3, |_| None).is_err());
+        assert!(optimal_error_budget(2.0, |_| Some(1.0)).is_err());
+    }
+
+    #[test]
+    fn rotation_synthesis_is_charged_to_the_rotation_budget() {
+        let charged = charge_rotation_synthesis(&ErrorBudget::new(1e-3, 1e-3, 1e-4), 5e-5)
+            .expect("covered by the rotation budget");
+        assert_eq!(
+            (
+                charged.logical(),
+                charged.magic_states(),
+                charged.rotations()
+            ),
+            (1e-3, 1e-3, 1e-4)
+        );
+        assert!(charge_rotation_synthesis(&ErrorBudget::new(1e-3, 1e-3, 1e-5), 5e-5).is_err());
+    }
+
+    #[test]
+    fn rotation_synthesis_is_taken_from_the_topological_budget() {
+        let charged = charge_rotation_synthesis(&ErrorBudget::new(1e-3, 1e-3, 0.0), 2e-4)
+            .expect("covered by the topological budget");
+        assert!((charged.logical() - 8e-4).abs() < 1e-15);
+        assert!((charged.rotations() - 2e-4).abs() < 1e-15);
+        assert!((charged.magic_states() - 1e-3).abs() < 1e-15);
+        assert!(charge_rotation_synthesis(&ErrorBudget::new(1e-3, 1e-3, 0.0), 1e-3).is_err());
+        // no synthesized rotation
+        let charged = charge_rotation_synthesis(&ErrorBudget::new(1e-3, 1e-3, 0.0), 0.0)
+            .expect("nothing to charge");
+        assert!(charged.rotations() == 0.0 && (charged.logical() - 1e-3).abs() < 1e-15);
+    }
+
+    #[test]
+    fn excess_is_flagged_above_the_total_budget() {
+        let budget = ErrorBudget::new(4e-4, 5e-4, 1e-4);
+        assert!(BudgetExcess::check(&budget, 9e-4).is_none());
+        assert!(BudgetExcess::check(&budget, 1e-3).is_none());
+        let excess = BudgetExcess::check(&budget, 1.5e-3).expect("over budget");
+        assert!((excess.budget - 1e-3).abs() < 1e-15);
+        assert!((excess.factor() - 1.5).abs() < 1e-12);
+        // a budget 1.5 times smaller brings the error back to 1e-3
+        assert!((excess.corrected_budget() - 1e-3 / 1.5).abs() < 1e-15);
+        assert!(excess.to_string().contains("by a factor 1.500"), "{excess}");
+    }
+
+    #[test]
+    fn probabilities_are_parsed_in_both_notations() {
+        for (text, value) in [
+            ("0.001", 1e-3),
+            ("1e-3", 1e-3),
+            (" 1E-3 ", 1e-3),
+            ("0", 0.0),
+            ("1", 1.0),
+        ] {
+            assert_eq!(parse_probability(text), Ok(value), "{text}");
+        }
+    }
+
+    #[test]
+    fn invalid_probabilities_are_rejected() {
+        for text in ["", "abc", "1.5", "-1e-3", "NaN", "inf", "5%"] {
+            assert!(parse_probability(text).is_err(), "{text}");
+        }
+        // too small to be represented, rather than rounded to 0
+        let err = parse_probability("1e-400").err();
+        assert!(err.is_some_and(|err| err.contains("too small")));
+        assert_eq!(parse_probability("0e-400"), Ok(0.0));
+    }
+
+    #[test]
+    fn consumption_compares_the_errors_with_their_budget() {
+        let budget = ErrorBudget::new(1e-3, 2e-3, 0.0);
+        let consumption = BudgetConsumption::new(&budget, 5e-4, 2e-3);
+        assert!((consumption.topological_fraction() - 0.5).abs() < 1e-12);
+        assert!((consumption.magic_fraction() - 1.0).abs() < 1e-12);
+        assert!((consumption.slack() - 5e-4).abs() < 1e-15);
+        assert_eq!(
+            consumption.to_string(),
+            "topological: 50% of budget used, magic: 100% (slack 5.000e-4)"
+        );
+    }
+
+    #[test]
+    fn consumption_without_budget() {
+        let consumption = BudgetConsumption::new(&ErrorBudget::new(1e-3, 0.0, 0.0), 2e-3, 0.0);
+        assert!(consumption.magic_fraction().abs() < f64::EPSILON);
+        assert!((consumption.topological_fraction() - 2.0).abs() < 1e-12);
+        assert!(consumption.slack().abs() < f64::EPSILON);
+        let overdrawn = BudgetConsumption::new(&ErrorBudget::new(1e-3, 0.0, 0.0), 0.0, 1e-6);
+        assert!(overdrawn.magic_fraction().is_infinite());
+    }
+}
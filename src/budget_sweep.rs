@@ -0,0 +1,156 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Estimates of one algorithm for many total error budgets.
+//!
+//! The standard "physical qubits vs target error" curves estimate the same
+//! logical counts for a grid of total error budgets, usually spaced
+//! logarithmically ([`log_space`]). [`estimate_many`] counts the algorithm
+//! once and estimates it for each budget with the same repetition code, whose
+//! clones share the cache of the logical error rates, so that the code
+//! distances already evaluated for a budget are not recomputed for the next.
+
+use std::fmt::Display;
+
+use crate::{
+    budget, scan,
+    units::{format_thousands, TimeSpan},
+    AliceAndBobEstimates, CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+
+/// Estimate of the algorithm for one total error budget.
+pub struct BudgetPoint {
+    /// Total error budget, split equally between the topological and magic
+    /// state errors.
+    pub total_budget: f64,
+    /// Estimates, or why the budget can't be met.
+    pub estimate: Result<AliceAndBobEstimates, String>,
+}
+
+/// Estimates of the algorithm for a list of total error budgets, in the order
+/// of the list.
+#[derive(Default)]
+pub struct BudgetSweep {
+    /// Estimate for each budget.
+    pub points: Vec<BudgetPoint>,
+}
+
+impl BudgetSweep {
+    /// Total error budgets with the physical qubits and runtime of their
+    /// estimates, skipping the budgets which can't be met, ready to be plotted.
+    #[must_use]
+    pub fn curve(&self) -> Vec<(f64, u64, TimeSpan)> {
+        self.points
+            .iter()
+            .filter_map(|point| {
+                let estimates = point.estimate.as_ref().ok()?;
+                Some((
+                    point.total_budget,
+                    estimates.physical_qubits(),
+                    estimates.runtime_duration(),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl Display for BudgetSweep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        scan::write_table(
+            f,
+            &format!("{:<10}", "budget"),
+            &[
+                ("physical qubits", 18),
+                ("runtime", 16),
+                ("total error", 14),
+            ],
+            self.points.iter().map(|point| {
+                let cells = point.estimate.as_ref().map(|estimates| {
+                    vec![
+                        format_thousands(estimates.physical_qubits()),
+                        estimates.runtime_duration().to_string(),
+                        format!("{:.2e}", estimates.total_error()),
+                    ]
+                });
+                (
+                    format!("{:<10}", format!("{:.1e}", point.total_budget)),
+                    cells,
+                )
+            }),
+        )
+    }
+}
+
+/// `points` values from `from` to `to` (both included) evenly spaced on a
+/// logarithmic scale, e.g. `log_space(1e-1, 1e-15, 15)` for the decades from
+/// 10⁻¹ to 10⁻¹⁵.
+///
+/// The bounds must be positive; a single point is `from`.
+pub fn log_space(from: f64, to: f64, points: usize) -> Result<Vec<f64>, String> {
+    for bound in [from, to] {
+        if !(bound.is_finite() && bound > 0.0) {
+            return Err(format!(
+                "bound {bound} of a logarithmic grid is not positive"
+            ));
+        }
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let step = (to.ln() - from.ln()) / points.saturating_sub(1).max(1) as f64;
+    #[allow(clippy::cast_precision_loss)]
+    Ok((0..points)
+        .map(|index| (from.ln() + step * index as f64).exp())
+        .collect())
+}
+
+/// Estimates of `counts` for each of the `total_budgets`, each split equally
+/// between the topological and magic state errors.
+///
+/// The logical counts are computed once by the caller and the error rates of
+/// the code are cached across the budgets. A budget which can't be met (e.g.
+/// below the error of the factories) has an error instead of estimates, and
+/// doesn't stop the sweep.
+#[must_use]
+pub fn estimate_many(
+    qubit: &CatQubit,
+    code: &RepetitionCode,
+    builder: &ToffoliBuilder,
+    counts: &LogicalCounts,
+    total_budgets: impl IntoIterator<Item = f64>,
+) -> BudgetSweep {
+    let points = total_budgets
+        .into_iter()
+        .map(|total_budget| {
+            let estimate = budget::split_error_budget(total_budget)
+                .and_then(|budget| scan::estimate(qubit, code, builder, counts.clone(), &budget));
+            tracing::debug!(
+                total_budget,
+                ok = estimate.is_ok(),
+                "total error budget estimated"
+            );
+            BudgetPoint {
+                total_budget,
+                estimate,
+            }
+        })
+        .collect();
+    BudgetSweep { points }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::log_space;
+
+    #[test]
+    fn log_space_spans_the_decades() {
+        let grid = log_space(1e-1, 1e-5, 5).expect("positive bounds");
+        for (value, expected) in grid.iter().zip([1e-1, 1e-2, 1e-3, 1e-4, 1e-5]) {
+            assert!((value / expected - 1.0).abs() < 1e-12, "{value}");
+        }
+        let single = log_space(1e-3, 1e-9, 1).expect("positive bounds");
+        assert!(single.len() == 1 && (single[0] / 1e-3 - 1.0).abs() < 1e-12);
+        assert_eq!(log_space(1e-3, 1e-9, 0), Ok(vec![]));
+        for (from, to) in [(0.0, 1e-3), (1e-3, -1.0), (f64::NAN, 1e-3)] {
+            assert!(log_space(from, to, 3).is_err(), "{from} {to}");
+        }
+    }
+}
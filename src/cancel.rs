@@ -0,0 +1,38 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Cancellation of long computations.
+//!
+//! Counting the resources of a large Q# program, or searching the code
+//! parameters for a frontier, can take minutes. A [`CancellationToken`] given
+//! to `LogicalCounts::with_cancellation` (with the `qsharp` feature) or [`RepetitionCode::with_cancellation`](crate::RepetitionCode::with_cancellation)
+//! can be cancelled from another thread (e.g. a Ctrl-C handler), the
+//! computation then stops with an error describing how far it went.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Shared flag requesting the cancellation of a computation.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    #[must_use]
+    /// Token which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the cancellation.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    /// Whether the cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
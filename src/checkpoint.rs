@@ -0,0 +1,262 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Checkpoints of the completed points of long sweeps and frontier builds.
+//!
+//! A [`Checkpoint`] appends each completed point to a file, one JSON object
+//! per line, flushed as soon as the point is completed. When a run is
+//! interrupted, opening the checkpoint again with [`OpenMode::Resume`] gives
+//! back the points already completed, to be skipped (e.g. the rows of a
+//! [`ParameterSweep`](crate::sweep::ParameterSweep), or the frontier points
+//! before [`FrontierPoints::resume_after`](crate::frontier::FrontierPoints::resume_after)),
+//! and the new points are appended after them. A line cut by the interruption
+//! is dropped.
+//!
+//! The first line of the file is a fingerprint of the options of the run
+//! (e.g. the qubit parameters and the logical counts), and a run with other
+//! options refuses to resume it. An existing checkpoint is only overwritten
+//! with [`OpenMode::Overwrite`], and is replaced atomically: the header and
+//! the completed points are written to a temporary file renamed over it.
+
+use std::{
+    fs::{rename, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// How to open a checkpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpenMode {
+    /// Start a new run, failing if the checkpoint exists.
+    Create,
+    /// Resume the run of an existing checkpoint with the same options, or
+    /// start a new one if there is none.
+    Resume,
+    /// Start a new run, discarding an existing checkpoint.
+    Overwrite,
+}
+
+/// First line of a checkpoint file.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    fingerprint: Value,
+}
+
+/// File of the completed points of a run.
+pub struct Checkpoint<T> {
+    path: PathBuf,
+    file: File,
+    completed: Vec<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> Checkpoint<T> {
+    /// Checkpoint at `path` of a run with the options `fingerprint`, with the
+    /// points completed by an interrupted run with the same options if
+    /// resumed.
+    pub fn open(
+        path: impl AsRef<Path>,
+        fingerprint: &impl Serialize,
+        mode: OpenMode,
+    ) -> Result<Self, String> {
+        let path = path.as_ref();
+        let fingerprint = serde_json::to_value(fingerprint).map_err(|err| err.to_string())?;
+        let completed = match mode {
+            OpenMode::Create if path.exists() => {
+                return Err(format!(
+                    "checkpoint {} already exists (resume or overwrite it)",
+                    path.display()
+                ))
+            }
+            OpenMode::Resume if path.exists() => {
+                let (recorded, completed) = Self::read(path)?;
+                if recorded != fingerprint {
+                    return Err(format!(
+                        "checkpoint {} was recorded with other options",
+                        path.display()
+                    ));
+                }
+                completed
+            }
+            _ => vec![],
+        };
+
+        // rewrite the completed points without a line cut by the interruption
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+        let mut file = File::create(&temporary)
+            .map_err(|err| format!("can't write {}: {err}", temporary.display()))?;
+        Self::write_line(&mut file, &Header { fingerprint }, &temporary)?;
+        for point in &completed {
+            Self::write_line(&mut file, point, &temporary)?;
+        }
+        drop(file);
+        rename(&temporary, path).map_err(|err| format!("can't write {}: {err}", path.display()))?;
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|err| format!("can't write {}: {err}", path.display()))?;
+        tracing::info!(
+            checkpoint = %path.display(),
+            completed = completed.len(),
+            "checkpoint opened"
+        );
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            completed,
+        })
+    }
+
+    /// Points completed by the interrupted run, to be skipped (empty once
+    /// taken).
+    pub fn take_completed(&mut self) -> Vec<T> {
+        std::mem::take(&mut self.completed)
+    }
+
+    /// Record a completed point.
+    pub fn record(&mut self, point: &T) -> Result<(), String> {
+        Self::write_line(&mut self.file, point, &self.path)
+    }
+
+    /// Path of the checkpoint.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Fingerprint and points of a checkpoint file, up to the first invalid
+    /// line.
+    fn read(path: &Path) -> Result<(Value, Vec<T>), String> {
+        let file =
+            File::open(path).map_err(|err| format!("can't read {}: {err}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+        let header: Header = lines
+            .next()
+            .and_then(|line| serde_json::from_str(&line.ok()?).ok())
+            .ok_or_else(|| format!("{} is not a checkpoint", path.display()))?;
+        let mut points = vec![];
+        for (index, line) in lines.enumerate() {
+            let line = line.map_err(|err| format!("can't read {}: {err}", path.display()))?;
+            match serde_json::from_str(&line) {
+                Ok(point) => points.push(point),
+                Err(err) => {
+                    tracing::warn!(
+                        checkpoint = %path.display(),
+                        line = index + 2,
+                        %err,
+                        "checkpoint truncated at an invalid line"
+                    );
+                    break;
+                }
+            }
+        }
+        Ok((header.fingerprint, points))
+    }
+
+    /// Append a JSON line to the file.
+    fn write_line(file: &mut File, point: &impl Serialize, path: &Path) -> Result<(), String> {
+        let line = serde_json::to_string(point).map_err(|err| err.to_string())?;
+        writeln!(file, "{line}")
+            .and_then(|()| file.flush())
+            .map_err(|err| format!("can't write {}: {err}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{Checkpoint, OpenMode};
+
+    /// Path of a checkpoint in the temporary directory, removed beforehand.
+    fn path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("ab-checkpoint-{}-{name}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn resumed_checkpoint_gives_the_completed_points() {
+        let path = path("resume");
+        let mut checkpoint = Checkpoint::open(&path, &"options", OpenMode::Create).expect("opened");
+        assert!(checkpoint.take_completed().is_empty());
+        checkpoint.record(&1).expect("recorded");
+        checkpoint.record(&2).expect("recorded");
+        drop(checkpoint);
+
+        let mut checkpoint =
+            Checkpoint::<u64>::open(&path, &"options", OpenMode::Resume).expect("resumed");
+        assert_eq!(checkpoint.take_completed(), [1, 2]);
+        checkpoint.record(&3).expect("recorded");
+        drop(checkpoint);
+        let mut checkpoint =
+            Checkpoint::<u64>::open(&path, &"options", OpenMode::Resume).expect("resumed");
+        assert_eq!(checkpoint.take_completed(), [1, 2, 3]);
+        assert!(!path.with_extension("jsonl.tmp").exists());
+        fs::remove_file(&path).expect("removed");
+    }
+
+    #[test]
+    fn line_cut_by_an_interruption_is_dropped() {
+        let path = path("cut");
+        fs::write(&path, "{\"fingerprint\":1}\n1\n2\n{\"cut").expect("written");
+        let mut checkpoint = Checkpoint::<u64>::open(&path, &1, OpenMode::Resume).expect("resumed");
+        assert_eq!(checkpoint.take_completed(), [1, 2]);
+        drop(checkpoint);
+        assert_eq!(
+            fs::read_to_string(&path).expect("read"),
+            "{\"fingerprint\":1}\n1\n2\n"
+        );
+        fs::remove_file(&path).expect("removed");
+    }
+
+    #[test]
+    fn checkpoint_of_other_options_is_refused() {
+        let path = path("options");
+        let mut checkpoint = Checkpoint::open(&path, &[1, 2], OpenMode::Create).expect("opened");
+        checkpoint.record(&1).expect("recorded");
+        drop(checkpoint);
+        assert!(Checkpoint::<u64>::open(&path, &[1, 3], OpenMode::Resume).is_err());
+        // nor overwritten when resuming fails
+        let mut checkpoint =
+            Checkpoint::<u64>::open(&path, &[1, 2], OpenMode::Resume).expect("resumed");
+        assert_eq!(checkpoint.take_completed(), [1]);
+        drop(checkpoint);
+
+        fs::write(&path, "1\n2\n").expect("written");
+        assert!(Checkpoint::<u64>::open(&path, &[1, 2], OpenMode::Resume).is_err());
+        fs::remove_file(&path).expect("removed");
+    }
+
+    #[test]
+    fn existing_checkpoint_is_only_overwritten_if_forced() {
+        let path = path("force");
+        let mut checkpoint = Checkpoint::open(&path, &"options", OpenMode::Create).expect("opened");
+        checkpoint.record(&1).expect("recorded");
+        drop(checkpoint);
+        assert!(Checkpoint::<u64>::open(&path, &"options", OpenMode::Create).is_err());
+
+        let checkpoint =
+            Checkpoint::<u64>::open(&path, &"other", OpenMode::Overwrite).expect("overwritten");
+        drop(checkpoint);
+        let mut checkpoint =
+            Checkpoint::<u64>::open(&path, &"other", OpenMode::Resume).expect("resumed");
+        assert!(checkpoint.take_completed().is_empty());
+        fs::remove_file(&path).expect("removed");
+    }
+
+    #[test]
+    fn missing_checkpoint_is_resumed_empty() {
+        let path = path("missing");
+        let mut checkpoint =
+            Checkpoint::<u64>::open(&path, &"options", OpenMode::Resume).expect("created");
+        assert!(checkpoint.take_completed().is_empty());
+        assert!(path.exists());
+        fs::remove_file(&path).expect("removed");
+    }
+}
@@ -12,21 +12,101 @@
 //! - average number of photons |α|²
 //!
 //! Hard-coded values:
-//! - 1/κ₂ = 100 ns (sets the gates speed)
-//! - (κ₁/κ₂)_th = 0.013 (obtained by circuit-level simulation)
+//! - 1/κ₂ = 100 ns by default (sets the gates speed, see
+//!   [`CatQubit::with_inverse_k2_ns`])
+//! - (κ₁/κ₂)_th = 0.013 (obtained by circuit-level simulation, see
+//!   [`phase_flip::PowerLawFit`])
 //! - max distance (for iteration) = 49
-//! - max |α|² (for iteration) = 30.0
+//! - max |α|² (for iteration) = 30.0, by steps of 1 (see
+//!   [`RepetitionCode::with_alpha_sq_step`])
+//!
+//! The constants of the phase-flip fit can be overridden, see
+//! [`RepetitionCode::with_fit`], or the whole fit replaced, see [`phase_flip`],
+//! as well as the schedule of the syndrome extraction, see [`schedule`]. For target errors
+//! below the bit-flip floor, the code can be concatenated in an outer code,
+//! see [`concatenated`], or replaced by a thin surface code, see [`surface`].
+//!
+//! Leakage (disabled by default, see [`CatQubit::with_leakage_rate`]) adds a
+//! logical error term with its own threshold, tunable with
+//! [`RepetitionCode::with_leakage_fit`]. Similarly, explicit measurement errors
+//! (see [`CatQubit::with_measurement_error`]) add a term tunable with
+//! [`RepetitionCode::with_measurement_fit`].
+//!
+//! When no code parameter reaches the required logical error rate, the error
+//! tells which bound to relax, see [`diagnosis`]. The evaluated code parameters
+//! can be recorded, see [`trace`], visited in fewer evaluations, see
+//! [`search`], and compared by qubits, time or volume, see
+//! [`ordering`]. The logical error rates are computed once for
+//! each qubit and code parameter, see [`CacheStatistics`].
 
-use num_traits::{FromPrimitive, ToPrimitive};
-use std::{cmp::Ordering, fmt::Display};
+use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, cmp::Ordering, fmt::Display, rc::Rc};
 
 use resource_estimator::estimates::ErrorCorrection;
 
-use crate::qubit::CatQubit;
+use crate::{
+    cancel::CancellationToken,
+    qubit::CatQubit,
+    units::{Rounding, TimeSpan},
+};
+
+use bit_flip::CxBitFlipTable;
+pub use cache::CacheStatistics;
+use cache::ErrorRateCache;
+use ordering::ParameterOrdering;
+use phase_flip::{suppression, PhaseFlipModel, PowerLawFit};
+use schedule::CycleSchedule;
+use search::SearchStrategy;
+use trace::SearchTrace;
+
+pub mod bit_flip;
+mod cache;
+pub mod concatenated;
+pub mod diagnosis;
+pub mod ordering;
+pub mod phase_flip;
+pub mod schedule;
+pub mod search;
+pub mod surface;
+pub mod trace;
+
+/// Maximum code distance of the search of code parameters.
+pub const MAX_DISTANCE: u64 = 49;
+
+/// Maximum |α|² of the search of code parameters.
+pub const MAX_ALPHA_SQ: f64 = 30.0;
+
+/// Minimum |α|² of the search of code parameters.
+const MIN_ALPHA_SQ: f64 = 1.0;
+
+/// Default step of the |α|² of the search of code parameters.
+const ALPHA_SQ_STEP: f64 = 1.0;
+
+/// Minimum step of the |α|² of the search of code parameters, bounding the
+/// number of evaluations (about 3000 |α|² per distance).
+const MIN_ALPHA_SQ_STEP: f64 = 0.01;
+
+/// Tolerance on the maximum |α|², for the rounding errors of the steps.
+const ALPHA_SQ_TOLERANCE: f64 = 1e-9;
 
 /// Represents a repetition code.
+#[derive(Clone)]
 pub struct RepetitionCode {
-    p_threshold: f64,
+    phase_flip_model: Rc<dyn PhaseFlipModel>,
+    cx_bit_flip: Option<Rc<CxBitFlipTable>>,
+    schedule: CycleSchedule,
+    leakage_prefactor: f64,
+    leakage_threshold: f64,
+    measurement_prefactor: f64,
+    measurement_threshold: f64,
+    cancellation: Option<CancellationToken>,
+    trace: Option<SearchTrace>,
+    ordering: ParameterOrdering,
+    strategy: SearchStrategy,
+    alpha_sq_step: f64,
+    // shared by the clones, reset when the models change
+    cache: Rc<RefCell<ErrorRateCache>>,
 }
 
 impl RepetitionCode {
@@ -36,41 +116,318 @@ impl RepetitionCode {
         Self::default()
     }
 
+    #[must_use]
+    /// Replace the model of the logical phase-flip probability (by default,
+    /// the [`PowerLawFit`] of arXiv:2302.06639).
+    pub fn with_phase_flip_model(mut self, model: impl PhaseFlipModel + 'static) -> Self {
+        self.phase_flip_model = Rc::new(model);
+        self.reset_cache();
+        self
+    }
+
+    /// Replace the constants of the phase-flip fit,
+    /// `prefactor * (|α|^(2·alpha_exponent) κ₁/κ₂ / threshold)^((d+1)/2)` per
+    /// round (by default 5.6e-2, 0.86 and 0.013, see [`PowerLawFit`]), e.g.
+    /// with the fit of newer simulations or of another cat implementation.
+    /// The prefactor and the threshold must be positive, and the exponent
+    /// non-negative.
+    ///
+    /// The leakage and measurement fits are unchanged.
+    pub fn with_fit(
+        self,
+        prefactor: f64,
+        alpha_exponent: f64,
+        threshold: f64,
+    ) -> Result<Self, String> {
+        if !(prefactor.is_finite() && prefactor > 0.0) {
+            return Err(format!(
+                "the prefactor of the phase-flip fit {prefactor} must be positive"
+            ));
+        }
+        if !(alpha_exponent.is_finite() && alpha_exponent >= 0.0) {
+            return Err(format!(
+                "the exponent of |α|² of the phase-flip fit {alpha_exponent} must be non-negative"
+            ));
+        }
+        if !(threshold.is_finite() && threshold > 0.0) {
+            return Err(format!(
+                "the threshold of the phase-flip fit {threshold} must be positive"
+            ));
+        }
+        Ok(self.with_phase_flip_model(PowerLawFit {
+            prefactor,
+            alpha_exponent,
+            threshold,
+        }))
+    }
+
+    /// Search the |α|² by steps of `step` (1 by default, at least 0.01), e.g.
+    /// 0.5 for finer code parameters at the cost of more evaluations.
+    pub fn with_alpha_sq_step(mut self, step: f64) -> Result<Self, String> {
+        if !(step.is_finite() && step >= MIN_ALPHA_SQ_STEP) {
+            return Err(format!(
+                "the step of |α|² {step} must be at least {MIN_ALPHA_SQ_STEP}"
+            ));
+        }
+        self.alpha_sq_step = step;
+        Ok(self)
+    }
+
+    #[must_use]
+    /// Step of the |α|² of the search of code parameters.
+    pub fn alpha_sq_step(&self) -> f64 {
+        self.alpha_sq_step
+    }
+
+    /// Set the schedule of the syndrome extraction (by default, the one of
+    /// arXiv:2302.06639), checked as [`CycleSchedule::checked`].
+    pub fn with_schedule(mut self, schedule: CycleSchedule) -> Result<Self, String> {
+        self.schedule = schedule.checked()?;
+        self.reset_cache();
+        Ok(self)
+    }
+
+    #[must_use]
+    /// Set the fit of the logical error due to leakage,
+    /// `prefactor * (leakage_rate / threshold)^((d+1)/2)` per round.
+    ///
+    /// By default, leakage is assumed to be as harmful as phase flips: same
+    /// prefactor and threshold as the phase-flip fit.
+    pub fn with_leakage_fit(mut self, prefactor: f64, threshold: f64) -> Self {
+        self.leakage_prefactor = prefactor;
+        self.reset_cache();
+        self.leakage_threshold = threshold;
+        self
+    }
+
+    #[must_use]
+    /// Set the fit of the logical error due to explicit ancilla measurement
+    /// errors, `prefactor * (measurement_error / threshold)^((d+1)/2)` per
+    /// round.
+    ///
+    /// By default, the threshold is the one of the repetition code under
+    /// phenomenological noise (about 10%, syndrome bits being repeated over d
+    /// rounds), with the prefactor of the phase-flip fit.
+    pub fn with_measurement_fit(mut self, prefactor: f64, threshold: f64) -> Self {
+        self.measurement_prefactor = prefactor;
+        self.reset_cache();
+        self.measurement_threshold = threshold;
+        self
+    }
+
+    #[must_use]
+    /// Stop the search of code parameters when `cancellation` is cancelled.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Whether the search of code parameters has been cancelled.
+    fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    #[must_use]
+    /// Description of the model of the logical phase-flip probability.
+    pub fn phase_flip_model_description(&self) -> String {
+        self.phase_flip_model.description()
+    }
+
+    #[must_use]
+    /// Schedule of the syndrome extraction.
+    pub fn schedule(&self) -> CycleSchedule {
+        self.schedule
+    }
+
+    #[must_use]
+    /// Prefactor and threshold of the fit of the logical error due to
+    /// leakage.
+    pub fn leakage_fit(&self) -> (f64, f64) {
+        (self.leakage_prefactor, self.leakage_threshold)
+    }
+
+    #[must_use]
+    /// Prefactor and threshold of the fit of the logical error due to explicit
+    /// measurement errors.
+    pub fn measurement_fit(&self) -> (f64, f64) {
+        (self.measurement_prefactor, self.measurement_threshold)
+    }
+
     #[must_use]
     /// Logical phaseflip probability per round, as given by
-    /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 28, eq. E1).
-    fn logical_phaseflip_probability(
+    /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 28, eq. E1),
+    /// or by the model set with [`RepetitionCode::with_phase_flip_model`].
+    ///
+    /// The logical error rate per logical cycle
+    /// ([`ErrorCorrection::logical_error_rate`]) is the number of rounds (d by
+    /// default) times the sum of the per-round probabilities. Returns [`None`]
+    /// if the phase-flip model can't compute it for these parameters (the
+    /// default fit always can, see [`phase_flip::suppression`] for large
+    /// distances).
+    pub fn logical_phaseflip_probability(
         &self,
         physical_qubit: &CatQubit,
         parameter: &CodeParameter,
     ) -> Option<f64> {
-        // arXiv:2302.06639 (p. 29, Fig. 26)
-        let prefactor = 5.6e-2;
-        let exponent = (i32::from_u64(parameter.distance)? + 1) / 2;
+        // Phase flips accumulate during the whole round, longer rounds increase
+        // the effective κ₁/κ₂ compared to the 5/κ₂ rounds of the fit.
+        let k1_k2 = physical_qubit.effective_k1_k2()
+            * self.round_duration(physical_qubit, parameter)
+            / CycleSchedule::REFERENCE_ROUND_DURATION;
+        self.phase_flip_model
+            .logical_phaseflip_probability(k1_k2, parameter)
+    }
 
-        // Logical phase-flip error rate per cycle of the repetition code
-        // arXiv:2302.06639 (p. 3, eq. 4)
-        Some(
-            prefactor
-                * ((parameter.alpha_sq.powf(0.86) * physical_qubit.k1_k2) / self.p_threshold)
-                    .powi(exponent),
-        )
+    #[must_use]
+    /// Duration of a round of the code, in units of 1/κ₂.
+    ///
+    /// By default, a round consists of 5 steps (arXiv:2302.06639, p. 28):
+    /// ancilla preparation, 2 CX and measurement (taking 2 steps); i.e. 5/κ₂
+    /// with fast CX gates.
+    pub fn round_duration(&self, physical_qubit: &CatQubit, parameter: &CodeParameter) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let cx_layers = CycleSchedule::CX_LAYERS as f64;
+        self.schedule.other_steps
+            + cx_layers * physical_qubit.compute_cx.duration(parameter.alpha_sq)
+    }
+
+    /// Duration of a logical cycle, rounded to the nearest nanosecond.
+    pub fn cycle_time(
+        &self,
+        qubit: &CatQubit,
+        parameter: &CodeParameter,
+    ) -> Result<TimeSpan, String> {
+        // arXiv:2302.06639 (p. 28, repetition code cycle time in d code cycles)
+        // Time for one round : 5/κ₂ with fast CX gates
+        let round_time = qubit.inverse_k2_ns() * self.round_duration(qubit, parameter); // ns
+
+        f64::from_u64(self.rounds(parameter))
+            .and_then(|rounds| TimeSpan::from_ns_f64(rounds * round_time, Rounding::Nearest))
+            .ok_or_else(|| "cannot compute logical cycle time".into())
+    }
+
+    #[must_use]
+    /// Number of rounds in a logical cycle.
+    pub fn rounds(&self, parameter: &CodeParameter) -> u64 {
+        self.schedule.rounds(parameter.distance)
+    }
+
+    #[must_use]
+    /// Logical bitflip probability per logical cycle, i.e. the number of
+    /// rounds times [`RepetitionCode::logical_bitflip_probability`].
+    pub fn logical_bitflip_rate(&self, parameter: &CodeParameter) -> Option<f64> {
+        Some(f64::from_u64(self.rounds(parameter))? * self.logical_bitflip_probability(parameter)?)
     }
 
     #[allow(clippy::similar_names)]
     #[must_use]
     /// Logical bitflip probability per round, as given in
     /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (eq. 3).
-    fn logical_bitflip_probability(parameter: &CodeParameter) -> Option<f64> {
-        // number of CX gates in a repetition code cycle
-        let ncx = 2 * (parameter.distance - 1);
+    ///
+    /// It only depends on the code parameters: 2(d-1) CX gates per round, each
+    /// with a bit-flip probability 0.5·exp(-2|α|²) (p. 26, eq. D8), or the
+    /// measured one set with [`RepetitionCode::with_cx_bit_flip_table`].
+    pub fn logical_bitflip_probability(&self, parameter: &CodeParameter) -> Option<f64> {
+        // number of CX gates in a repetition code round
+        let ncx = CycleSchedule::CX_LAYERS * (parameter.distance - 1);
 
-        // Bit-flip error probability of a CX gate (numerically estimated using
-        // full process tomography), arXiv:2302.06639 (p. 26, eq. D8)
-        let pcx = 0.5 * (-2.0 * parameter.alpha_sq).exp();
+        // Bit-flip error probability of a CX gate
+        let pcx = self.cx_bit_flip_probability(parameter.alpha_sq);
 
         Some(f64::from_u64(ncx)? * pcx)
     }
+
+    #[must_use]
+    /// Logical error probability per round due to leakage of the physical
+    /// qubits.
+    ///
+    /// Leaked cats are brought back by the two-photon dissipation but corrupt
+    /// the syndrome in the meantime, so a logical error requires about half of
+    /// the qubits to leak, as for phase flips.
+    pub fn logical_leakage_probability(
+        &self,
+        physical_qubit: &CatQubit,
+        parameter: &CodeParameter,
+    ) -> Option<f64> {
+        Some(
+            self.leakage_prefactor
+                * suppression(
+                    physical_qubit.effective_leakage_rate() / self.leakage_threshold,
+                    parameter.distance,
+                ),
+        )
+    }
+
+    #[must_use]
+    /// Logical error probability per round due to the explicit errors of the
+    /// ancilla measurements.
+    ///
+    /// A wrong syndrome bit is detected by the next round, so a logical error
+    /// requires about half of the d rounds to give faulty measurements.
+    pub fn logical_measurement_error_probability(
+        &self,
+        physical_qubit: &CatQubit,
+        parameter: &CodeParameter,
+    ) -> Option<f64> {
+        Some(
+            self.measurement_prefactor
+                * suppression(
+                    physical_qubit.effective_measurement_error() / self.measurement_threshold,
+                    parameter.distance,
+                ),
+        )
+    }
+
+    /// Logical error rate per logical cycle, without recording it into the
+    /// trace of the search.
+    pub(crate) fn unrecorded_logical_error_rate(
+        &self,
+        qubit: &CatQubit,
+        parameter: &CodeParameter,
+    ) -> Result<f64, String> {
+        if !qubit.supports_alpha_sq(parameter.alpha_sq) {
+            return Err(format!(
+                "|ɑ|² = {} is above the maximum |ɑ|² reachable by the qubits",
+                parameter.alpha_sq
+            ));
+        }
+
+        if let (Some(rounds), Some(lzp), Some(lxp), Some(llp), Some(lmp)) = (
+            f64::from_u64(self.rounds(parameter)),
+            self.logical_phaseflip_probability(qubit, parameter),
+            self.logical_bitflip_probability(parameter),
+            self.logical_leakage_probability(qubit, parameter),
+            self.logical_measurement_error_probability(qubit, parameter),
+        ) {
+            // arXiv:2302.06639 (p. 4, eq. 3 and app E2, p. 27)
+            // this is eq. 3 in a more compact form (with d rounds), plus the
+            // leakage and measurement terms
+            let error_rate = rounds * (lzp + lxp + llp + lmp); // logical phase-flip, logical
+                                                               // bit-flip, leakage, measurement
+            tracing::trace!(
+                %parameter,
+                error_rate,
+                phase_flip = lzp,
+                bit_flip = lxp,
+                leakage = llp,
+                measurement = lmp,
+                "logical error rate"
+            );
+            if error_rate.is_finite() {
+                Ok(error_rate)
+            } else {
+                Err(format!(
+                    "logical error rate of {parameter} overflows (phase flip {lzp:e}, bit flip \
+                     {lxp:e}, leakage {llp:e}, measurement {lmp:e}), the physical error rates \
+                     being above the thresholds"
+                ))
+            }
+        } else {
+            Err("cannot compute logical failure probability".into())
+        }
+    }
 }
 
 impl Default for RepetitionCode {
@@ -80,13 +437,30 @@ impl Default for RepetitionCode {
     /// (p. 4, Eq. (3), p. 28, Fig. 26). Note that this is not a variable you
     /// can tune, but the result of a circuit-level simulation.
     fn default() -> Self {
-        let p_threshold = 0.013;
-        Self { p_threshold }
+        let fit = PowerLawFit::default();
+        Self {
+            phase_flip_model: Rc::new(fit),
+            cx_bit_flip: None,
+            schedule: CycleSchedule::default(),
+            leakage_prefactor: fit.prefactor,
+            leakage_threshold: fit.threshold,
+            measurement_prefactor: fit.prefactor,
+            measurement_threshold: 0.1,
+            cancellation: None,
+            trace: None,
+            ordering: ParameterOrdering::default(),
+            strategy: SearchStrategy::default(),
+            alpha_sq_step: ALPHA_SQ_STEP,
+            cache: Rc::default(),
+        }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawCodeParameter")]
 /// Store the code distance and average photon number |α|².
+///
+/// Deserialization checks the parameters as [`CodeParameter::try_new`].
 pub struct CodeParameter {
     distance: u64,
     // Amplitude ɑ arXiv:2302.06639 (p. 3), average number of photons |ɑ|²
@@ -96,8 +470,59 @@ pub struct CodeParameter {
 impl CodeParameter {
     #[must_use]
     /// Set new values for the code parameters (distance, |α|²).
+    ///
+    /// Repetition codes have odd distances, an even distance is snapped to the
+    /// next odd one. Use [`CodeParameter::try_new`] to reject it instead.
     pub fn new(distance: u64, alpha_sq: f64) -> Self {
-        Self { distance, alpha_sq }
+        Self {
+            distance: distance | 1,
+            alpha_sq,
+        }
+    }
+
+    /// Set new values for the code parameters (distance, |α|²), checking that
+    /// the distance is odd and |α|² positive.
+    pub fn try_new(distance: u64, alpha_sq: f64) -> Result<Self, String> {
+        if distance == 0 {
+            Err("distance 0 is even, repetition codes have odd distances (try 1)".into())
+        } else if distance.is_multiple_of(2) {
+            Err(format!(
+                "distance {distance} is even, repetition codes have odd distances (try {} or {})",
+                distance - 1,
+                distance + 1
+            ))
+        } else if alpha_sq.is_nan() || alpha_sq <= 0.0 {
+            Err(format!("|ɑ|² = {alpha_sq} must be positive"))
+        } else {
+            Ok(Self { distance, alpha_sq })
+        }
+    }
+
+    #[must_use]
+    /// Code distance.
+    pub fn distance(&self) -> u64 {
+        self.distance
+    }
+
+    #[must_use]
+    /// Average number of photons |α|².
+    pub fn alpha_sq(&self) -> f64 {
+        self.alpha_sq
+    }
+}
+
+/// Unchecked [`CodeParameter`], for deserialization.
+#[derive(Deserialize)]
+struct RawCodeParameter {
+    distance: u64,
+    alpha_sq: f64,
+}
+
+impl TryFrom<RawCodeParameter> for CodeParameter {
+    type Error = String;
+
+    fn try_from(value: RawCodeParameter) -> Result<Self, Self::Error> {
+        Self::try_new(value.distance, value.alpha_sq)
     }
 }
 
@@ -107,53 +532,70 @@ impl Display for CodeParameter {
     }
 }
 
-/// Keeps the range of parameters on which to iterate.
+/// Keeps the range of parameters on which to iterate: the |α|² from the lower
+/// bound (from 1 at the next distances) to the maximum, by steps.
 struct CodeParameterRange {
     distance: u64,
-    alpha_sq: u64,
+    // first |α|² at the current distance, and index of the current one
+    first_alpha_sq: f64,
+    step_index: u32,
+    alpha_sq_step: f64,
     max_distance: u64,
-    max_alpha_sq: u64,
+    max_alpha_sq: f64,
 }
 
 impl CodeParameterRange {
     pub fn new(lower_bound: Option<&CodeParameter>, max_distance: u64, max_alpha_sq: f64) -> Self {
-        let lower_bound = lower_bound.cloned().unwrap_or(CodeParameter::new(1, 1.0));
+        let (distance, alpha_sq) =
+            lower_bound.map_or((1, MIN_ALPHA_SQ), |bound| (bound.distance, bound.alpha_sq));
 
         Self {
-            distance: lower_bound.distance,
-            alpha_sq: lower_bound
-                .alpha_sq
-                .to_u64()
-                .expect("alpha_sq failed to be represented as u64"),
+            // stepping by 2 must keep the distance odd
+            distance: distance | 1,
+            // also replaces a NaN lower bound
+            first_alpha_sq: alpha_sq.max(MIN_ALPHA_SQ),
+            step_index: 0,
+            alpha_sq_step: ALPHA_SQ_STEP,
             max_distance,
-            max_alpha_sq: max_alpha_sq
-                .to_u64()
-                .expect("max_alpha_sq failed be represented as u64"),
+            max_alpha_sq,
         }
     }
+
+    /// Iterate on the |α|² by steps of `step` (positive).
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.alpha_sq_step = step;
+        self
+    }
+
+    /// Current |α|², computed from the first one to avoid accumulating
+    /// rounding errors.
+    fn alpha_sq(&self) -> f64 {
+        self.first_alpha_sq + f64::from(self.step_index) * self.alpha_sq_step
+    }
 }
 
 impl Iterator for CodeParameterRange {
     type Item = CodeParameter;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.distance > self.max_distance {
-            None
-        } else {
-            let result = CodeParameter::new(
-                self.distance,
-                self.alpha_sq.to_f64().expect("alpha_sq doesn't fit in f64"),
-            );
-
-            if self.alpha_sq == self.max_alpha_sq {
-                self.distance += 2;
-                self.alpha_sq = 1;
-            } else {
-                self.alpha_sq += 1;
+        while self.distance <= self.max_distance {
+            let alpha_sq = self.alpha_sq();
+            if alpha_sq <= self.max_alpha_sq + ALPHA_SQ_TOLERANCE {
+                self.step_index += 1;
+                return Some(CodeParameter::new(
+                    self.distance,
+                    alpha_sq.min(self.max_alpha_sq),
+                ));
             }
-
-            Some(result)
+            if self.step_index == 0 && self.first_alpha_sq <= MIN_ALPHA_SQ {
+                // no |α|² at any distance (e.g. a NaN maximum)
+                return None;
+            }
+            self.distance += 2;
+            self.first_alpha_sq = MIN_ALPHA_SQ;
+            self.step_index = 0;
         }
+        None
     }
 }
 
@@ -165,7 +607,14 @@ impl ErrorCorrection for RepetitionCode {
         &self,
         lower_bound: Option<&Self::Parameter>,
     ) -> impl Iterator<Item = Self::Parameter> {
-        CodeParameterRange::new(lower_bound, 49, 30.0)
+        let cancellation = self.cancellation.clone();
+        CodeParameterRange::new(lower_bound, MAX_DISTANCE, MAX_ALPHA_SQ)
+            .with_step(self.alpha_sq_step)
+            .take_while(move |_| {
+                !cancellation
+                    .as_ref()
+                    .is_some_and(CancellationToken::is_cancelled)
+            })
     }
 
     fn physical_qubits(&self, parameter: &Self::Parameter) -> Result<u64, String> {
@@ -179,12 +628,10 @@ impl ErrorCorrection for RepetitionCode {
 
     fn logical_cycle_time(
         &self,
-        _qubit: &Self::Qubit,
+        qubit: &Self::Qubit,
         parameter: &Self::Parameter,
     ) -> Result<u64, String> {
-        // arXiv:2302.06639 (p. 28, repetition code cycle time in d code cycles)
-        // Time for one round : 5/κ₂
-        Ok(500 * parameter.distance) // ns, corresponds to 1/κ₂ = 100 ns
+        self.cycle_time(qubit, parameter).map(TimeSpan::as_ns)
     }
 
     fn logical_error_rate(
@@ -192,18 +639,11 @@ impl ErrorCorrection for RepetitionCode {
         qubit: &Self::Qubit,
         parameter: &Self::Parameter,
     ) -> Result<f64, String> {
-        if let (Some(code_distance_f64), Some(lzp), Some(lxp)) = (
-            f64::from_u64(parameter.distance),
-            self.logical_phaseflip_probability(qubit, parameter),
-            Self::logical_bitflip_probability(parameter),
-        ) {
-            // arXiv:2302.06639 (p. 4, eq. 3 and app E2, p. 27)
-            // this is eq. 3 in a more compact form
-            Ok(code_distance_f64 * (lzp + lxp)) // First: logical phase-flip, second part: logical
-                                                // bit-flip
-        } else {
-            Err("cannot compute logical failure probability".into())
+        let error_rate = self.cached_logical_error_rate(qubit, parameter);
+        if let Some(trace) = &self.trace {
+            trace.record(parameter, error_rate.as_ref().ok().copied());
         }
+        error_rate
     }
 
     fn compute_code_parameter(
@@ -211,7 +651,31 @@ impl ErrorCorrection for RepetitionCode {
         qubit: &Self::Qubit,
         required_logical_error_rate: f64,
     ) -> Result<Self::Parameter, String> {
-        self.compute_code_parameter_for_smallest_size(qubit, required_logical_error_rate)
+        let _span =
+            tracing::debug_span!("compute_code_parameter", required_logical_error_rate).entered();
+        if let Some(trace) = &self.trace {
+            trace.start(required_logical_error_rate);
+        }
+        let parameter = self.search_smallest(qubit, required_logical_error_rate, self.strategy);
+        if let Some(trace) = &self.trace {
+            trace.finish(parameter.as_ref().ok());
+        }
+        let parameter = parameter.map_err(|err| {
+            if self.is_cancelled() {
+                "search of code parameters cancelled".into()
+            } else {
+                format!(
+                    "{err} ({})",
+                    self.diagnose(qubit, required_logical_error_rate)
+                )
+            }
+        });
+        tracing::debug!(cache = %self.cache_statistics(), "logical error rates");
+        match &parameter {
+            Ok(parameter) => tracing::debug!(%parameter, "smallest code parameter"),
+            Err(err) => tracing::debug!(%err, "no code parameter"),
+        }
+        parameter
     }
 
     fn code_parameter_cmp(
@@ -231,11 +695,255 @@ impl ErrorCorrection for RepetitionCode {
             self.physical_qubits(p2),
             self.logical_cycle_time(qubit, p2),
         ) {
-            num_qubits1
-                .cmp(&num_qubits2)
-                .then(logical_cycle_time1.cmp(&logical_cycle_time2))
+            self.ordering.compare(
+                (num_qubits1, logical_cycle_time1),
+                (num_qubits2, logical_cycle_time2),
+            )
         } else {
             Ordering::Equal
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bit_flip::CxBitFlipTable, phase_flip::suppression, search::SearchStrategy, CodeParameter,
+        CodeParameterRange, RepetitionCode, MAX_ALPHA_SQ, MAX_DISTANCE,
+    };
+    use crate::qubit::CatQubit;
+
+    #[test]
+    fn new_snaps_even_distances() {
+        assert_eq!(CodeParameter::new(0, 1.0).distance(), 1);
+        assert_eq!(CodeParameter::new(1, 1.0).distance(), 1);
+        assert_eq!(CodeParameter::new(2, 1.0).distance(), 3);
+        assert_eq!(CodeParameter::new(48, 1.0).distance(), 49);
+        assert_eq!(CodeParameter::new(49, 1.0).distance(), 49);
+    }
+
+    #[test]
+    fn try_new_rejects_even_distances() {
+        let err = CodeParameter::try_new(4, 1.0).err();
+        assert!(err.is_some_and(|err| err.contains("odd") && err.contains("try 3 or 5")));
+
+        let err = CodeParameter::try_new(0, 1.0).err();
+        assert!(err.is_some_and(|err| err.ends_with("(try 1)")));
+
+        assert!(CodeParameter::try_new(5, 1.0).is_ok_and(|p| p.distance() == 5));
+    }
+
+    #[test]
+    fn try_new_rejects_non_positive_alpha_sq() {
+        assert!(CodeParameter::try_new(3, 0.0).is_err());
+        assert!(CodeParameter::try_new(3, -1.0).is_err());
+        assert!(CodeParameter::try_new(3, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn range_yields_odd_distances_from_even_lower_bound() {
+        // bypass the snapping of `CodeParameter::new`
+        let lower_bound = CodeParameter {
+            distance: 4,
+            alpha_sq: 1.0,
+        };
+        let distances: Vec<_> = CodeParameterRange::new(Some(&lower_bound), 11, 2.0)
+            .map(|p| p.distance())
+            .collect();
+        assert_eq!(distances, [5, 5, 7, 7, 9, 9, 11, 11]);
+    }
+
+    #[test]
+    fn range_boundaries() {
+        let all: Vec<_> = CodeParameterRange::new(None, 3, 2.0)
+            .map(|p| (p.distance(), p.alpha_sq()))
+            .collect();
+        assert_eq!(all, [(1, 1.0), (1, 2.0), (3, 1.0), (3, 2.0)]);
+
+        // lower bound at the maximum distance
+        let last: Vec<_> = CodeParameterRange::new(Some(&CodeParameter::new(3, 2.0)), 3, 2.0)
+            .map(|p| (p.distance(), p.alpha_sq()))
+            .collect();
+        assert_eq!(last, [(3, 2.0)]);
+
+        // lower bound above the maximum distance
+        assert_eq!(
+            CodeParameterRange::new(Some(&CodeParameter::new(5, 1.0)), 3, 2.0).count(),
+            0
+        );
+
+        // |α|² below 1 starts at 1
+        let first = CodeParameterRange::new(Some(&CodeParameter::new(1, 0.5)), 3, 2.0).next();
+        assert!(first.is_some_and(|p| p.distance() == 1 && (p.alpha_sq() - 1.0).abs() < 1e-12));
+    }
+
+    #[test]
+    fn range_keeps_fractional_lower_bounds() {
+        // |α|² of a factory, from the lower bound at its distance only
+        let all: Vec<_> = CodeParameterRange::new(Some(&CodeParameter::new(5, 7.15)), 7, 9.0)
+            .map(|p| (p.distance(), p.alpha_sq()))
+            .collect();
+        assert_eq!(all.len(), 2 + 9);
+        assert_eq!(all[0].0, 5);
+        assert!((all[0].1 - 7.15).abs() < 1e-12 && (all[1].1 - 8.15).abs() < 1e-12);
+        assert_eq!(all[2], (7, 1.0));
+
+        // a NaN lower bound starts at 1, a NaN maximum yields nothing
+        let bound = CodeParameter {
+            distance: 3,
+            alpha_sq: f64::NAN,
+        };
+        let first = CodeParameterRange::new(Some(&bound), 3, 2.0).next();
+        assert!(first.is_some_and(|p| (p.alpha_sq() - 1.0).abs() < 1e-12));
+        assert_eq!(CodeParameterRange::new(None, 3, f64::NAN).count(), 0);
+    }
+
+    #[test]
+    fn range_steps() {
+        let alphas: Vec<_> = CodeParameterRange::new(None, 1, 3.0)
+            .with_step(0.5)
+            .map(|p| p.alpha_sq())
+            .collect();
+        assert_eq!(alphas, [1.0, 1.5, 2.0, 2.5, 3.0]);
+
+        // no drift of the rounding errors, the maximum being reached exactly
+        let alphas: Vec<_> = CodeParameterRange::new(None, 1, 2.0)
+            .with_step(0.1)
+            .map(|p| p.alpha_sq())
+            .collect();
+        assert_eq!(alphas.len(), 11);
+        assert!(alphas
+            .last()
+            .is_some_and(|&last| (last - 2.0).abs() < f64::EPSILON));
+
+        assert!(RepetitionCode::new().with_alpha_sq_step(0.0).is_err());
+        assert!(RepetitionCode::new().with_alpha_sq_step(1e-12).is_err());
+        assert!(RepetitionCode::new().with_alpha_sq_step(0.01).is_ok());
+        assert!(RepetitionCode::new().with_alpha_sq_step(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn phase_flip_fits_are_validated() {
+        assert!(RepetitionCode::new().with_fit(5.6e-2, 0.86, 0.013).is_ok());
+        assert!(RepetitionCode::new().with_fit(5.6e-2, 0.0, 0.013).is_ok());
+        for (prefactor, alpha_exponent, threshold) in [
+            (0.0, 0.86, 0.013),
+            (-5.6e-2, 0.86, 0.013),
+            (f64::NAN, 0.86, 0.013),
+            (5.6e-2, -0.86, 0.013),
+            (5.6e-2, f64::INFINITY, 0.013),
+            (5.6e-2, 0.86, 0.0),
+            (5.6e-2, 0.86, -0.013),
+        ] {
+            assert!(
+                RepetitionCode::new()
+                    .with_fit(prefactor, alpha_exponent, threshold)
+                    .is_err(),
+                "{prefactor} {alpha_exponent} {threshold}"
+            );
+        }
+    }
+
+    #[test]
+    fn suppression_handles_extreme_distances() {
+        assert!((suppression(0.5, 3) - 0.25).abs() < 1e-15);
+        // exponents beyond i32, overflow and underflow
+        assert!(suppression(2.0, u64::MAX).is_infinite());
+        assert!(suppression(0.5, u64::MAX) == 0.0);
+        // subnormal results are flushed
+        assert!(1e-160_f64.powi(2).is_subnormal());
+        assert!(suppression(1e-160, 3) == 0.0);
+        assert!((suppression(1.0, u64::MAX) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn search_strategies_find_the_rectangular_optimum_in_fewer_evaluations() {
+        for k1_k2 in [1e-5, 1e-4, 1e-3] {
+            let qubit = CatQubit::new().with_k1_k2(k1_k2);
+            for required in [1e-4, 1e-7, 1e-10, 1e-13, 1e-16, 1e-30] {
+                // first parameter of the rectangular scan reaching the rate
+                let code = RepetitionCode::new();
+                let range = CodeParameterRange::new(None, MAX_DISTANCE, MAX_ALPHA_SQ);
+                let mut evaluations = 0;
+                let expected = range
+                    .inspect(|_| evaluations += 1)
+                    .find(|p| {
+                        code.unrecorded_logical_error_rate(&qubit, p)
+                            .is_ok_and(|rate| rate <= required)
+                    })
+                    .map(|p| (p.distance(), p.alpha_sq()));
+
+                for strategy in [SearchStrategy::Pruned, SearchStrategy::Diagonal] {
+                    let code = RepetitionCode::new();
+                    let found = code
+                        .search_smallest(&qubit, required, strategy)
+                        .ok()
+                        .map(|p| (p.distance(), p.alpha_sq()));
+                    assert_eq!(found, expected, "{strategy} at {k1_k2:e}, {required:e}");
+                    assert!(
+                        code.cache_statistics().misses <= evaluations,
+                        "{strategy} at {k1_k2:e}, {required:e}"
+                    );
+                }
+            }
+        }
+
+        // many points are skipped when no parameter reaches the rate
+        let qubit = CatQubit::new();
+        let rectangular = CodeParameterRange::new(None, MAX_DISTANCE, MAX_ALPHA_SQ).count();
+        for (strategy, fraction) in [
+            (SearchStrategy::Pruned, 0.9),
+            (SearchStrategy::Diagonal, 0.2),
+        ] {
+            let code = RepetitionCode::new();
+            assert!(code.search_smallest(&qubit, 1e-30, strategy).is_err());
+            let misses = code.cache_statistics().misses;
+            #[allow(clippy::cast_precision_loss)]
+            let evaluated = misses as f64 / rectangular as f64;
+            assert!(
+                evaluated < fraction,
+                "{strategy}: {misses} of {rectangular}"
+            );
+        }
+    }
+
+    #[test]
+    fn cx_bit_flip_table_interpolates_exponentially() {
+        let analytic = RepetitionCode::new();
+        let table = CxBitFlipTable::from_csv("alpha_sq,p_cx\n# measured\n6,3.07e-6\n2,9.16e-3\n")
+            .expect("valid table");
+        let measured = RepetitionCode::new().with_cx_bit_flip_table(table);
+        // exact for an exponential curve, inside and outside of the table
+        for alpha_sq in [1.0, 2.0, 3.5, 6.0, 10.0] {
+            let expected = analytic.cx_bit_flip_probability(alpha_sq);
+            let relative = measured.cx_bit_flip_probability(alpha_sq) / expected - 1.0;
+            assert!(relative.abs() < 1e-2, "|α|² = {alpha_sq}: {relative}");
+        }
+        let parameter = CodeParameter::new(11, 5.0);
+        let probability = |code: &RepetitionCode| {
+            code.logical_bitflip_probability(&parameter)
+                .expect("bit-flip probability")
+        };
+        let ratio = probability(&measured) / probability(&analytic);
+        assert!((ratio - 1.0).abs() < 1e-2);
+        assert!(CxBitFlipTable::from_csv("2,0.1\n2,0.2").is_err());
+        assert!(CxBitFlipTable::new(vec![(2.0, 0.0)]).is_err());
+    }
+
+    #[test]
+    fn code_parameters_are_checked_when_read() {
+        let json = serde_json::to_string(&CodeParameter::new(11, 12.5)).expect("parameter");
+        let read: CodeParameter = serde_json::from_str(&json).expect("saved parameter");
+        assert_eq!(read.distance(), 11);
+        assert!((read.alpha_sq() - 12.5).abs() < f64::EPSILON);
+        for invalid in [
+            r#"{"distance":4,"alpha_sq":1.0}"#,
+            r#"{"distance":3,"alpha_sq":0.0}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<CodeParameter>(invalid).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Bit-flip probability of the CX gates between cat qubits.
+//!
+//! By default, a CX gate flips the bits with the probability 0.5·exp(−2|α|²)
+//! estimated in [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 26,
+//! eq. D8). A [`CxBitFlipTable`] replaces it with a curve measured by process
+//! tomography, e.g. from the CSV
+//!
+//! ```csv
+//! alpha_sq,p_cx
+//! 2,1.1e-2
+//! 4,2.3e-4
+//! 6,5.0e-6
+//! ```
+//!
+//! The probability decreasing exponentially with |α|², its logarithm is
+//! interpolated linearly between the measured |α|², and extrapolated from the
+//! nearest two points outside of them (constant with a single point).
+
+use std::{fs::read_to_string, path::Path};
+
+use super::RepetitionCode;
+
+/// Bit-flip probability of a CX gate measured at several |α|².
+#[derive(Clone, Debug, PartialEq)]
+pub struct CxBitFlipTable {
+    // sorted by |α|², with distinct |α|² and positive probabilities
+    points: Vec<(f64, f64)>,
+}
+
+impl CxBitFlipTable {
+    /// Table of the (|α|², `p_CX`) `points`, in any order.
+    pub fn new(mut points: Vec<(f64, f64)>) -> Result<Self, String> {
+        if points.is_empty() {
+            return Err("no point in the CX bit-flip table".into());
+        }
+        for &(alpha_sq, probability) in &points {
+            if !(alpha_sq.is_finite() && alpha_sq > 0.0) {
+                return Err(format!("|α|² = {alpha_sq} is not positive"));
+            }
+            if !(probability > 0.0 && probability <= 1.0) {
+                return Err(format!(
+                    "CX bit-flip probability {probability} at |α|² = {alpha_sq} is not a \
+                     positive probability"
+                ));
+            }
+        }
+        points.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        if let Some(pair) = points
+            .windows(2)
+            .find(|pair| pair[0].0.total_cmp(&pair[1].0).is_eq())
+        {
+            return Err(format!("|α|² = {} is measured twice", pair[0].0));
+        }
+        Ok(Self { points })
+    }
+
+    /// Table of a CSV file (see [`CxBitFlipTable::from_csv`]).
+    pub fn from_file(filename: impl AsRef<Path>) -> Result<Self, String> {
+        let filename = filename.as_ref();
+        let content = read_to_string(filename)
+            .map_err(|err| format!("can't read {}: {err}", filename.display()))?;
+        Self::from_csv(&content)
+    }
+
+    /// Table of a CSV with |α|² and `p_CX` on each line, after an optional
+    /// header (`#` for comments).
+    pub fn from_csv(csv: &str) -> Result<Self, String> {
+        let mut points = vec![];
+        let mut header = false;
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line.split_once(',').and_then(|(alpha_sq, probability)| {
+                Some((
+                    alpha_sq.trim().parse().ok()?,
+                    probability.trim().parse().ok()?,
+                ))
+            });
+            match parsed {
+                Some(point) => points.push(point),
+                None if points.is_empty() && !header => header = true,
+                None => return Err(format!("line {}: expected |α|²,p_CX", index + 1)),
+            }
+        }
+        Self::new(points)
+    }
+
+    /// Measured (|α|², `p_CX`), by increasing |α|².
+    #[must_use]
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// Bit-flip probability of a CX gate at `alpha_sq`.
+    #[must_use]
+    pub fn probability(&self, alpha_sq: f64) -> f64 {
+        let segment = match self.points.as_slice() {
+            [(_, probability)] => return *probability,
+            points => {
+                // first segment ending above alpha_sq, or the last one
+                let end = points
+                    .iter()
+                    .position(|&(measured, _)| measured > alpha_sq)
+                    .unwrap_or(points.len() - 1)
+                    .clamp(1, points.len() - 1);
+                (points[end - 1], points[end])
+            }
+        };
+        let ((a0, p0), (a1, p1)) = segment;
+        let slope = (p1.ln() - p0.ln()) / (a1 - a0);
+        (p0.ln() + slope * (alpha_sq - a0)).exp().min(1.0)
+    }
+
+    /// Short description of the table, reported with the estimates.
+    #[must_use]
+    pub fn description(&self) -> String {
+        let first = self.points[0].0;
+        let last = self.points[self.points.len() - 1].0;
+        format!(
+            "measured ({} points, |α|² from {first} to {last})",
+            self.points.len()
+        )
+    }
+}
+
+impl RepetitionCode {
+    #[must_use]
+    /// Replace the bit-flip probability 0.5·exp(−2|α|²) of the CX gates with
+    /// a measured `table`.
+    pub fn with_cx_bit_flip_table(mut self, table: CxBitFlipTable) -> Self {
+        self.cx_bit_flip = Some(table.into());
+        self.reset_cache();
+        self
+    }
+
+    #[must_use]
+    /// Measured bit-flip probabilities of the CX gates, if any.
+    pub fn cx_bit_flip_table(&self) -> Option<&CxBitFlipTable> {
+        self.cx_bit_flip.as_deref()
+    }
+
+    #[must_use]
+    /// Bit-flip probability of a CX gate at `alpha_sq`, measured or
+    /// 0.5·exp(−2|α|²) (arXiv:2302.06639, p. 26, eq. D8).
+    pub fn cx_bit_flip_probability(&self, alpha_sq: f64) -> f64 {
+        match &self.cx_bit_flip {
+            Some(table) => table.probability(alpha_sq),
+            // numerically estimated using full process tomography
+            None => 0.5 * (-2.0 * alpha_sq).exp(),
+        }
+    }
+
+    #[must_use]
+    /// Short description of the bit-flip probability of the CX gates.
+    pub fn cx_bit_flip_description(&self) -> String {
+        self.cx_bit_flip
+            .as_ref()
+            .map_or_else(|| "0.5·exp(−2|α|²)".into(), |table| table.description())
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Memoization of the logical error rates.
+//!
+//! The searches of code parameters evaluate the same (distance, |α|²) pairs
+//! for each required logical error rate, i.e. for each point of a frontier and
+//! for each factory. The clones of a [`RepetitionCode`] share an
+//! [`ErrorRateCache`], keyed by the qubits and the code parameter, which is
+//! reset when the models of the code change. [`CacheStatistics`] tell how
+//! often it is hit.
+
+use std::{
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap},
+    fmt::Display,
+    rc::Rc,
+};
+
+use super::{CodeParameter, RepetitionCode};
+use crate::qubit::CatQubit;
+
+/// Hits and misses of the cache of logical error rates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStatistics {
+    /// Logical error rates found in the cache.
+    pub hits: u64,
+    /// Logical error rates computed.
+    pub misses: u64,
+}
+
+impl CacheStatistics {
+    /// Fraction of the evaluations found in the cache, 0 if there was none.
+    #[must_use]
+    pub fn hit_rate(&self) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let (hits, total) = (self.hits as f64, (self.hits + self.misses) as f64);
+        if total > 0.0 {
+            hits / total
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Display for CacheStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate)",
+            self.hits,
+            self.misses,
+            100.0 * self.hit_rate()
+        )
+    }
+}
+
+/// Logical error rates of one qubit, by distance and |α|² (as bits).
+type QubitRates = HashMap<(u64, u64), Result<f64, String>>;
+
+/// Logical error rates already computed, for each qubit.
+#[derive(Default)]
+pub(crate) struct ErrorRateCache {
+    // few different qubits are used at once (e.g. the computation qubits and
+    // the relaxed ones of a diagnosis), a list is enough
+    rates: Vec<(CatQubit, QubitRates)>,
+    statistics: CacheStatistics,
+}
+
+impl ErrorRateCache {
+    /// Rate of `parameter` for `qubit`, computed with `compute` if it is not
+    /// in the cache.
+    fn get_or_compute(
+        &mut self,
+        qubit: &CatQubit,
+        parameter: &CodeParameter,
+        compute: impl FnOnce() -> Result<f64, String>,
+    ) -> Result<f64, String> {
+        let index = self
+            .rates
+            .iter()
+            .position(|(cached, _)| cached == qubit)
+            .unwrap_or_else(|| {
+                self.rates.push((qubit.clone(), HashMap::new()));
+                self.rates.len() - 1
+            });
+        let key = (parameter.distance, parameter.alpha_sq.to_bits());
+        match self.rates[index].1.entry(key) {
+            Entry::Occupied(entry) => {
+                self.statistics.hits += 1;
+                entry.get().clone()
+            }
+            Entry::Vacant(entry) => {
+                self.statistics.misses += 1;
+                entry.insert(compute()).clone()
+            }
+        }
+    }
+}
+
+impl RepetitionCode {
+    /// Reset the cache of logical error rates, after a change of the models
+    /// (the clones keep the previous one).
+    pub(crate) fn reset_cache(&mut self) {
+        self.cache = Rc::new(RefCell::default());
+    }
+
+    /// Logical error rate per logical cycle, computed once for each qubit and
+    /// code parameter.
+    pub(crate) fn cached_logical_error_rate(
+        &self,
+        qubit: &CatQubit,
+        parameter: &CodeParameter,
+    ) -> Result<f64, String> {
+        self.cache
+            .borrow_mut()
+            .get_or_compute(qubit, parameter, || {
+                self.unrecorded_logical_error_rate(qubit, parameter)
+            })
+    }
+
+    #[must_use]
+    /// Hits and misses of the cache of logical error rates, shared with the
+    /// clones of the code (e.g. the ones of an estimation).
+    pub fn cache_statistics(&self) -> CacheStatistics {
+        self.cache.borrow().statistics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resource_estimator::estimates::ErrorCorrection;
+
+    use super::CacheStatistics;
+    use crate::{
+        code::{
+            bit_flip::CxBitFlipTable, schedule::CycleSchedule, search::SearchStrategy,
+            CodeParameter,
+        },
+        qubit::CatQubit,
+        RepetitionCode,
+    };
+
+    #[test]
+    fn repeated_searches_hit_the_cache() {
+        let code = RepetitionCode::new().with_search_strategy(SearchStrategy::Pruned);
+        let qubit = CatQubit::new();
+        let search = || {
+            code.compute_code_parameter(&qubit, 1e-8)
+                .expect("a parameter is found")
+        };
+
+        let first = search();
+        let after_first = code.cache_statistics();
+        assert!(after_first.misses > 0);
+
+        let second = search();
+        let after_second = code.cache_statistics();
+        assert_eq!(second.distance(), first.distance());
+        assert_eq!(after_second.misses, after_first.misses);
+        assert!(after_second.hits > after_first.hits);
+        // the clones share the cache
+        assert_eq!(code.clone().cache_statistics(), after_second);
+    }
+
+    #[test]
+    fn model_changes_reset_the_cache() {
+        let code = RepetitionCode::new();
+        let qubit = CatQubit::new();
+        let parameter = CodeParameter::new(11, 10.0);
+        code.logical_error_rate(&qubit, &parameter)
+            .expect("error rate");
+        let statistics = code.cache_statistics();
+        assert_eq!(statistics, CacheStatistics { hits: 0, misses: 1 });
+
+        let table = CxBitFlipTable::new(vec![(10.0, 1e-9)]).expect("valid table");
+        for changed in [
+            code.clone().with_leakage_fit(0.1, 0.1),
+            code.clone()
+                .with_schedule(CycleSchedule::default())
+                .expect("valid schedule"),
+            code.clone().with_cx_bit_flip_table(table),
+        ] {
+            assert_eq!(changed.cache_statistics(), CacheStatistics::default());
+            changed
+                .logical_error_rate(&qubit, &parameter)
+                .expect("error rate");
+            assert_eq!(changed.cache_statistics().misses, 1);
+            // the original code keeps its cache
+            assert_eq!(code.cache_statistics(), statistics);
+        }
+    }
+}
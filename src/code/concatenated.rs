@@ -0,0 +1,225 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Repetition code concatenated inside a small outer bit-flip code.
+//!
+//! The repetition code only corrects phase flips: its logical bit-flip
+//! probability, 2(d-1)·0.5·exp(-2|α|²) per round, only decreases with |α|²
+//! and grows with the distance. For extremely low target errors, this
+//! bit-flip floor can be pushed down by encoding `d_out` repetition code blocks
+//! into an outer repetition code correcting bit flips.
+//!
+//! Model of the outer code:
+//! - `d_out` data blocks and `d_out - 1` ancilla blocks, all of them being
+//!   repetition codes with the same parameters,
+//! - a round of the outer code measures the Z⊗Z stabilizers with 2 logical CX
+//!   and a measurement, i.e. 2.2 logical cycles of the inner code (same cost as
+//!   a logical CX in [`crate::LogicalCounts`]),
+//! - a logical cycle of the outer code consists of `d_out` rounds,
+//! - logical bit flips follow the usual repetition code scaling
+//!   `prefactor * (p / threshold)^((d_out+1)/2)` per round, with p the inner
+//!   bit-flip probability of a block over a round, and the phenomenological
+//!   threshold of about 10% (syndrome bits being measured by faulty ancillas),
+//! - a phase flip on any data block is a logical phase flip.
+//!
+//! Magic states produced by the Toffoli factories are not protected by the
+//! outer code.
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use resource_estimator::estimates::ErrorCorrection;
+use std::cmp::Ordering;
+
+use super::{phase_flip::suppression, CodeParameter, RepetitionCode};
+use crate::qubit::CatQubit;
+
+/// Repetition code (phase flips) concatenated in an outer repetition code
+/// (bit flips).
+#[derive(Clone)]
+pub struct ConcatenatedCode {
+    inner: RepetitionCode,
+    outer_distance: u64,
+    outer_round_cycles: f64,
+    outer_prefactor: f64,
+    outer_threshold: f64,
+}
+
+impl ConcatenatedCode {
+    /// Concatenate `inner` in an outer code of distance `outer_distance`,
+    /// which must be odd.
+    pub fn new(inner: RepetitionCode, outer_distance: u64) -> Result<Self, String> {
+        if outer_distance.is_multiple_of(2) {
+            return Err(format!(
+                "outer distance {outer_distance} must be odd (repetition code)"
+            ));
+        }
+        Ok(Self {
+            inner,
+            outer_distance,
+            outer_round_cycles: 2.2,
+            outer_prefactor: 0.1,
+            outer_threshold: 0.1,
+        })
+    }
+
+    #[must_use]
+    /// Inner repetition code.
+    pub fn inner(&self) -> &RepetitionCode {
+        &self.inner
+    }
+
+    #[must_use]
+    /// Distance of the outer code.
+    pub fn outer_distance(&self) -> u64 {
+        self.outer_distance
+    }
+
+    #[must_use]
+    /// Set the fit of the outer code logical bit-flip probability per round,
+    /// `prefactor * (p / threshold)^((d_out+1)/2)`.
+    pub fn with_outer_fit(mut self, prefactor: f64, threshold: f64) -> Self {
+        self.outer_prefactor = prefactor;
+        self.outer_threshold = threshold;
+        self
+    }
+
+    /// Number of repetition code blocks (data and ancillas).
+    fn blocks(&self) -> u64 {
+        2 * self.outer_distance - 1
+    }
+}
+
+impl ErrorCorrection for ConcatenatedCode {
+    type Qubit = CatQubit;
+    type Parameter = CodeParameter;
+
+    fn code_parameter_range(
+        &self,
+        lower_bound: Option<&Self::Parameter>,
+    ) -> impl Iterator<Item = Self::Parameter> {
+        self.inner.code_parameter_range(lower_bound)
+    }
+
+    fn physical_qubits(&self, parameter: &Self::Parameter) -> Result<u64, String> {
+        Ok(self.blocks() * self.inner.physical_qubits(parameter)?)
+    }
+
+    fn logical_qubits(&self, _parameter: &Self::Parameter) -> Result<u64, String> {
+        Ok(1)
+    }
+
+    fn logical_cycle_time(
+        &self,
+        qubit: &Self::Qubit,
+        parameter: &Self::Parameter,
+    ) -> Result<u64, String> {
+        let inner_cycle_time = self.inner.logical_cycle_time(qubit, parameter)?;
+
+        f64::from_u64(self.outer_distance * inner_cycle_time)
+            .and_then(|time| (time * self.outer_round_cycles).round().to_u64())
+            .ok_or_else(|| "cannot compute logical cycle time".into())
+    }
+
+    fn logical_error_rate(
+        &self,
+        qubit: &Self::Qubit,
+        parameter: &Self::Parameter,
+    ) -> Result<f64, String> {
+        let inner_error_rate = self.inner.logical_error_rate(qubit, parameter)?;
+        let (Some(inner_bit_flip), Some(outer_distance)) = (
+            self.inner.logical_bitflip_rate(parameter),
+            f64::from_u64(self.outer_distance),
+        ) else {
+            return Err("cannot compute logical failure probability".into());
+        };
+        let inner_phase_flip = inner_error_rate - inner_bit_flip;
+
+        // per round of the outer code
+        let bit_flip = self.outer_prefactor
+            * suppression(
+                inner_bit_flip * self.outer_round_cycles / self.outer_threshold,
+                self.outer_distance,
+            );
+        // any phase flip on a data block during the logical cycle
+        let phase_flip = outer_distance * inner_phase_flip * self.outer_round_cycles;
+
+        Ok(outer_distance * (bit_flip + phase_flip))
+    }
+
+    fn compute_code_parameter(
+        &self,
+        qubit: &Self::Qubit,
+        required_logical_error_rate: f64,
+    ) -> Result<Self::Parameter, String> {
+        self.compute_code_parameter_for_smallest_size(qubit, required_logical_error_rate)
+    }
+
+    fn code_parameter_cmp(
+        &self,
+        qubit: &Self::Qubit,
+        p1: &Self::Parameter,
+        p2: &Self::Parameter,
+    ) -> Ordering {
+        // Same order as the inner code, the outer code multiplies qubits and
+        // cycle time by constant factors.
+        self.inner.code_parameter_cmp(qubit, p1, p2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resource_estimator::estimates::ErrorCorrection;
+
+    use super::ConcatenatedCode;
+    use crate::{
+        code::{CodeParameter, RepetitionCode},
+        qubit::CatQubit,
+    };
+
+    #[test]
+    fn outer_distances_are_odd() {
+        for distance in [0, 2, 4] {
+            assert!(ConcatenatedCode::new(RepetitionCode::new(), distance).is_err());
+        }
+        assert!(ConcatenatedCode::new(RepetitionCode::new(), 1).is_ok());
+    }
+
+    #[test]
+    fn outer_code_multiplies_qubits_and_cycle_time() {
+        let qubit = CatQubit::new();
+        let inner = RepetitionCode::new();
+        let parameter = CodeParameter::new(11, 10.0);
+        let code = ConcatenatedCode::new(inner.clone(), 5).expect("odd distance");
+
+        // 2·5 − 1 blocks, a logical cycle of 5 rounds of 2.2 inner cycles
+        let inner_qubits = inner.physical_qubits(&parameter).expect("qubits");
+        assert_eq!(code.physical_qubits(&parameter), Ok(9 * inner_qubits));
+        let inner_time = inner
+            .logical_cycle_time(&qubit, &parameter)
+            .expect("cycle time");
+        #[allow(clippy::cast_precision_loss)]
+        let expected = (5.0 * 2.2 * inner_time as f64).round();
+        #[allow(clippy::cast_precision_loss)]
+        let time = code
+            .logical_cycle_time(&qubit, &parameter)
+            .expect("cycle time") as f64;
+        assert!((time - expected).abs() < f64::EPSILON, "{time} {expected}");
+    }
+
+    #[test]
+    fn outer_code_suppresses_the_bit_flip_floor() {
+        let qubit = CatQubit::new();
+        // phase flips negligible, bit flips about 1e-4 per inner cycle
+        let parameter = CodeParameter::new(31, 8.0);
+        let floor = RepetitionCode::new()
+            .logical_bitflip_rate(&parameter)
+            .expect("bit-flip rate");
+        let rate = |outer_distance| {
+            ConcatenatedCode::new(RepetitionCode::new(), outer_distance)
+                .expect("odd distance")
+                .logical_error_rate(&qubit, &parameter)
+                .expect("error rate")
+        };
+        assert!(rate(3) < floor, "{} {floor}", rate(3));
+        assert!(rate(9) < 1e-6 * rate(3), "{} {}", rate(9), rate(3));
+    }
+}
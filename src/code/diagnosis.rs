@@ -0,0 +1,136 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Diagnosis of unreachable logical error rates.
+//!
+//! The search of code parameters stops at distance 49 and |α|² = 30 (or the
+//! maximum |α|² reachable by the qubits). When no parameter within these
+//! bounds reaches the required logical error rate, an [`Infeasibility`] reports
+//! the best error rate which can be reached, the gap to the requirement, and
+//! the [`Limit`] to relax: the distance cap, the |α|² cap, or κ₁/κ₂ when no
+//! larger code helps (the phase flips are then above the threshold, or the bit
+//! flips dominate).
+
+use std::fmt::Display;
+
+use resource_estimator::estimates::ErrorCorrection;
+
+use super::{CodeParameter, CodeParameterRange, RepetitionCode, MAX_ALPHA_SQ, MAX_DISTANCE};
+use crate::qubit::CatQubit;
+
+/// Distance up to which a larger distance cap is tried.
+const RELAXED_DISTANCE: u64 = 2 * MAX_DISTANCE + 1;
+
+/// |α|² up to which a larger |α|² cap is tried.
+const RELAXED_ALPHA_SQ: f64 = 2.0 * MAX_ALPHA_SQ;
+
+/// Bound preventing the required logical error rate from being reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    /// The maximum code distance.
+    Distance,
+    /// The maximum |α|² (of the search, or reachable by the qubits).
+    AlphaSq,
+    /// Both the maximum distance and |α|², which must be relaxed together.
+    DistanceAndAlphaSq,
+    /// The physical error rates (κ₁/κ₂, thermal photons, leakage,
+    /// measurement errors): a larger code does not help.
+    K1K2,
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Distance => write!(f, "the distance cap ({MAX_DISTANCE})"),
+            Self::AlphaSq => write!(f, "the |ɑ|² cap"),
+            Self::DistanceAndAlphaSq => write!(f, "both the distance and |ɑ|² caps"),
+            Self::K1K2 => write!(f, "κ₁/κ₂ (or the other physical error rates)"),
+        }
+    }
+}
+
+/// Why a required logical error rate cannot be reached.
+#[derive(Clone)]
+pub struct Infeasibility {
+    /// Required logical error rate per logical cycle.
+    pub required: f64,
+    /// Best logical error rate per logical cycle within the bounds, with its
+    /// parameters, if any can be computed.
+    pub best: Option<(CodeParameter, f64)>,
+    /// Bound to relax to reach the requirement.
+    pub binding: Limit,
+}
+
+impl Infeasibility {
+    /// Ratio between the best error rate within the bounds and the required
+    /// one, if any can be computed.
+    #[must_use]
+    pub fn gap(&self) -> Option<f64> {
+        self.best
+            .as_ref()
+            .map(|(_, error_rate)| error_rate / self.required)
+    }
+}
+
+impl Display for Infeasibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.best, self.gap()) {
+            (Some((parameter, error_rate)), Some(gap)) => write!(
+                f,
+                "best reachable logical error rate is {error_rate:.3e} (distance {parameter}), \
+                 {gap:.1e}× the required {:.3e}; limited by {}",
+                self.required, self.binding
+            ),
+            _ => write!(
+                f,
+                "no logical error rate can be computed; limited by {}",
+                self.binding
+            ),
+        }
+    }
+}
+
+impl RepetitionCode {
+    /// Smallest logical error rate per logical cycle reachable by `qubit`
+    /// with distances up to `max_distance` and |α|² up to `max_alpha_sq`.
+    fn best_error_rate(
+        &self,
+        qubit: &CatQubit,
+        max_distance: u64,
+        max_alpha_sq: f64,
+    ) -> Option<(CodeParameter, f64)> {
+        CodeParameterRange::new(None, max_distance, max_alpha_sq)
+            .with_step(self.alpha_sq_step)
+            .filter_map(|parameter| {
+                let error_rate = self.logical_error_rate(qubit, &parameter).ok()?;
+                Some((parameter, error_rate))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    #[must_use]
+    /// Diagnose why no code parameter reaches the logical error rate
+    /// `required` (per logical cycle), by relaxing the distance and |α|²
+    /// caps.
+    pub fn diagnose(&self, qubit: &CatQubit, required: f64) -> Infeasibility {
+        let reaches = |qubit: &CatQubit, max_distance, max_alpha_sq| {
+            self.best_error_rate(qubit, max_distance, max_alpha_sq)
+                .is_some_and(|(_, error_rate)| error_rate <= required)
+        };
+        let uncapped = qubit.clone().without_max_alpha_sq();
+        let binding = if reaches(qubit, RELAXED_DISTANCE, MAX_ALPHA_SQ) {
+            Limit::Distance
+        } else if reaches(&uncapped, MAX_DISTANCE, RELAXED_ALPHA_SQ) {
+            Limit::AlphaSq
+        } else if reaches(&uncapped, RELAXED_DISTANCE, RELAXED_ALPHA_SQ) {
+            Limit::DistanceAndAlphaSq
+        } else {
+            Limit::K1K2
+        };
+        Infeasibility {
+            required,
+            best: self.best_error_rate(qubit, MAX_DISTANCE, MAX_ALPHA_SQ),
+            binding,
+        }
+    }
+}
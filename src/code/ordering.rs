@@ -0,0 +1,226 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Comparison of the code parameters.
+//!
+//! The estimation compares code parameters to choose among the ones reaching
+//! a required logical error rate ([`ErrorCorrection::code_parameter_cmp`]).
+//! By default, the fewest physical qubits win, the logical cycle time breaking
+//! ties, which favours small distances; a [`ParameterOrdering`] set with
+//! [`RepetitionCode::with_parameter_ordering`] optimizes speed or the
+//! space-time volume instead.
+//!
+//! [`ErrorCorrection::code_parameter_cmp`]:
+//!     resource_estimator::estimates::ErrorCorrection::code_parameter_cmp
+
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use super::RepetitionCode;
+
+/// Order of the code parameters, from the best one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterOrdering {
+    /// Fewest physical qubits, then shortest logical cycle.
+    #[default]
+    Qubits,
+    /// Shortest logical cycle, then fewest physical qubits.
+    Time,
+    /// Smallest space-time volume (physical qubits × logical cycle time).
+    Volume,
+    /// Smallest qubits^w × time^(1 − w), for a weight w of the qubits
+    /// between 0 (time only) and 1 (qubits only); 0.5 orders by volume.
+    Weighted(QubitWeight),
+}
+
+/// Weight of the qubits in [`ParameterOrdering::Weighted`], between 0 and 1.
+///
+/// Deserialization checks the weight as [`QubitWeight::new`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct QubitWeight(f64);
+
+impl QubitWeight {
+    /// Weight `qubit_weight` of the qubits, which must be between 0 and 1.
+    pub fn new(qubit_weight: f64) -> Result<Self, String> {
+        if (0.0..=1.0).contains(&qubit_weight) {
+            Ok(Self(qubit_weight))
+        } else {
+            Err(format!(
+                "weight of the qubits {qubit_weight} is not between 0 and 1"
+            ))
+        }
+    }
+
+    #[must_use]
+    /// Weight of the qubits, the one of the time being its complement to 1.
+    pub fn value(self) -> f64 {
+        self.0
+    }
+}
+
+impl TryFrom<f64> for QubitWeight {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<QubitWeight> for f64 {
+    fn from(weight: QubitWeight) -> Self {
+        weight.0
+    }
+}
+
+impl ParameterOrdering {
+    /// Weighted ordering, with a weight `qubit_weight` of the qubits between 0
+    /// and 1.
+    pub fn weighted(qubit_weight: f64) -> Result<Self, String> {
+        QubitWeight::new(qubit_weight).map(Self::Weighted)
+    }
+
+    /// Compare two code parameters from their physical qubits and logical
+    /// cycle times.
+    #[must_use]
+    pub fn compare(self, (qubits1, time1): (u64, u64), (qubits2, time2): (u64, u64)) -> Ordering {
+        let by_qubits = qubits1.cmp(&qubits2);
+        let by_time = time1.cmp(&time2);
+        match self {
+            Self::Qubits => by_qubits.then(by_time),
+            Self::Time => by_time.then(by_qubits),
+            Self::Volume => u128::from(qubits1)
+                .saturating_mul(u128::from(time1))
+                .cmp(&u128::from(qubits2).saturating_mul(u128::from(time2)))
+                .then(by_qubits),
+            Self::Weighted(QubitWeight(weight)) => {
+                // compared in logarithms, to avoid overflows
+                let cost = |qubits: u64, time: u64| {
+                    let ln = |value: u64| value.max(1).to_f64().unwrap_or(f64::MAX).ln();
+                    weight * ln(qubits) + (1.0 - weight) * ln(time)
+                };
+                cost(qubits1, time1)
+                    .total_cmp(&cost(qubits2, time2))
+                    .then(by_qubits)
+            }
+        }
+    }
+}
+
+impl Display for ParameterOrdering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Qubits => write!(f, "qubits"),
+            Self::Time => write!(f, "time"),
+            Self::Volume => write!(f, "volume"),
+            Self::Weighted(weight) => write!(f, "weighted:{}", weight.value()),
+        }
+    }
+}
+
+impl FromStr for ParameterOrdering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "qubits" => Ok(Self::Qubits),
+            "time" => Ok(Self::Time),
+            "volume" => Ok(Self::Volume),
+            _ => match s.strip_prefix("weighted:") {
+                Some(weight) => Self::weighted(
+                    weight
+                        .parse()
+                        .map_err(|_| format!("'{weight}' is not a weight of the qubits"))?,
+                ),
+                None => Err(format!(
+                    "unknown ordering '{s}' (expected qubits, time, volume or weighted:W)"
+                )),
+            },
+        }
+    }
+}
+
+impl RepetitionCode {
+    #[must_use]
+    /// Compare the code parameters with `ordering` (by default, fewest
+    /// physical qubits first).
+    pub fn with_parameter_ordering(mut self, ordering: ParameterOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    #[must_use]
+    /// Order of the code parameters.
+    pub fn parameter_ordering(&self) -> ParameterOrdering {
+        self.ordering
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resource_estimator::estimates::ErrorCorrection;
+
+    use super::{ParameterOrdering, QubitWeight};
+    use crate::{
+        code::schedule::{CycleSchedule, RoundsPerCycle},
+        qubit::{CatQubit, CxImplementation},
+        RepetitionCode,
+    };
+
+    /// Best parameter reaching a logical error rate of 1e-10 with `ordering`,
+    /// among all the parameters of the code.
+    fn best_parameter(ordering: ParameterOrdering) -> (u64, f64) {
+        // with adiabatic CX gates and a single round per cycle, larger |α|²
+        // shorten the cycles, and larger distances allow them
+        let qubit = CatQubit::new()
+            .with_cx_implementation(CxImplementation::Adiabatic)
+            .with_k1_k2(1e-4);
+        let schedule = CycleSchedule::new(3.0, RoundsPerCycle::Fixed(1)).expect("valid schedule");
+        let code = RepetitionCode::new()
+            .with_schedule(schedule)
+            .expect("valid schedule")
+            .with_parameter_ordering(ordering);
+        code.code_parameter_range(None)
+            .filter(|parameter| {
+                code.logical_error_rate(&qubit, parameter)
+                    .is_ok_and(|rate| rate <= 1e-10)
+            })
+            .min_by(|p1, p2| code.code_parameter_cmp(&qubit, p1, p2))
+            .map(|parameter| (parameter.distance(), parameter.alpha_sq()))
+            .expect("a parameter reaches the error rate")
+    }
+
+    #[test]
+    fn orderings_trade_qubits_for_time() {
+        let (qubits_distance, qubits_alpha_sq) = best_parameter(ParameterOrdering::Qubits);
+        for ordering in [
+            ParameterOrdering::Time,
+            ParameterOrdering::Volume,
+            ParameterOrdering::weighted(0.8).expect("valid weight"),
+        ] {
+            let (distance, alpha_sq) = best_parameter(ordering);
+            assert!(distance > qubits_distance, "{ordering}");
+            assert!(alpha_sq > qubits_alpha_sq, "{ordering}");
+        }
+    }
+
+    #[test]
+    fn weights_are_between_0_and_1() {
+        for weight in [-0.1, 1.1, f64::NAN] {
+            assert!(QubitWeight::new(weight).is_err());
+            assert!(format!("weighted:{weight}")
+                .parse::<ParameterOrdering>()
+                .is_err());
+        }
+        assert!(serde_json::from_str::<ParameterOrdering>(r#"{"weighted":2.0}"#).is_err());
+        let ordering = ParameterOrdering::weighted(0.5).expect("valid weight");
+        let json = serde_json::to_string(&ordering).expect("ordering is serialized");
+        assert_eq!(
+            serde_json::from_str::<ParameterOrdering>(&json).ok(),
+            Some(ordering)
+        );
+    }
+}
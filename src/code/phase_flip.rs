@@ -0,0 +1,112 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Models of the logical phase-flip probability of the repetition code.
+//!
+//! [`RepetitionCode`](crate::RepetitionCode) delegates the computation of its
+//! logical phase-flip probability per round to a [`PhaseFlipModel`]. The
+//! default one is the fit of
+//! [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 3, eq. 4), but
+//! other fits (from other simulations or papers) can be plugged in, either as
+//! a [`PowerLawFit`] with different constants, or as any closure taking the
+//! effective κ₁/κ₂ and the code parameters.
+//!
+//! The effective κ₁/κ₂ given to the models accounts for the qubit (thermal
+//! photons, see [`CatQubit::effective_k1_k2`](crate::CatQubit::effective_k1_k2)) and for the duration of the
+//! code rounds relative to the 5/κ₂ of the fit.
+//!
+//! The suppression of the errors with the distance, computed by
+//! [`suppression`], holds for any distance: scans of extreme parameters give
+//! infinite or zero probabilities rather than failing.
+
+use num_traits::ToPrimitive;
+
+use super::CodeParameter;
+
+/// Model of the logical phase-flip probability per round of the repetition
+/// code.
+pub trait PhaseFlipModel {
+    /// Logical phase-flip probability per round, [`None`] if it can't be
+    /// computed for these parameters.
+    fn logical_phaseflip_probability(&self, k1_k2: f64, parameter: &CodeParameter) -> Option<f64>;
+
+    /// Short description of the model, reported with the estimates.
+    fn description(&self) -> String {
+        "custom".into()
+    }
+}
+
+impl<F> PhaseFlipModel for F
+where
+    F: Fn(f64, &CodeParameter) -> Option<f64>,
+{
+    fn logical_phaseflip_probability(&self, k1_k2: f64, parameter: &CodeParameter) -> Option<f64> {
+        self(k1_k2, parameter)
+    }
+}
+
+/// Suppression `base^((distance + 1) / 2)` of the errors by a code of
+/// distance `distance`.
+///
+/// It is computed in f64 for any distance: a result too large to be
+/// represented is infinite (physical errors above the threshold), and a result
+/// too small to be represented normally (subnormal) is flushed to 0.
+#[must_use]
+pub fn suppression(base: f64, distance: u64) -> f64 {
+    let exponent = distance.div_ceil(2);
+    let power = match i32::try_from(exponent) {
+        Ok(exponent) => base.powi(exponent),
+        Err(_) => base.powf(exponent.to_f64().unwrap_or(f64::INFINITY)),
+    };
+    if power.is_subnormal() {
+        0.0
+    } else {
+        power
+    }
+}
+
+/// Power-law fit `prefactor * (|α|^(2·alpha_exponent) κ₁/κ₂ / threshold)^((d+1)/2)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerLawFit {
+    /// Prefactor of the fit.
+    pub prefactor: f64,
+    /// Exponent of |α|² in the effective physical phase-flip rate.
+    pub alpha_exponent: f64,
+    /// Threshold (κ₁/κ₂)_th.
+    pub threshold: f64,
+}
+
+impl Default for PowerLawFit {
+    /// Fit from [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 29,
+    /// Fig. 26), with threshold 0.013 (p. 4, Eq. (3), p. 28, Fig. 26).
+    fn default() -> Self {
+        Self {
+            prefactor: 5.6e-2,
+            alpha_exponent: 0.86,
+            threshold: 0.013,
+        }
+    }
+}
+
+impl PhaseFlipModel for PowerLawFit {
+    fn logical_phaseflip_probability(&self, k1_k2: f64, parameter: &CodeParameter) -> Option<f64> {
+        // Logical phase-flip error rate per cycle of the repetition code
+        // arXiv:2302.06639 (p. 3, eq. 4)
+        Some(
+            self.prefactor
+                * suppression(
+                    (parameter.alpha_sq().powf(self.alpha_exponent) * k1_k2) / self.threshold,
+                    parameter.distance(),
+                ),
+        )
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "power law ({:e} · (|α|^{} κ₁/κ₂ / {})^((d+1)/2))",
+            self.prefactor,
+            2.0 * self.alpha_exponent,
+            self.threshold
+        )
+    }
+}
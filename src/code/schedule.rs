@@ -0,0 +1,164 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Schedule of the syndrome extraction of the repetition code.
+//!
+//! In [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 28), a round
+//! of the repetition code takes 5 steps of duration 1/κ₂ (ancilla preparation,
+//! 2 CX gates, measurement over 2 steps), and a logical cycle consists of d
+//! rounds. [`CycleSchedule`] allows to model other syndrome extraction
+//! circuits.
+//!
+//! Each ancilla is always coupled to its 2 neighbouring data qubits, so a round
+//! always contains 2 layers of CX gates; their duration is set by the
+//! [`CxImplementation`](crate::CxImplementation) of the qubits.
+
+use serde::{Deserialize, Serialize};
+
+/// Number of rounds in a logical cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundsPerCycle {
+    /// As many rounds as the code distance.
+    Distance,
+    /// Fixed number of rounds, whatever the distance.
+    Fixed(u64),
+}
+
+/// Schedule of a logical cycle of the repetition code.
+///
+/// Deserialization checks the schedule as [`CycleSchedule::new`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawCycleSchedule")]
+pub struct CycleSchedule {
+    /// Duration of the steps of a round other than the CX gates (ancilla
+    /// preparation and measurement), in units of 1/κ₂.
+    pub other_steps: f64,
+    /// Number of rounds in a logical cycle.
+    pub rounds_per_cycle: RoundsPerCycle,
+}
+
+impl CycleSchedule {
+    /// Number of CX layers in a round.
+    pub const CX_LAYERS: u64 = 2;
+
+    /// Duration of a round with fast CX gates, in units of 1/κ₂, for which
+    /// the repetition code fit has been obtained.
+    pub const REFERENCE_ROUND_DURATION: f64 = 5.0;
+
+    /// Schedule with `other_steps` steps of 1/κ₂ besides the CX gates, which
+    /// must be non-negative, and `rounds_per_cycle` rounds, at least 1.
+    pub fn new(other_steps: f64, rounds_per_cycle: RoundsPerCycle) -> Result<Self, String> {
+        Self {
+            other_steps,
+            rounds_per_cycle,
+        }
+        .checked()
+    }
+
+    /// This schedule, if its steps are non-negative and it has at least one
+    /// round per logical cycle.
+    pub fn checked(self) -> Result<Self, String> {
+        if !(self.other_steps.is_finite() && self.other_steps >= 0.0) {
+            return Err(format!(
+                "{} steps besides the CX gates must be a non-negative duration",
+                self.other_steps
+            ));
+        }
+        if self.rounds_per_cycle == RoundsPerCycle::Fixed(0) {
+            return Err("a logical cycle must have at least one round".into());
+        }
+        Ok(self)
+    }
+
+    /// Number of rounds in a logical cycle of a code of distance `distance`.
+    #[must_use]
+    pub fn rounds(&self, distance: u64) -> u64 {
+        match self.rounds_per_cycle {
+            RoundsPerCycle::Distance => distance,
+            RoundsPerCycle::Fixed(rounds) => rounds,
+        }
+    }
+}
+
+/// Unchecked [`CycleSchedule`], for deserialization.
+#[derive(Deserialize)]
+struct RawCycleSchedule {
+    other_steps: f64,
+    rounds_per_cycle: RoundsPerCycle,
+}
+
+impl TryFrom<RawCycleSchedule> for CycleSchedule {
+    type Error = String;
+
+    fn try_from(value: RawCycleSchedule) -> Result<Self, Self::Error> {
+        Self::new(value.other_steps, value.rounds_per_cycle)
+    }
+}
+
+impl Default for CycleSchedule {
+    /// Schedule of [arXiv:2302.06639](https://arxiv.org/abs/2302.06639)
+    /// (p. 28): 3 steps besides the 2 CX, d rounds per logical cycle.
+    fn default() -> Self {
+        Self {
+            other_steps: 3.0,
+            rounds_per_cycle: RoundsPerCycle::Distance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CycleSchedule, RoundsPerCycle};
+    use crate::{code::CodeParameter, qubit::CatQubit, RepetitionCode};
+    use resource_estimator::estimates::ErrorCorrection;
+
+    #[test]
+    fn invalid_schedules_are_rejected() {
+        assert!(CycleSchedule::new(3.0, RoundsPerCycle::Fixed(0)).is_err());
+        for steps in [-1.0, f64::NAN, f64::INFINITY] {
+            assert!(CycleSchedule::new(steps, RoundsPerCycle::Distance).is_err());
+        }
+        assert!(CycleSchedule::new(0.0, RoundsPerCycle::Fixed(1)).is_ok());
+        let json = r#"{"other_steps":3.0,"rounds_per_cycle":{"Fixed":0}}"#;
+        assert!(serde_json::from_str::<CycleSchedule>(json).is_err());
+        let json = serde_json::to_string(&CycleSchedule::default()).expect("schedule");
+        assert_eq!(
+            serde_json::from_str::<CycleSchedule>(&json).ok(),
+            Some(CycleSchedule::default())
+        );
+    }
+
+    #[test]
+    fn fixed_rounds_scale_the_cycle() {
+        let qubit = CatQubit::new();
+        let parameter = CodeParameter::new(11, 10.0);
+        let code = |rounds_per_cycle| {
+            let schedule = CycleSchedule::new(3.0, rounds_per_cycle).expect("valid schedule");
+            RepetitionCode::new()
+                .with_schedule(schedule)
+                .expect("valid schedule")
+        };
+        let (distance, fixed) = (
+            code(RoundsPerCycle::Distance),
+            code(RoundsPerCycle::Fixed(1)),
+        );
+        assert_eq!(distance.rounds(&parameter), 11);
+        assert_eq!(fixed.rounds(&parameter), 1);
+
+        let time = |code: &RepetitionCode| {
+            code.logical_cycle_time(&qubit, &parameter)
+                .expect("cycle time")
+        };
+        // each cycle time is rounded to the nanosecond
+        assert!(time(&distance).abs_diff(11 * time(&fixed)) <= 11);
+        let rate = |code: &RepetitionCode| {
+            code.logical_error_rate(&qubit, &parameter)
+                .expect("error rate")
+        };
+        let (rate_distance, rate_fixed) = (rate(&distance), rate(&fixed));
+        assert!(
+            (rate_distance - 11.0 * rate_fixed).abs() <= 1e-9 * rate_distance,
+            "{rate_distance} {rate_fixed}"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Iteration strategies of the search of code parameters.
+//!
+//! The search returns the first code parameter of the range (by increasing
+//! distance, then |α|², by steps of [`RepetitionCode::alpha_sq_step`])
+//! reaching the required logical error rate. The
+//! [`SearchStrategy::Rectangular`] scan evaluates every |α|² from 1 at each
+//! distance, revisiting many points which can't reach the rate. At a given
+//! distance, the logical error rate first decreases with |α|² (bit flips) then
+//! increases (phase flips), so the other strategies skip the |α|² beyond the
+//! minimum of the rate:
+//! - [`SearchStrategy::Pruned`] scans |α|² from 1, and moves to the next
+//!   distance as soon as the rate increases;
+//! - [`SearchStrategy::Diagonal`] starts from the |α|² minimizing the rate at
+//!   the previous distance, descends to the minimum of the rate at this
+//!   distance, then walks down to the smallest |α|² reaching the required
+//!   rate.
+//!
+//! Both find the same parameter as the rectangular scan when the rate has a
+//! single minimum in |α|² at each distance, as with the default models; a
+//! custom phase-flip model (see [`super::phase_flip`]) without this property
+//! should keep the rectangular scan.
+
+use std::{fmt::Display, str::FromStr};
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::ErrorCorrection;
+
+use super::{
+    CodeParameter, RepetitionCode, ALPHA_SQ_TOLERANCE, MAX_ALPHA_SQ, MAX_DISTANCE, MIN_ALPHA_SQ,
+};
+use crate::qubit::CatQubit;
+
+/// Order in which the search visits the code parameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchStrategy {
+    /// Every |α|² at each distance.
+    #[default]
+    Rectangular,
+    /// |α|² from 1 at each distance, until the rate increases.
+    Pruned,
+    /// From the minimum of the rate at the previous distance.
+    Diagonal,
+}
+
+impl Display for SearchStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rectangular => write!(f, "rectangular"),
+            Self::Pruned => write!(f, "pruned"),
+            Self::Diagonal => write!(f, "diagonal"),
+        }
+    }
+}
+
+impl FromStr for SearchStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rectangular" => Ok(Self::Rectangular),
+            "pruned" => Ok(Self::Pruned),
+            "diagonal" => Ok(Self::Diagonal),
+            _ => Err(format!(
+                "unknown search strategy '{s}' (expected rectangular, pruned or diagonal)"
+            )),
+        }
+    }
+}
+
+impl RepetitionCode {
+    #[must_use]
+    /// Visit the code parameters in the order of `strategy` when searching
+    /// the smallest one (by default, every |α|² at each distance).
+    pub fn with_search_strategy(mut self, strategy: SearchStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    #[must_use]
+    /// Order in which the search visits the code parameters.
+    pub fn search_strategy(&self) -> SearchStrategy {
+        self.strategy
+    }
+
+    /// Smallest code parameter reaching `required_logical_error_rate`,
+    /// visited in the order of `strategy`.
+    pub(crate) fn search_smallest(
+        &self,
+        qubit: &CatQubit,
+        required_logical_error_rate: f64,
+        strategy: SearchStrategy,
+    ) -> Result<CodeParameter, String> {
+        // the |α|² are indexed from 1, by steps from the minimum
+        let steps = ((MAX_ALPHA_SQ - MIN_ALPHA_SQ) / self.alpha_sq_step + ALPHA_SQ_TOLERANCE)
+            .floor()
+            .to_u64()
+            .map_or(1, |steps| steps + 1);
+        let search = Search {
+            code: self,
+            qubit,
+            required_logical_error_rate,
+            steps,
+        };
+        match strategy {
+            SearchStrategy::Rectangular => {
+                self.compute_code_parameter_for_smallest_size(qubit, required_logical_error_rate)
+            }
+            SearchStrategy::Pruned => (1..=MAX_DISTANCE)
+                .step_by(2)
+                .take_while(|_| !self.is_cancelled())
+                .find_map(|distance| search.pruned(distance))
+                .ok_or_else(|| search.not_found()),
+            SearchStrategy::Diagonal => {
+                let mut start = 1;
+                (1..=MAX_DISTANCE)
+                    .step_by(2)
+                    .take_while(|_| !self.is_cancelled())
+                    .find_map(|distance| {
+                        let (found, minimum) = search.diagonal(distance, start);
+                        start = minimum;
+                        found
+                    })
+                    .ok_or_else(|| search.not_found())
+            }
+        }
+    }
+}
+
+/// State of a search at a required logical error rate.
+struct Search<'a> {
+    code: &'a RepetitionCode,
+    qubit: &'a CatQubit,
+    required_logical_error_rate: f64,
+    // number of |α|² at each distance
+    steps: u64,
+}
+
+impl Search<'_> {
+    /// Logical error rate at `distance` and at the |α|² of index `step`,
+    /// infinite if it can't be computed.
+    fn rate(&self, distance: u64, step: u64) -> f64 {
+        self.code
+            .logical_error_rate(self.qubit, &self.parameter(distance, step))
+            .unwrap_or(f64::INFINITY)
+    }
+
+    /// Code parameter at `distance` and at the |α|² of index `step`.
+    fn parameter(&self, distance: u64, step: u64) -> CodeParameter {
+        let steps = step.saturating_sub(1).to_f64().unwrap_or(f64::MAX);
+        let alpha_sq = MIN_ALPHA_SQ + steps * self.code.alpha_sq_step;
+        CodeParameter::new(distance, alpha_sq.min(MAX_ALPHA_SQ))
+    }
+
+    /// Smallest |α|² reaching the rate at `distance`, scanning |α|² from 1
+    /// until the rate increases.
+    fn pruned(&self, distance: u64) -> Option<CodeParameter> {
+        let mut previous = f64::INFINITY;
+        for step in 1..=self.steps {
+            let rate = self.rate(distance, step);
+            if rate <= self.required_logical_error_rate {
+                return Some(self.parameter(distance, step));
+            }
+            if rate > previous {
+                // past the minimum of the rate
+                return None;
+            }
+            previous = rate;
+        }
+        None
+    }
+
+    /// Smallest |α|² reaching the rate at `distance`, descending from the
+    /// |α|² of index `start`, with the index where the descent stopped (the
+    /// start of the next distance).
+    fn diagonal(&self, distance: u64, start: u64) -> (Option<CodeParameter>, u64) {
+        let mut minimum = start.clamp(1, self.steps);
+        let mut rate = self.rate(distance, minimum);
+        if rate.is_infinite() {
+            // no slope to follow (e.g. above the maximum |α|² of the qubits)
+            return (self.pruned(distance), 1);
+        }
+        // descend to the minimum of the rate, or to a rate reached (the |α|²
+        // below the start are then above the rate, or reached as well)
+        while rate > self.required_logical_error_rate {
+            let next = [minimum.checked_sub(1), Some(minimum + 1)]
+                .into_iter()
+                .flatten()
+                .filter(|&step| (1..=self.steps).contains(&step))
+                .map(|step| (step, self.rate(distance, step)))
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+            match next {
+                Some((step, next_rate)) if next_rate < rate => {
+                    minimum = step;
+                    rate = next_rate;
+                }
+                _ => return (None, minimum),
+            }
+        }
+        // walk down to the smallest |α|² reaching the rate
+        let mut smallest = minimum;
+        while smallest > 1 && self.rate(distance, smallest - 1) <= self.required_logical_error_rate
+        {
+            smallest -= 1;
+        }
+        (Some(self.parameter(distance, smallest)), minimum)
+    }
+
+    /// Error when no code parameter reaches the rate.
+    fn not_found(&self) -> String {
+        format!(
+            "no code parameter up to distance {MAX_DISTANCE} reaches the logical error rate {:e}",
+            self.required_logical_error_rate
+        )
+    }
+}
@@ -0,0 +1,427 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Thin rectangular surface code, correcting both phase flips and bit flips.
+//!
+//! The repetition code only corrects phase flips, its logical error rate is
+//! eventually dominated by bit flips (see
+//! [`RepetitionCode::logical_bitflip_probability`]), which only decrease with
+//! |α|². A rotated surface code with distances `dz` against phase flips and
+//! `dx` ≪ `dz` against bit flips trades a few more qubits for this floor.
+//!
+//! Code parameters:
+//! - distance `dx` against bit flips (odd, from 3 to 9)
+//! - distance `dz` against phase flips (odd, from 3 to 49)
+//! - average number of photons |α|² (from 1 to 30)
+//!
+//! Model:
+//! - `dx·dz` data qubits and `dx·dz - 1` ancillas,
+//! - a round consists of an ancilla preparation, 4 CX and a measurement (2
+//!   steps), a logical cycle of `max(dx, dz)` rounds,
+//! - phase flips follow the fit of the repetition code with a lower threshold,
+//!   `prefactor * (|α|^(2·0.86)·κ₁/κ₂ / threshold)^((dz+1)/2)` per round and
+//!   per column (`dx` columns),
+//! - bit flips, with a probability 0.5·exp(-2|α|²) per CX (4 per qubit and per
+//!   round), follow `prefactor * (p / threshold)^((dx+1)/2)` per round and per
+//!   row (`dz` rows).
+//!
+//! The default thresholds are rough estimates (half of the repetition code
+//! threshold for phase flips, 1% for bit flips) and can be set with
+//! [`ThinSurfaceCode::with_phase_flip_fit`] and
+//! [`ThinSurfaceCode::with_bit_flip_fit`]. The Toffoli factories are the ones
+//! of the repetition code, see [`SurfaceToffoliFactory`].
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use resource_estimator::estimates::{self, ErrorCorrection, FactoryBuilder};
+use std::{borrow::Cow, cmp::Ordering, fmt::Display, rc::Rc};
+
+use super::{phase_flip::suppression, schedule::CycleSchedule};
+use crate::{factories::ToffoliFactory, qubit::CatQubit, ToffoliBuilder};
+
+#[cfg(doc)]
+use super::RepetitionCode;
+
+/// Number of CX layers in a surface code round.
+const CX_LAYERS: u64 = 4;
+
+/// Thin rectangular (`dx` ≠ `dz`) surface code.
+#[derive(Clone, Copy, Debug)]
+pub struct ThinSurfaceCode {
+    phase_flip_prefactor: f64,
+    phase_flip_threshold: f64,
+    bit_flip_prefactor: f64,
+    bit_flip_threshold: f64,
+    max_dx: u64,
+    max_dz: u64,
+    max_alpha_sq: u64,
+}
+
+impl ThinSurfaceCode {
+    #[must_use]
+    /// Default initialization.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    /// Set the fit of the logical phase-flip probability per round and per
+    /// column, `prefactor * (|α|^(2·0.86)·κ₁/κ₂ / threshold)^((dz+1)/2)`.
+    pub fn with_phase_flip_fit(mut self, prefactor: f64, threshold: f64) -> Self {
+        self.phase_flip_prefactor = prefactor;
+        self.phase_flip_threshold = threshold;
+        self
+    }
+
+    #[must_use]
+    /// Set the fit of the logical bit-flip probability per round and per row,
+    /// `prefactor * (p / threshold)^((dx+1)/2)`.
+    pub fn with_bit_flip_fit(mut self, prefactor: f64, threshold: f64) -> Self {
+        self.bit_flip_prefactor = prefactor;
+        self.bit_flip_threshold = threshold;
+        self
+    }
+
+    #[must_use]
+    /// Set the maximum distance against bit flips explored by the parameter
+    /// search (9 by default).
+    pub fn with_max_dx(mut self, max_dx: u64) -> Self {
+        self.max_dx = max_dx;
+        self
+    }
+
+    #[must_use]
+    /// Duration of a round of the code, in units of 1/κ₂.
+    pub fn round_duration(physical_qubit: &CatQubit, parameter: &SurfaceCodeParameter) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let cx_layers = CX_LAYERS as f64;
+        CycleSchedule::default().other_steps
+            + cx_layers * physical_qubit.compute_cx().duration(parameter.alpha_sq)
+    }
+
+    #[must_use]
+    /// Number of rounds in a logical cycle.
+    pub fn rounds(parameter: &SurfaceCodeParameter) -> u64 {
+        parameter.dx.max(parameter.dz)
+    }
+
+    #[must_use]
+    /// Logical phase-flip probability per round.
+    pub fn logical_phaseflip_probability(
+        &self,
+        physical_qubit: &CatQubit,
+        parameter: &SurfaceCodeParameter,
+    ) -> Option<f64> {
+        let k1_k2 = physical_qubit.effective_k1_k2()
+            * Self::round_duration(physical_qubit, parameter)
+            / CycleSchedule::REFERENCE_ROUND_DURATION;
+        Some(
+            f64::from_u64(parameter.dx)?
+                * self.phase_flip_prefactor
+                * suppression(
+                    parameter.alpha_sq.powf(0.86) * k1_k2 / self.phase_flip_threshold,
+                    parameter.dz,
+                ),
+        )
+    }
+
+    #[must_use]
+    /// Logical bit-flip probability per round.
+    pub fn logical_bitflip_probability(&self, parameter: &SurfaceCodeParameter) -> Option<f64> {
+        let physical = f64::from_u64(CX_LAYERS)? * 0.5 * (-2.0 * parameter.alpha_sq).exp();
+        Some(
+            f64::from_u64(parameter.dz)?
+                * self.bit_flip_prefactor
+                * suppression(physical / self.bit_flip_threshold, parameter.dx),
+        )
+    }
+}
+
+impl Default for ThinSurfaceCode {
+    fn default() -> Self {
+        Self {
+            phase_flip_prefactor: 0.1,
+            phase_flip_threshold: 0.0065,
+            bit_flip_prefactor: 0.1,
+            bit_flip_threshold: 0.01,
+            max_dx: 9,
+            max_dz: 49,
+            max_alpha_sq: 30,
+        }
+    }
+}
+
+/// Distances against bit flips (`dx`) and phase flips (`dz`), and average
+/// photon number |α|².
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct SurfaceCodeParameter {
+    // field order gives the iteration order
+    dz: u64,
+    dx: u64,
+    alpha_sq: f64,
+}
+
+impl SurfaceCodeParameter {
+    #[must_use]
+    /// Set new values for the code parameters (dx, dz, |α|²).
+    pub fn new(dx: u64, dz: u64, alpha_sq: f64) -> Self {
+        Self { dz, dx, alpha_sq }
+    }
+
+    #[must_use]
+    /// Distance against bit flips.
+    pub fn dx(&self) -> u64 {
+        self.dx
+    }
+
+    #[must_use]
+    /// Distance against phase flips.
+    pub fn dz(&self) -> u64 {
+        self.dz
+    }
+
+    #[must_use]
+    /// Average number of photons |α|².
+    pub fn alpha_sq(&self) -> f64 {
+        self.alpha_sq
+    }
+}
+
+impl Display for SurfaceCodeParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{} (|ɑ|² = {})", self.dx, self.dz, self.alpha_sq)
+    }
+}
+
+impl ErrorCorrection for ThinSurfaceCode {
+    type Qubit = CatQubit;
+    type Parameter = SurfaceCodeParameter;
+
+    fn code_parameter_range(
+        &self,
+        lower_bound: Option<&Self::Parameter>,
+    ) -> impl Iterator<Item = Self::Parameter> {
+        let lower_bound = lower_bound.copied();
+        let (max_dx, max_alpha_sq) = (self.max_dx, self.max_alpha_sq);
+
+        (3..=self.max_dz)
+            .step_by(2)
+            .flat_map(move |dz| {
+                (3..=max_dx.min(dz)).step_by(2).flat_map(move |dx| {
+                    (1..=max_alpha_sq).filter_map(move |alpha_sq| {
+                        Some(SurfaceCodeParameter::new(dx, dz, alpha_sq.to_f64()?))
+                    })
+                })
+            })
+            .skip_while(move |parameter| lower_bound.is_some_and(|bound| *parameter < bound))
+    }
+
+    fn physical_qubits(&self, parameter: &Self::Parameter) -> Result<u64, String> {
+        Ok(2 * parameter.dx * parameter.dz - 1)
+    }
+
+    fn logical_qubits(&self, _parameter: &Self::Parameter) -> Result<u64, String> {
+        Ok(1)
+    }
+
+    fn logical_cycle_time(
+        &self,
+        qubit: &Self::Qubit,
+        parameter: &Self::Parameter,
+    ) -> Result<u64, String> {
+        let round_time = qubit.inverse_k2_ns() * Self::round_duration(qubit, parameter); // ns
+
+        f64::from_u64(Self::rounds(parameter))
+            .and_then(|rounds| (rounds * round_time).round().to_u64())
+            .ok_or_else(|| "cannot compute logical cycle time".into())
+    }
+
+    fn logical_error_rate(
+        &self,
+        qubit: &Self::Qubit,
+        parameter: &Self::Parameter,
+    ) -> Result<f64, String> {
+        if !qubit.supports_alpha_sq(parameter.alpha_sq) {
+            return Err(format!(
+                "|ɑ|² = {} is above the maximum |ɑ|² reachable by the qubits",
+                parameter.alpha_sq
+            ));
+        }
+
+        if let (Some(rounds), Some(lzp), Some(lxp)) = (
+            f64::from_u64(Self::rounds(parameter)),
+            self.logical_phaseflip_probability(qubit, parameter),
+            self.logical_bitflip_probability(parameter),
+        ) {
+            Ok(rounds * (lzp + lxp))
+        } else {
+            Err("cannot compute logical failure probability".into())
+        }
+    }
+
+    fn compute_code_parameter(
+        &self,
+        qubit: &Self::Qubit,
+        required_logical_error_rate: f64,
+    ) -> Result<Self::Parameter, String> {
+        self.compute_code_parameter_for_smallest_size(qubit, required_logical_error_rate)
+    }
+
+    fn code_parameter_cmp(
+        &self,
+        qubit: &Self::Qubit,
+        p1: &Self::Parameter,
+        p2: &Self::Parameter,
+    ) -> Ordering {
+        if let (
+            Ok(num_qubits1),
+            Ok(logical_cycle_time1),
+            Ok(num_qubits2),
+            Ok(logical_cycle_time2),
+        ) = (
+            self.physical_qubits(p1),
+            self.logical_cycle_time(qubit, p1),
+            self.physical_qubits(p2),
+            self.logical_cycle_time(qubit, p2),
+        ) {
+            num_qubits1
+                .cmp(&num_qubits2)
+                .then(logical_cycle_time1.cmp(&logical_cycle_time2))
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
+/// Toffoli factory (built with repetition codes) used alongside the surface
+/// code.
+///
+/// The interface between the factories and the surface code patches is not
+/// modeled, and the factories don't constrain the surface code parameters.
+#[derive(Clone)]
+pub struct SurfaceToffoliFactory(pub ToffoliFactory);
+
+impl estimates::Factory for SurfaceToffoliFactory {
+    type Parameter = SurfaceCodeParameter;
+
+    fn physical_qubits(&self) -> u64 {
+        self.0.physical_qubits()
+    }
+
+    fn duration(&self) -> u64 {
+        self.0.duration()
+    }
+
+    fn num_output_states(&self) -> u64 {
+        self.0.num_output_states()
+    }
+
+    fn max_code_parameter(&self) -> Option<Cow<Self::Parameter>> {
+        None
+    }
+}
+
+impl FactoryBuilder<ThinSurfaceCode> for ToffoliBuilder {
+    type Factory = SurfaceToffoliFactory;
+
+    /// Same factories as for the repetition code.
+    fn find_factories(
+        &self,
+        _ftp: &ThinSurfaceCode,
+        qubit: &Rc<CatQubit>,
+        _magic_state_type: usize,
+        output_error_rate: f64,
+        _max_code_parameter: &SurfaceCodeParameter,
+    ) -> Option<Vec<Cow<Self::Factory>>> {
+        Some(
+            self.find_toffoli_factories(qubit, output_error_rate)
+                .into_iter()
+                .map(|factory| Cow::Owned(SurfaceToffoliFactory(factory.into_owned())))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use num_traits::ToPrimitive;
+    use resource_estimator::estimates::ErrorCorrection;
+
+    use super::{SurfaceCodeParameter, ThinSurfaceCode};
+    use crate::CatQubit;
+
+    #[test]
+    fn range_is_ordered_by_dz_then_dx_then_alpha_sq() {
+        let code = ThinSurfaceCode::new().with_max_dx(5);
+        let range: Vec<_> = code.code_parameter_range(None).collect();
+        assert_eq!(range[0], SurfaceCodeParameter::new(3, 3, 1.0));
+        assert_eq!(range[30], SurfaceCodeParameter::new(3, 5, 1.0));
+        // dx never exceeds dz, nor its maximum
+        assert!(range.iter().all(|p| p.dx() <= p.dz().min(5)));
+        assert!(range.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let bound = SurfaceCodeParameter::new(5, 7, 12.0);
+        let from_bound = code.code_parameter_range(Some(&bound)).next();
+        assert_eq!(from_bound, Some(bound));
+    }
+
+    #[test]
+    fn patches_have_data_qubits_and_ancillas() {
+        let code = ThinSurfaceCode::new();
+        let parameter = SurfaceCodeParameter::new(3, 11, 10.0);
+        assert_eq!(code.physical_qubits(&parameter), Ok(2 * 3 * 11 - 1));
+        assert_eq!(code.logical_qubits(&parameter), Ok(1));
+        assert_eq!(ThinSurfaceCode::rounds(&parameter), 11);
+    }
+
+    #[test]
+    fn distances_suppress_their_own_errors() {
+        let code = ThinSurfaceCode::new();
+        let qubit = CatQubit::new();
+        let phase_flips = |dz| {
+            code.logical_phaseflip_probability(&qubit, &SurfaceCodeParameter::new(3, dz, 10.0))
+                .expect("phase-flip probability")
+        };
+        let bit_flips = |dx| {
+            code.logical_bitflip_probability(&SurfaceCodeParameter::new(dx, 11, 4.0))
+                .expect("bit-flip probability")
+        };
+        assert!(phase_flips(13) < phase_flips(11));
+        assert!(bit_flips(5) < bit_flips(3));
+    }
+
+    #[test]
+    fn alpha_sq_is_limited_by_the_qubits() {
+        let code = ThinSurfaceCode::new();
+        let qubit = CatQubit::new().with_max_alpha_sq(10.0).expect("valid |α|²");
+        assert!(code
+            .logical_error_rate(&qubit, &SurfaceCodeParameter::new(3, 11, 10.0))
+            .is_ok());
+        assert!(code
+            .logical_error_rate(&qubit, &SurfaceCodeParameter::new(3, 11, 11.0))
+            .is_err());
+    }
+
+    #[test]
+    fn smaller_patches_are_preferred() {
+        let code = ThinSurfaceCode::new();
+        let qubit = CatQubit::new();
+        let (small, large) = (
+            SurfaceCodeParameter::new(3, 11, 10.0),
+            SurfaceCodeParameter::new(3, 13, 10.0),
+        );
+        assert_eq!(
+            code.code_parameter_cmp(&qubit, &small, &large),
+            Ordering::Less
+        );
+        assert_eq!(
+            code.code_parameter_cmp(&qubit, &small, &small),
+            Ordering::Equal
+        );
+        // a logical cycle of max(dx, dz) rounds
+        let round = qubit.inverse_k2_ns() * ThinSurfaceCode::round_duration(&qubit, &small);
+        let cycle = code.logical_cycle_time(&qubit, &small).expect("cycle time");
+        assert_eq!(Some(cycle), (11.0 * round).round().to_u64());
+    }
+}
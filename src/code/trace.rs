@@ -0,0 +1,199 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Trace of the search of code parameters.
+//!
+//! A [`SearchTrace`] given to [`RepetitionCode::with_search_trace`] records
+//! every (distance, |α|²) evaluated by the searches of code parameters
+//! ([`ErrorCorrection::compute_code_parameter`]), with its logical error rate
+//! and whether it was selected, e.g. to understand a surprising choice of
+//! parameters or to plot the search. [`SearchTrace::to_csv`] dumps it with one
+//! row per evaluated point.
+//!
+//! The evaluations made outside of a search (e.g. by the diagnosis of an
+//! unreachable error rate, or to report the bit-flip errors) are not recorded.
+//!
+//! [`ErrorCorrection::compute_code_parameter`]:
+//!     resource_estimator::estimates::ErrorCorrection::compute_code_parameter
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Write,
+    rc::Rc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{CodeParameter, RepetitionCode};
+
+/// One code parameter evaluated during a search.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TracePoint {
+    /// Index of the search, from 0.
+    pub search: usize,
+    /// Logical error rate per logical cycle required by the search.
+    pub required_logical_error_rate: f64,
+    /// Code distance.
+    pub distance: u64,
+    /// Average number of photons |α|².
+    pub alpha_sq: f64,
+    /// Logical error rate per logical cycle, if it can be computed.
+    pub logical_error_rate: Option<f64>,
+    /// Whether the search selected this parameter.
+    pub selected: bool,
+}
+
+/// Shared record of the code parameters evaluated by the searches; clones
+/// record into the same trace.
+#[derive(Clone, Debug, Default)]
+pub struct SearchTrace {
+    points: Rc<RefCell<Vec<TracePoint>>>,
+    // index and required logical error rate of the running search
+    current: Rc<Cell<Option<(usize, f64)>>>,
+    searches: Rc<Cell<usize>>,
+}
+
+impl SearchTrace {
+    /// Empty trace.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluated points, in the order of evaluation.
+    #[must_use]
+    pub fn points(&self) -> Vec<TracePoint> {
+        self.points.borrow().clone()
+    }
+
+    /// Number of searches recorded.
+    #[must_use]
+    pub fn searches(&self) -> usize {
+        self.searches.get()
+    }
+
+    /// Points as CSV, with a header line.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "search,required_logical_error_rate,distance,alpha_sq,logical_error_rate,selected\n",
+        );
+        for point in self.points.borrow().iter() {
+            let error_rate = point
+                .logical_error_rate
+                .map_or_else(String::new, |rate| format!("{rate:e}"));
+            // writing into a String can't fail
+            let _ = writeln!(
+                csv,
+                "{},{:e},{},{},{error_rate},{}",
+                point.search,
+                point.required_logical_error_rate,
+                point.distance,
+                point.alpha_sq,
+                point.selected
+            );
+        }
+        csv
+    }
+
+    /// Start recording a search for `required_logical_error_rate`.
+    pub(crate) fn start(&self, required_logical_error_rate: f64) {
+        let search = self.searches.get();
+        self.searches.set(search + 1);
+        self.current
+            .set(Some((search, required_logical_error_rate)));
+    }
+
+    /// Stop recording the running search, marking `selected` as chosen.
+    pub(crate) fn finish(&self, selected: Option<&CodeParameter>) {
+        if let (Some((search, _)), Some(selected)) = (self.current.take(), selected) {
+            if let Some(point) = self.points.borrow_mut().iter_mut().rev().find(|point| {
+                point.search == search
+                    && point.distance == selected.distance
+                    && point.alpha_sq.total_cmp(&selected.alpha_sq).is_eq()
+            }) {
+                point.selected = true;
+            }
+        }
+    }
+
+    /// Record the evaluation of `parameter`, if a search is running.
+    pub(crate) fn record(&self, parameter: &CodeParameter, logical_error_rate: Option<f64>) {
+        if let Some((search, required_logical_error_rate)) = self.current.get() {
+            self.points.borrow_mut().push(TracePoint {
+                search,
+                required_logical_error_rate,
+                distance: parameter.distance,
+                alpha_sq: parameter.alpha_sq,
+                logical_error_rate,
+                selected: false,
+            });
+        }
+    }
+}
+
+impl RepetitionCode {
+    #[must_use]
+    /// Record the code parameters evaluated by the searches into `trace`.
+    pub fn with_search_trace(mut self, trace: SearchTrace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    #[must_use]
+    /// Trace of the searches, if set with
+    /// [`RepetitionCode::with_search_trace`].
+    pub fn search_trace(&self) -> Option<&SearchTrace> {
+        self.trace.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resource_estimator::estimates::ErrorCorrection;
+
+    use super::{CodeParameter, RepetitionCode, SearchTrace};
+    use crate::{code::search::SearchStrategy, qubit::CatQubit};
+
+    #[test]
+    fn searches_record_their_evaluations() {
+        let trace = SearchTrace::new();
+        let code = RepetitionCode::new()
+            .with_search_strategy(SearchStrategy::Pruned)
+            .with_search_trace(trace.clone());
+        let selected = code
+            .compute_code_parameter(&CatQubit::new(), 1e-7)
+            .expect("reachable error rate");
+
+        let points = trace.points();
+        assert_eq!(trace.searches(), 1);
+        assert!(points
+            .iter()
+            .all(|point| point.search == 0
+                && (point.required_logical_error_rate - 1e-7).abs() < 1e-20));
+        let chosen: Vec<_> = points
+            .iter()
+            .filter(|point| point.selected)
+            .map(|point| (point.distance, point.alpha_sq))
+            .collect();
+        assert_eq!(chosen, [(selected.distance(), selected.alpha_sq())]);
+        assert_eq!(trace.to_csv().lines().count(), points.len() + 1);
+    }
+
+    #[test]
+    fn evaluations_outside_searches_are_not_recorded() {
+        let trace = SearchTrace::new();
+        trace.record(&CodeParameter::new(3, 4.0), Some(1e-3));
+        trace.start(1e-5);
+        trace.record(&CodeParameter::new(5, 6.0), None);
+        trace.finish(None);
+        trace.record(&CodeParameter::new(7, 8.0), Some(1e-7));
+
+        assert_eq!(trace.searches(), 1);
+        assert_eq!(
+            trace.to_csv(),
+            "search,required_logical_error_rate,distance,alpha_sq,logical_error_rate,selected\n\
+             0,1e-5,5,6,,false\n"
+        );
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright (c) Alice & Bob
+// Licensed under the Apache License.
+
+//! Configuration files of the command line interface.
+//!
+//! A configuration file is a TOML table of options, named as on the command
+//! line without the leading dashes (e.g. `k1-k2 = 1e-4`, `error-budget =
+//! [1e-3, 1e-3, 0]`, `compact-layout = true`). The options of the file apply
+//! unless given (or a conflicting one is given) on the command line, and take
+//! precedence over the environment variables and the defaults. `config init`
+//! writes a [`template`] with every option commented out at its default.
+//!
+//! Only the options given before the subcommand are configurable: the options
+//! of the subcommands (e.g. `sweep --jobs`) are given on the command line, and
+//! rejected with their subcommand named if found in a configuration file.
+
+use std::{ffi::OsString, fmt::Write, fs, path::Path};
+
+use clap::{builder::ArgAction, Arg, Command};
+
+/// Options which are not assumptions of the estimates, and can't be set by a
+/// configuration file.
+const NOT_CONFIGURABLE: [&str; 5] = ["help", "version", "config", "verbose", "dry_run"];
+
+/// Width of the comments of the template.
+const WIDTH: usize = 78;
+
+/// Where the assumptions behind some options come from, in
+/// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639).
+const REFERENCES: [(&str, &str); 9] = [
+    ("k1_k2", "p. 3, eq. 4 (logical phase-flip probability)"),
+    (
+        "inverse_k2",
+        "p. 28 (duration of a round of the repetition code)",
+    ),
+    (
+        "phase_flip_fit",
+        "p. 29, Fig. 26, and p. 4, eq. 3 (threshold)",
+    ),
+    ("cx_bit_flip_table", "p. 26, eq. D8"),
+    ("compact_layout", "p. 27 (routing qubits of the layout)"),
+    ("no_routing_qubits", "p. 27 (routing qubits of the layout)"),
+    ("factory_placement", "p. 27 (routing qubits of the layout)"),
+    (
+        "measurement_cycles",
+        "p. 30, Fig. 27 (duration of the CX gates)",
+    ),
+    ("cascade", "Table III, p. 35 (precomputed factories)"),
+];
+
+/// Annotated configuration file with every option of `command`, commented
+/// out at its default.
+pub fn template(command: &Command) -> String {
+    let mut template = String::new();
+    comment(
+        &mut template,
+        &format!(
+            "Configuration of {} {}, read with --config FILE. Uncomment an \
+             option to change it; the options given on the command line take \
+             precedence over the ones of this file, which take precedence over \
+             the environment variables (AB_RE_K1_K2, ...). The options of the \
+             subcommands can only be given on the command line.",
+            command.get_name(),
+            command.get_version().unwrap_or_default()
+        ),
+    );
+    for arg in configurable(command) {
+        template.push('\n');
+        let help = arg
+            .get_long_help()
+            .or_else(|| arg.get_help())
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        comment(&mut template, &help);
+        if let Some((_, reference)) = REFERENCES
+            .iter()
+            .find(|(id, _)| arg.get_id().as_str() == *id)
+        {
+            comment(
+                &mut template,
+                &format!("See arXiv:2302.06639, {reference}."),
+            );
+        }
+        let long = arg.get_long().expect("configurable options are long");
+        writeln!(template, "# {long} = {}", default_value(arg)).expect("writing to a string");
+    }
+    template
+}
+
+/// Write the [`template`] to `to` (the standard output if `None`), without
+/// overwriting an existing file unless `force`.
+pub fn init(command: &Command, to: Option<&Path>, force: bool) -> Result<(), anyhow::Error> {
+    let template = template(command);
+    match to {
+        Some(path) if path.exists() && !force => anyhow::bail!(
+            "{} already exists, use --force to overwrite it",
+            path.display()
+        ),
+        Some(path) => fs::write(path, template)
+            .map_err(|err| anyhow::anyhow!("can't write {}: {err}", path.display())),
+        None => {
+            print!("{template}");
+            Ok(())
+        }
+    }
+}
+
+/// Options of the configuration file at `path`, each one with the command
+/// line arguments setting it, in the order of the file.
+pub fn read<'a>(
+    command: &'a Command,
+    path: &Path,
+) -> Result<Vec<(&'a Arg, Vec<OsString>)>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("can't read {}: {err}", path.display()))?;
+    let table: toml::Table = content
+        .parse()
+        .map_err(|err| format!("invalid configuration {}: {err}", path.display()))?;
+    table
+        .iter()
+        .map(|(key, value)| {
+            let arg = configurable(command)
+                .find(|arg| arg.get_long() == Some(key))
+                .ok_or_else(|| match subcommand_with(command, key) {
+                    Some(subcommand) => format!(
+                        "{key} in {} is an option of the subcommand {subcommand}, give it on the command line",
+                        path.display()
+                    ),
+                    None => format!("unknown option {key} in {}", path.display()),
+                })?;
+            let option = format!("--{key}");
+            let arguments = match value {
+                toml::Value::Boolean(true) => vec![option.into()],
+                toml::Value::Boolean(false) => vec![],
+                toml::Value::Array(values) => std::iter::once(Ok(option))
+                    .chain(values.iter().map(|value| scalar(key, value)))
+                    .map(|argument| argument.map(OsString::from))
+                    .collect::<Result<_, _>>()?,
+                value => vec![format!("{option}={}", scalar(key, value)?).into()],
+            };
+            Ok((arg, arguments))
+        })
+        .collect()
+}
+
+/// Options of `command` which can be set by a configuration file.
+fn configurable(command: &Command) -> impl Iterator<Item = &Arg> {
+    command.get_arguments().filter(|arg| {
+        arg.get_long().is_some() && !NOT_CONFIGURABLE.contains(&arg.get_id().as_str())
+    })
+}
+
+/// Name of the (nested) subcommand of `command` with the option `long`, if
+/// any.
+fn subcommand_with(command: &Command, long: &str) -> Option<String> {
+    command.get_subcommands().find_map(|subcommand| {
+        if subcommand
+            .get_arguments()
+            .any(|arg| arg.get_long() == Some(long))
+        {
+            Some(subcommand.get_name().to_string())
+        } else {
+            subcommand_with(subcommand, long)
+        }
+    })
+}
+
+/// Command line value of a string or a number.
+fn scalar(key: &str, value: &toml::Value) -> Result<String, String> {
+    match value {
+        toml::Value::String(value) => Ok(value.clone()),
+        toml::Value::Integer(value) => Ok(value.to_string()),
+        toml::Value::Float(value) => Ok(value.to_string()),
+        _ => Err(format!("{key} takes a string, a number or a list of them")),
+    }
+}
+
+/// Default of an option in TOML, or its value names (as strings) if it has
+/// none.
+fn default_value(arg: &Arg) -> String {
+    if matches!(arg.get_action(), ArgAction::SetTrue) {
+        return "false".into();
+    }
+    let values: Vec<String> = match arg.get_default_values() {
+        [] => arg
+            .get_value_names()
+            .unwrap_or_default()
+            .iter()
+            .map(|name| toml::Value::String(format!("<{name}>")).to_string())
+            .collect(),
+        defaults => defaults
+            .iter()
+            .map(|value| {
+                let value = value.to_string_lossy();
+                match value.parse::<f64>() {
+                    Ok(number) if number.is_finite() => value.into_owned(),
+                    _ => toml::Value::String(value.into_owned()).to_string(),
+                }
+            })
+            .collect(),
+    };
+    match values.as_slice() {
+        [] => {
+            toml::Value::String(format!("<{}>", arg.get_id().as_str().to_uppercase())).to_string()
+        }
+        [value] => value.clone(),
+        values => format!("[{}]", values.join(", ")),
+    }
+}
+
+/// Append `text` as comment lines of at most [`WIDTH`] characters.
+fn comment(template: &mut String, text: &str) {
+    let mut line = String::from("#");
+    for word in text.split_whitespace() {
+        if line.len() > 1 && line.chars().count() + 1 + word.chars().count() > WIDTH {
+            template.push_str(&line);
+            template.push('\n');
+            line = String::from("#");
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+    template.push_str(&line);
+    template.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use clap::{Arg, ArgAction, Command};
+
+    use super::{read, template};
+
+    fn command() -> Command {
+        Command::new("estimator")
+            .version("1.0")
+            .arg(
+                Arg::new("k1_k2")
+                    .long("k1-k2")
+                    .help("Ratio κ₁/κ₂ of the qubits")
+                    .default_value("1e-5"),
+            )
+            .arg(
+                Arg::new("error_budget")
+                    .long("error-budget")
+                    .num_args(3)
+                    .value_names(["LOGICAL", "MAGIC", "ROTATIONS"]),
+            )
+            .arg(Arg::new("qre_params").long("qre-params").value_name("FILE"))
+            .arg(Arg::new("model").long("model").default_value("latest"))
+            .arg(
+                Arg::new("compact_layout")
+                    .long("compact-layout")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(Arg::new("config").long("config"))
+            .subcommand(
+                Command::new("sweep").arg(Arg::new("jobs").long("jobs").help("Number of workers")),
+            )
+    }
+
+    fn file(name: &str, content: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("config-{}-{name}.toml", process::id()));
+        fs::write(&path, content).expect("temporary file");
+        path
+    }
+
+    fn arguments(content: &str, name: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+        let command = command();
+        let path = file(name, content);
+        let options = read(&command, &path);
+        fs::remove_file(&path).expect("temporary file");
+        options.map(|options| {
+            options
+                .into_iter()
+                .map(|(arg, arguments)| {
+                    (
+                        arg.get_id().to_string(),
+                        arguments
+                            .into_iter()
+                            .map(|argument| argument.to_string_lossy().into_owned())
+                            .collect(),
+                    )
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn uncommented_template_is_read_back() {
+        let command = command();
+        let template = template(&command);
+        assert!(!template.contains("# config ="), "{template}");
+        assert!(!template.contains("jobs"), "{template}");
+        let uncommented: String = template
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(option)
+                    if option.split_once(" = ").is_some_and(|(key, _)| {
+                        command
+                            .get_arguments()
+                            .any(|arg| arg.get_long() == Some(key))
+                    }) =>
+                {
+                    format!("{option}\n")
+                }
+                _ => format!("{line}\n"),
+            })
+            .collect();
+        let options = arguments(&uncommented, "template").expect("valid template");
+        let ids: Vec<_> = options.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "compact_layout",
+                "error_budget",
+                "k1_k2",
+                "model",
+                "qre_params"
+            ]
+        );
+    }
+
+    #[test]
+    fn options_become_arguments() {
+        let options = arguments(
+            "k1-k2 = 1e-4\nerror-budget = [1e-3, 2e-3, 0]\ncompact-layout = true\nmodel = \"gouzien2023\"\n",
+            "options",
+        )
+        .expect("valid configuration");
+        let expected = [
+            ("compact_layout", vec!["--compact-layout"]),
+            (
+                "error_budget",
+                vec!["--error-budget", "0.001", "0.002", "0"],
+            ),
+            ("k1_k2", vec!["--k1-k2=0.0001"]),
+            ("model", vec!["--model=gouzien2023"]),
+        ];
+        assert_eq!(options.len(), expected.len());
+        for ((id, arguments), (expected_id, expected_arguments)) in options.iter().zip(expected) {
+            assert_eq!(id, expected_id);
+            assert_eq!(arguments, &expected_arguments);
+        }
+        let disabled = arguments("compact-layout = false\n", "disabled").expect("valid");
+        assert_eq!(disabled, [("compact_layout".into(), vec![])]);
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        for (content, message) in [
+            ("speed = 2\n", "unknown option speed"),
+            ("jobs = 4\n", "option of the subcommand sweep"),
+            ("config = \"other.toml\"\n", "unknown option config"),
+            ("k1-k2 = { value = 1 }\n", "takes a string, a number"),
+            ("error-budget = [[1e-3]]\n", "takes a string, a number"),
+            ("k1-k2 = \n", "invalid configuration"),
+        ] {
+            let err = arguments(content, "invalid").expect_err(content);
+            assert!(err.contains(message), "{content}: {err}");
+        }
+    }
+}
@@ -5,30 +5,139 @@
 //! Tools to manipulate counts of logical qubits and gates.
 //!
 //! Can compute logical space and time overheads for resource estimation from Q#
-//! files. Can also directly instantiate a logical count from number of logical
-//! qubits, of CX and of CCX.
-
-use std::{fs::read_to_string, path::Path};
+//! files (with the `qsharp` feature, enabled by default), or import them from
+//! Qualtran resource exports (see [`LogicalCounts::from_qualtran`]), Stim
+//! circuits (see [`LogicalCounts::from_stim`]) or pyLIQTR resource summaries
+//! (see [`LogicalCounts::from_pyliqtr`]). Can also directly instantiate a
+//! logical count from number of logical qubits, of CX and of CCX.
+//!
+//! When counting from Q# or importing, gates that are not native
+//! bias-preserving operations are converted into CX and CCX according to a
+//! [`GateSet`].
+//!
+//! Counting large programs can take minutes, progress can be followed with
+//! `LogicalCounts::with_progress`, and the counting can be cancelled with
+//! `LogicalCounts::with_cancellation` or bounded with
+//! `LogicalCounts::with_gate_limit` and `LogicalCounts::with_time_limit` (e.g.
+//! for programs with unbounded loops). Repeated blocks marked with
+//! `RepeatEstimates` or `BeginEstimateCaching` in Q# are executed once.
+//!
+//! The counts of programs whose gates depend on measurement outcomes can be
+//! sampled over random outcomes, see [`sampling`].
+//!
+//! The "horizontal" routing qubits added to the logical qubits follow the
+//! layout of arXiv:2302.06639 by default, see [`routing`] for other
+//! conventions.
+//!
+//! The counts of programs run in sequence (e.g. several Q# entry points) can be
+//! aggregated with [`LogicalCounts::sequence`], or kept as segments with
+//! [`segmented::SegmentedCounts`].
 
-use num_bigint::BigUint;
-use num_complex::Complex;
 use num_traits::ToPrimitive;
-use qsc::{
-    interpret::{GenericReceiver, Interpreter},
-    Backend, LanguageFeatures, SourceMap, TargetCapabilityFlags,
-};
 use resource_estimator::estimates::{ErrorBudget, Overhead};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "qsharp")]
+use crate::cancel::CancellationToken;
+use gate_set::GateSet;
+use rotations::{RotationHistogram, RotationKind};
+use routing::RoutingOverhead;
+
+pub mod gate_set;
+pub mod pyliqtr;
+#[cfg(feature = "qsharp")]
+mod qsharp;
+#[cfg(feature = "qsharp")]
+pub use qsharp::CountingError;
+mod qualtran;
+pub mod rotations;
+pub mod routing;
+pub mod sampling;
+pub mod segmented;
+mod stim;
+
+/// Logical cycles of a CX gate.
+///
+/// arXiv:2302.06639 (p. 30, Fig. 27); measurement is counted as 0.2 cycles
+/// according to open source code (5 steps in a cycle).
+pub(crate) const CX_CYCLES: f64 = 2.2;
+
+/// Logical cycles of a CCX gate.
+///
+/// arXiv:2302.06639 (p. 36, Fig. 33); the cost is approximated as 3 CNOT (3 *
+/// 2.2), then 1.5 CNOT subject to measurement outcome (1.5 * 2.2), and
+/// measurement (0.2).
+pub(crate) const CCX_CYCLES: f64 = 10.1;
 
 /// Count the number of logical qubits, CX and CCX gates.
+///
+/// Only the counts and the gate set they were counted with (which sets the
+/// timing of the gates and the synthesis of the rotations) are
+/// (de)serialized, not the state used while counting from Q#.
 #[allow(clippy::struct_field_names)]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 #[must_use]
 pub struct LogicalCounts {
     pub(crate) qubit_count: u64,
     pub(crate) cx_count: u64,
     pub(crate) ccx_count: u64,
-
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) depth: Option<u64>,
+    // logical ANDs among the CCX gates
+    #[serde(default)]
+    pub(crate) and_count: u64,
+    #[serde(default)]
+    pub(crate) measurement_count: u64,
+    // measurements followed by a gate waiting for their outcome
+    #[serde(default)]
+    pub(crate) feed_forward_count: u64,
+    // rotations met when counting from Q#
+    #[serde(default, skip_serializing_if = "RotationHistogram::is_empty")]
+    pub(crate) rotations: RotationHistogram,
+    // T gates of the synthesized rotations (included in the CCX gates)
+    #[serde(default)]
+    pub(crate) rotation_t_count: u64,
+
+    #[serde(default, skip_serializing_if = "GateSet::is_default")]
+    gate_set: GateSet,
+    #[serde(default, skip_serializing_if = "RoutingOverhead::is_paper")]
+    routing: RoutingOverhead,
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
     free_list: Vec<usize>, // holds indices of allocated qubits
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    progress: Option<qsharp::Progress>,
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    cancellation: Option<CancellationToken>,
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    counted_gates: u64,
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    gate_limit: Option<u64>,
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    time_limit: Option<std::time::Duration>,
+    // start of the counting of the current program
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    started: Option<std::time::Instant>,
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    ands: qsharp::AndTracker,
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    blocks: qsharp::Blocks,
+    // whether measurements occurred since the last gate
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    measured: bool,
+    // generator of random measurement outcomes, all 0 if none
+    #[cfg(feature = "qsharp")]
+    #[serde(skip)]
+    outcomes: Option<crate::random::StochasticRng>,
 }
 
 impl LogicalCounts {
@@ -39,139 +148,321 @@ impl LogicalCounts {
             qubit_count,
             cx_count,
             ccx_count,
-            free_list: vec![],
+            ..Self::default()
         }
     }
 
-    /// Count the logical resources from a Q# file.
-    pub fn from_qsharp(filename: impl AsRef<Path>) -> Result<Self, String> {
-        let content = read_to_string(filename).map_err(|_| String::from("Cannot read filename"))?;
-
-        let sources = SourceMap::new([("source".into(), content.into())], None);
-
-        let mut interpreter = Interpreter::new(
-            true,
-            sources,
-            qsc::PackageType::Exe,
-            TargetCapabilityFlags::all(),
-            LanguageFeatures::default(),
-        )
-        .map_err(|_| String::from("Cannot create interpreter"))?;
+    /// Set the logical depth, in logical cycles, e.g. the parallel depth given
+    /// by another compiler. It replaces the depth computed from the numbers of
+    /// CX and CCX (assuming they are sequential).
+    pub fn with_depth(mut self, depth: u64) -> Self {
+        self.depth = Some(depth);
+        self
+    }
 
-        let mut counter = Self::default();
-        let mut stdout = std::io::stdout();
-        let mut out = GenericReceiver::new(&mut stdout);
+    /// Logical depth set with [`LogicalCounts::with_depth`], if any.
+    #[must_use]
+    pub fn depth(&self) -> Option<u64> {
+        self.depth
+    }
 
-        interpreter
-            .eval_entry_with_sim(&mut counter, &mut out)
-            .map_err(|_| String::from("Cannot estimate Q# code"))?;
+    /// Other counts with the same depth, measurements, gate set and routing
+    /// overhead.
+    #[allow(clippy::similar_names)]
+    pub(crate) fn with_counts(&self, qubit_count: u64, cx_count: u64, ccx_count: u64) -> Self {
+        Self {
+            depth: self.depth,
+            measurement_count: self.measurement_count,
+            feed_forward_count: self.feed_forward_count,
+            gate_set: self.gate_set.clone(),
+            routing: self.routing.clone(),
+            ..Self::new(qubit_count, cx_count, ccx_count)
+        }
+    }
 
-        Ok(counter)
+    /// Number of logical qubits.
+    #[must_use]
+    pub fn qubit_count(&self) -> u64 {
+        self.qubit_count
     }
-}
 
-impl Overhead for LogicalCounts {
-    /// The number of logical qubits to execute the algorithm after mapping.
-    ///
-    /// This number includes "horizontal" routing qubits for the logical qubits, including the top
-    /// one (in between the compute and factory parts). It does not include the "vertical" routing
-    /// qubits (they are included only for displaying the estimates results). It does not include
-    /// qubit used to produce magic states.
-    fn logical_qubits(&self) -> u64 {
-        let horizontal_routing_qubits = self.qubit_count.div_ceil(2) + 1;
+    /// Number of CX gates.
+    #[must_use]
+    pub fn cx_count(&self) -> u64 {
+        self.cx_count
+    }
 
-        self.qubit_count + horizontal_routing_qubits
+    /// Number of CCX gates.
+    #[must_use]
+    pub fn ccx_count(&self) -> u64 {
+        self.ccx_count
     }
 
-    #[allow(clippy::similar_names)]
-    fn logical_depth(&self, _: &ErrorBudget) -> u64 {
-        let cx_f = self.cx_count.to_f64().expect("#CX didn't convert to f64");
-        let ccx_f = self.ccx_count.to_f64().expect("#CCX didn't convert to f64");
+    /// Number of logical ANDs (CCX gates on a target in |0⟩, included in the
+    /// CCX gates).
+    #[must_use]
+    pub fn and_count(&self) -> u64 {
+        self.and_count
+    }
 
-        // arXiv:2302.06639 (p. 30, Fig. 27); measurement is counted as 0.2
-        // cycles according to open source code (5 steps in a cycle)
-        let cx_cycles = 2.2;
+    /// Number of measurements.
+    #[must_use]
+    pub fn measurement_count(&self) -> u64 {
+        self.measurement_count
+    }
 
-        // arXiv:2302.06639 (p. 36, Fig. 33); the cost is approximated as 3
-        // CNOT (3 * 2.2), then 1.5 CNOT subject to measurement outcome (1.5
-        // * 2.2), and measurement (0.2)
-        let ccx_cycles = 10.1;
+    /// Number of feed-forwards, i.e. of measurements (or sets of simultaneous
+    /// measurements) followed by a gate waiting for their outcome.
+    #[must_use]
+    pub fn feed_forward_count(&self) -> u64 {
+        self.feed_forward_count
+    }
 
-        ((cx_f * cx_cycles) + (ccx_f * ccx_cycles))
-            .ceil()
-            .to_u64()
-            .expect("logical depth is too large")
+    /// Rotations met when counting from Q#, by angle.
+    #[must_use]
+    pub fn rotations(&self) -> &RotationHistogram {
+        &self.rotations
     }
 
-    fn num_magic_states(&self, _: &ErrorBudget, _: usize) -> u64 {
-        self.ccx_count
+    /// Number of T gates of the synthesized rotations, included in the CCX
+    /// gates through the T conversion of the gate set.
+    #[must_use]
+    pub fn rotation_t_count(&self) -> u64 {
+        self.rotation_t_count
     }
-}
 
-impl Backend for LogicalCounts {
-    type ResultType = bool;
+    /// Probability that the synthesis of the generic rotations fails: the
+    /// precision of the synthesis of the gate set (see
+    /// [`rotations::RotationSynthesis::precision`]) for each rotation, to be covered by
+    /// the rotation part of the error budget.
+    #[must_use]
+    pub fn rotation_synthesis_error(&self) -> f64 {
+        self.rotations
+            .count(RotationKind::Generic)
+            .to_f64()
+            .unwrap_or(f64::MAX)
+            * self.gate_set.rotation_synthesis.precision()
+    }
 
-    fn ccx(&mut self, _ctl0: usize, _ctl1: usize, _q: usize) {
-        self.ccx_count += 1;
+    /// Set the number of measurements and of feed-forwards, e.g. for counts
+    /// entered directly.
+    pub fn with_measurements(mut self, measurement_count: u64, feed_forward_count: u64) -> Self {
+        self.measurement_count = measurement_count;
+        self.feed_forward_count = feed_forward_count;
+        self
     }
 
-    fn cx(&mut self, _ctl: usize, _q: usize) {
-        self.cx_count += 1;
+    /// Costs of the non-native gates used when counting from Q#.
+    #[must_use]
+    pub fn gate_set(&self) -> &GateSet {
+        &self.gate_set
     }
 
-    fn cy(&mut self, _ctl: usize, _q: usize) {
-        self.cx_count += 1;
+    /// Set how the "horizontal" routing qubits are counted (by default,
+    /// [`RoutingOverhead::Paper`]), e.g. to reproduce the qubit counts of
+    /// another paper.
+    pub fn with_routing_overhead(mut self, routing: RoutingOverhead) -> Self {
+        self.routing = routing;
+        self
     }
 
-    fn cz(&mut self, _ctl: usize, _q: usize) {
-        self.cx_count += 1;
+    /// How the "horizontal" routing qubits are counted.
+    #[must_use]
+    pub fn routing_overhead(&self) -> &RoutingOverhead {
+        &self.routing
     }
 
-    fn h(&mut self, _q: usize) {}
+    /// Counts of a workflow whose stages (e.g. state preparation, phase
+    /// estimation, readout) run in sequence: the qubits are reused from a
+    /// stage to the next, and the gates and depths add up.
+    ///
+    /// The stages must have the same gate set.
+    pub fn sequence(stages: impl IntoIterator<Item = Self>) -> Result<Self, String> {
+        let mut stages = stages.into_iter();
+        let first = stages.next().ok_or("a workflow needs at least one stage")?;
+        stages.try_fold(first, |workflow, stage| {
+            if stage.gate_set != workflow.gate_set {
+                return Err(format!(
+                    "stages counted with different gate sets ({} and {})",
+                    workflow.gate_set, stage.gate_set
+                ));
+            }
+            // the logical depth does not depend on the error budget
+            let budget = ErrorBudget::new(0.0, 0.0, 0.0);
+            let depth = (workflow.depth.is_some() || stage.depth.is_some())
+                .then(|| workflow.logical_depth(&budget) + stage.logical_depth(&budget));
+            let mut rotations = workflow.rotations.clone();
+            rotations.merge(&stage.rotations);
+            Ok(Self {
+                depth,
+                rotations,
+                rotation_t_count: workflow.rotation_t_count + stage.rotation_t_count,
+                measurement_count: workflow.measurement_count + stage.measurement_count,
+                feed_forward_count: workflow.feed_forward_count + stage.feed_forward_count,
+                and_count: workflow.and_count + stage.and_count,
+                ..workflow.with_counts(
+                    workflow.qubit_count.max(stage.qubit_count),
+                    workflow.cx_count + stage.cx_count,
+                    workflow.ccx_count + stage.ccx_count,
+                )
+            })
+        })
+    }
 
-    fn m(&mut self, _q: usize) -> Self::ResultType {
-        false
+    /// Slow down the CX gates by `routing_factor` (see
+    /// [`GateSet::with_routing_factor`]), e.g. for counts which were not
+    /// obtained with a gate set.
+    pub fn with_routing_factor(mut self, routing_factor: f64) -> Result<Self, String> {
+        self.gate_set = self.gate_set.with_routing_factor(routing_factor)?;
+        Ok(self)
     }
 
-    fn mresetz(&mut self, _q: usize) -> Self::ResultType {
-        false
+    /// Set the duration of the measurements and the feed-forward latency, in
+    /// logical cycles (see [`GateSet::with_measurement_cycles`] and
+    /// [`GateSet::with_feed_forward_cycles`]).
+    pub fn with_measurement_timing(
+        mut self,
+        measurement_cycles: f64,
+        feed_forward_cycles: f64,
+    ) -> Result<Self, String> {
+        self.gate_set = self
+            .gate_set
+            .with_measurement_cycles(measurement_cycles)?
+            .with_feed_forward_cycles(feed_forward_cycles)?;
+        Ok(self)
     }
+}
 
-    fn reset(&mut self, _q: usize) {}
+impl Overhead for LogicalCounts {
+    /// The number of logical qubits to execute the algorithm after mapping.
+    ///
+    /// This number includes "horizontal" routing qubits for the logical qubits, by default including
+    /// the top one (in between the compute and factory parts), see [`RoutingOverhead`]. It does
+    /// not include the "vertical" routing qubits (they are included only for displaying the
+    /// estimates results). It does not include qubit used to produce magic states.
+    fn logical_qubits(&self) -> u64 {
+        let horizontal_routing_qubits = self.routing.routing_qubits(self.qubit_count);
 
-    fn sadj(&mut self, _q: usize) {}
+        self.qubit_count + horizontal_routing_qubits
+    }
+
+    /// The depth set with [`LogicalCounts::with_depth`], or the sequential
+    /// depth of the CX and CCX gates, the CX gates being slowed down by the
+    /// routing factor of the gate set, and of the measurements and
+    /// feed-forwards.
+    #[allow(clippy::similar_names)]
+    fn logical_depth(&self, _: &ErrorBudget) -> u64 {
+        if let Some(depth) = self.depth {
+            return depth;
+        }
+        let cx_f = self.cx_count.to_f64().expect("#CX didn't convert to f64");
+        let ccx_f = self.ccx_count.to_f64().expect("#CCX didn't convert to f64");
 
-    fn s(&mut self, _q: usize) {}
+        let measurements_f = self
+            .measurement_count
+            .to_f64()
+            .expect("#measurements didn't convert to f64");
+        let feed_forwards_f = self
+            .feed_forward_count
+            .to_f64()
+            .expect("#feed-forwards didn't convert to f64");
+
+        ((cx_f * CX_CYCLES * self.gate_set.routing_factor)
+            + (ccx_f * CCX_CYCLES)
+            + (measurements_f * self.gate_set.measurement_cycles)
+            + (feed_forwards_f * self.gate_set.feed_forward_cycles))
+            .ceil()
+            .to_u64()
+            .expect("logical depth is too large")
+    }
 
-    fn swap(&mut self, _q0: usize, _q1: usize) {
-        self.cx_count += 3;
+    fn num_magic_states(&self, _: &ErrorBudget, _: usize) -> u64 {
+        self.ccx_count
     }
+}
 
-    fn x(&mut self, _q: usize) {}
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_4;
 
-    fn y(&mut self, _q: usize) {}
+    use resource_estimator::estimates::{ErrorBudget, Overhead};
 
-    fn z(&mut self, _q: usize) {}
+    use super::{rotations::RotationKind, routing::RoutingOverhead, LogicalCounts};
 
-    fn qubit_allocate(&mut self) -> usize {
-        if let Some(qubit) = self.free_list.pop() {
-            qubit
-        } else {
-            let qubit = self.qubit_count;
-            self.qubit_count += 1;
-            qubit.to_usize().expect("qubit is too large")
+    /// Counts with the T gates of `rotations` generic rotations and a T
+    /// gate.
+    fn with_rotations(rotations: u32) -> LogicalCounts {
+        let mut counts = LogicalCounts::new(10, 100, 20);
+        for rotation in 0..rotations {
+            counts.rotations.record(0.1 * f64::from(rotation + 1));
         }
+        counts.rotations.record(FRAC_PI_4);
+        counts.rotation_t_count = 100 * u64::from(rotations) + 1;
+        counts
     }
 
-    fn qubit_release(&mut self, q: usize) {
-        self.free_list.push(q);
+    #[test]
+    fn sequence_keeps_the_rotations_of_the_stages() {
+        let counts =
+            LogicalCounts::sequence([with_rotations(2), with_rotations(3)]).expect("same gate set");
+        assert_eq!(counts.rotations().count(RotationKind::Generic), 5);
+        assert_eq!(counts.rotations().count(RotationKind::T), 2);
+        assert_eq!(counts.rotation_t_count(), 502);
+        assert_eq!(
+            (counts.qubit_count(), counts.cx_count(), counts.ccx_count()),
+            (10, 200, 40)
+        );
+        // 10⁻¹⁰ per generic rotation by default
+        let error = counts.rotation_synthesis_error();
+        assert!(error <= 5e-10 && error > 4.5e-10);
     }
 
-    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
-        (vec![], 0)
+    #[test]
+    fn routing_overhead_is_saved_with_the_counts() {
+        let counts = LogicalCounts::new(10, 100, 20).with_routing_overhead(RoutingOverhead::None);
+        let json = serde_json::to_string(&counts).expect("built-in routing");
+        let read: LogicalCounts = serde_json::from_str(&json).expect("saved counts");
+        assert_eq!(read.logical_qubits(), 10);
+
+        // the default routing is left out, and read back
+        let json = serde_json::to_string(&LogicalCounts::new(10, 100, 20)).expect("counts");
+        assert!(!json.contains("routing"));
+        let read: LogicalCounts = serde_json::from_str(&json).expect("saved counts");
+        assert_eq!(read.logical_qubits(), 10 + 6);
     }
 
-    fn qubit_is_zero(&mut self, _q: usize) -> bool {
-        true
+    #[test]
+    fn counts_are_saved_and_read_back() {
+        let counts = LogicalCounts::new(10, 100, 20)
+            .with_depth(500)
+            .with_measurements(30, 5);
+        let json = serde_json::to_string(&counts).expect("counts");
+        assert!(!json.contains("gate_set"));
+        let read: LogicalCounts = serde_json::from_str(&json).expect("saved counts");
+        assert_eq!(
+            (
+                read.qubit_count(),
+                read.cx_count(),
+                read.ccx_count(),
+                read.depth(),
+                read.measurement_count(),
+                read.feed_forward_count()
+            ),
+            (10, 100, 20, Some(500), 30, 5)
+        );
+
+        // the timing of the gates is saved with the counts
+        let budget = ErrorBudget::new(1e-3, 1e-3, 0.0);
+        let counts = LogicalCounts::new(10, 100, 20)
+            .with_measurements(30, 5)
+            .with_routing_factor(2.0)
+            .and_then(|counts| counts.with_measurement_timing(1.0, 3.0))
+            .expect("valid timing");
+        let json = serde_json::to_string(&counts).expect("counts");
+        let read: LogicalCounts = serde_json::from_str(&json).expect("saved counts");
+        // 100·2.2·2 + 20·10.1 + 30·1 + 5·3
+        assert_eq!(counts.logical_depth(&budget), 687);
+        assert_eq!(read.logical_depth(&budget), 687);
+        assert_eq!(read.gate_set(), counts.gate_set());
     }
 }
@@ -0,0 +1,325 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Description of the gates natively available on the architecture.
+//!
+//! The architecture of [arXiv:2302.06639](https://arxiv.org/abs/2302.06639)
+//! natively provides bias-preserving operations only: X, Z, CX, CCX (through
+//! magic states), preparations and measurements in the X and Z bases. Other
+//! gates met in Q# programs (H, S, CZ, ...) must be decomposed, with some
+//! overhead, into these operations. A [`GateSet`] gives the cost of each of
+//! these gates, in number of CX and CCX, that the counter adds to the counts.
+//!
+//! The cost of routing depends on the layout: SWAP gates may be free (when
+//! absorbed in the relabelling of the qubits) or cost more than 3 CX, and the
+//! CX gates between distant qubits may be slower. The cost of SWAP can be set
+//! with [`GateSet::with_swap`] (see [`parse_swap_cost`]), and the depth of the
+//! CX gates inflated with [`GateSet::with_routing_factor`].
+//!
+//! Measurements are native, but they take time: each one lasts
+//! [`GateSet::measurement_cycles`], and a gate depending on the outcome of a
+//! measurement waits for the classical feed-forward latency
+//! [`GateSet::feed_forward_cycles`] (e.g. in repeat-until-success circuits).
+//!
+//! Rotations by angles which are not multiples of π/2 are synthesized into T
+//! gates, themselves converted into CCX, see [`RotationSynthesis`].
+
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+use super::rotations::RotationSynthesis;
+
+/// Cost of a gate, in number of logical CX and CCX gates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GateCost {
+    /// Number of CX gates.
+    pub cx: u64,
+    /// Number of CCX gates.
+    pub ccx: u64,
+}
+
+impl GateCost {
+    /// Gate without cost (native, or tracked in the Pauli frame).
+    pub const FREE: Self = Self { cx: 0, ccx: 0 };
+
+    /// Cost of `cx` CX gates.
+    #[must_use]
+    pub const fn cx(cx: u64) -> Self {
+        Self { cx, ccx: 0 }
+    }
+
+    /// Cost of `ccx` CCX gates.
+    #[must_use]
+    pub const fn ccx(ccx: u64) -> Self {
+        Self { cx: 0, ccx }
+    }
+}
+
+/// Costs of the gates which are not native bias-preserving operations.
+///
+/// X, Y, Z, CX, CCX and measurements are native and are not configurable, only
+/// the duration of the measurements is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GateSet {
+    /// Hadamard gate.
+    pub h: GateCost,
+    /// S and S† gates.
+    pub s: GateCost,
+    /// Controlled-Y gate.
+    pub cy: GateCost,
+    /// Controlled-Z gate.
+    pub cz: GateCost,
+    /// SWAP gate.
+    pub swap: GateCost,
+    /// Factor applied to the duration of the CX gates for the routing between
+    /// distant logical qubits (1 when all the CX are between neighbours).
+    #[serde(default = "no_routing")]
+    pub routing_factor: f64,
+    /// Whether logical ANDs are uncomputed by measurement (without magic
+    /// state) rather than by a CCX.
+    #[serde(default)]
+    pub measurement_uncompute: bool,
+    /// Whether, with measurement-based uncompute, the CCX gates undone later
+    /// on a target not in |0⟩ (e.g. in `within … apply` blocks) are also
+    /// uncomputed by measurement, using an ancilla qubit.
+    #[serde(default = "mirrored_uncompute")]
+    pub mirrored_uncompute: bool,
+    /// Duration of a measurement, in logical cycles.
+    #[serde(default = "measurement_cycles")]
+    pub measurement_cycles: f64,
+    /// Classical latency between a measurement and a gate depending on its
+    /// outcome, in logical cycles.
+    #[serde(default)]
+    pub feed_forward_cycles: f64,
+    /// Synthesis of the rotations which are not Clifford gates.
+    #[serde(default)]
+    pub rotation_synthesis: RotationSynthesis,
+}
+
+/// Duration of a measurement, in logical cycles.
+///
+/// arXiv:2302.06639 (p. 30); measurement is counted as 0.2 cycles according to
+/// open source code (1 step out of 5 in a cycle).
+fn measurement_cycles() -> f64 {
+    0.2
+}
+
+/// Mirrored CCX gates are uncomputed by measurement by default.
+fn mirrored_uncompute() -> bool {
+    true
+}
+
+/// Routing factor of CX gates between neighbours.
+fn no_routing() -> f64 {
+    1.0
+}
+
+/// Parse the cost of a SWAP gate: `free`, `3cx`, or a number of CX.
+pub fn parse_swap_cost(s: &str) -> Result<GateCost, String> {
+    match s {
+        "free" => Ok(GateCost::FREE),
+        "3cx" => Ok(GateCost::cx(3)),
+        _ => s
+            .parse()
+            .map(GateCost::cx)
+            .map_err(|_| format!("unknown SWAP cost '{s}' (expected free, 3cx or a number of CX)")),
+    }
+}
+
+impl GateSet {
+    /// Simplified counting of [arXiv:2302.06639](https://arxiv.org/abs/2302.06639)
+    /// (no consequences for modular arithmetic circuits): 1-qubit Clifford
+    /// gates are free, CY and CZ are counted as CX, and SWAP as 3 CX.
+    #[must_use]
+    pub fn simplified() -> Self {
+        Self {
+            h: GateCost::FREE,
+            s: GateCost::FREE,
+            cy: GateCost::cx(1),
+            cz: GateCost::cx(1),
+            swap: GateCost::cx(3),
+            routing_factor: 1.0,
+            measurement_uncompute: false,
+            mirrored_uncompute: true,
+            measurement_cycles: measurement_cycles(),
+            feed_forward_cycles: 0.0,
+            rotation_synthesis: RotationSynthesis::default(),
+        }
+    }
+
+    /// Only the bias-preserving operations are native:
+    /// - H is teleported through an ancilla prepared in |+⟩, costing 1 CX,
+    /// - S and S† are not bias-preserving on the repetition code and are
+    ///   injected as a magic state, counted as a CCX,
+    /// - CY and CZ require basis changes on the target (H or S), so they cost a
+    ///   CX plus twice the cost of the basis change,
+    /// - SWAP is 3 CX.
+    #[must_use]
+    pub fn bias_preserving() -> Self {
+        Self {
+            h: GateCost::cx(1),
+            s: GateCost::ccx(1),
+            cy: GateCost { cx: 1, ccx: 2 },
+            cz: GateCost::cx(3),
+            swap: GateCost::cx(3),
+            routing_factor: 1.0,
+            measurement_uncompute: false,
+            mirrored_uncompute: true,
+            measurement_cycles: measurement_cycles(),
+            feed_forward_cycles: 0.0,
+            rotation_synthesis: RotationSynthesis::default(),
+        }
+    }
+
+    /// Set the cost of the SWAP gates.
+    #[must_use]
+    pub fn with_swap(self, swap: GateCost) -> Self {
+        Self { swap, ..self }
+    }
+
+    /// Set the factor applied to the duration of the CX gates for routing (at
+    /// least 1).
+    pub fn with_routing_factor(self, routing_factor: f64) -> Result<Self, String> {
+        if !(routing_factor >= 1.0 && routing_factor.is_finite()) {
+            return Err(format!(
+                "routing factor {routing_factor} must be a number at least 1"
+            ));
+        }
+        Ok(Self {
+            routing_factor,
+            ..self
+        })
+    }
+
+    /// Set the duration of a measurement, in logical cycles.
+    pub fn with_measurement_cycles(self, measurement_cycles: f64) -> Result<Self, String> {
+        if !(measurement_cycles >= 0.0 && measurement_cycles.is_finite()) {
+            return Err(format!(
+                "measurement duration {measurement_cycles} must be a non-negative number of cycles"
+            ));
+        }
+        Ok(Self {
+            measurement_cycles,
+            ..self
+        })
+    }
+
+    /// Set the classical feed-forward latency, in logical cycles.
+    pub fn with_feed_forward_cycles(self, feed_forward_cycles: f64) -> Result<Self, String> {
+        if !(feed_forward_cycles >= 0.0 && feed_forward_cycles.is_finite()) {
+            return Err(format!(
+                "feed-forward latency {feed_forward_cycles} must be a non-negative number of cycles"
+            ));
+        }
+        Ok(Self {
+            feed_forward_cycles,
+            ..self
+        })
+    }
+
+    /// Set the synthesis of the non-Clifford rotations.
+    #[must_use]
+    pub fn with_rotation_synthesis(self, rotation_synthesis: RotationSynthesis) -> Self {
+        Self {
+            rotation_synthesis,
+            ..self
+        }
+    }
+
+    /// Uncompute the logical ANDs by measurement: the target is measured in the
+    /// X basis (a H and a measurement), and a CZ between the controls corrects
+    /// the phase when the outcome is 1 (counted as always applied). No magic
+    /// state is consumed, contrarily to the CCX of the compute.
+    #[must_use]
+    pub fn with_measurement_uncompute(self) -> Self {
+        Self {
+            measurement_uncompute: true,
+            ..self
+        }
+    }
+
+    /// Only uncompute the logical ANDs by measurement, and not the mirrored
+    /// CCX gates of `within … apply` blocks, for conservative estimates.
+    #[must_use]
+    pub fn without_mirrored_uncompute(self) -> Self {
+        Self {
+            mirrored_uncompute: false,
+            ..self
+        }
+    }
+
+    /// Cost of the measurement-based uncomputation of a logical AND.
+    #[must_use]
+    pub fn measurement_uncompute_cost(&self) -> GateCost {
+        GateCost {
+            cx: self.h.cx + self.cz.cx,
+            ccx: self.h.ccx + self.cz.ccx,
+        }
+    }
+
+    /// Whether this is the default gate set, left out when saving counts.
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the gates other than SWAP have the same costs as in `other`.
+    fn same_gates(&self, other: &Self) -> bool {
+        (self.h, self.s, self.cy, self.cz) == (other.h, other.s, other.cy, other.cz)
+    }
+}
+
+impl Default for GateSet {
+    /// Simplified counting, see [`GateSet::simplified`].
+    fn default() -> Self {
+        Self::simplified()
+    }
+}
+
+impl Display for GateSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.same_gates(&Self::simplified()) {
+            write!(f, "simplified")?;
+        } else if self.same_gates(&Self::bias_preserving()) {
+            write!(f, "bias-preserving")?;
+        } else {
+            write!(f, "custom")?;
+        }
+        if self.swap != GateCost::cx(3) {
+            write!(f, ", SWAP = {} CX", self.swap.cx)?;
+        }
+        #[allow(clippy::float_cmp)]
+        if self.routing_factor != 1.0 {
+            write!(f, ", routing ×{}", self.routing_factor)?;
+        }
+        match (self.measurement_uncompute, self.mirrored_uncompute) {
+            (true, true) => write!(f, ", measurement-based uncompute")?,
+            (true, false) => write!(f, ", measurement-based AND uncompute")?,
+            (false, _) => {}
+        }
+        #[allow(clippy::float_cmp)]
+        if self.measurement_cycles != measurement_cycles() {
+            write!(f, ", measurement {} cycles", self.measurement_cycles)?;
+        }
+        if self.feed_forward_cycles > 0.0 {
+            write!(f, ", feed-forward {} cycles", self.feed_forward_cycles)?;
+        }
+        if self.rotation_synthesis != RotationSynthesis::default() {
+            write!(f, ", rotations: {}", self.rotation_synthesis)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GateSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simplified" => Ok(Self::simplified()),
+            "bias-preserving" => Ok(Self::bias_preserving()),
+            _ => Err(format!(
+                "unknown gate set '{s}' (expected simplified or bias-preserving)"
+            )),
+        }
+    }
+}
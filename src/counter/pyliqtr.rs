@@ -0,0 +1,274 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Import of the logical resource summaries of pyLIQTR.
+//!
+//! [pyLIQTR](https://github.com/isi-usc-edu/pyLIQTR) (and other tools working
+//! on `OpenQASM` circuits) summarize the logical resources of a circuit as a
+//! number of logical qubits, of T gates, of Toffoli gates and of Clifford
+//! gates, exported as JSON:
+//!
+//! ```json
+//! {"LogicalQubits": 100, "T": 4000, "Toffoli": 10, "Clifford": 50000}
+//! ```
+//!
+//! The architecture has no T factories: T gates are converted into CCX with a
+//! [`TConversion`]. Clifford gates are counted as CX (an upper bound, single
+//! qubit Cliffords being cheaper), unless the number of CX is given (`CX` or
+//! `CNOT`), in which case the other Cliffords are ignored. Rotations (whose
+//! angles are not given) are synthesized into T gates as generic rotations
+//! with a [`RotationSynthesis`], the synthesis error being charged to the
+//! rotation error budget (see [`LogicalCounts::rotation_synthesis_error`]).
+
+use std::{fmt::Display, fs::read_to_string, path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{gate_set::GateSet, rotations::RotationSynthesis, LogicalCounts};
+
+/// Conversion of T gates into CCX gates, as a number of T gates per CCX.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TConversion {
+    t_per_ccx: u64,
+}
+
+impl TConversion {
+    /// T gates of a logical AND (a Toffoli gate on a target in |0⟩, uncomputed
+    /// by measurement): 4 T gates per CCX.
+    #[must_use]
+    pub const fn and() -> Self {
+        Self { t_per_ccx: 4 }
+    }
+
+    /// T gates of the textbook decomposition of a Toffoli gate: 7 T gates per
+    /// CCX.
+    #[must_use]
+    pub const fn toffoli() -> Self {
+        Self { t_per_ccx: 7 }
+    }
+
+    /// Catalyzed conversion of a CCZ state into 2 T states: 2 T gates per CCX.
+    #[must_use]
+    pub const fn catalyzed() -> Self {
+        Self { t_per_ccx: 2 }
+    }
+
+    /// Custom conversion of `t_per_ccx` T gates per CCX.
+    pub fn per_ccx(t_per_ccx: u64) -> Result<Self, String> {
+        if t_per_ccx == 0 {
+            return Err("the number of T gates per CCX must be positive".into());
+        }
+        Ok(Self { t_per_ccx })
+    }
+
+    /// Number of T gates per CCX.
+    #[must_use]
+    pub fn t_per_ccx(&self) -> u64 {
+        self.t_per_ccx
+    }
+
+    /// Number of CCX gates replacing `t_count` T gates.
+    #[must_use]
+    pub fn ccx_count(&self, t_count: u64) -> u64 {
+        t_count.div_ceil(self.t_per_ccx)
+    }
+}
+
+impl Default for TConversion {
+    /// Conversion through logical ANDs, see [`TConversion::and`].
+    fn default() -> Self {
+        Self::and()
+    }
+}
+
+impl Display for TConversion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            conversion if conversion == Self::and() => write!(f, "and"),
+            conversion if conversion == Self::toffoli() => write!(f, "toffoli"),
+            conversion if conversion == Self::catalyzed() => write!(f, "catalyzed"),
+            Self { t_per_ccx } => write!(f, "{t_per_ccx}"),
+        }
+    }
+}
+
+impl FromStr for TConversion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "and" => Ok(Self::and()),
+            "toffoli" => Ok(Self::toffoli()),
+            "catalyzed" => Ok(Self::catalyzed()),
+            _ => s
+                .parse()
+                .ok()
+                .and_then(|t_per_ccx| Self::per_ccx(t_per_ccx).ok())
+                .ok_or_else(|| {
+                    format!(
+                        "unknown T conversion '{s}' (expected and, toffoli, catalyzed or a \
+                         positive number of T gates per CCX)"
+                    )
+                }),
+        }
+    }
+}
+
+/// Resource summary of pyLIQTR.
+#[derive(Deserialize)]
+struct ResourceSummary {
+    #[serde(alias = "LogicalQubits", alias = "qubits")]
+    logical_qubits: u64,
+    #[serde(default, alias = "T", alias = "t")]
+    t_count: u64,
+    #[serde(
+        default,
+        alias = "Toffoli",
+        alias = "toffoli",
+        alias = "CCX",
+        alias = "ccx"
+    )]
+    toffoli_count: u64,
+    #[serde(default, alias = "CX", alias = "cx", alias = "CNOT", alias = "cnot")]
+    cx_count: Option<u64>,
+    #[serde(default, alias = "Clifford", alias = "clifford")]
+    clifford_count: u64,
+    #[serde(default, alias = "Rotations", alias = "rotations")]
+    rotation_count: u64,
+}
+
+impl LogicalCounts {
+    /// Import a resource summary of pyLIQTR from a JSON file, converting the T
+    /// gates into CCX with `conversion`, and synthesizing the rotations with
+    /// `synthesis`.
+    pub fn from_pyliqtr(
+        filename: impl AsRef<Path>,
+        conversion: TConversion,
+        synthesis: RotationSynthesis,
+    ) -> Result<Self, String> {
+        let filename = filename.as_ref();
+        let content = read_to_string(filename)
+            .map_err(|err| format!("can't read {}: {err}", filename.display()))?;
+        Self::from_pyliqtr_json(&content, conversion, synthesis)
+    }
+
+    /// Import a resource summary of pyLIQTR given as JSON, converting the T
+    /// gates into CCX with `conversion`, and synthesizing the rotations with
+    /// `synthesis`.
+    pub fn from_pyliqtr_json(
+        json: &str,
+        conversion: TConversion,
+        synthesis: RotationSynthesis,
+    ) -> Result<Self, String> {
+        let summary: ResourceSummary = serde_json::from_str(json)
+            .map_err(|err| format!("invalid pyLIQTR resource summary: {err}"))?;
+
+        let rotation_t_count = summary
+            .rotation_count
+            .checked_mul(synthesis.t_per_rotation())
+            .ok_or("too many rotations")?;
+        let ccx_count = summary
+            .toffoli_count
+            .checked_add(conversion.ccx_count(summary.t_count))
+            .and_then(|ccx| ccx.checked_add(synthesis.ccx_count(rotation_t_count)))
+            .ok_or("too many Toffoli gates")?;
+        let mut counts = Self {
+            gate_set: GateSet::default().with_rotation_synthesis(synthesis),
+            rotation_t_count,
+            ..Self::new(
+                summary.logical_qubits,
+                summary.cx_count.unwrap_or(summary.clifford_count),
+                ccx_count,
+            )
+        };
+        counts.rotations.record_unknown(summary.rotation_count);
+        tracing::info!(
+            qubits = counts.qubit_count,
+            cx = counts.cx_count,
+            ccx = counts.ccx_count,
+            t = summary.t_count,
+            rotations = summary.rotation_count,
+            rotation_t = rotation_t_count,
+            %conversion,
+            "imported pyLIQTR resources"
+        );
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogicalCounts, RotationSynthesis, TConversion};
+    use crate::counter::rotations::RotationKind;
+
+    fn counts(json: &str, conversion: TConversion) -> Result<LogicalCounts, String> {
+        LogicalCounts::from_pyliqtr_json(json, conversion, RotationSynthesis::new(30, conversion))
+    }
+
+    #[test]
+    fn t_gates_are_converted_into_ccx() {
+        let json = r#"{"LogicalQubits": 100, "T": 4001, "Toffoli": 10, "Clifford": 50000}"#;
+        for (conversion, ccx) in [
+            (TConversion::and(), 10 + 1001),
+            (TConversion::toffoli(), 10 + 572),
+            (TConversion::catalyzed(), 10 + 2001),
+            (TConversion::per_ccx(1).expect("positive"), 10 + 4001),
+        ] {
+            let counts = counts(json, conversion).expect("valid summary");
+            assert_eq!(counts.ccx_count(), ccx, "{conversion}");
+            assert_eq!(counts.qubit_count(), 100);
+            // Cliffords as CX
+            assert_eq!(counts.cx_count(), 50000);
+        }
+    }
+
+    #[test]
+    fn cx_count_replaces_the_cliffords() {
+        let counts = counts(
+            r#"{"qubits": 10, "t": 8, "CNOT": 70, "Clifford": 500}"#,
+            TConversion::and(),
+        )
+        .expect("valid summary");
+        assert_eq!((counts.cx_count(), counts.ccx_count()), (70, 2));
+    }
+
+    #[test]
+    fn rotations_are_synthesized() {
+        let counts = counts(
+            r#"{"LogicalQubits": 10, "T": 4, "Rotations": 3}"#,
+            TConversion::and(),
+        )
+        .expect("valid summary");
+        // 1 CCX for the T gates, 90 T gates of the rotations in 23 CCX
+        assert_eq!(counts.ccx_count(), 1 + 23);
+        assert_eq!(counts.rotation_t_count(), 90);
+        assert_eq!(counts.rotations().count(RotationKind::Generic), 3);
+        // 2^-10 per rotation
+        let error = counts.rotation_synthesis_error();
+        assert!((error - 3.0 / 1024.0).abs() < 1e-12, "{error}");
+    }
+
+    #[test]
+    fn conversions_are_parsed() {
+        for (text, t_per_ccx) in [("and", 4), ("toffoli", 7), ("catalyzed", 2), ("5", 5)] {
+            let conversion: TConversion = text.parse().expect("valid conversion");
+            assert_eq!(conversion.t_per_ccx(), t_per_ccx);
+            assert_eq!(conversion.to_string(), text);
+        }
+        for text in ["0", "-1", "T"] {
+            assert!(text.parse::<TConversion>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn invalid_summaries_are_rejected() {
+        for json in [
+            r#"{"T": 4}"#,
+            r#"{"LogicalQubits": -1}"#,
+            r#"{"LogicalQubits": 1, "Toffoli": 18446744073709551615, "T": 4}"#,
+            r#"{"LogicalQubits": 1, "Rotations": 18446744073709551615}"#,
+        ] {
+            assert!(counts(json, TConversion::and()).is_err(), "{json}");
+        }
+    }
+}
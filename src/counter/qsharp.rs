@@ -0,0 +1,1104 @@
+// Copyright (c) Microsoft Corporation.
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Counting of the logical resources of Q# programs (requires the `qsharp`
+//! feature).
+//!
+//! [`LogicalCounts`] is used as a [`Backend`] of the Q# interpreter, which
+//! calls it for each gate of the program.
+//!
+//! Rotations are recorded by angle (see [`super::rotations`]): Pauli and
+//! Clifford ones cost their basis changes and S gates, and the T gates of the
+//! other ones are converted into CCX at the end of the counting.
+//!
+//! Measurements are counted, and a gate following measurements counts as a
+//! feed-forward (assuming it waits for their outcomes).
+//!
+//! CCX gates on a target in |0⟩ are logical ANDs. They are tracked to detect
+//! their uncomputation, i.e. a CCX with the same controls and target while
+//! none of them has been modified since: with
+//! [`GateSet::with_measurement_uncompute`], it is counted as a measurement
+//! followed by a classically controlled CZ, without magic state.
+//!
+//! The other CCX gates undone later in the same way are typically the compute
+//! and uncompute of a `within … apply` block. Unless disabled with
+//! [`GateSet::without_mirrored_uncompute`], the pair is then counted as an AND
+//! computed on an ancilla qubit, CX-ed onto the target before and after the
+//! `apply` block, and uncomputed by measurement: one CCX instead of two.
+//!
+//! Large programs (e.g. the loops of cryptographic circuits) can be counted in
+//! seconds instead of hours with the markers of `Std.ResourceEstimation`:
+//! - in `within { RepeatEstimates(n); } apply { body(); }`, the body is
+//!   executed once and its cost counted `n` times;
+//! - in `if BeginEstimateCaching(name, variant) { body(); EndEstimateCaching(); }`,
+//!   the body is executed the first time only, and its cost added again the
+//!   next times with the same name and variant. The body must not depend on
+//!   the state of the program (other than its variant), and logical ANDs are
+//!   not tracked across a cached body.
+
+use std::{
+    collections::HashMap,
+    f64::consts::FRAC_PI_4,
+    fs::read_to_string,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use num_bigint::BigUint;
+use num_complex::Complex;
+use num_traits::ToPrimitive;
+use qsc::{
+    interpret::{GenericReceiver, Interpreter, Value},
+    Backend, LanguageFeatures, SourceMap, TargetCapabilityFlags,
+};
+use rand::Rng;
+
+use super::{
+    gate_set::{GateCost, GateSet},
+    rotations::RotationKind,
+    sampling::SampledCounts,
+    LogicalCounts,
+};
+use crate::{cancel::CancellationToken, random};
+
+/// Number of counted gates between two calls of the progress callback.
+const PROGRESS_INTERVAL: u64 = 1 << 20;
+
+/// Callback receiving the counts so far.
+pub(super) type Progress = Arc<dyn Fn(&LogicalCounts) + Send + Sync>;
+
+/// Number of counted gates between two checks of the time limit.
+const TIME_CHECK_INTERVAL: u64 = 1 << 12;
+
+/// Error of a counting from Q#.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CountingError {
+    /// The program can't be read, compiled or run.
+    Program(String),
+    /// The counting was cancelled.
+    Cancelled(String),
+    /// The counting exceeded its gate or time limit.
+    LimitExceeded(String),
+}
+
+impl std::fmt::Display for CountingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Program(message) | Self::Cancelled(message) | Self::LimitExceeded(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CountingError {}
+
+impl From<CountingError> for String {
+    fn from(err: CountingError) -> Self {
+        err.to_string()
+    }
+}
+
+/// Payload of the unwinding stopping the interpreter.
+enum Interrupted {
+    /// The counting was cancelled.
+    Cancelled,
+    /// The counting exceeded its gate limit.
+    GateLimit(u64),
+    /// The counting exceeded its time limit.
+    TimeLimit(Duration),
+}
+
+/// CCX gate computed, whose qubits have not been modified since.
+#[derive(Clone)]
+struct Computed {
+    // controls, with their versions
+    controls: [(usize, u64); 2],
+    // whether the target was in |0⟩
+    is_and: bool,
+    // index of the gate
+    gate: u64,
+}
+
+/// Uncomputation of a CCX gate.
+pub(super) enum Uncompute {
+    /// Of a logical AND.
+    And,
+    /// Of a CCX gate on a target not in |0⟩ (e.g. the adjoint of a `within`
+    /// block), computed at the given gate index.
+    Mirrored(u64),
+}
+
+/// CCX gates computed and not modified since, to detect their uncomputation.
+#[derive(Clone, Default)]
+pub(super) struct AndTracker {
+    // number of modifications of each qubit
+    versions: Vec<u64>,
+    // whether each qubit is known to be in |0⟩
+    zero: Vec<bool>,
+    // CCX computed on each target
+    computed: HashMap<usize, Computed>,
+    // gate indices of the compute and uncompute of the mirrored CCX
+    mirrored: Vec<(u64, u64)>,
+}
+
+impl AndTracker {
+    /// Make room for the qubit `q`.
+    fn track(&mut self, q: usize) {
+        if q >= self.versions.len() {
+            self.versions.resize(q + 1, 0);
+            self.zero.resize(q + 1, false);
+        }
+    }
+
+    /// Record that the qubit `q` has been modified.
+    fn modified(&mut self, q: usize) {
+        self.track(q);
+        self.versions[q] += 1;
+        self.zero[q] = false;
+        self.computed.remove(&q);
+    }
+
+    /// Record that the qubit `q` is in |0⟩ (allocated or reset).
+    fn reset(&mut self, q: usize) {
+        self.modified(q);
+        self.zero[q] = true;
+    }
+
+    /// Controls with their current versions.
+    fn controls(&mut self, ctl0: usize, ctl1: usize) -> [(usize, u64); 2] {
+        self.track(ctl0.max(ctl1));
+        let mut controls = [(ctl0, self.versions[ctl0]), (ctl1, self.versions[ctl1])];
+        controls.sort_unstable();
+        controls
+    }
+
+    /// Record the CCX gate of index `gate`, returning whether it computes an
+    /// AND.
+    fn compute(&mut self, ctl0: usize, ctl1: usize, q: usize, gate: u64) -> bool {
+        self.track(q);
+        let is_and = self.zero[q];
+        self.modified(q);
+        let controls = self.controls(ctl0, ctl1);
+        self.computed.insert(
+            q,
+            Computed {
+                controls,
+                is_and,
+                gate,
+            },
+        );
+        is_and
+    }
+
+    /// Whether the CCX gate of index `gate` uncomputes a previous one,
+    /// recording it if so. The uncomputation of CCX gates whose target was
+    /// not in |0⟩ is only detected if `mirrored`.
+    fn uncompute(
+        &mut self,
+        ctl0: usize,
+        ctl1: usize,
+        q: usize,
+        gate: u64,
+        mirrored: bool,
+    ) -> Option<Uncompute> {
+        let controls = self.controls(ctl0, ctl1);
+        let uncompute = match self.computed.get(&q) {
+            Some(computed) if computed.controls == controls && computed.is_and => Uncompute::And,
+            Some(computed) if computed.controls == controls && mirrored => {
+                Uncompute::Mirrored(computed.gate)
+            }
+            _ => return None,
+        };
+        match uncompute {
+            Uncompute::And => self.reset(q),
+            Uncompute::Mirrored(since) => {
+                self.modified(q);
+                self.mirrored.push((since, gate));
+            }
+        }
+        Some(uncompute)
+    }
+
+    /// Forget the computed CCX gates, e.g. after a block whose gates are not
+    /// known.
+    fn forget(&mut self) {
+        self.computed.clear();
+        self.zero.fill(false);
+    }
+
+    /// Number of ancilla qubits needed by the mirrored CCX gates: each one
+    /// holds the AND of the controls from the compute to the uncompute.
+    fn ancillas(&self) -> u64 {
+        let mut events: Vec<_> = self
+            .mirrored
+            .iter()
+            .flat_map(|&(start, end)| [(start, 1), (end, -1)])
+            .collect();
+        events.sort_unstable();
+        let mut live: i64 = 0;
+        let mut peak: i64 = 0;
+        for (_, change) in events {
+            live += change;
+            peak = peak.max(live);
+        }
+        peak.unsigned_abs()
+    }
+}
+
+/// Counts at the start of a block, or cost of a block.
+#[derive(Clone, Copy, Default)]
+struct Snapshot {
+    cx: u64,
+    ccx: u64,
+    ands: u64,
+    measurements: u64,
+    feed_forwards: u64,
+    rotation_t: u64,
+}
+
+impl Snapshot {
+    /// Cost since `start`.
+    fn since(self, start: Self) -> Self {
+        Self {
+            cx: self.cx - start.cx,
+            ccx: self.ccx - start.ccx,
+            ands: self.ands - start.ands,
+            measurements: self.measurements - start.measurements,
+            feed_forwards: self.feed_forwards - start.feed_forwards,
+            rotation_t: self.rotation_t - start.rotation_t,
+        }
+    }
+}
+
+/// Blocks of the program whose cost is counted once and then repeated.
+#[derive(Clone, Default)]
+pub(super) struct Blocks {
+    // blocks being repeated: counts at their start and number of repetitions
+    repeats: Vec<(Snapshot, u64)>,
+    // blocks being cached: name and variant, and counts at their start
+    caching: Vec<(String, Snapshot)>,
+    // cost of the cached blocks
+    cache: HashMap<String, Snapshot>,
+}
+
+impl LogicalCounts {
+    /// Count the logical resources from a Q# file, with the simplified gate
+    /// counting (see [`GateSet::simplified`]).
+    pub fn from_qsharp(filename: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_qsharp_with_gate_set(filename, GateSet::default())
+    }
+
+    /// Count the logical resources from a Q# file, converting non-native gates
+    /// according to `gate_set`.
+    pub fn from_qsharp_with_gate_set(
+        filename: impl AsRef<Path>,
+        gate_set: GateSet,
+    ) -> Result<Self, String> {
+        Self::qsharp_counter(gate_set)
+            .count_qsharp(filename)
+            .map_err(String::from)
+    }
+
+    /// Count the logical resources from a Q# file, converting non-native gates
+    /// according to `gate_set`, and calling `progress` with the counts so far
+    /// every million gates or so.
+    pub fn from_qsharp_with_progress(
+        filename: impl AsRef<Path>,
+        gate_set: GateSet,
+        progress: impl Fn(&LogicalCounts) + Send + Sync + 'static,
+    ) -> Result<Self, String> {
+        Self::qsharp_counter(gate_set)
+            .with_progress(progress)
+            .count_qsharp(filename)
+            .map_err(String::from)
+    }
+
+    /// Empty counts, to be filled by [`LogicalCounts::count_qsharp`],
+    /// converting non-native gates according to `gate_set`.
+    pub fn qsharp_counter(gate_set: GateSet) -> Self {
+        Self {
+            gate_set,
+            ..Self::default()
+        }
+    }
+
+    /// Call `progress` with the counts so far every million gates or so while
+    /// counting from Q#.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(&LogicalCounts) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Draw the measurement outcomes at random from `seed` while counting from
+    /// Q#, instead of always 0.
+    pub fn with_random_outcomes(mut self, seed: u64) -> Self {
+        self.outcomes = Some(random::seeded(seed));
+        self
+    }
+
+    /// Count the resources of the Q# program in `filename` `samples` times,
+    /// with random measurement outcomes drawn from `seed` (see
+    /// [`super::sampling`]).
+    pub fn sample_qsharp(
+        self,
+        filename: impl AsRef<Path>,
+        samples: usize,
+        seed: u64,
+    ) -> Result<SampledCounts, CountingError> {
+        let _span = tracing::info_span!("sample_qsharp", samples, seed).entered();
+        let mut seeds = random::seeded(seed);
+        let counts = (0..samples.max(1))
+            .map(|_| {
+                self.clone()
+                    .with_random_outcomes(seeds.gen())
+                    .count_qsharp(filename.as_ref())
+            })
+            .collect::<Result<_, _>>()?;
+        SampledCounts::new(seed, counts).map_err(CountingError::Program)
+    }
+
+    /// Stop counting from Q# when `cancellation` is cancelled.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Stop counting from Q# with an error once `max_gates` gates have been
+    /// interpreted (the repeated and cached blocks counting once), e.g. for
+    /// programs with unbounded loops.
+    pub fn with_gate_limit(mut self, max_gates: u64) -> Self {
+        self.gate_limit = Some(max_gates);
+        self
+    }
+
+    /// Stop counting each Q# program with an error once it has run for
+    /// `limit` (checked while gates are interpreted).
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Add the resources of the Q# program in `filename` to the counts.
+    ///
+    /// If cancelled or over its limits, the error gives the counts reached so
+    /// far.
+    pub fn count_qsharp(mut self, filename: impl AsRef<Path>) -> Result<Self, CountingError> {
+        let _span =
+            tracing::info_span!("count_qsharp", file = %filename.as_ref().display()).entered();
+        let content = read_to_string(filename)
+            .map_err(|_| CountingError::Program(String::from("Cannot read filename")))?;
+
+        let sources = SourceMap::new([("source".into(), content.into())], None);
+
+        let mut interpreter = Interpreter::new(
+            true,
+            sources,
+            qsc::PackageType::Exe,
+            TargetCapabilityFlags::all(),
+            LanguageFeatures::default(),
+        )
+        .map_err(|_| CountingError::Program(String::from("Cannot create interpreter")))?;
+
+        let mut stdout = std::io::stdout();
+        let mut out = GenericReceiver::new(&mut stdout);
+
+        // The interpreter can't be stopped from the backend, so cancellation
+        // and the limits unwind through it (without calling the panic hook).
+        self.started = Some(Instant::now());
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            interpreter.eval_entry_with_sim(&mut self, &mut out)
+        }));
+        self.progress = None;
+        self.started = None;
+        match result {
+            Ok(result) => result
+                .map_err(|_| CountingError::Program(String::from("Cannot estimate Q# code")))?,
+            Err(payload) => match payload.downcast::<Interrupted>() {
+                Ok(interrupted) => {
+                    let progress = format!(
+                        "after {} qubits, {} CX and {} CCX",
+                        self.qubit_count, self.cx_count, self.ccx_count
+                    );
+                    return Err(match *interrupted {
+                        Interrupted::Cancelled => {
+                            CountingError::Cancelled(format!("counting cancelled {progress}"))
+                        }
+                        Interrupted::GateLimit(max_gates) => CountingError::LimitExceeded(format!(
+                            "counting exceeded {max_gates:e} gates {progress}"
+                        )),
+                        Interrupted::TimeLimit(limit) => CountingError::LimitExceeded(format!(
+                            "counting exceeded its time limit of {limit:?} {progress}"
+                        )),
+                    });
+                }
+                Err(payload) => resume_unwind(payload),
+            },
+        };
+        let ancillas = self.ands.ancillas();
+        self.qubit_count += ancillas;
+        let rotation_ccx = self
+            .gate_set
+            .rotation_synthesis
+            .ccx_count(self.rotation_t_count);
+        self.ccx_count += rotation_ccx;
+        for (angle, kind, count) in self.rotations.iter() {
+            tracing::debug!(angle, %kind, count, "rotations");
+        }
+        tracing::info!(
+            qubits = self.qubit_count,
+            cx = self.cx_count,
+            ccx = self.ccx_count,
+            ands = self.and_count,
+            measurements = self.measurement_count,
+            feed_forwards = self.feed_forward_count,
+            rotation_t = self.rotation_t_count,
+            rotation_ccx,
+            ancillas,
+            gates = self.counted_gates,
+            "counted logical resources"
+        );
+
+        Ok(self)
+    }
+
+    /// Current counts.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            cx: self.cx_count,
+            ccx: self.ccx_count,
+            ands: self.and_count,
+            measurements: self.measurement_count,
+            feed_forwards: self.feed_forward_count,
+            rotation_t: self.rotation_t_count,
+        }
+    }
+
+    /// Add `times` the cost of a block, failing if a count overflows.
+    fn add_block(&mut self, cost: Snapshot, times: u64) -> Result<(), String> {
+        let add = |count: u64, cost: u64| {
+            cost.checked_mul(times)
+                .and_then(|cost| count.checked_add(cost))
+                .ok_or_else(|| format!("counts overflow after repeating a block {times} times"))
+        };
+        let counts = Snapshot {
+            cx: add(self.cx_count, cost.cx)?,
+            ccx: add(self.ccx_count, cost.ccx)?,
+            ands: add(self.and_count, cost.ands)?,
+            measurements: add(self.measurement_count, cost.measurements)?,
+            feed_forwards: add(self.feed_forward_count, cost.feed_forwards)?,
+            rotation_t: add(self.rotation_t_count, cost.rotation_t)?,
+        };
+        self.restore(counts);
+        Ok(())
+    }
+
+    /// Set the counts to `counts`.
+    fn restore(&mut self, counts: Snapshot) {
+        self.cx_count = counts.cx;
+        self.ccx_count = counts.ccx;
+        self.and_count = counts.ands;
+        self.measurement_count = counts.measurements;
+        self.feed_forward_count = counts.feed_forwards;
+        self.rotation_t_count = counts.rotation_t;
+    }
+
+    /// Start a block executed once and counted `count` times.
+    fn begin_repeat(&mut self, arg: &Value) -> Result<Value, String> {
+        let Value::Int(count) = arg else {
+            return Err("BeginRepeatEstimates expects a number of repetitions".into());
+        };
+        let count =
+            u64::try_from(*count).map_err(|_| format!("can't repeat estimates {count} times"))?;
+        self.blocks.repeats.push((self.snapshot(), count));
+        Ok(Value::unit())
+    }
+
+    /// End the innermost repeated block, counting its cost the remaining
+    /// times.
+    fn end_repeat(&mut self) -> Result<Value, String> {
+        let (start, count) = self
+            .blocks
+            .repeats
+            .pop()
+            .ok_or("EndRepeatEstimates without BeginRepeatEstimates")?;
+        let cost = self.snapshot().since(start);
+        tracing::debug!(count, cx = cost.cx, ccx = cost.ccx, "repeated block");
+        if count == 0 {
+            // the block has been executed once anyway
+            self.restore(start);
+        } else {
+            self.add_block(cost, count - 1)?;
+        }
+        Ok(Value::unit())
+    }
+
+    /// Start a cached block, returning whether it must be executed (the first
+    /// time), otherwise adding its cost.
+    fn begin_caching(&mut self, arg: &Value) -> Result<Value, String> {
+        let key = match arg {
+            Value::Tuple(args) => match &**args {
+                [Value::String(name), Value::Int(variant)] => format!("{name}/{variant}"),
+                _ => return Err("BeginEstimateCaching expects a name and a variant".into()),
+            },
+            _ => return Err("BeginEstimateCaching expects a name and a variant".into()),
+        };
+        if let Some(&cost) = self.blocks.cache.get(&key) {
+            tracing::trace!(block = key, "cached block");
+            self.add_block(cost, 1)?;
+            // the qubits modified by the block are unknown
+            self.ands.forget();
+            return Ok(Value::Bool(false));
+        }
+        self.blocks.caching.push((key, self.snapshot()));
+        Ok(Value::Bool(true))
+    }
+
+    /// End the innermost cached block, recording its cost.
+    fn end_caching(&mut self) -> Result<Value, String> {
+        let (key, start) = self
+            .blocks
+            .caching
+            .pop()
+            .ok_or("EndEstimateCaching without BeginEstimateCaching")?;
+        let cost = self.snapshot().since(start);
+        tracing::debug!(block = key, cx = cost.cx, ccx = cost.ccx, "caching block");
+        self.blocks.cache.insert(key, cost);
+        Ok(Value::unit())
+    }
+
+    /// Add the cost of a non-native gate acting on `targets`.
+    fn add_cost_on(&mut self, cost: GateCost, targets: &[usize]) {
+        for &q in targets {
+            self.ands.modified(q);
+        }
+        self.add_cost(cost);
+    }
+
+    /// Add the cost of a non-native gate.
+    fn add_cost(&mut self, cost: GateCost) {
+        self.cx_count += cost.cx;
+        self.ccx_count += cost.ccx;
+        self.count_gate();
+    }
+
+    /// Add the cost of a rotation by `angle` on `targets`, `basis` being the
+    /// cost of the basis changes mapping it to a Z rotation of one qubit. The
+    /// T gates of the non-Clifford rotations are converted into CCX at the end
+    /// of the counting.
+    fn rotation(&mut self, angle: f64, basis: GateCost, targets: &[usize]) {
+        let kind = self.rotations.record(angle);
+        let cost = match kind {
+            RotationKind::Pauli => GateCost::FREE,
+            RotationKind::Clifford => GateCost {
+                cx: basis.cx + self.gate_set.s.cx,
+                ccx: basis.ccx + self.gate_set.s.ccx,
+            },
+            RotationKind::T | RotationKind::Generic => basis,
+        };
+        self.rotation_t_count += self.gate_set.rotation_synthesis.t_count(kind);
+        self.add_cost_on(cost, targets);
+    }
+
+    /// Cost of mapping the X basis to the Z basis and back on `qubits`.
+    fn x_basis(&self, qubits: u64) -> GateCost {
+        GateCost {
+            cx: 2 * qubits * self.gate_set.h.cx,
+            ccx: 2 * qubits * self.gate_set.h.ccx,
+        }
+    }
+
+    /// Cost of mapping the Y basis to the Z basis and back on `qubits`.
+    fn y_basis(&self, qubits: u64) -> GateCost {
+        let x = self.x_basis(qubits);
+        GateCost {
+            cx: x.cx + 2 * qubits * self.gate_set.s.cx,
+            ccx: x.ccx + 2 * qubits * self.gate_set.s.ccx,
+        }
+    }
+
+    /// Count a measurement, whose outcome the next gates may wait for, and
+    /// draw its outcome.
+    fn measure(&mut self) -> bool {
+        self.measurement_count += 1;
+        self.measured = true;
+        self.outcomes.as_mut().is_some_and(Rng::gen)
+    }
+
+    /// Count a gate for progress reporting, and the feed-forward of the
+    /// previous measurements (an upper bound, the gate may not depend on their
+    /// outcomes).
+    fn count_gate(&mut self) {
+        if std::mem::take(&mut self.measured) {
+            self.feed_forward_count += 1;
+        }
+        self.counted_gates += 1;
+        if let Some(max_gates) = self.gate_limit {
+            if self.counted_gates > max_gates {
+                resume_unwind(Box::new(Interrupted::GateLimit(max_gates)));
+            }
+        }
+        if let (Some(limit), Some(started)) = (self.time_limit, self.started) {
+            if self.counted_gates.is_multiple_of(TIME_CHECK_INTERVAL) && started.elapsed() > limit {
+                resume_unwind(Box::new(Interrupted::TimeLimit(limit)));
+            }
+        }
+        if self.counted_gates.is_multiple_of(PROGRESS_INTERVAL) {
+            tracing::debug!(
+                qubits = self.qubit_count,
+                cx = self.cx_count,
+                ccx = self.ccx_count,
+                "counting"
+            );
+            if let Some(progress) = &self.progress {
+                progress(self);
+            }
+            if self
+                .cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+            {
+                resume_unwind(Box::new(Interrupted::Cancelled));
+            }
+        }
+    }
+}
+
+impl Backend for LogicalCounts {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        if self.gate_set.measurement_uncompute {
+            let uncompute = self.ands.uncompute(
+                ctl0,
+                ctl1,
+                q,
+                self.counted_gates,
+                self.gate_set.mirrored_uncompute,
+            );
+            match uncompute {
+                // measurement in the X basis and classically controlled CZ
+                Some(Uncompute::And) => {
+                    // the CZ is counted whatever the outcome
+                    self.measure();
+                    self.add_cost(self.gate_set.measurement_uncompute_cost());
+                    return;
+                }
+                // the compute is counted as an AND on an ancilla, CX-ed onto
+                // the target before and after the mirrored section
+                Some(Uncompute::Mirrored(_)) => {
+                    self.measure();
+                    let cost = self.gate_set.measurement_uncompute_cost();
+                    self.add_cost(GateCost {
+                        cx: cost.cx + 2,
+                        ..cost
+                    });
+                    return;
+                }
+                None => {}
+            }
+        }
+        if self.ands.compute(ctl0, ctl1, q, self.counted_gates) {
+            self.and_count += 1;
+        }
+        self.ccx_count += 1;
+        self.count_gate();
+    }
+
+    fn cx(&mut self, _ctl: usize, q: usize) {
+        self.ands.modified(q);
+        self.cx_count += 1;
+        self.count_gate();
+    }
+
+    fn cy(&mut self, _ctl: usize, q: usize) {
+        self.add_cost_on(self.gate_set.cy, &[q]);
+    }
+
+    fn cz(&mut self, _ctl: usize, q: usize) {
+        self.add_cost_on(self.gate_set.cz, &[q]);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.add_cost_on(self.gate_set.h, &[q]);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.ands.modified(q);
+        self.measure()
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.ands.reset(q);
+        self.measure()
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.ands.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.rotation(theta, self.x_basis(1), &[q]);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        let basis = self.x_basis(2);
+        self.rotation(
+            theta,
+            GateCost {
+                cx: basis.cx + 2,
+                ..basis
+            },
+            &[q0, q1],
+        );
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.rotation(theta, self.y_basis(1), &[q]);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        let basis = self.y_basis(2);
+        self.rotation(
+            theta,
+            GateCost {
+                cx: basis.cx + 2,
+                ..basis
+            },
+            &[q0, q1],
+        );
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.rotation(theta, GateCost::FREE, &[q]);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation(theta, GateCost::cx(2), &[q0, q1]);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.add_cost_on(self.gate_set.s, &[q]);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.add_cost_on(self.gate_set.s, &[q]);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.add_cost_on(self.gate_set.swap, &[q0, q1]);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.rotation(FRAC_PI_4, GateCost::FREE, &[q]);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.rotation(-FRAC_PI_4, GateCost::FREE, &[q]);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.ands.modified(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.ands.modified(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.ands.modified(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        let qubit = if let Some(qubit) = self.free_list.pop() {
+            qubit
+        } else {
+            let qubit = self.qubit_count;
+            self.qubit_count += 1;
+            qubit.to_usize().expect("qubit is too large")
+        };
+        self.ands.reset(qubit);
+        qubit
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.free_list.push(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        (vec![], 0)
+    }
+
+    fn qubit_is_zero(&mut self, _q: usize) -> bool {
+        true
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        match name {
+            "BeginRepeatEstimatesInternal" => Some(self.begin_repeat(&arg)),
+            "EndRepeatEstimatesInternal" => Some(self.end_repeat()),
+            "BeginEstimateCaching" => Some(self.begin_caching(&arg)),
+            "EndEstimateCaching" => Some(self.end_caching()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        panic::{catch_unwind, AssertUnwindSafe},
+        time::{Duration, Instant},
+    };
+
+    use qsc::{interpret::Value, Backend};
+
+    use super::{AndTracker, GateSet, Interrupted, LogicalCounts, Uncompute, TIME_CHECK_INTERVAL};
+
+    /// Tracker with the qubits `0..qubits` allocated in |0⟩.
+    fn allocated(qubits: usize) -> AndTracker {
+        let mut tracker = AndTracker::default();
+        for q in 0..qubits {
+            tracker.reset(q);
+        }
+        tracker
+    }
+
+    #[test]
+    fn and_is_uncomputed_by_the_same_ccx() {
+        let mut tracker = allocated(3);
+        assert!(tracker.compute(0, 1, 2, 0));
+        assert!(matches!(
+            tracker.uncompute(1, 0, 2, 1, true),
+            Some(Uncompute::And)
+        ));
+        // the target is back in |0⟩: the next CCX computes an AND again
+        assert!(tracker.compute(0, 1, 2, 2));
+        assert_eq!(tracker.ancillas(), 0);
+    }
+
+    #[test]
+    fn modified_target_is_not_uncomputed() {
+        let mut tracker = allocated(3);
+        assert!(tracker.compute(0, 1, 2, 0));
+        tracker.modified(2);
+        assert!(tracker.uncompute(0, 1, 2, 1, true).is_none());
+    }
+
+    #[test]
+    fn modified_control_is_not_uncomputed() {
+        let mut tracker = allocated(3);
+        assert!(tracker.compute(0, 1, 2, 0));
+        tracker.modified(1);
+        assert!(tracker.uncompute(0, 1, 2, 1, true).is_none());
+        // other controls neither
+        let mut tracker = allocated(4);
+        assert!(tracker.compute(0, 1, 2, 0));
+        assert!(tracker.uncompute(0, 3, 2, 1, true).is_none());
+    }
+
+    #[test]
+    fn ccx_on_a_modified_target_is_mirrored() {
+        let mut tracker = allocated(4);
+        tracker.modified(2);
+        assert!(!tracker.compute(0, 1, 2, 5));
+        assert!(matches!(
+            tracker.uncompute(0, 1, 2, 9, true),
+            Some(Uncompute::Mirrored(5))
+        ));
+        // the target is not known to be in |0⟩ after a mirrored uncompute
+        assert!(!tracker.compute(0, 1, 2, 10));
+        assert_eq!(tracker.ancillas(), 1);
+    }
+
+    #[test]
+    fn mirrored_uncompute_can_be_disabled() {
+        let mut tracker = allocated(3);
+        tracker.modified(2);
+        assert!(!tracker.compute(0, 1, 2, 0));
+        assert!(tracker.uncompute(0, 1, 2, 1, false).is_none());
+        assert_eq!(tracker.ancillas(), 0);
+    }
+
+    #[test]
+    fn ancillas_are_the_peak_of_overlapping_mirrored_pairs() {
+        let mut tracker = allocated(6);
+        for q in [2, 5] {
+            tracker.modified(q);
+        }
+        // nested pairs on the targets 2 and 5, then a pair after both
+        assert!(!tracker.compute(0, 1, 2, 0));
+        assert!(!tracker.compute(3, 4, 5, 1));
+        assert!(tracker.uncompute(3, 4, 5, 2, true).is_some());
+        assert!(tracker.uncompute(0, 1, 2, 3, true).is_some());
+        assert!(!tracker.compute(0, 1, 2, 4));
+        assert!(tracker.uncompute(0, 1, 2, 5, true).is_some());
+        assert_eq!(tracker.ancillas(), 2);
+    }
+
+    #[test]
+    fn forgotten_ccx_are_not_uncomputed() {
+        let mut tracker = allocated(3);
+        assert!(tracker.compute(0, 1, 2, 0));
+        tracker.forget();
+        assert!(tracker.uncompute(0, 1, 2, 1, true).is_none());
+    }
+
+    /// Counts of a `within { CCX(0, 1, 2) } apply { CX(2, 3) }` block on a
+    /// target flipped beforehand, and of an AND computed and uncomputed.
+    fn within_apply(gate_set: GateSet) -> LogicalCounts {
+        let mut counts = LogicalCounts::qsharp_counter(gate_set);
+        let qubits: Vec<_> = (0..5).map(|_| counts.qubit_allocate()).collect();
+        counts.x(qubits[2]);
+        counts.ccx(qubits[0], qubits[1], qubits[2]);
+        counts.cx(qubits[2], qubits[3]);
+        counts.ccx(qubits[0], qubits[1], qubits[2]);
+        counts.ccx(qubits[0], qubits[1], qubits[4]);
+        counts.ccx(qubits[0], qubits[1], qubits[4]);
+        counts
+    }
+
+    #[test]
+    fn backend_counts_uncomputed_ccx_as_measurements() {
+        let counts = within_apply(GateSet::default().with_measurement_uncompute());
+        // one CCX per pair, the mirrored one on an ancilla
+        assert_eq!(counts.ccx_count(), 2);
+        assert_eq!(counts.and_count(), 1);
+        assert_eq!(counts.measurement_count(), 2);
+        assert_eq!(counts.ands.ancillas(), 1);
+
+        let counts = within_apply(
+            GateSet::default()
+                .with_measurement_uncompute()
+                .without_mirrored_uncompute(),
+        );
+        assert_eq!(counts.ccx_count(), 3);
+        assert_eq!(counts.measurement_count(), 1);
+        assert_eq!(counts.ands.ancillas(), 0);
+
+        let counts = within_apply(GateSet::default());
+        assert_eq!(counts.ccx_count(), 4);
+        assert_eq!(counts.measurement_count(), 0);
+    }
+
+    /// Call a custom intrinsic of `Std.ResourceEstimation`.
+    fn intrinsic(counts: &mut LogicalCounts, name: &str, arg: Value) -> Result<Value, String> {
+        counts.custom_intrinsic(name, arg).expect("known intrinsic")
+    }
+
+    fn cached_block(name: &str) -> Value {
+        Value::Tuple([Value::String(name.into()), Value::Int(0)].into())
+    }
+
+    #[test]
+    fn repeated_block_is_counted_each_time() {
+        let mut counts = LogicalCounts::qsharp_counter(GateSet::default());
+        let qubits: Vec<_> = (0..3).map(|_| counts.qubit_allocate()).collect();
+        counts.cx(qubits[0], qubits[1]);
+        intrinsic(&mut counts, "BeginRepeatEstimatesInternal", Value::Int(10)).expect("begun");
+        counts.cx(qubits[0], qubits[1]);
+        counts.ccx(qubits[0], qubits[1], qubits[2]);
+        // nested blocks multiply
+        intrinsic(&mut counts, "BeginRepeatEstimatesInternal", Value::Int(3)).expect("begun");
+        counts.cx(qubits[1], qubits[2]);
+        intrinsic(&mut counts, "EndRepeatEstimatesInternal", Value::unit()).expect("ended");
+        intrinsic(&mut counts, "EndRepeatEstimatesInternal", Value::unit()).expect("ended");
+        assert_eq!(counts.cx_count(), 1 + 10 * (1 + 3));
+        assert_eq!(counts.ccx_count(), 10);
+        assert_eq!(counts.qubit_count(), 3);
+
+        // a block repeated 0 times is not counted
+        intrinsic(&mut counts, "BeginRepeatEstimatesInternal", Value::Int(0)).expect("begun");
+        counts.cx(qubits[0], qubits[1]);
+        intrinsic(&mut counts, "EndRepeatEstimatesInternal", Value::unit()).expect("ended");
+        assert_eq!(counts.cx_count(), 41);
+
+        assert!(intrinsic(&mut counts, "BeginRepeatEstimatesInternal", Value::Int(-1)).is_err());
+        assert!(intrinsic(&mut counts, "EndRepeatEstimatesInternal", Value::unit()).is_err());
+    }
+
+    #[test]
+    fn repeated_block_fails_on_overflow() {
+        let mut counts = LogicalCounts::qsharp_counter(GateSet::default());
+        let qubits: Vec<_> = (0..2).map(|_| counts.qubit_allocate()).collect();
+        intrinsic(
+            &mut counts,
+            "BeginRepeatEstimatesInternal",
+            Value::Int(i64::MAX),
+        )
+        .expect("begun");
+        counts.cx(qubits[0], qubits[1]);
+        counts.cx(qubits[0], qubits[1]);
+        counts.cx(qubits[0], qubits[1]);
+        assert!(intrinsic(&mut counts, "EndRepeatEstimatesInternal", Value::unit()).is_err());
+        // the counts are left untouched
+        assert_eq!(counts.cx_count(), 3);
+    }
+
+    #[test]
+    fn cached_block_is_executed_once() {
+        let mut counts = LogicalCounts::qsharp_counter(GateSet::default());
+        let qubits: Vec<_> = (0..3).map(|_| counts.qubit_allocate()).collect();
+        for _ in 0..4 {
+            let execute = intrinsic(&mut counts, "BeginEstimateCaching", cached_block("adder"))
+                .expect("begun");
+            if matches!(execute, Value::Bool(true)) {
+                counts.cx(qubits[0], qubits[1]);
+                counts.ccx(qubits[0], qubits[1], qubits[2]);
+                intrinsic(&mut counts, "EndEstimateCaching", Value::unit()).expect("ended");
+            }
+        }
+        assert_eq!(counts.cx_count(), 4);
+        assert_eq!(counts.ccx_count(), 4);
+        // other names are cached separately
+        let execute =
+            intrinsic(&mut counts, "BeginEstimateCaching", cached_block("other")).expect("begun");
+        assert!(matches!(execute, Value::Bool(true)));
+        assert!(intrinsic(&mut counts, "BeginEstimateCaching", Value::Int(0)).is_err());
+    }
+
+    /// Interruption of `counts` while counting `gates` CX gates.
+    fn interruption(mut counts: LogicalCounts, gates: u64) -> Option<Interrupted> {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let qubits: Vec<_> = (0..2).map(|_| counts.qubit_allocate()).collect();
+            for _ in 0..gates {
+                counts.cx(qubits[0], qubits[1]);
+            }
+        }));
+        result
+            .err()
+            .and_then(|payload| payload.downcast::<Interrupted>().ok())
+            .map(|interrupted| *interrupted)
+    }
+
+    #[test]
+    fn counting_stops_after_the_gate_limit() {
+        let counter = || LogicalCounts::qsharp_counter(GateSet::default()).with_gate_limit(2);
+        assert!(interruption(counter(), 2).is_none());
+        assert!(matches!(
+            interruption(counter(), 3),
+            Some(Interrupted::GateLimit(2))
+        ));
+    }
+
+    #[test]
+    fn counting_stops_after_the_time_limit() {
+        let mut counts =
+            LogicalCounts::qsharp_counter(GateSet::default()).with_time_limit(Duration::ZERO);
+        // the limit is only checked while a program is counted
+        assert!(interruption(counts.clone(), TIME_CHECK_INTERVAL).is_none());
+        counts.started = Some(Instant::now());
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(interruption(counts.clone(), TIME_CHECK_INTERVAL - 1).is_none());
+        assert!(matches!(
+            interruption(counts, TIME_CHECK_INTERVAL),
+            Some(Interrupted::TimeLimit(Duration::ZERO))
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Import of the logical resources exported from Qualtran.
+//!
+//! [Qualtran](https://github.com/quantumlib/Qualtran) describes algorithms as
+//! call graphs of bloqs. The leaf bloq counts of the call graph (as returned by
+//! `get_bloq_call_graph`, a.k.a. sigma) and the qubit count (`QubitCount`) are
+//! exported as JSON:
+//!
+//! ```json
+//! {"qubits": 100, "bloq_counts": {"Toffoli": 1000, "CNOT": 5000, "And†": 20}}
+//! ```
+//!
+//! Each leaf bloq is converted into CX and CCX according to a [`GateSet`].
+//! Non-Clifford bloqs other than Toffoli gates (T gates, rotations, ...) are
+//! not supported by the architecture and are rejected.
+
+use std::{collections::BTreeMap, fs::read_to_string, path::Path};
+
+use serde::Deserialize;
+
+use super::{
+    gate_set::{GateCost, GateSet},
+    LogicalCounts,
+};
+
+/// Resources exported from Qualtran.
+#[derive(Deserialize)]
+struct QualtranExport {
+    /// Number of logical qubits.
+    #[serde(alias = "num_qubits", alias = "qubit_count")]
+    qubits: u64,
+    /// Number of each leaf bloq of the call graph.
+    #[serde(alias = "sigma", alias = "leaf_counts")]
+    bloq_counts: BTreeMap<String, u64>,
+}
+
+/// Name of a leaf bloq without its parameters, and whether it is an adjoint.
+///
+/// Bloq names are those of Qualtran's string representation, possibly followed
+/// by their parameters in parentheses, and by † for adjoints (before or after
+/// the parameters).
+fn bloq_name(name: &str) -> (&str, bool) {
+    let adjoint = name.trim_end().ends_with('†');
+    let name = name.split('(').next().unwrap_or_default().trim();
+    match name.strip_suffix('†') {
+        Some(name) => (name.trim_end(), true),
+        None => (name, adjoint),
+    }
+}
+
+/// Cost of a leaf bloq, or `None` if it is not supported.
+fn bloq_cost(name: &str, gate_set: &GateSet) -> Option<GateCost> {
+    let (name, adjoint) = bloq_name(name);
+    match name {
+        // logical AND, uncomputed by measurement and a classically controlled CZ
+        "And" if adjoint => Some(gate_set.cz),
+        "Toffoli" | "And" | "CCX" | "CCNOT" => Some(GateCost::ccx(1)),
+        "CNOT" | "CX" => Some(GateCost::cx(1)),
+        "CZ" => Some(gate_set.cz),
+        "CY" => Some(gate_set.cy),
+        "Hadamard" | "H" => Some(gate_set.h),
+        "SGate" | "S" => Some(gate_set.s),
+        "TwoBitSwap" | "SWAP" => Some(gate_set.swap),
+        "XGate" | "YGate" | "ZGate" | "X" | "Y" | "Z" | "MeasureZ" | "MeasureX" | "ZeroState"
+        | "OneState" | "PlusState" | "MinusState" | "ZeroEffect" | "OneEffect" | "PlusEffect"
+        | "MinusEffect" | "Allocate" | "Free" | "GlobalPhase" => Some(GateCost::FREE),
+        _ => None,
+    }
+}
+
+impl LogicalCounts {
+    /// Import the logical resources exported from Qualtran in a JSON file,
+    /// with the simplified gate counting (see [`GateSet::simplified`]).
+    pub fn from_qualtran(filename: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_qualtran_with_gate_set(filename, GateSet::default())
+    }
+
+    /// Import the logical resources exported from Qualtran in a JSON file,
+    /// converting the leaf bloqs according to `gate_set`.
+    pub fn from_qualtran_with_gate_set(
+        filename: impl AsRef<Path>,
+        gate_set: GateSet,
+    ) -> Result<Self, String> {
+        let filename = filename.as_ref();
+        let content = read_to_string(filename)
+            .map_err(|err| format!("can't read {}: {err}", filename.display()))?;
+        Self::from_qualtran_json(&content, gate_set)
+    }
+
+    /// Import the logical resources exported from Qualtran as JSON, converting
+    /// the leaf bloqs according to `gate_set`.
+    pub fn from_qualtran_json(json: &str, gate_set: GateSet) -> Result<Self, String> {
+        let export: QualtranExport = serde_json::from_str(json)
+            .map_err(|err| format!("invalid Qualtran resource export: {err}"))?;
+
+        let unsupported: Vec<_> = export
+            .bloq_counts
+            .keys()
+            .filter(|name| bloq_cost(name, &gate_set).is_none())
+            .map(String::as_str)
+            .collect();
+        if !unsupported.is_empty() {
+            return Err(format!(
+                "unsupported bloqs: {} (only Clifford and Toffoli gates are supported, \
+                 decompose the other bloqs in Qualtran)",
+                unsupported.join(", ")
+            ));
+        }
+
+        let mut counts = Self {
+            qubit_count: export.qubits,
+            gate_set,
+            ..Self::default()
+        };
+        let overflow = || String::from("too many gates in the Qualtran resource export");
+        let add = |total: u64, cost: u64, count: u64| {
+            cost.checked_mul(count)
+                .and_then(|cost| total.checked_add(cost))
+                .ok_or_else(overflow)
+        };
+        for (name, &count) in &export.bloq_counts {
+            let cost = bloq_cost(name, &counts.gate_set).unwrap_or_default();
+            counts.cx_count = add(counts.cx_count, cost.cx, count)?;
+            counts.ccx_count = add(counts.ccx_count, cost.ccx, count)?;
+            match bloq_name(name) {
+                ("And", false) => counts.and_count = add(counts.and_count, 1, count)?,
+                ("MeasureZ" | "MeasureX", _) => {
+                    counts.measurement_count = add(counts.measurement_count, 1, count)?;
+                }
+                // the CZ correction waits for the measurement
+                ("And", true) => {
+                    counts.measurement_count = add(counts.measurement_count, 1, count)?;
+                    counts.feed_forward_count = add(counts.feed_forward_count, 1, count)?;
+                }
+                _ => {}
+            }
+        }
+        tracing::info!(
+            qubits = counts.qubit_count,
+            cx = counts.cx_count,
+            ccx = counts.ccx_count,
+            bloqs = export.bloq_counts.len(),
+            "imported Qualtran resources"
+        );
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bloq_name, GateSet, LogicalCounts};
+
+    fn counts(json: &str) -> Result<LogicalCounts, String> {
+        LogicalCounts::from_qualtran_json(json, GateSet::default())
+    }
+
+    #[test]
+    fn bloq_names_drop_their_parameters() {
+        assert_eq!(bloq_name("Toffoli"), ("Toffoli", false));
+        assert_eq!(bloq_name("And(cv1=1, cv2=0)"), ("And", false));
+        assert_eq!(bloq_name("And†"), ("And", true));
+        assert_eq!(bloq_name("And†(cv1=1, cv2=0)"), ("And", true));
+        assert_eq!(bloq_name("And(cv1=1, cv2=0)†"), ("And", true));
+    }
+
+    #[test]
+    fn leaf_bloqs_are_converted() {
+        let counts = counts(
+            r#"{"qubits": 100, "bloq_counts": {"Toffoli": 1000, "CNOT": 5000, "And": 20,
+                "And(cv1=0, cv2=1)": 5, "And†": 25, "TwoBitSwap": 2, "Hadamard": 7,
+                "MeasureZ": 3, "XGate": 9}}"#,
+        )
+        .expect("supported bloqs");
+        assert_eq!(counts.qubit_count(), 100);
+        // SWAP as 3 CX and And† as a CZ, counted as a CX
+        assert_eq!(counts.cx_count(), 5000 + 6 + 25);
+        assert_eq!(counts.ccx_count(), 1025);
+        assert_eq!(counts.and_count(), 25);
+        assert_eq!(counts.measurement_count(), 3 + 25);
+        assert_eq!(counts.feed_forward_count(), 25);
+    }
+
+    #[test]
+    fn aliases_of_the_fields_are_accepted() {
+        let counts = counts(r#"{"num_qubits": 10, "sigma": {"CCX": 4}}"#).expect("valid export");
+        assert_eq!((counts.qubit_count(), counts.ccx_count()), (10, 4));
+    }
+
+    #[test]
+    fn unknown_bloqs_are_rejected() {
+        let err = counts(r#"{"qubits": 10, "bloq_counts": {"TGate": 4, "Rz(0.1)": 2, "CNOT": 1}}"#)
+            .err()
+            .unwrap_or_default();
+        assert!(err.contains("Rz(0.1), TGate"), "{err}");
+        assert!(counts(r#"{"qubits": 10}"#).is_err());
+        assert!(counts("not json").is_err());
+    }
+
+    #[test]
+    fn overflowing_counts_are_rejected() {
+        assert!(
+            counts(r#"{"qubits": 10, "bloq_counts": {"TwoBitSwap": 18446744073709551615}}"#)
+                .is_err()
+        );
+        assert!(counts(
+            r#"{"qubits": 10, "bloq_counts": {"CNOT": 18446744073709551615, "CX": 1}}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,323 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Rotations met when counting, and their synthesis.
+//!
+//! The architecture has no native rotation: a rotation by a multiple of π is a
+//! Pauli (free), by an odd multiple of π/2 a Clifford (an S gate, up to
+//! Paulis), by an odd multiple of π/4 a T gate (up to Cliffords), and any other
+//! angle is synthesized into a sequence of T gates. T gates are then converted
+//! into CCX with a [`TConversion`], see [`RotationSynthesis`].
+//!
+//! The angles are recorded in a [`RotationHistogram`], with a resolution of
+//! 10⁻⁶π. Rotations imported without their angle (e.g. from a pyLIQTR summary)
+//! are recorded as generic ones.
+
+use std::{collections::BTreeMap, f64::consts::PI, fmt::Display};
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use super::pyliqtr::TConversion;
+
+/// Resolution of the angles of the histogram, in fractions of π.
+const BUCKETS_PER_PI: u64 = 1_000_000;
+
+/// Kind of a rotation, deciding its synthesis cost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RotationKind {
+    /// Multiple of π: a Pauli (or the identity), free.
+    Pauli,
+    /// Odd multiple of π/2: an S gate, up to Paulis.
+    Clifford,
+    /// Odd multiple of π/4: a T gate, up to Cliffords.
+    T,
+    /// Any other angle, synthesized into a sequence of T gates.
+    Generic,
+}
+
+impl RotationKind {
+    /// Kind of the rotation of the bucket `bucket` of the histogram.
+    fn of_bucket(bucket: u64) -> Self {
+        let quarter_pi = BUCKETS_PER_PI / 4;
+        if !bucket.is_multiple_of(quarter_pi) {
+            return Self::Generic;
+        }
+        match (bucket / quarter_pi) % 4 {
+            0 => Self::Pauli,
+            2 => Self::Clifford,
+            _ => Self::T,
+        }
+    }
+
+    /// Kind of a rotation by `angle` (in radians).
+    #[must_use]
+    pub fn of(angle: f64) -> Self {
+        Self::of_bucket(bucket(angle))
+    }
+}
+
+impl Display for RotationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pauli => write!(f, "Pauli"),
+            Self::Clifford => write!(f, "Clifford"),
+            Self::T => write!(f, "T"),
+            Self::Generic => write!(f, "generic"),
+        }
+    }
+}
+
+/// Bucket of the histogram of a rotation by `angle`, the angle being taken
+/// modulo 2π (rotations by θ and θ + 2π only differ by a global phase).
+fn bucket(angle: f64) -> u64 {
+    let buckets = 2 * BUCKETS_PER_PI;
+    (angle.rem_euclid(2.0 * PI) / PI * BUCKETS_PER_PI.to_f64().unwrap_or_default())
+        .round()
+        .to_u64()
+        .unwrap_or_default()
+        % buckets
+}
+
+/// Number of rotations by each angle.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationHistogram {
+    // rotations by each angle, in 10⁻⁶π, modulo 2π
+    counts: BTreeMap<u64, u64>,
+    // generic rotations by unknown angles
+    #[serde(default, skip_serializing_if = "is_zero")]
+    unknown: u64,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_zero(count: &u64) -> bool {
+    *count == 0
+}
+
+impl RotationHistogram {
+    /// Record a rotation by `angle` (in radians), returning its kind.
+    pub fn record(&mut self, angle: f64) -> RotationKind {
+        let bucket = bucket(angle);
+        *self.counts.entry(bucket).or_default() += 1;
+        RotationKind::of_bucket(bucket)
+    }
+
+    /// Record `count` generic rotations whose angles are unknown.
+    pub fn record_unknown(&mut self, count: u64) {
+        self.unknown += count;
+    }
+
+    /// Add the rotations of `other`, e.g. of a stage run after these ones.
+    pub fn merge(&mut self, other: &Self) {
+        for (&bucket, &count) in &other.counts {
+            *self.counts.entry(bucket).or_default() += count;
+        }
+        self.unknown += other.unknown;
+    }
+
+    /// Whether no rotation has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty() && self.unknown == 0
+    }
+
+    /// Number of rotations of the given kind, the ones by unknown angles being
+    /// generic.
+    #[must_use]
+    pub fn count(&self, kind: RotationKind) -> u64 {
+        let unknown = if kind == RotationKind::Generic {
+            self.unknown
+        } else {
+            0
+        };
+        self.iter()
+            .filter(|&(_, bucket_kind, _)| bucket_kind == kind)
+            .map(|(_, _, count)| count)
+            .sum::<u64>()
+            + unknown
+    }
+
+    /// Angles (in radians, between 0 and 2π), kinds and numbers of the
+    /// rotations by known angles, by increasing angle.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, RotationKind, u64)> + '_ {
+        self.counts.iter().map(|(&bucket, &count)| {
+            let angle = bucket.to_f64().unwrap_or_default() * PI
+                / BUCKETS_PER_PI.to_f64().unwrap_or_default();
+            (angle, RotationKind::of_bucket(bucket), count)
+        })
+    }
+}
+
+impl Display for RotationHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (angle, kind, count) in self.iter() {
+            writeln!(f, "{:>12.6}π {kind:<9}{count:>12}", angle / PI)?;
+        }
+        if self.unknown > 0 {
+            writeln!(
+                f,
+                "{:>13} {:<9}{:>12}",
+                "unknown",
+                RotationKind::Generic,
+                self.unknown
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Synthesis of the rotations into T gates, converted into CCX.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationSynthesis {
+    t_per_rotation: u64,
+    conversion: TConversion,
+}
+
+impl RotationSynthesis {
+    /// Synthesis of the generic rotations into `t_per_rotation` T gates each,
+    /// converted into CCX with `conversion`.
+    #[must_use]
+    pub fn new(t_per_rotation: u64, conversion: TConversion) -> Self {
+        Self {
+            t_per_rotation,
+            conversion,
+        }
+    }
+
+    /// Synthesis of the generic rotations up to `precision` (in diamond
+    /// norm), with the 3·log₂(1/ε) T gates of the Ross-Selinger algorithm
+    /// ([arXiv:1403.2975](https://arxiv.org/abs/1403.2975)).
+    pub fn for_precision(precision: f64, conversion: TConversion) -> Result<Self, String> {
+        if !(precision > 0.0 && precision < 1.0) {
+            return Err(format!(
+                "rotation precision {precision} must be between 0 and 1 (excluded)"
+            ));
+        }
+        let t_per_rotation = (3.0 * (1.0 / precision).log2())
+            .ceil()
+            .to_u64()
+            .ok_or("rotation precision is too small")?;
+        Ok(Self::new(t_per_rotation, conversion))
+    }
+
+    /// Number of T gates of a generic rotation.
+    #[must_use]
+    pub fn t_per_rotation(&self) -> u64 {
+        self.t_per_rotation
+    }
+
+    /// Precision (in diamond norm) of the synthesis of a generic rotation
+    /// into [`RotationSynthesis::t_per_rotation`] T gates, at most the one
+    /// given to [`RotationSynthesis::for_precision`]. The rotations of the
+    /// other kinds are exact.
+    #[must_use]
+    pub fn precision(&self) -> f64 {
+        (-self.t_per_rotation.to_f64().unwrap_or(f64::MAX) / 3.0).exp2()
+    }
+
+    /// Number of T gates of a rotation of the given kind.
+    #[must_use]
+    pub fn t_count(&self, kind: RotationKind) -> u64 {
+        match kind {
+            RotationKind::Pauli | RotationKind::Clifford => 0,
+            RotationKind::T => 1,
+            RotationKind::Generic => self.t_per_rotation,
+        }
+    }
+
+    /// Number of CCX gates replacing `t_count` T gates.
+    #[must_use]
+    pub fn ccx_count(&self, t_count: u64) -> u64 {
+        self.conversion.ccx_count(t_count)
+    }
+}
+
+impl Default for RotationSynthesis {
+    /// Synthesis up to a precision of 10⁻¹⁰, through logical ANDs.
+    fn default() -> Self {
+        Self::for_precision(1e-10, TConversion::and()).expect("valid default precision")
+    }
+}
+
+impl Display for RotationSynthesis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} T per rotation, T conversion {}",
+            self.t_per_rotation, self.conversion
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use super::{RotationHistogram, RotationKind, RotationSynthesis};
+    use crate::counter::pyliqtr::TConversion;
+
+    #[test]
+    fn rotations_are_classified_by_angle() {
+        for (angle, kind) in [
+            (0.0, RotationKind::Pauli),
+            (PI, RotationKind::Pauli),
+            (-PI, RotationKind::Pauli),
+            (4.0 * PI, RotationKind::Pauli),
+            (FRAC_PI_2, RotationKind::Clifford),
+            (-FRAC_PI_2, RotationKind::Clifford),
+            (3.0 * FRAC_PI_2, RotationKind::Clifford),
+            (FRAC_PI_4, RotationKind::T),
+            (-FRAC_PI_4, RotationKind::T),
+            (5.0 * FRAC_PI_4, RotationKind::T),
+            (0.1, RotationKind::Generic),
+            (FRAC_PI_4 + 1e-3, RotationKind::Generic),
+        ] {
+            assert_eq!(RotationKind::of(angle), kind, "{angle}");
+        }
+        // below the resolution of the histogram
+        assert_eq!(RotationKind::of(FRAC_PI_4 + 1e-9), RotationKind::T);
+    }
+
+    #[test]
+    fn histogram_counts_the_angles_modulo_2_pi() {
+        let mut histogram = RotationHistogram::default();
+        assert!(histogram.is_empty());
+        assert_eq!(histogram.record(FRAC_PI_4), RotationKind::T);
+        assert_eq!(histogram.record(FRAC_PI_4 + 2.0 * PI), RotationKind::T);
+        assert_eq!(histogram.record(0.3), RotationKind::Generic);
+        assert_eq!(histogram.record(FRAC_PI_2), RotationKind::Clifford);
+        assert_eq!(histogram.count(RotationKind::T), 2);
+        assert_eq!(histogram.count(RotationKind::Generic), 1);
+        assert_eq!(histogram.count(RotationKind::Pauli), 0);
+        // by increasing angle: 0.3, π/4 and π/2
+        let counts: Vec<_> = histogram.iter().map(|(_, _, count)| count).collect();
+        assert_eq!(counts, [1, 2, 1]);
+
+        let mut other = RotationHistogram::default();
+        other.record(0.3);
+        other.record(PI);
+        other.record_unknown(3);
+        histogram.merge(&other);
+        assert_eq!(histogram.count(RotationKind::Generic), 5);
+        assert_eq!(histogram.count(RotationKind::Pauli), 1);
+    }
+
+    #[test]
+    fn synthesis_follows_the_precision() {
+        let synthesis =
+            RotationSynthesis::for_precision(1e-10, TConversion::and()).expect("valid precision");
+        // 3·log₂(10¹⁰) = 99.66
+        assert_eq!(synthesis.t_per_rotation(), 100);
+        assert!(synthesis.precision() <= 1e-10 && synthesis.precision() > 0.9e-10);
+        assert_eq!(synthesis, RotationSynthesis::default());
+        assert_eq!(synthesis.t_count(RotationKind::Pauli), 0);
+        assert_eq!(synthesis.t_count(RotationKind::Clifford), 0);
+        assert_eq!(synthesis.t_count(RotationKind::T), 1);
+        assert_eq!(synthesis.t_count(RotationKind::Generic), 100);
+        // 4 T gates per AND
+        assert_eq!(synthesis.ccx_count(101), 26);
+        for precision in [0.0, 1.0, -1e-3, f64::NAN] {
+            assert!(RotationSynthesis::for_precision(precision, TConversion::and()).is_err());
+        }
+    }
+}
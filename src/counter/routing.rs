@@ -0,0 +1,126 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! "Horizontal" routing qubits of the layout.
+//!
+//! In the layout of [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p.
+//! 27), the logical qubits are laid out in a row, with a routing qubit for
+//! each pair of them and one more between the compute and factory parts. Other
+//! papers count the routing differently (or not at all): the convention is
+//! chosen with a [`RoutingOverhead`], see
+//! `LogicalCounts::with_routing_overhead`.
+//!
+//! The "vertical" routing qubits are added to the physical qubits of the
+//! estimates, whatever the convention.
+//!
+//! The built-in conventions are saved with the counts (as `"none"` or
+//! `"paper"`); a custom one can't be, and saving counts with it fails rather
+//! than silently falling back to the default.
+
+use std::{fmt::Display, sync::Arc};
+
+use serde::{ser::Error, Deserialize, Serialize, Serializer};
+
+/// Number of "horizontal" routing qubits for a number of logical qubits.
+#[derive(Clone, Default, Deserialize)]
+#[serde(from = "BuiltInRouting")]
+pub enum RoutingOverhead {
+    /// No routing qubit, e.g. to compare with counts already including them.
+    None,
+    /// ⌈n/2⌉ + 1 routing qubits for n logical qubits, as in arXiv:2302.06639.
+    #[default]
+    Paper,
+    /// Routing qubits given by a function of the number of logical qubits.
+    Custom(Arc<dyn Fn(u64) -> u64 + Send + Sync>),
+}
+
+impl RoutingOverhead {
+    /// Routing qubits given by `routing_qubits`, a function of the number of
+    /// logical qubits.
+    pub fn custom(routing_qubits: impl Fn(u64) -> u64 + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(routing_qubits))
+    }
+
+    /// Whether this is the convention of arXiv:2302.06639 (the default).
+    pub(crate) fn is_paper(&self) -> bool {
+        matches!(self, Self::Paper)
+    }
+
+    /// Number of routing qubits for `qubit_count` logical qubits.
+    #[must_use]
+    pub fn routing_qubits(&self, qubit_count: u64) -> u64 {
+        match self {
+            Self::None => 0,
+            Self::Paper => qubit_count.div_ceil(2) + 1,
+            Self::Custom(routing_qubits) => routing_qubits(qubit_count),
+        }
+    }
+}
+
+impl std::fmt::Debug for RoutingOverhead {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Paper => write!(f, "Paper"),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+impl Display for RoutingOverhead {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Paper => write!(f, "n/2 + 1 (arXiv:2302.06639)"),
+            Self::Custom(_) => write!(f, "custom"),
+        }
+    }
+}
+
+/// Built-in conventions, as saved with the counts.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum BuiltInRouting {
+    None,
+    Paper,
+}
+
+impl From<BuiltInRouting> for RoutingOverhead {
+    fn from(routing: BuiltInRouting) -> Self {
+        match routing {
+            BuiltInRouting::None => Self::None,
+            BuiltInRouting::Paper => Self::Paper,
+        }
+    }
+}
+
+impl Serialize for RoutingOverhead {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::None => BuiltInRouting::None.serialize(serializer),
+            Self::Paper => BuiltInRouting::Paper.serialize(serializer),
+            Self::Custom(_) => Err(S::Error::custom(
+                "a custom routing overhead can't be saved with the counts",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoutingOverhead;
+
+    #[test]
+    fn built_in_conventions_are_saved() {
+        for (routing, json) in [
+            (RoutingOverhead::None, "\"none\""),
+            (RoutingOverhead::Paper, "\"paper\""),
+        ] {
+            assert_eq!(serde_json::to_string(&routing).ok().as_deref(), Some(json));
+            let read: RoutingOverhead = serde_json::from_str(json).expect("built-in convention");
+            assert_eq!(read.routing_qubits(10), routing.routing_qubits(10));
+        }
+        assert!(serde_json::to_string(&RoutingOverhead::custom(|n| n)).is_err());
+        assert!(serde_json::from_str::<RoutingOverhead>("\"custom\"").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Counts of probabilistic programs, sampled over measurement outcomes.
+//!
+//! The gates of a Q# program may depend on measurement outcomes (e.g.
+//! repeat-until-success loops), while the counting follows a single path, with
+//! all outcomes being 0. With `LogicalCounts::sample_qsharp` (requires the
+//! `qsharp` feature), the program is counted several times with random
+//! outcomes drawn from a seeded generator (see [`crate::random`]), and the
+//! [`SampledCounts`] give the distribution of the counts.
+
+use std::fmt::Display;
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use resource_estimator::estimates::{ErrorBudget, Overhead};
+
+use super::LogicalCounts;
+
+/// Logical depth of counts.
+fn depth(counts: &LogicalCounts) -> u64 {
+    // the logical depth does not depend on the error budget
+    counts.logical_depth(&ErrorBudget::new(0.0, 0.0, 0.0))
+}
+
+/// Distribution of a quantity over the samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Statistics {
+    /// Mean value.
+    pub mean: f64,
+    /// Median value.
+    pub median: u64,
+    /// 90th percentile.
+    pub p90: u64,
+    /// Largest value.
+    pub max: u64,
+}
+
+/// Counts of a program sampled over random measurement outcomes.
+#[derive(Clone)]
+pub struct SampledCounts {
+    seed: u64,
+    // by increasing logical depth
+    samples: Vec<LogicalCounts>,
+}
+
+impl SampledCounts {
+    /// Distribution of the `samples` counted with outcomes drawn from `seed`.
+    pub fn new(seed: u64, mut samples: Vec<LogicalCounts>) -> Result<Self, String> {
+        if samples.is_empty() {
+            return Err("at least one sample is needed".into());
+        }
+        samples.sort_by_cached_key(depth);
+        Ok(Self { seed, samples })
+    }
+
+    /// Seed of the generator of the outcomes.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Samples, by increasing logical depth.
+    pub fn samples(&self) -> &[LogicalCounts] {
+        &self.samples
+    }
+
+    /// Sample at the `quantile` (between 0 and 1) of the logical depth, e.g.
+    /// 0.9 for the counts of the 90th percentile.
+    pub fn at_quantile(&self, quantile: f64) -> &LogicalCounts {
+        let last = f64::from_usize(self.samples.len() - 1).expect("too many samples");
+        let index = (quantile.clamp(0.0, 1.0) * last)
+            .round()
+            .to_usize()
+            .expect("invalid quantile");
+        &self.samples[index]
+    }
+
+    /// Distribution of `value` over the samples.
+    pub fn statistics(&self, value: impl Fn(&LogicalCounts) -> u64) -> Statistics {
+        let mut values: Vec<_> = self.samples.iter().map(value).collect();
+        values.sort_unstable();
+        let last = values.len() - 1;
+        let percentile = |q: f64| {
+            let index = (q * f64::from_usize(last).expect("too many samples")).round();
+            values[index.to_usize().expect("invalid percentile")]
+        };
+        let sum: f64 = values.iter().filter_map(ToPrimitive::to_f64).sum();
+        Statistics {
+            mean: sum / f64::from_usize(values.len()).expect("too many samples"),
+            median: percentile(0.5),
+            p90: percentile(0.9),
+            max: values[last],
+        }
+    }
+}
+
+impl Display for SampledCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} samples of the measurement outcomes (seed {}):",
+            self.samples.len(),
+            self.seed
+        )?;
+        writeln!(
+            f,
+            "  {:<16}{:>16}{:>14}{:>14}{:>14}",
+            "", "mean", "median", "p90", "max"
+        )?;
+        for (quantity, statistics) in [
+            (
+                "logical qubits",
+                self.statistics(LogicalCounts::qubit_count),
+            ),
+            ("CX", self.statistics(LogicalCounts::cx_count)),
+            ("CCX", self.statistics(LogicalCounts::ccx_count)),
+            ("logical depth", self.statistics(depth)),
+        ] {
+            writeln!(
+                f,
+                "  {quantity:<16}{:>16.1}{:>14}{:>14}{:>14}",
+                statistics.mean, statistics.median, statistics.p90, statistics.max
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Counts of algorithms made of sequential segments.
+//!
+//! [`LogicalCounts::sequence`] merges the counts of phases run in sequence
+//! (e.g. a modular exponentiation, then a QFT) into a single one, losing what
+//! each phase consumes. A [`SegmentedCounts`] keeps the ordered segments: the
+//! algorithm is estimated with their merged counts
+//! ([`SegmentedCounts::merged`]), whose qubits are reused from a segment to
+//! the next (the width is the widest segment) while the gates add up, and the
+//! segments give the demand of each phase (see [`crate::phases`]) and the
+//! space-time volume actually occupied by the logical qubits
+//! ([`SegmentedCounts::logical_qubit_cycles`]), a narrow segment leaving the
+//! qubits of the widest one idle.
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::{ErrorBudget, Overhead};
+use serde::{Deserialize, Serialize};
+
+use super::LogicalCounts;
+
+/// Named counts of a segment.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Segment {
+    /// Name of the segment, e.g. its Q# file.
+    pub name: String,
+    /// Counts of the segment.
+    pub counts: LogicalCounts,
+}
+
+/// Counts of segments run in sequence.
+#[derive(Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct SegmentedCounts {
+    segments: Vec<Segment>,
+}
+
+impl SegmentedCounts {
+    /// Counts of the `segments`, in the order they run.
+    ///
+    /// There must be at least one segment, and the segments must have the
+    /// same gate set.
+    pub fn new(
+        segments: impl IntoIterator<Item = (impl Into<String>, LogicalCounts)>,
+    ) -> Result<Self, String> {
+        let segments: Vec<_> = segments
+            .into_iter()
+            .map(|(name, counts)| Segment {
+                name: name.into(),
+                counts,
+            })
+            .collect();
+        let first = segments
+            .first()
+            .ok_or("segmented counts need at least one segment")?;
+        if let Some(segment) = segments
+            .iter()
+            .find(|segment| segment.counts.gate_set() != first.counts.gate_set())
+        {
+            return Err(format!(
+                "segments {} and {} counted with different gate sets ({} and {})",
+                first.name,
+                segment.name,
+                first.counts.gate_set(),
+                segment.counts.gate_set()
+            ));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Segments, in the order they run.
+    #[must_use]
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Counts of the whole algorithm, merged with [`LogicalCounts::sequence`].
+    pub fn merged(&self) -> Result<LogicalCounts, String> {
+        LogicalCounts::sequence(self.segments.iter().map(|segment| segment.counts.clone()))
+    }
+
+    /// Logical qubits times logical cycles summed over the segments (routing
+    /// qubits included): the space-time volume over which the logical qubits
+    /// accumulate errors, smaller than the width times the depth when the
+    /// segments have different widths.
+    #[must_use]
+    pub fn logical_qubit_cycles(&self) -> u64 {
+        let budget = ErrorBudget::new(0.0, 0.0, 0.0);
+        self.segments
+            .iter()
+            .map(|segment| {
+                segment
+                    .counts
+                    .logical_qubits()
+                    .saturating_mul(segment.counts.logical_depth(&budget))
+            })
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Logical qubits of the widest segment (routing qubits included), as in
+    /// the merged counts.
+    #[must_use]
+    pub fn logical_qubits(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| segment.counts.logical_qubits())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Fraction of the space-time volume of the widest segment over the whole
+    /// depth during which the logical qubits are allocated, between 0 and 1.
+    ///
+    /// Both volumes use the depths of the segments, each one rounded up to a
+    /// whole cycle, which may exceed the depth of the merged counts by up to a
+    /// cycle per segment.
+    #[must_use]
+    pub fn occupancy(&self) -> f64 {
+        let budget = ErrorBudget::new(0.0, 0.0, 0.0);
+        let depth = self
+            .segments
+            .iter()
+            .map(|segment| segment.counts.logical_depth(&budget))
+            .fold(0, u64::saturating_add);
+        let volume =
+            self.logical_qubits().to_f64().unwrap_or(f64::MAX) * depth.to_f64().unwrap_or(f64::MAX);
+        if volume > 0.0 {
+            self.logical_qubit_cycles().to_f64().unwrap_or(f64::MAX) / volume
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resource_estimator::estimates::{ErrorBudget, Overhead};
+
+    use super::SegmentedCounts;
+    use crate::{testing::close, LogicalCounts};
+
+    fn counts(qubits: u64, cx: u64, ccx: u64) -> LogicalCounts {
+        LogicalCounts::new(qubits, cx, ccx)
+    }
+
+    #[test]
+    fn width_is_the_widest_segment() {
+        let segments = SegmentedCounts::new([("a", counts(10, 5, 1)), ("b", counts(30, 0, 2))])
+            .expect("same gate sets");
+        // routing qubits included
+        assert_eq!(segments.logical_qubits(), counts(30, 0, 0).logical_qubits());
+        let merged = segments.merged().expect("same gate sets");
+        assert_eq!(merged.logical_qubits(), segments.logical_qubits());
+        assert_eq!(segments.segments().len(), 2);
+        assert_eq!(segments.segments()[1].name, "b");
+    }
+
+    #[test]
+    fn merged_counts_add_the_gates() {
+        let budget = ErrorBudget::new(0.0, 0.0, 0.0);
+        let (a, b) = (counts(10, 5, 1), counts(30, 3, 2));
+        let depth = a.logical_depth(&budget) + b.logical_depth(&budget);
+        let segments = SegmentedCounts::new([("a", a), ("b", b)]).expect("same gate sets");
+        let merged = segments.merged().expect("same gate sets");
+        assert_eq!(merged.num_magic_states(&budget, 0), 3);
+        assert_eq!(merged.cx_count(), 8);
+        // each depth rounded up, the merged one rounded once
+        assert!(merged.logical_depth(&budget) <= depth);
+        assert!(merged.logical_depth(&budget) + 2 > depth);
+    }
+
+    #[test]
+    fn segments_need_the_same_gate_set() {
+        let a = counts(10, 5, 1);
+        let b = counts(10, 5, 1)
+            .with_routing_factor(2.0)
+            .expect("valid routing factor");
+        let err = SegmentedCounts::new([("a", a), ("b", b)]).err();
+        assert!(err.is_some_and(|err| err.contains("different gate sets")));
+        let none: [(&str, LogicalCounts); 0] = [];
+        assert!(SegmentedCounts::new(none).is_err());
+    }
+
+    #[test]
+    fn occupancy_counts_the_idle_qubits() {
+        let budget = ErrorBudget::new(0.0, 0.0, 0.0);
+        let (wide, narrow) = (counts(40, 0, 10), counts(10, 0, 10));
+        let depth = wide.logical_depth(&budget);
+        assert_eq!(narrow.logical_depth(&budget), depth);
+        let (w, n) = (wide.logical_qubits(), narrow.logical_qubits());
+        let segments =
+            SegmentedCounts::new([("wide", wide.clone()), ("narrow", narrow)]).expect("same");
+        assert_eq!(segments.logical_qubit_cycles(), (w + n) * depth);
+        #[allow(clippy::cast_precision_loss)]
+        let expected = (w + n) as f64 / (2 * w) as f64;
+        assert!(close(segments.occupancy(), expected));
+
+        let single = SegmentedCounts::new([("wide", wide)]).expect("one segment");
+        assert!(close(single.occupancy(), 1.0));
+        let empty = SegmentedCounts::new([("empty", counts(0, 0, 0))]).expect("one segment");
+        assert!(close(empty.occupancy(), 1.0));
+    }
+}
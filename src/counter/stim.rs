@@ -0,0 +1,456 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Import of the logical resources of Stim circuits.
+//!
+//! [Stim](https://github.com/quantumlib/Stim) circuits are Clifford circuits,
+//! commonly used as benchmarks by the QEC community. Each gate is counted as if
+//! its qubits were logical qubits:
+//! - CX gates are native, and the other controlled Pauli gates (CY, CZ, XCX,
+//!   ...) cost a CX plus the basis changes of their control and target,
+//! - other gates are decomposed into CX, H and S, converted according to a
+//!   [`GateSet`],
+//! - Pauli product measurements and rotations (`MPP`, `MXX`, `SPP`, ...) are
+//!   decomposed into one CX per Pauli onto an ancilla qubit, after the basis
+//!   changes of the measured qubits,
+//! - gates controlled by a measurement record or a sweep bit only update the
+//!   Pauli frame and are free (without feed-forward latency), as are noise
+//!   channels and annotations,
+//! - measurements are counted, one per measured qubit or Pauli product.
+//!
+//! `REPEAT` blocks are counted once per repetition, and a circuit whose counts
+//! overflow is rejected.
+
+use std::{fs::read_to_string, path::Path, str::Lines};
+
+use super::{
+    gate_set::{GateCost, GateSet},
+    LogicalCounts,
+};
+
+/// Counts of a block of a circuit.
+#[derive(Clone, Copy, Default)]
+struct Tally {
+    cx: u64,
+    ccx: u64,
+    measurements: u64,
+    // largest qubit index + 1
+    qubits: u64,
+    // a Pauli product is measured with an ancilla
+    ancilla: bool,
+}
+
+impl Tally {
+    /// Add `count` times the cost of a gate, or `None` on overflow.
+    fn add(&mut self, cost: GateCost, count: u64) -> Option<()> {
+        self.cx = self.cx.checked_add(cost.cx.checked_mul(count)?)?;
+        self.ccx = self.ccx.checked_add(cost.ccx.checked_mul(count)?)?;
+        Some(())
+    }
+
+    /// Add `count` measurements, or `None` on overflow.
+    fn measure(&mut self, count: u64) -> Option<()> {
+        self.measurements = self.measurements.checked_add(count)?;
+        Some(())
+    }
+
+    /// Add the counts of `repetitions` runs of a block.
+    fn add_repeated(&mut self, block: Self, repetitions: u64) -> Option<()> {
+        self.cx = self.cx.checked_add(block.cx.checked_mul(repetitions)?)?;
+        self.ccx = self.ccx.checked_add(block.ccx.checked_mul(repetitions)?)?;
+        self.measurements = self
+            .measurements
+            .checked_add(block.measurements.checked_mul(repetitions)?)?;
+        self.qubits = self.qubits.max(block.qubits);
+        self.ancilla |= block.ancilla;
+        Some(())
+    }
+}
+
+/// Sum of gate costs.
+fn sum(costs: &[GateCost]) -> GateCost {
+    costs.iter().fold(GateCost::FREE, |sum, cost| GateCost {
+        cx: sum.cx + cost.cx,
+        ccx: sum.ccx + cost.ccx,
+    })
+}
+
+/// Cost of mapping the Pauli `basis` to Z and back.
+fn to_z(basis: char, gate_set: &GateSet) -> GateCost {
+    match basis {
+        'X' => sum(&[gate_set.h, gate_set.h]),
+        'Y' => sum(&[gate_set.h, gate_set.h, gate_set.s, gate_set.s]),
+        _ => GateCost::FREE,
+    }
+}
+
+/// Cost of mapping the Pauli `basis` to X and back.
+fn to_x(basis: char, gate_set: &GateSet) -> GateCost {
+    match basis {
+        'Z' => sum(&[gate_set.h, gate_set.h]),
+        'Y' => sum(&[gate_set.s, gate_set.s]),
+        _ => GateCost::FREE,
+    }
+}
+
+/// Cost of a controlled Pauli gate, controlled by the Pauli `control` (Z for
+/// a usual control) and applying the Pauli `target`.
+fn controlled_pauli(control: char, target: char, gate_set: &GateSet) -> GateCost {
+    match (control, target) {
+        ('Z', 'Y') => gate_set.cy,
+        ('Z', 'Z') => gate_set.cz,
+        _ => sum(&[
+            GateCost::cx(1),
+            to_z(control, gate_set),
+            to_x(target, gate_set),
+        ]),
+    }
+}
+
+/// Cost of a Pauli product measurement of the `paulis` (with an ancilla).
+fn pauli_product(paulis: impl IntoIterator<Item = char>, gate_set: &GateSet) -> GateCost {
+    let mut cost = GateCost::FREE;
+    let mut factors = 0;
+    for pauli in paulis {
+        cost = sum(&[cost, to_z(pauli, gate_set)]);
+        factors += 1;
+    }
+    if factors > 1 {
+        cost.cx += factors;
+    }
+    cost
+}
+
+/// Cost of a gate on a single qubit, a pair of qubits, or a Pauli product, and
+/// the number of targets it acts on, or `None` for an unknown gate.
+fn gate_cost(name: &str, gate_set: &GateSet) -> Option<(GateCost, usize)> {
+    let single = |cost| Some((cost, 1));
+    let pair = |cost| Some((cost, 2));
+    match name {
+        // Paulis, measurements and resets in the X and Z bases
+        "I" | "X" | "Y" | "Z" | "M" | "MZ" | "MX" | "R" | "RZ" | "RX" | "MR" | "MRZ" | "MRX" => {
+            single(GateCost::FREE)
+        }
+        "MY" | "RY" | "MRY" | "S" | "S_DAG" | "SQRT_Z" | "SQRT_Z_DAG" | "H_XY" | "H_NXY" => {
+            single(gate_set.s)
+        }
+        "H" | "H_XZ" | "H_NXZ" | "SQRT_Y" | "SQRT_Y_DAG" => single(gate_set.h),
+        "SQRT_X" | "SQRT_X_DAG" | "H_YZ" | "H_NYZ" => {
+            single(sum(&[gate_set.h, gate_set.h, gate_set.s]))
+        }
+        "C_XYZ" | "C_ZYX" | "C_NXYZ" | "C_XNYZ" | "C_XYNZ" | "C_NZYX" | "C_ZNYX" | "C_ZYNX" => {
+            single(sum(&[gate_set.h, gate_set.s]))
+        }
+        "CX" | "CNOT" | "ZCX" => pair(GateCost::cx(1)),
+        "CY" | "ZCY" => pair(gate_set.cy),
+        "CZ" | "ZCZ" => pair(gate_set.cz),
+        "XCX" | "XCY" | "XCZ" | "YCX" | "YCY" | "YCZ" => {
+            let mut bases = name.chars();
+            let control = bases.next().unwrap_or('Z');
+            let target = bases.nth(1).unwrap_or('X');
+            pair(controlled_pauli(control, target, gate_set))
+        }
+        "SWAP" => pair(gate_set.swap),
+        "ISWAP" | "ISWAP_DAG" => pair(sum(&[gate_set.swap, gate_set.cz, gate_set.s, gate_set.s])),
+        "CXSWAP" | "SWAPCX" => pair(GateCost::cx(2)),
+        "CZSWAP" | "SWAPCZ" => pair(sum(&[gate_set.swap, gate_set.cz])),
+        "SQRT_XX" | "SQRT_XX_DAG" | "SQRT_YY" | "SQRT_YY_DAG" | "SQRT_ZZ" | "SQRT_ZZ_DAG" => {
+            let basis = name.chars().nth(5).unwrap_or('Z');
+            pair(sum(&[
+                GateCost::cx(2),
+                gate_set.s,
+                to_z(basis, gate_set),
+                to_z(basis, gate_set),
+            ]))
+        }
+        "MXX" | "MYY" | "MZZ" => {
+            let basis = name.chars().nth(1).unwrap_or('Z');
+            pair(pauli_product([basis, basis], gate_set))
+        }
+        // noise channels and annotations
+        "DEPOLARIZE1"
+        | "DEPOLARIZE2"
+        | "X_ERROR"
+        | "Y_ERROR"
+        | "Z_ERROR"
+        | "I_ERROR"
+        | "II_ERROR"
+        | "PAULI_CHANNEL_1"
+        | "PAULI_CHANNEL_2"
+        | "E"
+        | "CORRELATED_ERROR"
+        | "ELSE_CORRELATED_ERROR"
+        | "HERALDED_ERASE"
+        | "HERALDED_PAULI_CHANNEL_1"
+        | "DETECTOR"
+        | "OBSERVABLE_INCLUDE"
+        | "QUBIT_COORDS"
+        | "SHIFT_COORDS"
+        | "TICK"
+        | "MPAD" => Some((GateCost::FREE, 0)),
+        _ => None,
+    }
+}
+
+/// Whether an instruction measures its targets (`MPAD` only pads the
+/// measurement record).
+fn is_measurement(name: &str) -> bool {
+    name.starts_with('M') && name != "MPAD"
+}
+
+/// Whether a target is a measurement record or a sweep bit (a classical
+/// control).
+fn is_classical(target: &str) -> bool {
+    target.starts_with("rec[") || target.starts_with("sweep[")
+}
+
+/// Qubit index of a target (`5`, `!5`, `X5`), if it is a qubit.
+fn qubit_index(target: &str) -> Option<u64> {
+    target.trim_start_matches(['!', 'X', 'Y', 'Z']).parse().ok()
+}
+
+/// Count the instructions of `lines` up to the end of the block.
+fn count_block(
+    lines: &mut std::iter::Enumerate<Lines>,
+    gate_set: &GateSet,
+    nested: bool,
+) -> Result<Tally, String> {
+    let mut tally = Tally::default();
+    while let Some((number, line)) = lines.next() {
+        let error = |message: String| format!("line {}: {message}", number + 1);
+        let overflow = || error("too many gates".into());
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "}" {
+            return if nested {
+                Ok(tally)
+            } else {
+                Err(error("unmatched '}'".into()))
+            };
+        }
+
+        let (instruction, targets) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        // drop the arguments, e.g. X_ERROR(0.01) or DETECTOR(1, 2)
+        let name = instruction.split('(').next().unwrap_or_default();
+        let name = name.to_ascii_uppercase();
+        let targets: Vec<_> = targets
+            .rsplit_once(')')
+            .map_or(targets, |(_, targets)| targets)
+            .split_whitespace()
+            .collect();
+
+        if name == "REPEAT" {
+            let repetitions = targets
+                .first()
+                .and_then(|count| count.parse::<u64>().ok())
+                .filter(|_| targets.get(1) == Some(&"{"))
+                .ok_or_else(|| error("expected 'REPEAT <count> {'".into()))?;
+            let block = count_block(lines, gate_set, true)?;
+            tally
+                .add_repeated(block, repetitions)
+                .ok_or_else(overflow)?;
+            continue;
+        }
+
+        // the targets of MPAD are measurement results, not qubits
+        for index in targets
+            .iter()
+            .filter(|_| name != "MPAD")
+            .flat_map(|target| target.split('*'))
+            .filter_map(qubit_index)
+        {
+            tally.qubits = tally.qubits.max(index.saturating_add(1));
+        }
+
+        if name == "MPP" || name == "SPP" || name == "SPP_DAG" {
+            tally.ancilla = true;
+            for product in &targets {
+                let paulis = product
+                    .split('*')
+                    .map(|factor| factor.trim_start_matches('!').chars().next().unwrap_or('Z'));
+                let cost = pauli_product(paulis, gate_set);
+                if name == "MPP" {
+                    tally.add(cost, 1).ok_or_else(overflow)?;
+                    tally.measure(1).ok_or_else(overflow)?;
+                } else {
+                    // rotation: product computed, S on the ancilla, uncomputed
+                    tally
+                        .add(sum(&[cost, cost, gate_set.s]), 1)
+                        .ok_or_else(overflow)?;
+                }
+            }
+            continue;
+        }
+
+        let (cost, arity) = gate_cost(&name, gate_set)
+            .ok_or_else(|| error(format!("unknown instruction '{instruction}'")))?;
+        if matches!(name.as_str(), "MXX" | "MYY" | "MZZ") {
+            tally.ancilla = true;
+        }
+        if is_measurement(&name) {
+            tally
+                .measure((targets.len() / arity.max(1)) as u64)
+                .ok_or_else(overflow)?;
+        }
+        match arity {
+            0 => {}
+            1 => tally.add(cost, targets.len() as u64).ok_or_else(overflow)?,
+            _ => {
+                for pair in targets.chunks(2) {
+                    // classically controlled Paulis update the Pauli frame
+                    if !pair.iter().any(|target| is_classical(target)) {
+                        tally.add(cost, 1).ok_or_else(overflow)?;
+                    }
+                }
+            }
+        }
+    }
+
+    if nested {
+        Err("unterminated REPEAT block".into())
+    } else {
+        Ok(tally)
+    }
+}
+
+impl LogicalCounts {
+    /// Import the logical resources of a Stim circuit, with the simplified
+    /// gate counting (see [`GateSet::simplified`]).
+    pub fn from_stim(filename: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_stim_with_gate_set(filename, GateSet::default())
+    }
+
+    /// Import the logical resources of a Stim circuit, converting the gates
+    /// according to `gate_set`.
+    pub fn from_stim_with_gate_set(
+        filename: impl AsRef<Path>,
+        gate_set: GateSet,
+    ) -> Result<Self, String> {
+        let filename = filename.as_ref();
+        let content = read_to_string(filename)
+            .map_err(|err| format!("can't read {}: {err}", filename.display()))?;
+        Self::from_stim_circuit(&content, gate_set)
+    }
+
+    /// Import the logical resources of a Stim circuit given as text,
+    /// converting the gates according to `gate_set`.
+    pub fn from_stim_circuit(circuit: &str, gate_set: GateSet) -> Result<Self, String> {
+        let tally = count_block(&mut circuit.lines().enumerate(), &gate_set, false)
+            .map_err(|err| format!("invalid Stim circuit, {err}"))?;
+
+        let counts = Self {
+            qubit_count: tally.qubits + u64::from(tally.ancilla),
+            cx_count: tally.cx,
+            ccx_count: tally.ccx,
+            measurement_count: tally.measurements,
+            gate_set,
+            ..Self::default()
+        };
+        tracing::info!(
+            qubits = counts.qubit_count,
+            cx = counts.cx_count,
+            ccx = counts.ccx_count,
+            measurements = counts.measurement_count,
+            "imported Stim circuit"
+        );
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GateSet, LogicalCounts};
+
+    fn counts(circuit: &str) -> Result<LogicalCounts, String> {
+        LogicalCounts::from_stim_circuit(circuit, GateSet::default())
+    }
+
+    /// Qubits, CX, CCX and measurements of a circuit.
+    fn tally(circuit: &str) -> (u64, u64, u64, u64) {
+        let counts = counts(circuit).expect("valid circuit");
+        (
+            counts.qubit_count(),
+            counts.cx_count(),
+            counts.ccx_count(),
+            counts.measurement_count(),
+        )
+    }
+
+    #[test]
+    fn gates_are_counted_on_their_targets() {
+        let circuit = "
+            # comments and blank lines are ignored
+            R 0 1 2
+            H 0
+            CX 0 1 1 2
+            cz 2 3
+            SWAP 0 4
+            X_ERROR(0.01) 0 1
+            TICK
+            M 0 !1 2
+        ";
+        // CZ as a CX and SWAP as 3 CX in the simplified gate set
+        assert_eq!(tally(circuit), (5, 2 + 1 + 3, 0, 3));
+    }
+
+    #[test]
+    fn classically_controlled_paulis_are_free() {
+        assert_eq!(tally("M 0\nCX rec[-1] 1 sweep[0] 2 0 1"), (3, 1, 0, 1));
+    }
+
+    #[test]
+    fn pauli_products_use_an_ancilla() {
+        // 3 factors onto the ancilla, and 2 for the second product
+        assert_eq!(tally("MPP X0*Y1*Z2 Z3*Z4"), (6, 5, 0, 2));
+        assert_eq!(tally("MZZ 0 1 2 3"), (5, 4, 0, 2));
+    }
+
+    #[test]
+    fn repeated_blocks_are_multiplied() {
+        let circuit = "
+            CX 0 1
+            REPEAT 10 {
+                CX 1 2
+                REPEAT 3 {
+                    M 3
+                }
+            }
+        ";
+        assert_eq!(tally(circuit), (4, 1 + 10, 0, 30));
+    }
+
+    #[test]
+    fn mpad_targets_are_not_qubits() {
+        assert_eq!(tally("MPAD 0 1 1\nM 0"), (1, 0, 0, 1));
+    }
+
+    #[test]
+    fn malformed_circuits_are_rejected() {
+        for circuit in [
+            "FOO 0",
+            "REPEAT 10\nCX 0 1\n}",
+            "REPEAT ten {\nCX 0 1\n}",
+            "REPEAT 10 {\nCX 0 1",
+            "CX 0 1\n}",
+        ] {
+            assert!(counts(circuit).is_err(), "{circuit}");
+        }
+        let err = counts("H 0\nFOO 1").err().unwrap_or_default();
+        assert!(err.contains("line 2"), "{err}");
+    }
+
+    #[test]
+    fn overflowing_counts_are_rejected() {
+        let circuit = "
+            REPEAT 18446744073709551615 {
+                REPEAT 18446744073709551615 {
+                    CX 0 1
+                }
+            }
+        ";
+        assert!(counts(circuit).is_err());
+        assert!(counts("REPEAT 18446744073709551615 {\nCX 0 1 2 3\n}").is_err());
+    }
+}
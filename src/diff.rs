@@ -0,0 +1,353 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Comparison of the resources of two versions of an algorithm.
+//!
+//! A [`Diff`] lists the change of each quantity between two logical counts
+//! (qubits, CX, CCX, logical depth) and, when estimated, between the resulting
+//! physical resources. Saved estimates (`--output json`) can be compared as
+//! well, see [`Diff::records`], to follow the impact of each optimization of an
+//! algorithm, and a new estimate can be checked against a saved baseline with
+//! [`BaselineCheck`], to catch the regressions.
+
+use std::fmt::Display;
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::{ErrorBudget, Overhead};
+
+use crate::{
+    scan, schema::EstimateRecord, AliceAndBobEstimates, CatQubit, LogicalCounts, RepetitionCode,
+    ToffoliBuilder,
+};
+
+/// Change of one quantity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Delta {
+    /// Compared quantity.
+    pub quantity: &'static str,
+    /// Value before the change.
+    pub before: u64,
+    /// Value after the change.
+    pub after: u64,
+}
+
+impl Delta {
+    /// Absolute change (negative when the quantity decreases).
+    #[must_use]
+    pub fn change(&self) -> i128 {
+        i128::from(self.after) - i128::from(self.before)
+    }
+
+    /// Relative change in %, if the quantity was not 0 before.
+    #[must_use]
+    pub fn relative_percent(&self) -> Option<f64> {
+        match (self.change().to_f64(), self.before.to_f64()) {
+            (Some(change), Some(before)) if before > 0.0 => Some(100.0 * change / before),
+            _ => None,
+        }
+    }
+}
+
+/// Quantity compared between two versions, with how to get it.
+type Quantity<T> = (&'static str, fn(&T) -> u64);
+
+/// Changes of the `quantities` between `before` and `after`.
+fn deltas<T>(before: &T, after: &T, quantities: &[Quantity<T>]) -> Vec<Delta> {
+    quantities
+        .iter()
+        .map(|(quantity, value)| Delta {
+            quantity,
+            before: value(before),
+            after: value(after),
+        })
+        .collect()
+}
+
+/// Changes of the resources between two versions of an algorithm.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    /// Changes of the logical counts, then of the physical resources.
+    pub deltas: Vec<Delta>,
+}
+
+impl Diff {
+    /// Changes of the logical counts.
+    #[must_use]
+    pub fn counts(before: &LogicalCounts, after: &LogicalCounts) -> Self {
+        let quantities: [Quantity<LogicalCounts>; 4] = [
+            ("logical qubits", LogicalCounts::qubit_count),
+            ("CX", LogicalCounts::cx_count),
+            ("CCX", LogicalCounts::ccx_count),
+            // the logical depth does not depend on the error budget
+            ("logical depth", |counts| {
+                counts.logical_depth(&ErrorBudget::new(0.0, 0.0, 0.0))
+            }),
+        ];
+        Self {
+            deltas: deltas(before, after, &quantities),
+        }
+    }
+
+    /// Changes of the physical resources between two saved estimates, and of
+    /// their logical counts if both were saved.
+    #[must_use]
+    pub fn records(before: &EstimateRecord, after: &EstimateRecord) -> Self {
+        let mut diff = match (&before.counts, &after.counts) {
+            (Some(before), Some(after)) => Self::counts(before, after),
+            _ => Self::default(),
+        };
+        let quantities: [Quantity<EstimateRecord>; 5] = [
+            ("physical qubits", |record| record.physical_qubits),
+            ("factory qubits", |record| {
+                record.physical_qubits_for_factories
+            }),
+            ("runtime (ns)", |record| record.runtime_ns),
+            ("logical cycles", |record| record.logical_cycles),
+            ("code distance", |record| record.code_parameter.distance()),
+        ];
+        diff.deltas.extend(deltas(before, after, &quantities));
+        diff
+    }
+
+    /// Changes between two estimates, logical counts included.
+    #[must_use]
+    pub fn estimates(before: &AliceAndBobEstimates, after: &AliceAndBobEstimates) -> Self {
+        Self::records(&before.into(), &after.into())
+    }
+
+    /// Estimate both logical counts with the same assumptions, and compare
+    /// them.
+    pub fn new(
+        qubit: &CatQubit,
+        code: &RepetitionCode,
+        builder: &ToffoliBuilder,
+        budget: &ErrorBudget,
+        before: &LogicalCounts,
+        after: &LogicalCounts,
+    ) -> Result<Self, String> {
+        let before = scan::estimate(qubit, code, builder, before.clone(), budget)?;
+        let after = scan::estimate(qubit, code, builder, after.clone(), budget)?;
+        Ok(Self::estimates(&before, &after))
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "  {:<18}{:>16}{:>16}{:>16}{:>10}",
+            "", "before", "after", "change", "%"
+        )?;
+        for delta in &self.deltas {
+            let relative = delta
+                .relative_percent()
+                .map_or_else(|| "-".into(), |percent| format!("{percent:+.1}"));
+            writeln!(
+                f,
+                "  {:<18}{:>16}{:>16}{:>+16}{relative:>10}",
+                delta.quantity,
+                delta.before,
+                delta.after,
+                delta.change()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Comparison of an estimate with a saved baseline, a regression being an
+/// increase of the physical qubits, runtime or total error beyond a relative
+/// tolerance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaselineCheck {
+    /// Changes from the baseline to the estimate.
+    pub diff: Diff,
+    /// Total error probability of the baseline and of the estimate.
+    pub total_error: (f64, f64),
+    /// Relative increase tolerated before a regression (e.g. 0.05 for 5%).
+    pub tolerance: f64,
+}
+
+impl BaselineCheck {
+    /// Quantities checked for regressions.
+    const CHECKED: [&'static str; 2] = ["physical qubits", "runtime (ns)"];
+
+    /// Compare `estimate` with `baseline`, tolerating relative increases up
+    /// to `tolerance`.
+    #[must_use]
+    pub fn new(baseline: &EstimateRecord, estimate: &EstimateRecord, tolerance: f64) -> Self {
+        Self {
+            diff: Diff::records(baseline, estimate),
+            total_error: (baseline.errors.total, estimate.errors.total),
+            tolerance,
+        }
+    }
+
+    /// Quantities increased beyond the tolerance, with their relative change
+    /// in %.
+    #[must_use]
+    pub fn regressions(&self) -> Vec<(&'static str, f64)> {
+        let limit = 100.0 * self.tolerance;
+        let mut regressions: Vec<_> = self
+            .diff
+            .deltas
+            .iter()
+            .filter(|delta| Self::CHECKED.contains(&delta.quantity))
+            .filter_map(|delta| {
+                let percent = match delta.relative_percent() {
+                    Some(percent) => percent,
+                    // from nothing to something
+                    None if delta.after > 0 => f64::INFINITY,
+                    None => return None,
+                };
+                (percent > limit).then_some((delta.quantity, percent))
+            })
+            .collect();
+        let (before, after) = self.total_error;
+        if let Some(percent) = error_change_percent(before, after).filter(|&p| p > limit) {
+            regressions.push(("total error", percent));
+        }
+        regressions
+    }
+}
+
+/// Relative change in % of an error probability, infinite from 0 to a
+/// positive error, [`None`] if it stays 0.
+fn error_change_percent(before: f64, after: f64) -> Option<f64> {
+    if before > 0.0 {
+        Some(100.0 * (after - before) / before)
+    } else if after > 0.0 {
+        Some(f64::INFINITY)
+    } else {
+        None
+    }
+}
+
+impl Display for BaselineCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.diff)?;
+        let (before, after) = self.total_error;
+        writeln!(
+            f,
+            "  {:<18}{before:>16.3e}{after:>16.3e}{:>+16.3e}{:>10}",
+            "total error",
+            after - before,
+            error_change_percent(before, after)
+                .map_or_else(|| "-".into(), |percent| format!("{percent:+.1}"))
+        )?;
+        let regressions = self.regressions();
+        if regressions.is_empty() {
+            writeln!(f, "no regression (tolerance {}%)", 100.0 * self.tolerance)
+        } else {
+            for (quantity, percent) in regressions {
+                writeln!(
+                    f,
+                    "regression: {quantity} {percent:+.1}% (tolerance {}%)",
+                    100.0 * self.tolerance
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BaselineCheck, Delta, Diff};
+    use crate::LogicalCounts;
+
+    fn check(qubits: (u64, u64), total_error: (f64, f64), tolerance: f64) -> BaselineCheck {
+        BaselineCheck {
+            diff: Diff {
+                deltas: vec![
+                    Delta {
+                        quantity: "physical qubits",
+                        before: qubits.0,
+                        after: qubits.1,
+                    },
+                    Delta {
+                        quantity: "code distance",
+                        before: 10,
+                        after: 20,
+                    },
+                ],
+            },
+            total_error,
+            tolerance,
+        }
+    }
+
+    #[test]
+    fn deltas_are_relative_to_the_value_before() {
+        let delta = Delta {
+            quantity: "CX",
+            before: 200,
+            after: 150,
+        };
+        assert_eq!(delta.change(), -50);
+        assert_eq!(delta.relative_percent(), Some(-25.0));
+        let from_zero = Delta { before: 0, ..delta };
+        assert_eq!(from_zero.relative_percent(), None);
+    }
+
+    #[test]
+    fn increases_beyond_the_tolerance_are_regressions() {
+        // 10% more qubits, the code distance is not checked
+        assert_eq!(check((100, 110), (1e-3, 1e-3), 0.05).regressions().len(), 1);
+        assert!(check((100, 110), (1e-3, 1e-3), 0.1)
+            .regressions()
+            .is_empty());
+        // tolerances above 100%
+        assert!(check((100, 250), (1e-3, 1e-3), 2.0)
+            .regressions()
+            .is_empty());
+        let regressions = check((100, 100), (1e-3, 2e-3), 0.5).regressions();
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].0, "total error");
+        assert!((regressions[0].1 - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn baselines_of_zero_are_compared() {
+        assert!(check((0, 0), (0.0, 0.0), 0.0).regressions().is_empty());
+        let regressions = check((0, 10), (0.0, 1e-3), 0.0).regressions();
+        assert_eq!(
+            regressions,
+            [
+                ("physical qubits", f64::INFINITY),
+                ("total error", f64::INFINITY)
+            ]
+        );
+        let report = check((0, 0), (0.0, 0.0), 0.0).to_string();
+        assert!(!report.contains("NaN"), "{report}");
+        assert!(report.contains("no regression"), "{report}");
+    }
+
+    #[test]
+    fn counts_are_compared_gate_by_gate() {
+        let before = LogicalCounts::new(100, 1_000, 200);
+        let after = LogicalCounts::new(100, 800, 250);
+        let diff = Diff::counts(&before, &after);
+        let quantities: Vec<_> = diff.deltas.iter().map(|delta| delta.quantity).collect();
+        assert_eq!(quantities, ["logical qubits", "CX", "CCX", "logical depth"]);
+        assert_eq!(diff.deltas[0].change(), 0);
+        assert_eq!(diff.deltas[1].change(), -200);
+        assert_eq!(diff.deltas[2].relative_percent(), Some(25.0));
+
+        let report = diff.to_string();
+        assert!(
+            report.contains(
+                "  CX                            1000             800            -200     -20.0"
+            ),
+            "{report}"
+        );
+        assert_eq!(
+            Diff::counts(&before, &before)
+                .deltas
+                .iter()
+                .map(Delta::change)
+                .sum::<i128>(),
+            0
+        );
+    }
+}
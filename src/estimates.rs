@@ -3,37 +3,562 @@
 
 //! Convenience structure to display resource estimation results.
 
-use std::{fmt::Display, ops::Deref};
+use std::{fmt::Display, ops::Deref, rc::Rc};
 
-use num_traits::{FromPrimitive, ToPrimitive};
-use resource_estimator::estimates::{FactoryPart, Overhead, PhysicalResourceEstimationResult};
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::{
+    Factory, FactoryPart, Overhead, PhysicalResourceEstimationResult,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::{code::RepetitionCode, counter::LogicalCounts, factories::ToffoliFactory};
+use crate::{
+    budget::{BudgetConsumption, BudgetExcess},
+    code::{schedule::CycleSchedule, RepetitionCode},
+    counter::{segmented::SegmentedCounts, LogicalCounts},
+    factories::{
+        fleet::FactoryFleet,
+        sizing::{MagicStateRate, Overproduction, RightSizing},
+        ToffoliBuilder, ToffoliFactory,
+    },
+    frontier::{Estimation, FrontierPoints},
+    geometry::{ChipDimensions, ChipGeometry},
+    layout::{LayoutPolicy, PaperLayout},
+    phases::PhaseAnalysis,
+    provenance::Provenance,
+    qubit::CatQubit,
+    report::ReportSection,
+    restart::{AlgorithmRetry, HeraldedRestart, RetryEstimate, SampledRuntime},
+    syndrome::SyndromeData,
+    units::{format_thousands, TimeSpan, TimeUnit},
+    utilization::Utilization,
+    validity::{Extrapolation, ValidityRange},
+    wiring::{WiringCount, WiringModel},
+};
+
+/// Contributions of phase flips (Z errors) and bit flips (X errors) to an error
+/// probability.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ErrorContributions {
+    /// Error probability due to phase flips (Z errors).
+    pub phase_flip: f64,
+    /// Error probability due to bit flips (X errors).
+    pub bit_flip: f64,
+}
+
+impl ErrorContributions {
+    #[must_use]
+    /// Total error probability (without the cross term).
+    pub fn total(&self) -> f64 {
+        self.phase_flip + self.bit_flip
+    }
+}
 
 /// Represents a physical resources estimate for Alice & Bob's architecture.
-pub struct AliceAndBobEstimates(
-    PhysicalResourceEstimationResult<RepetitionCode, ToffoliFactory, LogicalCounts>,
-);
+pub struct AliceAndBobEstimates {
+    result: PhysicalResourceEstimationResult<RepetitionCode, ToffoliFactory, LogicalCounts>,
+    // logical bit-flip probability per logical qubit and logical cycle
+    bit_flip_rate: f64,
+    // rounds of the code in a logical cycle
+    rounds: u64,
+    time_unit: TimeUnit,
+    restart: Option<HeraldedRestart>,
+    sampled_runtime: Option<SampledRuntime>,
+    retry: Option<RetryEstimate>,
+    fleet: Option<FactoryFleet>,
+    right_sizing: Option<RightSizing>,
+    provenance: Option<Provenance>,
+    extrapolations: Vec<Extrapolation>,
+    // vertical routing, the one of the paper if none
+    layout: Option<Rc<dyn LayoutPolicy>>,
+    utilization: Option<Utilization>,
+    phases: Option<PhaseAnalysis>,
+    chip: Option<ChipDimensions>,
+    wiring: Option<WiringCount>,
+    syndrome: Option<SyndromeData>,
+}
 
 impl AliceAndBobEstimates {
+    #[must_use]
+    /// Wrap an estimation result, with the code used to obtain it (see
+    /// [`AliceAndBobEstimates::estimate`] to estimate and wrap at once).
+    pub fn new(
+        result: PhysicalResourceEstimationResult<RepetitionCode, ToffoliFactory, LogicalCounts>,
+        code: &RepetitionCode,
+    ) -> Self {
+        let bit_flip_rate = code
+            .logical_bitflip_rate(result.logical_patch().code_parameter())
+            .expect("can't compute bit-flip probability");
+        let rounds = code.rounds(result.logical_patch().code_parameter());
+
+        Self {
+            result,
+            bit_flip_rate,
+            rounds,
+            time_unit: TimeUnit::default(),
+            restart: None,
+            retry: None,
+            sampled_runtime: None,
+            fleet: None,
+            right_sizing: None,
+            provenance: None,
+            extrapolations: vec![],
+            layout: None,
+            utilization: None,
+            phases: None,
+            chip: None,
+            wiring: None,
+            syndrome: None,
+        }
+    }
+
+    /// Estimate `estimation`, the bit-flip rate and the rounds being computed
+    /// with the code it was built with (a result alone doesn't keep it, see
+    /// [`AliceAndBobEstimates::new`]).
+    pub fn estimate(estimation: &Estimation) -> Result<Self, String> {
+        estimation
+            .estimate()
+            .map(|result| Self::new(result, estimation.ftp()))
+            .map_err(|err| err.to_string())
+    }
+
+    /// Stream the frontier of `estimation`, each point being wrapped as soon
+    /// as it is found (see [`FrontierPoints`]); dropping the iterator stops
+    /// the search.
+    pub fn frontier_points(estimation: Estimation) -> impl Iterator<Item = Result<Self, String>> {
+        let code = estimation.ftp().clone();
+        FrontierPoints::new(estimation).map(move |result| {
+            result
+                .map(|result| Self::new(result, &code))
+                .map_err(|err| err.to_string())
+        })
+    }
+
+    #[must_use]
+    /// Set the unit used to display durations (automatic by default).
+    pub fn with_time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.time_unit = time_unit;
+        self
+    }
+
+    #[must_use]
+    /// Report the expected restarts and wall-clock time when heralded errors
+    /// lead to restarting the algorithm.
+    pub fn with_restart(mut self, restart: HeraldedRestart) -> Self {
+        self.restart = Some(restart);
+        self
+    }
+
+    #[must_use]
+    /// Also report percentiles of the wall-clock time with restarts, from
+    /// `samples` runs sampled with a generator seeded with `seed` (requires
+    /// [`AliceAndBobEstimates::with_restart`]).
+    pub fn with_sampled_runtime(mut self, samples: usize, seed: u64) -> Self {
+        self.sampled_runtime = self
+            .restart
+            .map(|restart| restart.sample_runtimes(&self, samples, seed));
+        self
+    }
+
+    #[must_use]
+    /// Also report the expected runs and total runtime when the algorithm is
+    /// repeated until it succeeds (set the restart strategy first, if any).
+    pub fn with_retry(mut self, retry: AlgorithmRetry) -> Self {
+        self.retry = Some(retry.estimate(&self));
+        self
+    }
+
+    #[must_use]
+    /// Expected runs and total runtime over the retries, if computed with
+    /// [`AliceAndBobEstimates::with_retry`].
+    pub fn retry(&self) -> Option<RetryEstimate> {
+        self.retry
+    }
+
+    #[must_use]
+    /// Percentiles of the wall-clock time with restarts, if sampled with
+    /// [`AliceAndBobEstimates::with_sampled_runtime`].
+    pub fn sampled_runtime(&self) -> Option<SampledRuntime> {
+        self.sampled_runtime
+    }
+
+    #[must_use]
+    /// Report the heterogeneous factory fleet with the fewest qubits producing
+    /// the magic states at the rate of the estimate, within its magic state
+    /// error budget (see [`ToffoliBuilder::optimal_fleet`]). The estimate
+    /// keeps its factories and physical qubits.
+    pub fn with_mixed_fleet(mut self, builder: &ToffoliBuilder, qubit: &CatQubit) -> Self {
+        let (Some(num_states), Some(runtime)) =
+            (self.num_magic_states(0).to_f64(), self.runtime().to_f64())
+        else {
+            return self;
+        };
+        if num_states > 0.0 {
+            self.fleet = builder.optimal_fleet(
+                qubit,
+                num_states / runtime,
+                self.error_budget().magic_states() / num_states,
+            );
+        }
+        self
+    }
+
+    #[must_use]
+    /// Heterogeneous factory fleet, if computed with
+    /// [`AliceAndBobEstimates::with_mixed_fleet`].
+    pub fn mixed_fleet(&self) -> Option<&FactoryFleet> {
+        self.fleet.as_ref()
+    }
+
+    #[must_use]
+    /// Record the assumptions used to compute the estimate, reported with it.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    #[must_use]
+    /// Assumptions used to compute the estimate, if recorded with
+    /// [`AliceAndBobEstimates::with_provenance`].
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+
+    #[must_use]
+    /// Check the estimate (obtained with `qubit`) against the validity ranges
+    /// of the models, reporting the extrapolated assumptions.
+    pub fn with_validity_check(mut self, range: &ValidityRange, qubit: &CatQubit) -> Self {
+        self.extrapolations = range.extrapolations(&self, qubit);
+        self
+    }
+
+    #[must_use]
+    /// Assumptions extrapolated outside of the validity ranges, as found by
+    /// [`AliceAndBobEstimates::with_validity_check`].
+    pub fn extrapolations(&self) -> &[Extrapolation] {
+        &self.extrapolations
+    }
+
+    #[must_use]
+    /// Count the vertical routing qubits with `layout` (by default, the
+    /// [`PaperLayout`] with all-to-all routing).
+    pub fn with_layout(mut self, layout: impl LayoutPolicy + 'static) -> Self {
+        self.layout = Some(Rc::new(layout));
+        self
+    }
+
+    #[must_use]
+    /// Policy for the vertical routing qubits.
+    pub fn layout(&self) -> &dyn LayoutPolicy {
+        self.layout.as_deref().unwrap_or(&PaperLayout)
+    }
+
+    #[must_use]
+    /// Also report the utilization of the regions of the layout (see
+    /// [`Utilization`]).
+    pub fn with_utilization(mut self) -> Self {
+        self.utilization = Some(Utilization::new(&self));
+        self
+    }
+
+    #[must_use]
+    /// Utilization of the regions of the layout, if computed with
+    /// [`AliceAndBobEstimates::with_utilization`].
+    pub fn utilization(&self) -> Option<&Utilization> {
+        self.utilization.as_ref()
+    }
+
+    #[must_use]
+    /// Also report the demand of Toffoli states of the segments of `phases`,
+    /// the phases of the algorithm run in sequence (see [`PhaseAnalysis`]).
+    pub fn with_phases(mut self, phases: &SegmentedCounts) -> Self {
+        self.phases = PhaseAnalysis::new(&self, phases);
+        self
+    }
+
+    #[must_use]
+    /// Demand of the phases of the algorithm, if computed with
+    /// [`AliceAndBobEstimates::with_phases`].
+    pub fn phases(&self) -> Option<&PhaseAnalysis> {
+        self.phases.as_ref()
+    }
+
+    #[must_use]
+    /// Also report the dimensions of the chip with the qubit pitch of
+    /// `geometry`.
+    pub fn with_chip_geometry(mut self, geometry: ChipGeometry) -> Self {
+        self.chip = Some(geometry.dimensions(&self));
+        self
+    }
+
+    #[must_use]
+    /// Dimensions of the chip, if computed with
+    /// [`AliceAndBobEstimates::with_chip_geometry`].
+    pub fn chip_dimensions(&self) -> Option<ChipDimensions> {
+        self.chip
+    }
+
+    #[must_use]
+    /// Also report the control and readout lines of the physical qubits with
+    /// the coefficients of `wiring`.
+    pub fn with_wiring(mut self, wiring: &WiringModel) -> Self {
+        self.wiring = Some(wiring.lines(self.physical_qubits()));
+        self
+    }
+
+    #[must_use]
+    /// Control and readout lines, if computed with
+    /// [`AliceAndBobEstimates::with_wiring`].
+    pub fn wiring(&self) -> Option<WiringCount> {
+        self.wiring
+    }
+
+    #[must_use]
+    /// Also report the syndrome data sent to the decoder, with
+    /// `bits_per_measurement` bits per measurement.
+    pub fn with_syndrome_data(mut self, bits_per_measurement: u32) -> Self {
+        self.syndrome = Some(SyndromeData::new(&self, bits_per_measurement));
+        self
+    }
+
+    #[must_use]
+    /// Syndrome data, if computed with
+    /// [`AliceAndBobEstimates::with_syndrome_data`].
+    pub fn syndrome_data(&self) -> Option<SyndromeData> {
+        self.syndrome
+    }
+
+    #[must_use]
+    /// Restart strategy, if set with [`AliceAndBobEstimates::with_restart`].
+    pub fn restart(&self) -> Option<HeraldedRestart> {
+        self.restart
+    }
+
+    #[must_use]
+    /// Report the factory type and copies producing the consumed Toffoli
+    /// states within the runtime with the fewest qubits, within the magic
+    /// state error budget (see [`ToffoliBuilder::right_sized`]). The estimate
+    /// keeps its factories and physical qubits.
+    pub fn with_factory_right_sizing(mut self, builder: &ToffoliBuilder, qubit: &CatQubit) -> Self {
+        let states = self.toffoli_states();
+        if let Some(states_f64) = states.to_f64().filter(|&states| states > 0.0) {
+            self.right_sizing = builder.right_sized(
+                qubit,
+                states,
+                self.runtime_duration(),
+                self.error_budget().magic_states() / states_f64,
+            );
+        }
+        self
+    }
+
+    #[must_use]
+    /// Right-sized factories, if computed with
+    /// [`AliceAndBobEstimates::with_factory_right_sizing`].
+    pub fn factory_right_sizing(&self) -> Option<&RightSizing> {
+        self.right_sizing.as_ref()
+    }
+
+    #[must_use]
+    /// Toffoli states produced by the factories compared with the consumed
+    /// ones, if the estimate has factories.
+    pub fn toffoli_overproduction(&self) -> Option<Overproduction> {
+        let part = self.toffoli_factory_part()?;
+        let consumed = self.toffoli_states();
+        (consumed > 0).then(|| Overproduction {
+            produced: part
+                .copies()
+                .saturating_mul(part.runs())
+                .saturating_mul(part.factory().num_output_states()),
+            consumed,
+        })
+    }
+
     #[must_use]
     /// Give a reference to the [`FactoryPart`] used in the estimate.
-    fn toffoli_factory_part(&self) -> Option<&FactoryPart<ToffoliFactory>> {
+    pub(crate) fn toffoli_factory_part(&self) -> Option<&FactoryPart<ToffoliFactory>> {
         self.factory_parts()[0].as_ref()
     }
 
     #[must_use]
     /// Count the number of physical qubits, routing qubits included.
     pub fn physical_qubits(&self) -> u64 {
-        // "Vertical" routing qubits must be added to ensure all-to-all connectivity
-        // Formula from arXiv: 2302.06639, p. 27. `logical_qubits()` include the "horizontal
-        // routing qubits", including the one between the computation qubits and factories.
-        let additional_routing_qubits = 2
-            * ((3
-                * (self.layout_overhead().logical_qubits()
-                    + self.toffoli_factory_part().map_or(0, FactoryPart::copies) * 5))
-                - 1);
-        self.0.physical_qubits() + additional_routing_qubits
+        // "Vertical" routing qubits are added according to the layout (by default, to ensure
+        // all-to-all connectivity). `logical_qubits()` include the "horizontal routing qubits",
+        // including the one between the computation qubits and factories.
+        let additional_routing_qubits = self.layout().vertical_routing_qubits(
+            self.layout_overhead().logical_qubits(),
+            self.toffoli_factory_part().map_or(0, FactoryPart::copies),
+        );
+        self.result.physical_qubits() + additional_routing_qubits
+    }
+
+    #[must_use]
+    /// Physical qubits of the factories, with the interface routing qubit of
+    /// each factory at the main code distance (see
+    /// [`ToffoliFactory::layout_physical_qubits`]).
+    pub fn layout_physical_qubits_for_factories(&self) -> u64 {
+        let main_distance = self.logical_patch().code_parameter().distance();
+        self.toffoli_factory_part().map_or(0, |part| {
+            part.copies() * part.factory().layout_physical_qubits(main_distance)
+        })
+    }
+
+    #[must_use]
+    /// Physical qubits with the exact layout of the factories, which differ
+    /// from [`AliceAndBobEstimates::physical_qubits`] when the factory
+    /// distance differs from the main code distance.
+    pub fn layout_physical_qubits(&self) -> u64 {
+        self.physical_qubits() - self.physical_qubits_for_factories()
+            + self.layout_physical_qubits_for_factories()
+    }
+
+    #[must_use]
+    /// Number of Toffoli magic states consumed by the computation.
+    pub fn toffoli_states(&self) -> u64 {
+        self.num_magic_states(0)
+    }
+
+    #[must_use]
+    /// Toffoli states produced per second by each factory, if the estimate
+    /// has factories.
+    pub fn factory_rate(&self) -> Option<f64> {
+        let duration = self.toffoli_factory_part()?.factory().preparation_time();
+        (duration.as_ns() > 0).then(|| 1.0 / duration.as_seconds())
+    }
+
+    #[must_use]
+    /// Toffoli states produced by each copy of the factory during the
+    /// runtime, if the estimate has factories.
+    pub fn states_per_factory(&self) -> Option<u64> {
+        let part = self.toffoli_factory_part()?;
+        Some(
+            part.runs()
+                .saturating_mul(part.factory().num_output_states()),
+        )
+    }
+
+    #[must_use]
+    /// Time spent by all the copies of the factory preparing states, summed
+    /// over the copies, if the estimate has factories.
+    pub fn total_factory_runtime(&self) -> Option<TimeSpan> {
+        let part = self.toffoli_factory_part()?;
+        let runs = part.copies().saturating_mul(part.runs());
+        Some(TimeSpan::from_ns(
+            runs.saturating_mul(part.factory().preparation_time().as_ns()),
+        ))
+    }
+
+    #[must_use]
+    /// Fraction of the runtime during which the copies of the factory prepare
+    /// the consumed states (capped to 1), if the estimate has factories: the
+    /// rest of the time, they are idle or produce states in excess.
+    pub fn factory_duty_cycle(&self) -> Option<f64> {
+        let part = self.toffoli_factory_part()?;
+        let available = part.copies().to_f64()? * self.runtime_duration().as_ns_f64();
+        let busy = self.toffoli_states().to_f64()? * part.factory().preparation_time().as_ns_f64();
+        (available > 0.0).then(|| (busy / available).min(1.0))
+    }
+
+    #[must_use]
+    /// Toffoli states per second required by the computation and provided by
+    /// all the copies of the factory, if the estimate consumes any.
+    pub fn magic_state_rate(&self) -> Option<MagicStateRate> {
+        let part = self.toffoli_factory_part()?;
+        let runtime = self.runtime_duration().as_seconds();
+        let consumed = self.toffoli_states();
+        (consumed > 0 && runtime > 0.0).then(|| MagicStateRate {
+            required: consumed.to_f64().unwrap_or(f64::MAX) / runtime,
+            provided: part.copies().to_f64().unwrap_or(f64::MAX)
+                * self.factory_rate().unwrap_or_default(),
+        })
+    }
+
+    #[must_use]
+    /// Number of repetition code patches of the computation part, its routing
+    /// qubits included, each of 2d − 1 physical qubits at the code distance d.
+    pub fn compute_patches(&self) -> u64 {
+        let distance = self.logical_patch().code_parameter().distance();
+        (self.physical_qubits() - self.physical_qubits_for_factories()).div_ceil(2 * distance - 1)
+    }
+
+    #[must_use]
+    /// Number of repetition code patches of the factories, at the factory
+    /// distance.
+    pub fn factory_patches(&self) -> u64 {
+        self.toffoli_factory_part().map_or(0, |part| {
+            part.copies() * part.factory().physical_qubits() / (2 * self.factory_distance(part) - 1)
+        })
+    }
+
+    /// Code distance of the factories of `part`.
+    fn factory_distance(&self, part: &FactoryPart<ToffoliFactory>) -> u64 {
+        part.factory().max_code_parameter().map_or(
+            self.logical_patch().code_parameter().distance(),
+            |parameter| parameter.distance(),
+        )
+    }
+
+    #[must_use]
+    /// Number of physical CX gates executed over the whole computation,
+    /// counted as for [`AliceAndBobEstimates::physical_measurement_count`]:
+    /// 2(d − 1) CX per patch and per round.
+    pub fn physical_cx_count(&self) -> u64 {
+        CycleSchedule::CX_LAYERS.saturating_mul(self.physical_measurement_count())
+    }
+
+    #[must_use]
+    /// Number of physical (ancilla) measurements executed over the whole
+    /// computation: d − 1 measurements per patch and per round, for the
+    /// [`AliceAndBobEstimates::compute_patches`] at the code distance and the
+    /// [`AliceAndBobEstimates::factory_patches`] at the factory distance, the
+    /// factories running a round at each round of the computation.
+    pub fn physical_measurement_count(&self) -> u64 {
+        let distance = self.logical_patch().code_parameter().distance();
+        let factory_ancillas = self.toffoli_factory_part().map_or(0, |part| {
+            self.factory_patches() * (self.factory_distance(part) - 1)
+        });
+        self.num_cycles()
+            .saturating_mul(self.rounds)
+            .saturating_mul(self.compute_patches() * (distance - 1) + factory_ancillas)
+    }
+
+    #[must_use]
+    /// Rounds of syndrome measurement of the code in a logical cycle.
+    pub fn rounds(&self) -> u64 {
+        self.rounds
+    }
+
+    #[must_use]
+    /// Number of logical cycles of the computation.
+    pub fn logical_cycles(&self) -> u64 {
+        self.num_cycles()
+    }
+
+    #[must_use]
+    /// Duration of a logical cycle.
+    pub fn cycle_time(&self) -> TimeSpan {
+        TimeSpan::from_ns(self.logical_patch().logical_cycle_time())
+    }
+
+    #[must_use]
+    /// Duration of a logical cycle in nanoseconds (see
+    /// [`AliceAndBobEstimates::cycle_time`]).
+    pub fn cycle_time_ns(&self) -> u64 {
+        self.cycle_time().as_ns()
+    }
+
+    #[must_use]
+    /// Runtime of the computation (see [`AliceAndBobEstimates::runtime`] for
+    /// the nanoseconds).
+    pub fn runtime_duration(&self) -> TimeSpan {
+        TimeSpan::from_ns(self.runtime())
+    }
+
+    #[must_use]
+    /// Logical clock frequency in Hz, i.e. the number of logical cycles
+    /// performed per second.
+    pub fn logical_clock_rate(&self) -> f64 {
+        1.0 / self.cycle_time().as_seconds()
     }
 
     #[must_use]
@@ -53,18 +578,295 @@ impl AliceAndBobEstimates {
     pub fn total_error(&self) -> f64 {
         // Error is computed as 'logical + magic' without the cross term since it is
         // largely sub-leading here, and negative anyway
-        let logical = (self.num_cycles() * self.layout_overhead().logical_qubits())
+        self.computation_error_contributions().total() + self.factory_error_contributions().total()
+    }
+
+    #[must_use]
+    /// Part of the topological and magic state budgets used by the errors of
+    /// the computation part and of the magic states.
+    pub fn budget_consumption(&self) -> BudgetConsumption {
+        BudgetConsumption::new(
+            self.error_budget(),
+            self.computation_error_contributions().total(),
+            self.factory_error_contributions().total(),
+        )
+    }
+
+    #[must_use]
+    /// Excess of the total error over the error budget, if the chosen
+    /// parameters (rounded, without the cross term) miss it.
+    pub fn budget_excess(&self) -> Option<BudgetExcess> {
+        BudgetExcess::check(self.error_budget(), self.total_error())
+    }
+
+    #[must_use]
+    /// Split the error of the computation part (logical qubits, without the
+    /// factories) into phase-flip and bit-flip contributions.
+    ///
+    /// Leakage and explicit measurement errors, if any, are counted in the
+    /// phase-flip part.
+    pub fn computation_error_contributions(&self) -> ErrorContributions {
+        let volume = (self.num_cycles() * self.layout_overhead().logical_qubits())
             .to_f64()
-            .expect("can't convert volume as f64")
-            * self.logical_patch().logical_error_rate();
-        let magic_states = self.toffoli_factory_part().map_or(0.0, |p| {
+            .expect("can't convert volume as f64");
+        let logical_error_rate = self.logical_patch().logical_error_rate();
+
+        ErrorContributions {
+            phase_flip: volume * (logical_error_rate - self.bit_flip_rate),
+            bit_flip: volume * self.bit_flip_rate,
+        }
+    }
+
+    #[must_use]
+    /// Split the error of the magic states into phase-flip and bit-flip
+    /// contributions.
+    ///
+    /// The precomputed factories error probabilities
+    /// ([arXiv:2302.06639](https://arxiv.org/abs/2302.06639), Table III) only
+    /// account for phase flips, bit flips being exponentially suppressed in
+    /// |α|², so they are entirely reported as phase flips.
+    pub fn factory_error_contributions(&self) -> ErrorContributions {
+        let phase_flip = self.toffoli_factory_part().map_or(0.0, |p| {
             self.num_magic_states(0)
                 .to_f64()
                 .expect("can't convert number of magic states as f64")
                 * p.factory().error_probability()
         });
 
-        logical + magic_states
+        ErrorContributions {
+            phase_flip,
+            bit_flip: 0.0,
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    /// Quantities of the report, as (label, formatted value) pairs grouped in
+    /// sections. This is shared by all the output formats.
+    pub fn report_sections(&self) -> Vec<ReportSection> {
+        let computation = self.computation_error_contributions();
+        let factories = self.factory_error_contributions();
+
+        let summary = vec![
+            (
+                "# physical qubits",
+                format_thousands(self.physical_qubits()),
+            ),
+            ("runtime", self.runtime_duration().format(self.time_unit)),
+            ("total error", format!("{:.5}", self.total_error())),
+            ("logical cycles", format_thousands(self.logical_cycles())),
+            ("cycle time", self.cycle_time().format(self.time_unit)),
+            (
+                "logical clock rate",
+                format!("{:.2} kHz", self.logical_clock_rate() / 1e3),
+            ),
+        ];
+        let mut resources = vec![
+            (
+                "code distance",
+                self.logical_patch().code_parameter().to_string(),
+            ),
+            (
+                "#factories",
+                self.toffoli_factory_part()
+                    .map_or(0, FactoryPart::copies)
+                    .to_string(),
+            ),
+            (
+                "factories distance",
+                self.toffoli_factory_part()
+                    .map_or_else(|| "-".into(), |part| part.factory().to_string()),
+            ),
+            (
+                "factory fraction",
+                format!("{:.2}%", self.factory_fraction()),
+            ),
+            ("Toffoli states", format_thousands(self.toffoli_states())),
+            (
+                "factory rate",
+                self.factory_rate().map_or_else(
+                    || "-".into(),
+                    |rate| format!("{rate:.1} states/s per factory"),
+                ),
+            ),
+            (
+                "physical CX",
+                format!(
+                    "{:.3e}",
+                    self.physical_cx_count().to_f64().unwrap_or(f64::MAX)
+                ),
+            ),
+            (
+                "physical measurements",
+                format!(
+                    "{:.3e}",
+                    self.physical_measurement_count()
+                        .to_f64()
+                        .unwrap_or(f64::MAX)
+                ),
+            ),
+            (
+                "# qubits (exact layout)",
+                format!(
+                    "{} ({:+} vs approximation)",
+                    format_thousands(self.layout_physical_qubits()),
+                    i128::from(self.layout_physical_qubits()) - i128::from(self.physical_qubits())
+                ),
+            ),
+        ];
+        let mut errors = vec![
+            (
+                "Z error (compute)",
+                format!("{:.3e}", computation.phase_flip),
+            ),
+            ("X error (compute)", format!("{:.3e}", computation.bit_flip)),
+            (
+                "Z error (factories)",
+                format!("{:.3e}", factories.phase_flip),
+            ),
+            ("X error (factories)", format!("{:.3e}", factories.bit_flip)),
+            ("budget used", self.budget_consumption().to_string()),
+        ];
+
+        if let Some(excess) = self.budget_excess() {
+            errors.push(("over budget", format!("by a factor {:.3}", excess.factor())));
+        }
+
+        if let Some(layout) = &self.layout {
+            resources.push(("layout", layout.description()));
+        }
+
+        if let Some(overproduction) = self.toffoli_overproduction() {
+            resources.push(("Toffoli overproduction", overproduction.to_string()));
+        }
+
+        if let Some(rate) = self.magic_state_rate() {
+            resources.push(("magic state rate", rate.to_string()));
+        }
+
+        if let Some(duty_cycle) = self.factory_duty_cycle() {
+            resources.push(("factory duty cycle", format!("{:.1}%", 100.0 * duty_cycle)));
+        }
+
+        let mut sections = vec![summary, resources, errors];
+
+        if let Some(fleet) = &self.fleet {
+            sections.push(vec![
+                ("mixed factories", fleet.to_string()),
+                (
+                    "mixed factory qubits",
+                    format_thousands(fleet.physical_qubits()),
+                ),
+            ]);
+        }
+
+        if let Some(sizing) = &self.right_sizing {
+            sections.push(vec![
+                ("right-sized factories", sizing.to_string()),
+                (
+                    "factory qubits saved",
+                    format_thousands(
+                        self.physical_qubits_for_factories()
+                            .saturating_sub(sizing.physical_qubits),
+                    ),
+                ),
+            ]);
+        }
+
+        if let Some(utilization) = &self.utilization {
+            sections.push(utilization.report_section());
+        }
+
+        if let Some(phases) = &self.phases {
+            sections.push(phases.report_section(self.time_unit));
+        }
+
+        // hardware: chip dimensions and wiring
+        let hardware: ReportSection = self
+            .chip
+            .iter()
+            .flat_map(ChipDimensions::report_section)
+            .chain(self.wiring.iter().flat_map(WiringCount::report_section))
+            .collect();
+        if !hardware.is_empty() {
+            sections.push(hardware);
+        }
+
+        if let Some(syndrome) = &self.syndrome {
+            sections.push(syndrome.report_section());
+        }
+
+        sections.extend(self.restart_sections());
+
+        if let Some(provenance) = &self.provenance {
+            sections.push(provenance.report_section());
+        }
+
+        if !self.extrapolations.is_empty() {
+            sections.push(
+                self.extrapolations
+                    .iter()
+                    .map(|extrapolation| ("extrapolated", extrapolation.to_string()))
+                    .collect(),
+            );
+        }
+
+        sections
+    }
+
+    /// Sections of the report on the restart and retry strategies, if set.
+    fn restart_sections(&self) -> Vec<ReportSection> {
+        let mut sections = vec![];
+        if let Some(restart) = self.restart {
+            let total_error = self.total_error();
+            sections.push(vec![
+                (
+                    "expected restarts",
+                    format!("{:.3e}", restart.expected_restarts(total_error)),
+                ),
+                (
+                    "expected runtime",
+                    self.time_unit.format(restart.expected_runtime(self)),
+                ),
+                (
+                    "undetected error",
+                    format!("{:.5}", restart.undetected_error(total_error)),
+                ),
+            ]);
+        }
+
+        if let Some(retry) = self.retry {
+            sections.push(vec![
+                (
+                    "success probability",
+                    format!("{:.3}", retry.success_probability),
+                ),
+                ("expected runs", format!("{:.3}", retry.expected_runs)),
+                (
+                    "expected total runtime",
+                    self.time_unit.format(retry.expected_runtime_ns),
+                ),
+            ]);
+        }
+
+        if let Some(sampled) = self.sampled_runtime {
+            sections.push(vec![
+                (
+                    "runtime p50/p90/p99",
+                    format!(
+                        "{} / {} / {}",
+                        self.time_unit.format(sampled.median_ns),
+                        self.time_unit.format(sampled.p90_ns),
+                        self.time_unit.format(sampled.p99_ns)
+                    ),
+                ),
+                (
+                    "samples (seed)",
+                    format!("{} ({})", sampled.samples, sampled.seed),
+                ),
+            ]);
+        }
+        sections
     }
 }
 
@@ -72,17 +874,21 @@ impl Deref for AliceAndBobEstimates {
     type Target = PhysicalResourceEstimationResult<RepetitionCode, ToffoliFactory, LogicalCounts>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.result
     }
 }
 
+/// Wrap a result estimated with the default [`RepetitionCode`], kept for
+/// compatibility: the bit-flip rate and the rounds are wrong for any other
+/// code, prefer [`AliceAndBobEstimates::estimate`] or
+/// [`AliceAndBobEstimates::new`].
 impl From<PhysicalResourceEstimationResult<RepetitionCode, ToffoliFactory, LogicalCounts>>
     for AliceAndBobEstimates
 {
     fn from(
         value: PhysicalResourceEstimationResult<RepetitionCode, ToffoliFactory, LogicalCounts>,
     ) -> Self {
-        Self(value)
+        Self::new(value, &RepetitionCode::new())
     }
 }
 
@@ -91,32 +897,12 @@ impl Display for AliceAndBobEstimates {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f,)?;
         writeln!(f, "─────────────────────────────")?;
-        writeln!(f, "# physical qubits:    {}", self.physical_qubits())?;
-        writeln!(
-            f,
-            "runtime:             {:.2} hrs",
-            f64::from_u64(self.runtime()).expect("runtime is too large") / 1e9 / 3600.0
-        )?;
-        writeln!(f, "total error:         {:.5}", self.total_error())?;
-        writeln!(f, "─────────────────────────────")?;
-        writeln!(
-            f,
-            "code distance:       {}",
-            self.logical_patch().code_parameter()
-        )?;
-        writeln!(
-            f,
-            "#factories:          {}",
-            self.toffoli_factory_part().map_or(0, FactoryPart::copies)
-        )?;
-        writeln!(
-            f,
-            "factories distance:  {}",
-            self.toffoli_factory_part()
-                .expect("No factory part")
-                .factory()
-        )?;
-        writeln!(f, "factory fraction:    {:.2}%", self.factory_fraction())?;
-        writeln!(f, "─────────────────────────────")
+        for section in self.report_sections() {
+            for (label, value) in section {
+                writeln!(f, "{:<21}{value}", format!("{label}:"))?;
+            }
+            writeln!(f, "─────────────────────────────")?;
+        }
+        Ok(())
     }
 }
@@ -12,13 +12,40 @@
 //! precomputed (see Table III, p. 35). The table is hard-coded in the
 //! implementation of [`Default`] for [`ToffoliBuilder`].
 //! Note that 1/κ₂ = 100 ns and κ₁/κ₂ = 1e-5 are hard-coded (values used in the
-//! precomputation).
+//! precomputation). When the effective κ₁/κ₂ of the qubits differs (e.g. due
+//! to thermal photons, or to a different κ₂ in the factories), the error
+//! probabilities of the factories are extrapolated from the precomputed ones,
+//! and their durations scale with the 1/κ₂ of the factories.
+//!
+//! For very low target errors, factories can be cascaded with a second
+//! distillation stage, see [`cascade`]. Alternatively, the Toffoli gates can be
+//! executed directly, see [`direct`]. Factories of different types can be
+//! combined to reach the magic state rate with fewer qubits, see [`fleet`],
+//! and the states produced in excess are reported, see [`sizing`].
+//!
+//! Magic state errors below the lowest error of the factories are reported by
+//! [`ToffoliBuilder::check_budget`], with these alternatives.
 
-use num_traits::FromPrimitive;
-use resource_estimator::estimates::{self, FactoryBuilder};
+use cascade::{Cascade, DistillationStage};
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::{self, ErrorBudget, FactoryBuilder, Overhead};
+use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, fmt::Display, rc::Rc};
 
-use crate::{code::CodeParameter, CatQubit, RepetitionCode};
+use crate::{
+    code::{concatenated::ConcatenatedCode, schedule::CycleSchedule, CodeParameter},
+    qubit::{CxImplementation, REFERENCE_INVERSE_K2_NS},
+    units::{Rounding, TimeSpan},
+    CatQubit, LogicalCounts, RepetitionCode,
+};
+
+pub mod cascade;
+pub mod direct;
+pub mod fleet;
+pub mod sizing;
+
+/// κ₁/κ₂ used to precompute the performances of the factories.
+pub(crate) const REFERENCE_K1_K2: f64 = 1e-5;
 
 /// Struct containing parameters of Toffoli magic states factories based on
 /// fault-tolerant measurement of stabilizers of the Toffoli magic state.
@@ -42,14 +69,22 @@ use crate::{code::CodeParameter, CatQubit, RepetitionCode};
 /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (Table III, p. 35) and
 /// are available through [`ToffoliBuilder`]'s [`Default`] trait.
 ///
-/// Value 1/κ₂ = 100 ns and κ₁/κ₂ = 1e-5 are hard-coded.
-#[derive(Clone, PartialEq)]
+/// Value 1/κ₂ = 100 ns and κ₁/κ₂ = 1e-5 are hard-coded, see
+/// [`ToffoliFactory::for_qubit`] for other qubits.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ToffoliFactory {
     code_distance: usize,
     alpha_sq: f64,
     error_probability: f64,
     acceptance_probability: f64,
     steps: usize,
+    cx_implementation: CxImplementation,
+    // second distillation stage fed by this factory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cascade: Option<Cascade>,
+    // 1/κ₂ in ns, if not the one of the precomputation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inverse_k2_ns: Option<f64>,
 }
 
 impl ToffoliFactory {
@@ -59,6 +94,49 @@ impl ToffoliFactory {
         self.error_probability
     }
 
+    /// Probability that a preparation is accepted, failed ones being retried.
+    #[must_use]
+    pub fn acceptance_probability(&self) -> f64 {
+        self.acceptance_probability
+    }
+
+    /// Average duration of the magic state preparation.
+    ///
+    /// Note that contrarily to the code used in the main part of the processor,
+    /// as in [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) the CNOTs are
+    /// by default implemented in an adiabatic way, with a gate time of
+    /// 89.2/(κ₂|α|²) (see p. 32, and [`CxImplementation`]).
+    /// 1/κ₂ = 100 ns and κ₁/κ₂ = 1e-5 are hard-coded, unless the factory has
+    /// been adapted to other qubits ([`ToffoliFactory::for_qubit`]).
+    ///
+    /// The factory is heralded, this duration take into account that retry
+    /// might be required.
+    ///
+    /// The duration saturates at the longest one, e.g. when no state is ever
+    /// accepted.
+    #[must_use]
+    pub fn preparation_time(&self) -> TimeSpan {
+        // If you change it, you also need to recompute the default factories.
+        let t = self.inverse_k2_ns.unwrap_or(REFERENCE_INVERSE_K2_NS); // 1/κ₂ [nanoseconds]
+
+        // Complete formula for adiabatic gates is: π/(8 |α|^2 sqrt(2κ₁κ₂)).
+        // Using it would allow to change κ₂ at κ₁/κ₂ constant.
+        let gate_time = self.cx_implementation.duration(self.alpha_sq) * t;
+
+        // Second stage: logical cycles of d rounds with 2 CX.
+        let stage_time = self.cascade.map_or(0.0, |Cascade { stage, .. }| {
+            let round_time = CycleSchedule::default().other_steps * t + 2.0 * gate_time;
+            stage.logical_cycles * self.code_distance.to_f64().unwrap_or(f64::MAX) * round_time
+        });
+        let steps = self.steps.to_f64().unwrap_or(f64::MAX);
+
+        TimeSpan::from_ns_f64(
+            (gate_time * steps + stage_time) / self.acceptance_probability,
+            Rounding::Nearest,
+        )
+        .unwrap_or(TimeSpan::from_ns(u64::MAX))
+    }
+
     /// Space-time volume of the factory (including retries).
     #[must_use]
     pub fn normalized_volume(&self) -> u64 {
@@ -67,7 +145,96 @@ impl ToffoliFactory {
 
         assert_eq!(self.num_output_states(), 1);
 
-        self.physical_qubits() * self.duration()
+        self.physical_qubits() * self.preparation_time().as_ns()
+    }
+
+    /// Number of physical qubits in each factory, when the main code has
+    /// distance `main_distance`.
+    ///
+    /// Contrarily to [`estimates::Factory::physical_qubits`], the patches
+    /// internal to the factory have the factory distance, and only the routing
+    /// qubit interfacing the factory with the computation part has the main
+    /// code distance.
+    #[must_use]
+    pub fn layout_physical_qubits(&self, main_distance: u64) -> u64 {
+        use estimates::Factory;
+
+        let factory_patch = 2 * self.code_distance as u64 - 1;
+        let interface_patch = 2 * main_distance - 1;
+        self.physical_qubits() - factory_patch + interface_patch
+    }
+
+    /// Factory with its error probability extrapolated to the effective
+    /// κ₁/κ₂ of the factories ([`CatQubit::factory_k1_k2`]) and to the factory
+    /// CX implementation of `qubit`, its duration scaling with the 1/κ₂ of the
+    /// factories.
+    ///
+    /// The error is dominated by phase flips, so it is scaled as the phase-flip
+    /// error of a repetition code of the factory distance d, i.e. as
+    /// (κ₁T/κ₂)^((d+1)/2) with T the CX gate duration (see
+    /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639), p. 3, eq. 4).
+    /// This is a first-order extrapolation: the acceptance probability is kept
+    /// unchanged, and the non-adiabatic errors of fast CX gates are neglected.
+    #[must_use]
+    pub fn for_qubit(&self, qubit: &CatQubit) -> Cow<Self> {
+        let ratio = qubit.factory_k1_k2() / REFERENCE_K1_K2
+            * qubit.factory_cx.duration(self.alpha_sq)
+            / self.cx_implementation.duration(self.alpha_sq);
+        #[allow(clippy::float_cmp)]
+        let inverse_k2_ns = (qubit.factory_inverse_k2_ns() != REFERENCE_INVERSE_K2_NS)
+            .then_some(qubit.factory_inverse_k2_ns());
+        #[allow(clippy::float_cmp)]
+        if ratio == 1.0 && inverse_k2_ns == self.inverse_k2_ns {
+            return Cow::Borrowed(self);
+        }
+
+        let exponent =
+            i32::try_from(self.code_distance.div_ceil(2)).expect("factory distance is too large");
+        Cow::Owned(match self.cascade {
+            Some(cascade) => {
+                let input_error = cascade.input_error * ratio.powi(exponent);
+                Self {
+                    error_probability: cascade.stage.output_error(input_error),
+                    cx_implementation: qubit.factory_cx,
+                    inverse_k2_ns,
+                    cascade: Some(Cascade {
+                        input_error,
+                        ..cascade
+                    }),
+                    ..self.clone()
+                }
+            }
+            None => Self {
+                error_probability: self.error_probability * ratio.powi(exponent),
+                cx_implementation: qubit.factory_cx,
+                inverse_k2_ns,
+                ..self.clone()
+            },
+        })
+    }
+
+    /// Factory whose output states are distilled by a second `stage`.
+    ///
+    /// The stage detects errors on its inputs, lowering the acceptance
+    /// probability.
+    #[must_use]
+    pub fn cascaded(&self, stage: DistillationStage) -> Self {
+        Self {
+            error_probability: stage.output_error(self.error_probability),
+            acceptance_probability: self.acceptance_probability
+                * stage.acceptance_probability(self.error_probability),
+            cascade: Some(Cascade {
+                stage,
+                input_error: self.error_probability,
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// Whether the output states are distilled by a second stage.
+    #[must_use]
+    pub fn is_cascaded(&self) -> bool {
+        self.cascade.is_some()
     }
 }
 
@@ -81,40 +248,29 @@ impl estimates::Factory for ToffoliFactory {
     /// The routing qubit under the factories is associated with the compute qubits.
     ///
     /// Note that the formula might not be exact when factories internal distance is
-    /// different than the main code distance, but it is negligeable.
+    /// different than the main code distance, but it is negligeable (see
+    /// [`ToffoliFactory::layout_physical_qubits`] for the exact count).
     /// Additionnaly, note that that is might not even be a real problem as only one of the 4
     /// factory qubit needs to be accessed through all it's physical qubits.
     fn physical_qubits(&self) -> u64 {
         let num_logical_qubits: u64 = 4;
         let horizontal_routing_qubits: u64 = 1;
 
-        (num_logical_qubits + horizontal_routing_qubits) * (2 * self.code_distance as u64 - 1)
+        let patch_qubits = 2 * self.code_distance as u64 - 1;
+        let first_stage = (num_logical_qubits + horizontal_routing_qubits) * patch_qubits;
+
+        match self.cascade {
+            Some(Cascade { stage, .. }) => {
+                stage.inputs * first_stage + stage.logical_qubits * patch_qubits
+            }
+            None => first_stage,
+        }
     }
 
-    /// Average duration of the magic state preparation.
-    ///
-    /// Note that contrarily to the code used in the main part of the processor,
-    /// as in [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) the CNOTs are
-    /// implemented in an adiabatic way, with a gate time of 89.2/(κ₂|α|²) (see
-    /// p. 32).
-    /// 1/κ₂ = 100 ns and κ₁/κ₂ = 1e-5 are hard-coded.
-    ///
-    /// The factory is heralded, this duration take into account that retry
-    /// might be required.
+    /// Average duration of the magic state preparation, in nanoseconds (see
+    /// [`ToffoliFactory::preparation_time`]).
     fn duration(&self) -> u64 {
-        // If you change it, you also need to recompute the default factories.
-        let t = 100.0; // 1/κ₂ [nanoseconds]
-
-        // The more accurate # of time steps 89.2 was taken from the Github code
-        // (vs 89 in arXiv:2302.06639 (p. 32))
-        // Complete formula is: π/(8 |α|^2 sqrt(2κ₁κ₂)). Using it would allow to
-        // change κ₂ at κ₁/κ₂ constant.
-        let gate_time = 89.2 * t / self.alpha_sq;
-
-        f64::from_usize(self.steps)
-            .map(|steps| (gate_time * steps / self.acceptance_probability).round())
-            .and_then(u64::from_f64)
-            .expect("Cannot compute runtime of factory.")
+        self.preparation_time().as_ns()
     }
 
     fn num_output_states(&self) -> u64 {
@@ -145,14 +301,20 @@ impl PartialOrd for ToffoliFactory {
 
 impl Display for ToffoliFactory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} (|ɑ|² = {})", self.code_distance, self.alpha_sq)
+        write!(f, "{} (|ɑ|² = {})", self.code_distance, self.alpha_sq)?;
+        if self.is_cascaded() {
+            write!(f, " + 2nd stage")?;
+        }
+        Ok(())
     }
 }
 
 /// Contains a bunch of factories, and knows how to choose the best one.
+#[derive(Clone)]
 pub struct ToffoliBuilder {
     factories: Vec<ToffoliFactory>,
-    lowest_error_probability: f64,
+    // factories accepting their outputs less often are excluded
+    acceptance_floor: f64,
 }
 
 impl Default for ToffoliBuilder {
@@ -167,6 +329,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 1.05e-3,
                 steps: 23,
                 acceptance_probability: 0.84,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 3,
@@ -174,6 +339,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 1.02e-4,
                 steps: 29,
                 acceptance_probability: 0.745,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 3,
@@ -181,6 +349,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 8.14e-5,
                 steps: 35,
                 acceptance_probability: 0.66,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 5,
@@ -188,6 +359,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 4.62e-6,
                 steps: 46,
                 acceptance_probability: 0.456,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 5,
@@ -195,6 +369,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 7.00e-7,
                 steps: 53,
                 acceptance_probability: 0.362,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 5,
@@ -202,6 +379,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 5.36e-7,
                 steps: 60,
                 acceptance_probability: 0.288,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 7,
@@ -209,6 +389,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 6.14e-8,
                 steps: 73,
                 acceptance_probability: 0.148,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 7,
@@ -216,6 +399,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 8.40e-9,
                 steps: 81,
                 acceptance_probability: 0.105,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 7,
@@ -223,6 +409,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 5.16e-9,
                 steps: 89,
                 acceptance_probability: 0.0727,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 9,
@@ -230,6 +419,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 2.28e-9,
                 steps: 104,
                 acceptance_probability: 0.0262,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 9,
@@ -237,6 +429,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 2.30e-10,
                 steps: 113,
                 acceptance_probability: 0.0154,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 9,
@@ -244,6 +439,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 7.36e-11,
                 steps: 122,
                 acceptance_probability: 0.00975,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 19,
@@ -251,6 +449,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 7.90e-12,
                 steps: 9576,
                 acceptance_probability: 1.0,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 21,
@@ -258,6 +459,9 @@ impl Default for ToffoliBuilder {
                 error_probability: 5.40e-13,
                 steps: 14112,
                 acceptance_probability: 1.0,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
             ToffoliFactory {
                 code_distance: 23,
@@ -265,19 +469,143 @@ impl Default for ToffoliBuilder {
                 error_probability: 3.74e-14,
                 steps: 21344,
                 acceptance_probability: 1.0,
+                cx_implementation: CxImplementation::Adiabatic,
+                cascade: None,
+                inverse_k2_ns: None,
             },
         ];
 
-        let lowest_error_probability = factories
+        Self {
+            factories,
+            acceptance_floor: 0.0,
+        }
+    }
+}
+
+impl ToffoliBuilder {
+    /// Factories among which the best one is chosen, i.e. the ones of the
+    /// table above the acceptance floor.
+    pub fn factories(&self) -> impl Iterator<Item = &ToffoliFactory> {
+        self.factories
             .iter()
+            .filter(|factory| factory.acceptance_probability >= self.acceptance_floor)
+    }
+
+    /// Exclude the factories whose acceptance probability is below `floor`:
+    /// their preparation restarts so often that some runs last far longer
+    /// than the average (e.g. about 1% for the distance 9 factories).
+    pub fn with_acceptance_floor(mut self, floor: f64) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&floor) {
+            return Err(format!(
+                "acceptance floor {floor} is not a probability (expected between 0 and 1)"
+            ));
+        }
+        self.acceptance_floor = floor;
+        Ok(self)
+    }
+
+    /// Minimum acceptance probability of the factories.
+    #[must_use]
+    pub fn acceptance_floor(&self) -> f64 {
+        self.acceptance_floor
+    }
+
+    /// Lowest error probability of the factories.
+    fn lowest_error_probability(&self) -> f64 {
+        self.factories()
             .map(|f| f.error_probability)
             .min_by(f64::total_cmp)
-            .unwrap_or_default();
+            .unwrap_or_default()
+    }
 
-        Self {
-            factories,
-            lowest_error_probability,
+    /// Hash of the table of factories (64-bit FNV-1a of its JSON
+    /// serialization, in hexadecimal), stable across platforms and versions.
+    #[must_use]
+    pub fn table_hash(&self) -> String {
+        let json = serde_json::to_vec(&self.factories).expect("can't serialize factories");
+        let hash = json.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        format!("{hash:016x}")
+    }
+
+    /// Add, for each precomputed factory, a cascaded factory whose outputs are
+    /// distilled by a second `stage`, extending the reachable error range
+    /// (the cascaded factories of a previous call are replaced).
+    #[must_use]
+    pub fn with_cascade(mut self, stage: DistillationStage) -> Self {
+        self.factories.retain(|factory| !factory.is_cascaded());
+        let cascaded: Vec<_> = self
+            .factories
+            .iter()
+            .map(|factory| factory.cascaded(stage))
+            .collect();
+        self.factories.extend(cascaded);
+        self
+    }
+
+    /// Check that the factories can reach the error probability
+    /// `output_error_rate` per magic state, suggesting how to reach lower
+    /// errors otherwise.
+    pub fn check_output_error_rate(&self, output_error_rate: f64) -> Result<(), String> {
+        let lowest = self.lowest_error_probability();
+        if output_error_rate > lowest {
+            return Ok(());
         }
+        let cascade = if self.factories.iter().any(ToffoliFactory::is_cascaded) {
+            ""
+        } else {
+            "add cascaded factories (--cascade, or `ToffoliBuilder::with_cascade`), "
+        };
+        let floor = if self.acceptance_floor > 0.0 {
+            "lower the acceptance floor, "
+        } else {
+            ""
+        };
+        Err(format!(
+            "requested magic state error {output_error_rate:.3e} is below the lowest error of the \
+             factories ({lowest:.3e}); {cascade}{floor}give a larger magic state error budget, or \
+             execute the Toffoli gates directly (see `factories::direct`)"
+        ))
+    }
+
+    /// Check that the factories can reach the error per magic state given by
+    /// `budget` to the CCX gates of `counts`.
+    pub fn check_budget(&self, counts: &LogicalCounts, budget: &ErrorBudget) -> Result<(), String> {
+        match counts.num_magic_states(budget, 0).to_f64() {
+            Some(states) if states > 0.0 => {
+                self.check_output_error_rate(budget.magic_states() / states)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Provide a sorted (by volume) list of factories that reach the target
+    /// logical error rate, and whose |α|² is reachable by the qubits.
+    pub(crate) fn find_toffoli_factories(
+        &self,
+        qubit: &CatQubit,
+        output_error_rate: f64,
+    ) -> Vec<Cow<ToffoliFactory>> {
+        if let Err(err) = self.check_output_error_rate(output_error_rate) {
+            tracing::debug!(%err, "no Toffoli factory");
+            return vec![];
+        }
+
+        let mut factories: Vec<_> = self
+            .factories()
+            .filter(|factory| qubit.supports_alpha_sq(factory.alpha_sq))
+            .map(|factory| factory.for_qubit(qubit))
+            .filter(|factory| factory.error_probability <= output_error_rate)
+            .collect();
+        factories.sort_unstable();
+        tracing::debug!(
+            output_error_rate,
+            candidates = factories.len(),
+            smallest = factories.first().map(ToString::to_string),
+            "Toffoli factories reaching the error rate"
+        );
+        factories
     }
 }
 
@@ -285,34 +613,55 @@ impl FactoryBuilder<RepetitionCode> for ToffoliBuilder {
     type Factory = ToffoliFactory;
 
     /// Provide a sorted (by volume) list of factories that reach the target
-    /// logical error rate.
+    /// logical error rate, and whose |α|² is reachable by the qubits.
     fn find_factories(
         &self,
         _ftp: &RepetitionCode,
-        _qubit: &Rc<CatQubit>,
+        qubit: &Rc<CatQubit>,
         _magic_state_type: usize,
         output_error_rate: f64,
         _max_code_parameter: &CodeParameter,
     ) -> Option<Vec<Cow<Self::Factory>>> {
-        assert!(
-            output_error_rate > self.lowest_error_probability,
-            "Requested error probability is too low"
-        );
-
-        let mut factories: Vec<_> = self
-            .factories
-            .iter()
-            .filter_map(|factory| {
-                (factory.error_probability <= output_error_rate).then_some(Cow::Borrowed(factory))
-            })
-            .collect();
-        factories.sort_unstable();
-        Some(factories)
+        Some(self.find_toffoli_factories(qubit, output_error_rate))
     }
 
     /// Number of types of magic states.
+    ///
+    /// Only Toffoli states are produced, so they get the whole magic state
+    /// budget; with more types, see [`crate::budget::split_magic_state_budget`].
     fn num_magic_state_types(&self) -> usize {
         // Same implementation as the provided one.
         1
     }
 }
+
+impl FactoryBuilder<ConcatenatedCode> for ToffoliBuilder {
+    type Factory = ToffoliFactory;
+
+    /// Same factories as for the repetition code (the magic states are not
+    /// protected by the outer code).
+    fn find_factories(
+        &self,
+        _ftp: &ConcatenatedCode,
+        qubit: &Rc<CatQubit>,
+        _magic_state_type: usize,
+        output_error_rate: f64,
+        _max_code_parameter: &CodeParameter,
+    ) -> Option<Vec<Cow<Self::Factory>>> {
+        Some(self.find_toffoli_factories(qubit, output_error_rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ToffoliBuilder, ToffoliFactory};
+
+    #[test]
+    fn factories_are_saved_and_read_back() {
+        for factory in ToffoliBuilder::default().factories() {
+            let json = serde_json::to_string(factory).expect("factory");
+            let read: ToffoliFactory = serde_json::from_str(&json).expect("saved factory");
+            assert!(read == *factory, "{json}");
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Two-level distillation cascade.
+//!
+//! The precomputed factories bottom out at an error probability of 3.74e-14.
+//! Lower errors can be reached by feeding the output of several factories
+//! into a second distillation stage, which quadratically suppresses the error
+//! of its input states and detects the other errors (hence a lower acceptance
+//! probability). See [`crate::ToffoliBuilder::with_cascade`].
+//!
+//! The input factories run in parallel (one per input state), and the second
+//! stage is encoded in repetition codes with the same parameters as the input
+//! factories.
+
+use serde::{Deserialize, Serialize};
+
+/// Second distillation stage, fed by the precomputed factories.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DistillationStage {
+    /// Number of input Toffoli states per output state.
+    pub inputs: u64,
+    /// Output error is `error_coefficient * p²`, with p the input error.
+    pub error_coefficient: f64,
+    /// Number of logical qubits of the stage (routing qubits included).
+    pub logical_qubits: u64,
+    /// Duration of the stage, in logical cycles (of d rounds).
+    pub logical_cycles: f64,
+}
+
+impl DistillationStage {
+    #[must_use]
+    /// Error probability of an output state.
+    pub fn output_error(&self, input_error: f64) -> f64 {
+        self.error_coefficient * input_error.powi(2)
+    }
+
+    #[must_use]
+    /// Probability that no error is detected on the input states.
+    pub fn acceptance_probability(&self, input_error: f64) -> f64 {
+        (1.0 - input_error).powi(i32::try_from(self.inputs).unwrap_or(i32::MAX))
+    }
+}
+
+impl Default for DistillationStage {
+    /// 8 input states, output error 28p², as typical Toffoli distillation
+    /// protocols.
+    fn default() -> Self {
+        Self {
+            inputs: 8,
+            error_coefficient: 28.0,
+            logical_qubits: 6,
+            logical_cycles: 12.0,
+        }
+    }
+}
+
+/// Second stage of a cascaded factory, with the error of its inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Cascade {
+    pub(crate) stage: DistillationStage,
+    pub(crate) input_error: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use resource_estimator::estimates::Factory;
+
+    use super::DistillationStage;
+    use crate::{testing::close, ToffoliBuilder};
+
+    #[test]
+    fn stage_suppresses_the_input_error_quadratically() {
+        let stage = DistillationStage::default();
+        assert!(close(stage.output_error(1e-7), 28.0 * 1e-14));
+        assert!(close(stage.acceptance_probability(1e-3), 0.999_f64.powi(8)));
+        assert!(close(stage.acceptance_probability(0.0), 1.0));
+    }
+
+    #[test]
+    fn cascaded_factories_are_replaced() {
+        let builder = ToffoliBuilder::default();
+        let precomputed = builder.factories().count();
+        let stage = DistillationStage {
+            inputs: 15,
+            ..DistillationStage::default()
+        };
+        let cascaded = builder
+            .with_cascade(DistillationStage::default())
+            .with_cascade(stage);
+        assert_eq!(cascaded.factories().count(), 2 * precomputed);
+        // the second stage replaces the first one
+        assert_eq!(
+            cascaded.factories().filter(|f| f.is_cascaded()).count(),
+            precomputed
+        );
+        let (first, second): (Vec<_>, Vec<_>) =
+            cascaded.factories().partition(|f| !f.is_cascaded());
+        for (factory, cascaded) in first.iter().zip(second) {
+            assert!(cascaded.physical_qubits() > 15 * factory.physical_qubits());
+        }
+    }
+
+    #[test]
+    fn cascaded_factories_trade_qubits_and_time_for_lower_errors() {
+        let stage = DistillationStage::default();
+        let builder = ToffoliBuilder::default();
+        for factory in builder.factories() {
+            let cascaded = factory.cascaded(stage);
+            assert!(close(
+                cascaded.error_probability(),
+                stage.output_error(factory.error_probability())
+            ));
+            assert!(cascaded.acceptance_probability() < factory.acceptance_probability());
+            assert!(cascaded.physical_qubits() > stage.inputs * factory.physical_qubits());
+            assert!(cascaded.preparation_time() > factory.preparation_time());
+            assert!(cascaded.to_string().ends_with(" + 2nd stage"));
+        }
+    }
+}
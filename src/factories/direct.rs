@@ -0,0 +1,212 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Direct execution of the Toffoli gates, without magic state factories.
+//!
+//! Some proposals execute the Toffoli gates directly on the logical qubits,
+//! e.g. by pieceable fault tolerance, instead of consuming magic states. This
+//! is modeled as a "factory" without physical qubits, whose "magic state" is
+//! the gate itself:
+//! - the gate takes a fixed number of logical cycles of the computation code,
+//! - its error is the error of the 3 involved logical qubits during these
+//!   cycles, times a factor accounting for the non fault-tolerant pieces of the
+//!   gate.
+//!
+//! The duration of the Toffoli gates is already part of the logical depth
+//! ([`crate::LogicalCounts`]), so that the time spent in the gate only sets the
+//! number of gates executed in parallel. Use [`DirectToffoliBuilder`] instead of
+//! [`crate::ToffoliBuilder`] to compare both approaches on the same counts.
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use resource_estimator::estimates::{self, ErrorCorrection, FactoryBuilder};
+use std::{borrow::Cow, fmt::Display, rc::Rc};
+
+use crate::{code::CodeParameter, CatQubit, RepetitionCode};
+
+/// Toffoli gate executed directly on the logical qubits.
+#[derive(Clone)]
+pub struct DirectToffoli {
+    code_parameter: CodeParameter,
+    error_probability: f64,
+    duration: u64,
+}
+
+impl DirectToffoli {
+    /// Logical error probability of the gate.
+    #[must_use]
+    pub fn error_probability(&self) -> f64 {
+        self.error_probability
+    }
+}
+
+impl estimates::Factory for DirectToffoli {
+    type Parameter = CodeParameter;
+
+    /// The gate is executed in place, without additional qubits.
+    fn physical_qubits(&self) -> u64 {
+        0
+    }
+
+    fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    fn num_output_states(&self) -> u64 {
+        1
+    }
+
+    fn max_code_parameter(&self) -> Option<Cow<Self::Parameter>> {
+        Some(Cow::Borrowed(&self.code_parameter))
+    }
+}
+
+impl Display for DirectToffoli {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "direct ({})", self.code_parameter)
+    }
+}
+
+/// Executes the Toffoli gates directly instead of using factories.
+pub struct DirectToffoliBuilder {
+    cycles: f64,
+    error_factor: f64,
+}
+
+impl DirectToffoliBuilder {
+    /// Set the duration of a gate, in logical cycles (3 by default), which
+    /// must be positive.
+    pub fn with_cycles(mut self, cycles: f64) -> Result<Self, String> {
+        if !(cycles.is_finite() && cycles > 0.0) {
+            return Err(format!(
+                "direct Toffoli duration {cycles} must be a positive number of cycles"
+            ));
+        }
+        self.cycles = cycles;
+        Ok(self)
+    }
+
+    /// Set the factor applied to the error of the 3 logical qubits during the
+    /// gate (10 by default), which must be positive.
+    pub fn with_error_factor(mut self, error_factor: f64) -> Result<Self, String> {
+        if !(error_factor.is_finite() && error_factor > 0.0) {
+            return Err(format!(
+                "direct Toffoli error factor {error_factor} must be a positive number"
+            ));
+        }
+        self.error_factor = error_factor;
+        Ok(self)
+    }
+
+    /// Gate executed with the computation code parameters.
+    fn gate(
+        &self,
+        ftp: &RepetitionCode,
+        qubit: &CatQubit,
+        code_parameter: &CodeParameter,
+    ) -> Result<DirectToffoli, String> {
+        let logical_error_rate = ftp.logical_error_rate(qubit, code_parameter)?;
+        let cycle_time = ftp.logical_cycle_time(qubit, code_parameter)?;
+
+        Ok(DirectToffoli {
+            code_parameter: code_parameter.clone(),
+            error_probability: self.error_factor * 3.0 * self.cycles * logical_error_rate,
+            duration: f64::from_u64(cycle_time)
+                .and_then(|time| (time * self.cycles).round().to_u64())
+                .ok_or("cannot compute duration of the direct Toffoli")?,
+        })
+    }
+}
+
+impl Default for DirectToffoliBuilder {
+    fn default() -> Self {
+        Self {
+            cycles: 3.0,
+            error_factor: 10.0,
+        }
+    }
+}
+
+impl FactoryBuilder<RepetitionCode> for DirectToffoliBuilder {
+    type Factory = DirectToffoli;
+
+    /// Provide the direct gate on the computation code parameters, if it
+    /// reaches the target logical error rate.
+    fn find_factories(
+        &self,
+        ftp: &RepetitionCode,
+        qubit: &Rc<CatQubit>,
+        _magic_state_type: usize,
+        output_error_rate: f64,
+        max_code_parameter: &CodeParameter,
+    ) -> Option<Vec<Cow<Self::Factory>>> {
+        let gate = self.gate(ftp, qubit, max_code_parameter).ok()?;
+
+        Some(if gate.error_probability <= output_error_rate {
+            vec![Cow::Owned(gate)]
+        } else {
+            vec![]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use resource_estimator::estimates::{ErrorCorrection, Factory, FactoryBuilder};
+
+    use super::DirectToffoliBuilder;
+    use crate::{code::CodeParameter, CatQubit, RepetitionCode};
+
+    #[test]
+    fn gate_lasts_its_cycles_on_three_qubits() {
+        let (code, qubit) = (RepetitionCode::new(), CatQubit::new());
+        let parameter = CodeParameter::new(11, 10.0);
+        let builder = DirectToffoliBuilder::default()
+            .with_cycles(4.0)
+            .and_then(|builder| builder.with_error_factor(2.0))
+            .expect("valid gate");
+        let gate = builder
+            .gate(&code, &qubit, &parameter)
+            .expect("computable gate");
+
+        let rate = code
+            .logical_error_rate(&qubit, &parameter)
+            .expect("error rate");
+        let cycle_time = code
+            .logical_cycle_time(&qubit, &parameter)
+            .expect("cycle time");
+        assert!((gate.error_probability() - 2.0 * 3.0 * 4.0 * rate).abs() <= 1e-12 * rate);
+        assert_eq!(gate.duration(), 4 * cycle_time);
+        assert_eq!(gate.physical_qubits(), 0);
+    }
+
+    #[test]
+    fn gate_is_rejected_above_the_output_error_rate() {
+        let (code, qubit) = (RepetitionCode::new(), Rc::new(CatQubit::new()));
+        let parameter = CodeParameter::new(11, 10.0);
+        let builder = DirectToffoliBuilder::default();
+        let error = builder
+            .gate(&code, &qubit, &parameter)
+            .expect("computable gate")
+            .error_probability();
+
+        let found = |output_error_rate| {
+            builder
+                .find_factories(&code, &qubit, 0, output_error_rate, &parameter)
+                .map(|factories| factories.len())
+        };
+        assert_eq!(found(2.0 * error), Some(1));
+        assert_eq!(found(0.5 * error), Some(0));
+    }
+
+    #[test]
+    fn invalid_gates_are_rejected() {
+        for value in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(DirectToffoliBuilder::default().with_cycles(value).is_err());
+            assert!(DirectToffoliBuilder::default()
+                .with_error_factor(value)
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Heterogeneous factory fleets.
+//!
+//! The resource estimator picks a single factory type, every factory being
+//! good enough for the magic state error budget. Magic states of different
+//! errors can however be mixed, as long as the total error stays in the
+//! budget: e.g. a few low-error factories plus many cheap ones can reach the
+//! required magic state rate with fewer qubits.
+//!
+//! For a target rate and average error per state, the optimal fleet (without
+//! rounding of the copies) combines at most two factory types, so all the
+//! single types and pairs are tried.
+//!
+//! A fleet is a report only: the resource estimator can't place factories of
+//! several types, so the estimate keeps its single factory type, and its
+//! physical qubits and runtime. The fleet tells how many factory qubits mixing
+//! the types would need instead.
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::Factory;
+use std::fmt::Display;
+
+use super::{ToffoliBuilder, ToffoliFactory};
+use crate::CatQubit;
+
+/// Copies of a factory in a fleet, and the fraction of the magic states they
+/// produce.
+#[derive(Clone)]
+pub struct FleetMember {
+    /// Factory.
+    pub factory: ToffoliFactory,
+    /// Number of copies running in parallel.
+    pub copies: u64,
+    /// Fraction of the consumed magic states produced by these copies.
+    pub fraction: f64,
+}
+
+/// Set of factories of possibly different types.
+#[derive(Clone)]
+pub struct FactoryFleet {
+    members: Vec<FleetMember>,
+}
+
+impl FactoryFleet {
+    #[must_use]
+    /// Factories of the fleet.
+    pub fn members(&self) -> &[FleetMember] {
+        &self.members
+    }
+
+    #[must_use]
+    /// Number of physical qubits of all the factories.
+    pub fn physical_qubits(&self) -> u64 {
+        self.members
+            .iter()
+            .map(|member| member.copies * member.factory.physical_qubits())
+            .sum()
+    }
+
+    #[must_use]
+    /// Average error probability of the consumed magic states.
+    pub fn error_per_state(&self) -> f64 {
+        self.members
+            .iter()
+            .map(|member| member.fraction * member.factory.error_probability())
+            .sum()
+    }
+}
+
+impl Display for FactoryFleet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, member) in self.members.iter().enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{} × {}", member.copies, member.factory)?;
+        }
+        Ok(())
+    }
+}
+
+impl ToffoliBuilder {
+    /// Fleet with the fewest physical qubits producing `rate` magic states per
+    /// ns, with an average error per state below `max_error_per_state`.
+    ///
+    /// Returns [`None`] if no factory reaches the error.
+    #[must_use]
+    pub fn optimal_fleet(
+        &self,
+        qubit: &CatQubit,
+        rate: f64,
+        max_error_per_state: f64,
+    ) -> Option<FactoryFleet> {
+        let candidates: Vec<_> = self
+            .factories()
+            .filter(|factory| qubit.supports_alpha_sq(factory.alpha_sq))
+            .map(|factory| factory.for_qubit(qubit).into_owned())
+            .collect();
+
+        let member = |factory: &ToffoliFactory, fraction: f64| {
+            let throughput = fraction * rate * factory.preparation_time().as_ns_f64();
+            Some(FleetMember {
+                factory: factory.clone(),
+                copies: throughput.ceil().to_u64()?,
+                fraction,
+            })
+        };
+
+        let mut fleets = vec![];
+        for low in candidates
+            .iter()
+            .filter(|factory| factory.error_probability <= max_error_per_state)
+        {
+            fleets.extend(member(low, 1.0).map(|m| FactoryFleet { members: vec![m] }));
+
+            for high in candidates
+                .iter()
+                .filter(|factory| factory.error_probability > max_error_per_state)
+            {
+                // smallest fraction of low-error states keeping the average error
+                let fraction = (high.error_probability - max_error_per_state)
+                    / (high.error_probability - low.error_probability);
+                if let (Some(low), Some(high)) =
+                    (member(low, fraction), member(high, 1.0 - fraction))
+                {
+                    fleets.push(FactoryFleet {
+                        members: vec![low, high],
+                    });
+                }
+            }
+        }
+
+        fleets.into_iter().min_by_key(FactoryFleet::physical_qubits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CatQubit, ToffoliBuilder};
+
+    /// One Toffoli state per µs.
+    const RATE: f64 = 1e-3;
+
+    #[test]
+    fn cheapest_factory_is_alone_when_it_reaches_the_error() {
+        let fleet = ToffoliBuilder::default()
+            .optimal_fleet(&CatQubit::new(), RATE, 1e-2)
+            .expect("every factory reaches the error");
+        // 65.13 µs per state for the 25 qubits of the d = 3, |α|² = 3.75 one
+        assert_eq!(fleet.members().len(), 1);
+        assert_eq!(fleet.members()[0].copies, 66);
+        assert_eq!(fleet.physical_qubits(), 66 * 25);
+    }
+
+    #[test]
+    fn two_factory_types_are_mixed_within_the_error() {
+        let target = 1e-5;
+        let fleet = ToffoliBuilder::default()
+            .optimal_fleet(&CatQubit::new(), RATE, target)
+            .expect("a factory reaches the error");
+        assert_eq!(fleet.members().len(), 2);
+        assert!(fleet.error_per_state() <= target * (1.0 + 1e-12));
+        let fractions: f64 = fleet.members().iter().map(|member| member.fraction).sum();
+        assert!((fractions - 1.0).abs() < 1e-12);
+        // the cheapest single type below 1e-5 (d = 5, |α|² = 7.15) needs 126
+        // copies of 45 qubits
+        assert!(fleet.physical_qubits() < 126 * 45);
+    }
+
+    #[test]
+    fn no_fleet_without_a_factory_reaching_the_error() {
+        assert!(ToffoliBuilder::default()
+            .optimal_fleet(&CatQubit::new(), RATE, 1e-20)
+            .is_none());
+    }
+}
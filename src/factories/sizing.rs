@@ -0,0 +1,232 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Toffoli states produced in excess.
+//!
+//! The factories run during the whole computation, whose runtime is set by the
+//! logical depth: the copies are rounded up, and a fast factory picked for
+//! its error may produce many more states than consumed. The
+//! [`Overproduction`] of an estimate compares the states its factories can
+//! produce with the ones consumed.
+//!
+//! When the computation is the bottleneck, slower and smaller factories, or
+//! fewer copies, are enough: [`ToffoliBuilder::right_sized`] gives the factory
+//! type and copies producing the consumed states within the runtime with the
+//! fewest physical qubits. This is a report only: the estimate keeps its
+//! factories, the right-sized ones telling how many physical qubits they
+//! would save.
+//!
+//! The statistics of the production of each estimate (duty cycle of the
+//! factories, states produced per copy, [`MagicStateRate`]) are given by
+//! [`AliceAndBobEstimates`](crate::AliceAndBobEstimates), e.g.
+//! [`AliceAndBobEstimates::factory_duty_cycle`](crate::AliceAndBobEstimates::factory_duty_cycle).
+
+use std::fmt::Display;
+
+use resource_estimator::estimates::Factory;
+use serde::{Deserialize, Serialize};
+
+use super::{ToffoliBuilder, ToffoliFactory};
+use crate::{units::TimeSpan, CatQubit};
+
+/// Toffoli states produced by the factories of an estimate, compared with the
+/// ones consumed by the computation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Overproduction {
+    /// States the factories produce during the runtime (copies × runs).
+    pub produced: u64,
+    /// States consumed by the computation.
+    pub consumed: u64,
+}
+
+impl Overproduction {
+    /// Ratio of the produced to the consumed states, at least 1 when the
+    /// factories keep up with the computation.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> f64 {
+        self.produced as f64 / self.consumed as f64
+    }
+
+    /// States produced but not consumed.
+    #[must_use]
+    pub fn wasted(&self) -> u64 {
+        self.produced.saturating_sub(self.consumed)
+    }
+}
+
+impl Display for Overproduction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "×{:.2} ({} states wasted of {} produced)",
+            self.ratio(),
+            self.wasted(),
+            self.produced
+        )
+    }
+}
+
+/// Toffoli states per second required by the computation, compared with the
+/// ones the factories provide.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MagicStateRate {
+    /// States consumed per second of runtime, on average.
+    pub required: f64,
+    /// States produced per second by all the copies of the factory.
+    pub provided: f64,
+}
+
+impl MagicStateRate {
+    /// Ratio of the provided to the required rate, at least 1 when the
+    /// factories keep up with the computation on average.
+    #[must_use]
+    pub fn margin(&self) -> f64 {
+        self.provided / self.required
+    }
+}
+
+impl Display for MagicStateRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} states/s required, {:.1} provided (×{:.2})",
+            self.required,
+            self.provided,
+            self.margin()
+        )
+    }
+}
+
+/// Factories producing the consumed states within the runtime with the
+/// fewest physical qubits.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RightSizing {
+    /// Factory type.
+    pub factory: ToffoliFactory,
+    /// Number of copies running in parallel.
+    pub copies: u64,
+    /// Physical qubits of all the copies.
+    pub physical_qubits: u64,
+    /// States produced compared with the consumed ones.
+    pub overproduction: Overproduction,
+}
+
+impl Display for RightSizing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} × {} ({} physical qubits, overproduction {:.2})",
+            self.copies,
+            self.factory,
+            self.physical_qubits,
+            self.overproduction.ratio()
+        )
+    }
+}
+
+impl ToffoliBuilder {
+    /// Factory type and copies with the fewest physical qubits producing
+    /// `states` Toffoli states within `runtime`, with an error per state below
+    /// `max_error_per_state`.
+    ///
+    /// Returns [`None`] if no factory reaches the error, or completes a
+    /// preparation within the runtime (or if the copies overflow).
+    #[must_use]
+    pub fn right_sized(
+        &self,
+        qubit: &CatQubit,
+        states: u64,
+        runtime: TimeSpan,
+        max_error_per_state: f64,
+    ) -> Option<RightSizing> {
+        if states == 0 {
+            return None;
+        }
+        self.factories()
+            .filter(|factory| qubit.supports_alpha_sq(factory.alpha_sq))
+            .map(|factory| factory.for_qubit(qubit).into_owned())
+            .filter(|factory| factory.error_probability <= max_error_per_state)
+            .filter_map(|factory| {
+                // states produced by each copy within the runtime
+                let per_copy = runtime
+                    .as_ns()
+                    .checked_div(factory.preparation_time().as_ns())?
+                    .checked_mul(factory.num_output_states())
+                    .filter(|&per_copy| per_copy > 0)?;
+                let copies = states.div_ceil(per_copy);
+                Some(RightSizing {
+                    physical_qubits: copies.checked_mul(factory.physical_qubits())?,
+                    overproduction: Overproduction {
+                        produced: copies.checked_mul(per_copy)?,
+                        consumed: states,
+                    },
+                    factory,
+                    copies,
+                })
+            })
+            .min_by_key(|sizing| (sizing.physical_qubits, sizing.copies))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resource_estimator::estimates::Factory;
+
+    use super::Overproduction;
+    use crate::{units::TimeSpan, CatQubit, ToffoliBuilder};
+
+    #[test]
+    fn overproduction_compares_the_states() {
+        let overproduction = Overproduction {
+            produced: 150,
+            consumed: 100,
+        };
+        assert!((overproduction.ratio() - 1.5).abs() < 1e-12);
+        assert_eq!(overproduction.wasted(), 50);
+        let short = Overproduction {
+            produced: 50,
+            consumed: 100,
+        };
+        assert_eq!(short.wasted(), 0);
+    }
+
+    #[test]
+    fn right_sized_factories_produce_the_states_in_time() {
+        let (builder, qubit) = (ToffoliBuilder::default(), CatQubit::new());
+        let runtime = TimeSpan::from_ns(1_000_000_000);
+        let sizing = builder
+            .right_sized(&qubit, 1_000_000, runtime, 1.0)
+            .expect("a factory keeps up");
+        let per_copy = runtime.as_ns() / sizing.factory.preparation_time().as_ns()
+            * sizing.factory.num_output_states();
+        assert_eq!(sizing.copies, 1_000_000_u64.div_ceil(per_copy));
+        assert_eq!(
+            sizing.physical_qubits,
+            sizing.copies * sizing.factory.physical_qubits()
+        );
+        assert_eq!(sizing.overproduction.produced, sizing.copies * per_copy);
+        assert!(sizing.overproduction.produced >= 1_000_000);
+        // no other factory is smaller
+        for factory in builder.factories() {
+            let factory = factory.for_qubit(&qubit);
+            let per_copy =
+                runtime.as_ns() / factory.preparation_time().as_ns() * factory.num_output_states();
+            if per_copy > 0 && qubit.supports_alpha_sq(factory.alpha_sq) {
+                let copies = 1_000_000_u64.div_ceil(per_copy);
+                assert!(copies * factory.physical_qubits() >= sizing.physical_qubits);
+            }
+        }
+    }
+
+    #[test]
+    fn right_sizing_needs_states_a_runtime_and_a_reachable_error() {
+        let (builder, qubit) = (ToffoliBuilder::default(), CatQubit::new());
+        let runtime = TimeSpan::from_ns(1_000_000_000);
+        assert!(builder.right_sized(&qubit, 0, runtime, 1.0).is_none());
+        assert!(builder
+            .right_sized(&qubit, 100, TimeSpan::from_ns(1), 1.0)
+            .is_none());
+        assert!(builder.right_sized(&qubit, 100, runtime, 0.0).is_none());
+    }
+}
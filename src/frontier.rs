@@ -0,0 +1,241 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Frontier in three dimensions: physical qubits, runtime and total error.
+//!
+//! [`PhysicalResourceEstimation::build_frontier`] trades the physical qubits
+//! for the runtime at a fixed error budget. When the budget itself is
+//! negotiable, [`build_error_frontier`] also builds the frontiers of budgets
+//! scaled down by [`BUDGET_SCALES`], and [`pareto_front`] keeps the estimates
+//! which no other one beats on the physical qubits, the runtime and the
+//! achieved total error at once.
+//!
+//! [`FrontierPoints`] streams the frontier instead of building it at once:
+//! each point is the estimate with the fewest physical qubits among the ones
+//! faster than the previous point, so that consumers can process the points
+//! as they are found (e.g. update a plot) and stop early (e.g. once a runtime
+//! is reached). [`build_frontier_with_checkpoint`] records each point in a
+//! [`Checkpoint`], so that an interrupted build resumes after its last point.
+
+use std::rc::Rc;
+
+use resource_estimator::estimates::{
+    Error, ErrorBudget, PhysicalResourceEstimation, PhysicalResourceEstimationResult,
+};
+
+use crate::{
+    checkpoint::Checkpoint, factories::ToffoliFactory, schema::EstimateRecord,
+    AliceAndBobEstimates, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+
+/// Fractions of the error budget whose frontiers are built by
+/// [`build_error_frontier`].
+pub const BUDGET_SCALES: [f64; 5] = [1.0, 0.3, 0.1, 0.03, 0.01];
+
+/// Estimation of the logical counts with the repetition code and Toffoli
+/// factories.
+pub type Estimation = PhysicalResourceEstimation<RepetitionCode, ToffoliBuilder, LogicalCounts>;
+
+/// Result of an [`Estimation`].
+pub type EstimationResult =
+    PhysicalResourceEstimationResult<RepetitionCode, ToffoliFactory, LogicalCounts>;
+
+/// Frontiers of `estimation` with its error budget scaled by each of
+/// [`BUDGET_SCALES`].
+///
+/// The scaled budgets which can't be met (e.g. magic state errors below the
+/// factories) are skipped; an error is returned only if the full budget can't
+/// be met.
+pub fn build_error_frontier(estimation: &Estimation) -> Result<Vec<EstimationResult>, Error> {
+    let budget = estimation.error_budget();
+    let mut results = estimation.build_frontier()?;
+    for scale in &BUDGET_SCALES[1..] {
+        let scaled = PhysicalResourceEstimation::new(
+            estimation.ftp().clone(),
+            estimation.qubit().clone(),
+            estimation.factory_builder().clone(),
+            Rc::new(estimation.layout_overhead().clone()),
+            ErrorBudget::new(
+                budget.logical() * scale,
+                budget.magic_states() * scale,
+                budget.rotations() * scale,
+            ),
+        );
+        match scaled.build_frontier() {
+            Ok(frontier) => results.extend(frontier),
+            Err(err) => {
+                tracing::debug!(scale, %err, "scaled error budget can't be met");
+                break;
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Points of the frontier of an [`Estimation`], from the fewest physical
+/// qubits to the shortest runtime, estimated one at a time.
+///
+/// The first item is an error if the estimation has no solution; the
+/// iteration ends when no faster estimate is found.
+pub struct FrontierPoints {
+    estimation: Estimation,
+    found: usize,
+    done: bool,
+}
+
+impl FrontierPoints {
+    /// Frontier of `estimation`, keeping its constraints (e.g. its maximum
+    /// number of physical qubits).
+    #[must_use]
+    pub fn new(estimation: Estimation) -> Self {
+        Self {
+            estimation,
+            found: 0,
+            done: false,
+        }
+    }
+
+    /// Frontier of `estimation` resumed after the `found` points of an
+    /// interrupted build, the last one with the runtime `last_runtime` (in
+    /// ns): the next point is strictly faster.
+    #[must_use]
+    pub fn resume_after(mut estimation: Estimation, found: usize, last_runtime: u64) -> Self {
+        let done = match last_runtime.checked_sub(1) {
+            Some(max_duration) if max_duration > 0 => {
+                estimation.set_max_duration(max_duration);
+                false
+            }
+            _ => true,
+        };
+        Self {
+            estimation,
+            found,
+            done,
+        }
+    }
+}
+
+impl Iterator for FrontierPoints {
+    type Item = Result<EstimationResult, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.estimation.estimate() {
+            Ok(result) => {
+                self.found += 1;
+                // the next point must be strictly faster
+                match result.runtime().checked_sub(1) {
+                    Some(max_duration) if max_duration > 0 => {
+                        self.estimation.set_max_duration(max_duration);
+                    }
+                    _ => self.done = true,
+                }
+                tracing::debug!(
+                    point = self.found,
+                    physical_qubits = result.physical_qubits(),
+                    runtime = result.runtime(),
+                    "frontier point"
+                );
+                Some(Ok(result))
+            }
+            Err(err) => {
+                self.done = true;
+                // only the absence of any solution is an error
+                (self.found == 0).then_some(Err(err))
+            }
+        }
+    }
+}
+
+/// Frontier of `estimation`, resumed after the points completed in
+/// `checkpoint` by an interrupted build and recording each new point as soon
+/// as it is found.
+pub fn build_frontier_with_checkpoint(
+    estimation: Estimation,
+    checkpoint: &mut Checkpoint<EstimateRecord>,
+) -> Result<Vec<EstimateRecord>, String> {
+    let mut records = checkpoint.take_completed();
+    let code = estimation.ftp().clone();
+    let points = match records.last() {
+        Some(last) => FrontierPoints::resume_after(estimation, records.len(), last.runtime_ns),
+        None => FrontierPoints::new(estimation),
+    };
+    for result in points {
+        let estimates = AliceAndBobEstimates::new(result.map_err(|err| err.to_string())?, &code);
+        let record = EstimateRecord::from(&estimates);
+        checkpoint.record(&record)?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Physical qubits, runtime and total error of an estimate, to be minimized.
+type Objectives = (u64, u64, f64);
+
+/// Whether `a` is at least as good as `b` on the physical qubits, the runtime
+/// and the total error, and better on one of them.
+fn dominates(a: Objectives, b: Objectives) -> bool {
+    let ((qubits, runtime, error), (other_qubits, other_runtime, other_error)) = (a, b);
+    qubits <= other_qubits
+        && runtime <= other_runtime
+        && error <= other_error
+        && (qubits < other_qubits || runtime < other_runtime || error < other_error)
+}
+
+/// Whether each of `points` is dominated by another one.
+fn dominated(points: &[Objectives]) -> Vec<bool> {
+    points
+        .iter()
+        .map(|&point| points.iter().any(|&other| dominates(other, point)))
+        .collect()
+}
+
+/// Estimates of `estimates` not dominated by another one on the physical
+/// qubits, the runtime and the total error, sorted by physical qubits.
+#[must_use]
+pub fn pareto_front(estimates: Vec<AliceAndBobEstimates>) -> Vec<AliceAndBobEstimates> {
+    let points: Vec<Objectives> = estimates
+        .iter()
+        .map(|estimate| {
+            (
+                estimate.physical_qubits(),
+                estimate.runtime(),
+                estimate.total_error(),
+            )
+        })
+        .collect();
+    let mut front: Vec<_> = estimates
+        .into_iter()
+        .zip(dominated(&points))
+        .filter_map(|(estimate, dominated)| (!dominated).then_some(estimate))
+        .collect();
+    front.sort_by_key(AliceAndBobEstimates::physical_qubits);
+    front
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dominated, dominates};
+
+    #[test]
+    fn domination_needs_a_strict_improvement() {
+        assert!(dominates((10, 100, 1e-3), (10, 100, 2e-3)));
+        assert!(dominates((9, 100, 1e-3), (10, 200, 1e-3)));
+        assert!(!dominates((10, 100, 1e-3), (10, 100, 1e-3)));
+        assert!(!dominates((9, 200, 1e-3), (10, 100, 1e-3)));
+    }
+
+    #[test]
+    fn front_keeps_the_trade_offs() {
+        let points = [
+            (10, 300, 1e-3),
+            (20, 100, 1e-3),
+            (20, 200, 1e-3),
+            (30, 300, 1e-4),
+            (30, 300, 1e-4),
+        ];
+        assert_eq!(dominated(&points), [false, false, true, false, false]);
+    }
+}
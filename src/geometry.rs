@@ -0,0 +1,144 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Physical dimensions of the chip.
+//!
+//! Given the pitch of the physical qubits (the side of the square taken by a
+//! cat qubit with its resonator and wiring), a [`ChipGeometry`] estimates the
+//! area and the linear dimensions of the processor. The repetition code
+//! patches are lines of 2d − 1 cat qubits laid side by side
+//! ([arXiv:2302.06639](https://arxiv.org/abs/2302.06639), p. 27), so the chip
+//! is a strip 2d − 1 qubits high; the side of a square chip of the same area is
+//! given as well.
+
+use std::str::FromStr;
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::{report::ReportSection, AliceAndBobEstimates};
+
+/// Pitch of the physical qubits, to compute the dimensions of the chip.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChipGeometry {
+    pitch_um: f64,
+}
+
+impl ChipGeometry {
+    /// Physical qubits spaced by `pitch_um` micrometers (cat qubit and
+    /// resonator).
+    pub fn new(pitch_um: f64) -> Result<Self, String> {
+        if !(pitch_um.is_finite() && pitch_um > 0.0) {
+            return Err(format!("qubit pitch {pitch_um} µm must be positive"));
+        }
+        Ok(Self { pitch_um })
+    }
+
+    /// Pitch of the physical qubits, in micrometers.
+    #[must_use]
+    pub fn pitch_um(&self) -> f64 {
+        self.pitch_um
+    }
+
+    /// Dimensions of the chip of `estimates`.
+    #[must_use]
+    pub fn dimensions(&self, estimates: &AliceAndBobEstimates) -> ChipDimensions {
+        self.strip(
+            estimates.physical_qubits(),
+            estimates.logical_patch().code_parameter().distance(),
+        )
+    }
+
+    /// Dimensions of a chip of `qubits` physical qubits in patches of
+    /// distance `distance`.
+    fn strip(self, qubits: u64, distance: u64) -> ChipDimensions {
+        let pitch_mm = self.pitch_um / 1000.0;
+        let qubits = qubits.to_f64().unwrap_or(f64::MAX);
+        let rows = (2 * distance - 1).to_f64().unwrap_or(1.0);
+        ChipDimensions {
+            pitch_um: self.pitch_um,
+            area_mm2: qubits * pitch_mm * pitch_mm,
+            height_mm: rows * pitch_mm,
+            width_mm: (qubits / rows).ceil() * pitch_mm,
+        }
+    }
+}
+
+impl FromStr for ChipGeometry {
+    type Err = String;
+
+    /// Parse the pitch in micrometers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pitch_um = s
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{s}' is not a qubit pitch in µm (e.g. 500)"))?;
+        Self::new(pitch_um)
+    }
+}
+
+/// Physical dimensions of a chip.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChipDimensions {
+    /// Pitch of the physical qubits, in micrometers.
+    pub pitch_um: f64,
+    /// Area of the chip, in mm².
+    pub area_mm2: f64,
+    /// Height of the strip of patches, in mm.
+    pub height_mm: f64,
+    /// Width of the strip of patches, in mm.
+    pub width_mm: f64,
+}
+
+impl ChipDimensions {
+    /// Side of a square chip of the same area, in mm.
+    #[must_use]
+    pub fn square_side_mm(&self) -> f64 {
+        self.area_mm2.sqrt()
+    }
+
+    #[must_use]
+    /// Dimensions as (label, formatted value) pairs.
+    pub fn report_section(&self) -> ReportSection {
+        vec![
+            ("qubit pitch", format!("{} µm", self.pitch_um)),
+            ("chip area", format!("{:.1} mm²", self.area_mm2)),
+            (
+                "chip strip",
+                format!("{:.1} mm × {:.1} mm", self.width_mm, self.height_mm),
+            ),
+            (
+                "square chip side",
+                format!("{:.1} mm", self.square_side_mm()),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChipGeometry;
+
+    #[test]
+    fn chips_are_strips_of_patches() {
+        // 1000 qubits in rows of 2·5 − 1 = 9 qubits spaced by 0.5 mm.
+        let dimensions = ChipGeometry::new(500.0)
+            .expect("positive pitch")
+            .strip(1000, 5);
+        assert!((dimensions.area_mm2 - 250.0).abs() < 1e-9);
+        assert!((dimensions.height_mm - 4.5).abs() < 1e-12);
+        assert!((dimensions.width_mm - 56.0).abs() < 1e-12);
+        assert!((dimensions.square_side_mm() - 250.0_f64.sqrt()).abs() < 1e-12);
+        assert_eq!(dimensions.report_section().len(), 4);
+    }
+
+    #[test]
+    fn pitches_are_positive() {
+        assert!("500"
+            .parse::<ChipGeometry>()
+            .is_ok_and(|geometry| (geometry.pitch_um() - 500.0).abs() < f64::EPSILON));
+        for pitch in ["0", "-1", "inf", "NaN", "wide"] {
+            assert!(pitch.parse::<ChipGeometry>().is_err(), "{pitch}");
+        }
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright (c) Alice & Bob
+// Licensed under the Apache License.
+
+//! Interactive mode of the command line interface.
+//!
+//! The logical counts are computed once (counting a large Q# program can take
+//! minutes), then the assumptions and constraints can be changed and the
+//! estimate recomputed from a prompt.
+
+use std::{
+    io::{self, BufRead, Write},
+    rc::Rc,
+    str::FromStr,
+};
+
+use qsharp_alice_bob_resource_estimator::{
+    budget, counter::gate_set::GateSet, provenance::Provenance, AliceAndBobEstimates, CatQubit,
+    LogicalCounts, OutputFormat, RepetitionCode, TimeUnit, ToffoliBuilder,
+};
+use resource_estimator::estimates::{ErrorBudget, PhysicalResourceEstimation};
+
+const HELP: &str = "\
+commands:
+  counts QUBITS CX CCX       set the logical counts
+  load FILE [GATE_SET]       count the logical resources of a Q# file
+  budget TOTAL [MAGIC_FRAC]  split a total error budget (equally by default)
+  budget TOPO MAGIC ROT      set the detailed error budget
+  k1k2 VALUE                 set κ₁/κ₂
+  thermal N_TH               set the average number of thermal photons
+  max-alpha-sq VALUE|none    cap the average number of photons |α|²
+  max-qubits N|none          constrain the number of physical qubits
+  max-duration NS|none       constrain the runtime (in ns)
+  units UNIT                 unit of the runtime
+  output FORMAT              output format
+  show                       show the current settings
+  estimate                   estimate the resources
+  frontier                   show the frontier of good estimates
+  quit                       leave";
+
+/// State of the interactive session.
+pub struct Session {
+    qubit: CatQubit,
+    code: RepetitionCode,
+    builder: ToffoliBuilder,
+    budget: ErrorBudget,
+    counts: Option<LogicalCounts>,
+    units: TimeUnit,
+    output: OutputFormat,
+    max_duration: Option<u64>,
+    max_physical_qubits: Option<u64>,
+}
+
+impl Session {
+    /// Session starting from the settings of the command line.
+    pub fn new(
+        qubit: CatQubit,
+        code: RepetitionCode,
+        builder: ToffoliBuilder,
+        budget: ErrorBudget,
+        counts: Option<LogicalCounts>,
+        units: TimeUnit,
+        output: OutputFormat,
+    ) -> Self {
+        Self {
+            qubit,
+            code,
+            builder,
+            budget,
+            counts,
+            units,
+            output,
+            max_duration: None,
+            max_physical_qubits: None,
+        }
+    }
+
+    /// Read commands from the standard input until `quit` or end of input.
+    pub fn run(mut self) -> Result<(), anyhow::Error> {
+        println!("Type `help` for the list of commands.");
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+        loop {
+            print!("> ");
+            io::stdout().flush()?;
+            let Some(line) = lines.next().transpose()? else {
+                return Ok(());
+            };
+            let words: Vec<_> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => {}
+                ["quit" | "exit"] => return Ok(()),
+                [command, args @ ..] => {
+                    if let Err(err) = self.execute(command, args) {
+                        println!("error: {err}");
+                    }
+                }
+            }
+        }
+    }
+
+    fn execute(&mut self, command: &str, args: &[&str]) -> Result<(), String> {
+        match (command, args) {
+            ("help", []) => println!("{HELP}"),
+            ("counts", [qubits, cx, ccx]) => {
+                self.counts = Some(LogicalCounts::new(parse(qubits)?, parse(cx)?, parse(ccx)?));
+            }
+            ("load", [filename]) => {
+                self.counts = Some(LogicalCounts::from_qsharp(filename)?);
+            }
+            ("load", [filename, gate_set]) => {
+                self.counts = Some(LogicalCounts::from_qsharp_with_gate_set(
+                    filename,
+                    parse::<GateSet>(gate_set)?,
+                )?);
+            }
+            ("budget", [total]) => {
+                self.budget = budget::split_error_budget(budget::parse_probability(total)?)?;
+            }
+            ("budget", [total, magic_fraction]) => {
+                self.budget = budget::fractional_error_budget(
+                    budget::parse_probability(total)?,
+                    parse(magic_fraction)?,
+                    0.0,
+                )?;
+            }
+            ("budget", [logical, magic, rotations]) => {
+                self.budget = budget::error_budget(
+                    budget::parse_probability(logical)?,
+                    budget::parse_probability(magic)?,
+                    budget::parse_probability(rotations)?,
+                )?;
+            }
+            ("k1k2", [value]) => {
+                let k1_k2 = crate::parse_positive(value)?;
+                self.qubit = std::mem::take(&mut self.qubit).with_k1_k2(k1_k2);
+            }
+            ("thermal", [value]) => {
+                let n_th = crate::parse_non_negative(value)?;
+                self.qubit = std::mem::take(&mut self.qubit).with_thermal_photons(n_th);
+            }
+            ("max-alpha-sq", [value]) => {
+                let qubit = self.qubit.clone();
+                self.qubit = match parse_optional(value)? {
+                    Some(max_alpha_sq) => qubit.with_max_alpha_sq(max_alpha_sq)?,
+                    None => qubit.without_max_alpha_sq(),
+                };
+            }
+            ("max-qubits", [value]) => self.max_physical_qubits = parse_optional(value)?,
+            ("max-duration", [value]) => self.max_duration = parse_optional(value)?,
+            ("units", [value]) => self.units = parse(value)?,
+            ("output", [value]) => self.output = parse(value)?,
+            ("show", []) => self.show(),
+            ("estimate", []) => self.estimate(false)?,
+            ("frontier", []) => self.estimate(true)?,
+            _ => return Err(format!("invalid command `{command}`, type `help`")),
+        }
+        Ok(())
+    }
+
+    fn show(&self) {
+        println!("κ₁/κ₂:           {:e}", self.qubit.k1_k2());
+        println!("thermal photons: {}", self.qubit.thermal_photons());
+        println!("max |α|²:        {:?}", self.qubit.max_alpha_sq());
+        println!(
+            "error budget:    {} / {} / {}",
+            self.budget.logical(),
+            self.budget.magic_states(),
+            self.budget.rotations()
+        );
+        println!("max qubits:      {:?}", self.max_physical_qubits);
+        println!("max duration:    {:?}", self.max_duration);
+        match &self.counts {
+            Some(counts) => println!(
+                "counts:          {} qubits, {} CX, {} CCX",
+                counts.qubit_count(),
+                counts.cx_count(),
+                counts.ccx_count()
+            ),
+            None => println!("counts:          none"),
+        }
+    }
+
+    fn estimate(&self, frontier: bool) -> Result<(), String> {
+        print!("{}", self.output.render(&self.estimates(frontier)?));
+        Ok(())
+    }
+
+    /// Estimate of the current settings, or its frontier if `frontier`.
+    fn estimates(&self, frontier: bool) -> Result<Vec<AliceAndBobEstimates>, String> {
+        let counts = self
+            .counts
+            .clone()
+            .ok_or("no logical counts, use `counts` or `load`")?;
+        let provenance = Provenance::new(&self.qubit, &self.code, &self.builder, &counts);
+        let budget = budget::budget_for_counts(&self.budget, &counts)?;
+        self.builder.check_budget(&counts, &budget)?;
+
+        let mut estimation = PhysicalResourceEstimation::new(
+            self.code.clone(),
+            Rc::new(self.qubit.clone()),
+            self.builder.clone(),
+            Rc::new(counts),
+            budget,
+        );
+        if let Some(max_duration) = self.max_duration {
+            estimation.set_max_duration(max_duration);
+        }
+        if let Some(max_physical_qubits) = self.max_physical_qubits {
+            estimation.set_max_physical_qubits(max_physical_qubits);
+        }
+
+        let wrap = |r| {
+            AliceAndBobEstimates::new(r, &self.code)
+                .with_time_unit(self.units)
+                .with_provenance(provenance.clone())
+        };
+        if frontier {
+            Ok(estimation
+                .build_frontier()
+                .map_err(|err| err.to_string())?
+                .into_iter()
+                .map(wrap)
+                .collect())
+        } else {
+            Ok(vec![wrap(
+                estimation.estimate().map_err(|err| err.to_string())?,
+            )])
+        }
+    }
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}`"))
+}
+
+fn parse_optional<T: FromStr>(value: &str) -> Result<Option<T>, String> {
+    if value == "none" {
+        Ok(None)
+    } else {
+        parse(value).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qsharp_alice_bob_resource_estimator::{
+        budget, CatQubit, LogicalCounts, OutputFormat, RepetitionCode, TimeUnit, ToffoliBuilder,
+    };
+
+    use super::Session;
+
+    /// Session with the error correction `code`.
+    fn session(code: RepetitionCode) -> Session {
+        Session::new(
+            CatQubit::new(),
+            code,
+            ToffoliBuilder::default(),
+            budget::split_error_budget(1e-3).expect("valid budget"),
+            Some(LogicalCounts::new(10, 1000, 100)),
+            TimeUnit::default(),
+            OutputFormat::default(),
+        )
+    }
+
+    /// Physical qubits of the estimate of a session with the error correction
+    /// `code`.
+    fn physical_qubits(code: RepetitionCode) -> u64 {
+        let estimates = session(code)
+            .estimates(false)
+            .expect("the counts can be estimated");
+        estimates[0].physical_qubits()
+    }
+
+    #[test]
+    fn session_estimates_with_the_code_of_the_command_line() {
+        // a phase-flip fit 10 times worse needs larger codes
+        let worse = RepetitionCode::new()
+            .with_fit(0.56, 0.86, 0.013)
+            .expect("valid fit");
+        assert!(physical_qubits(worse) > physical_qubits(RepetitionCode::new()));
+    }
+
+    #[test]
+    fn invalid_qubit_settings_are_rejected() {
+        let mut session = session(RepetitionCode::new());
+        for (command, value) in [("k1k2", "-1e-5"), ("k1k2", "0"), ("thermal", "-0.5")] {
+            assert!(
+                session.execute(command, &[value]).is_err(),
+                "{command} {value}"
+            );
+        }
+        assert!(session.execute("thermal", &["0.5"]).is_ok());
+        assert!((session.qubit.k1_k2() - CatQubit::new().k1_k2()).abs() < f64::EPSILON);
+        assert!((session.qubit.thermal_photons() - 0.5).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Policies for the "vertical" routing qubits of the layout.
+//!
+//! The logical qubits (with their "horizontal" routing qubits, see
+//! [`crate::counter::routing`]) and the factories are laid out in a row. In
+//! [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 27), "vertical"
+//! routing qubits are added along the row to ensure all-to-all connectivity,
+//! see [`PaperLayout`]. The factories can be placed otherwise with a
+//! [`FactoryPlacement`] (two rows flanking the compute row, or interleaved
+//! with the logical qubits), changing the length of the routing. A
+//! [`CompactLayout`] drops them, the gates between
+//! distant qubits being routed through the horizontal routing qubits (their
+//! slowdown can be accounted for with
+//! [`GateSet::with_routing_factor`](crate::counter::gate_set::GateSet::with_routing_factor)).
+//!
+//! Any other policy can be plugged into the estimates with
+//! [`AliceAndBobEstimates::with_layout`](crate::AliceAndBobEstimates::with_layout),
+//! as a [`LayoutPolicy`] or as a closure.
+
+use std::{fmt::Display, str::FromStr};
+
+/// Policy deciding the number of "vertical" routing qubits, added to the
+/// physical qubits of the estimates.
+pub trait LayoutPolicy {
+    /// Physical qubits of the vertical routing, for `logical_qubits` logical
+    /// qubits (horizontal routing included) and `factories` Toffoli
+    /// factories.
+    fn vertical_routing_qubits(&self, logical_qubits: u64, factories: u64) -> u64;
+
+    /// Short description of the policy, reported with the estimates.
+    fn description(&self) -> String {
+        "custom".into()
+    }
+}
+
+impl<F> LayoutPolicy for F
+where
+    F: Fn(u64, u64) -> u64,
+{
+    fn vertical_routing_qubits(&self, logical_qubits: u64, factories: u64) -> u64 {
+        self(logical_qubits, factories)
+    }
+}
+
+/// Layout of arXiv:2302.06639 (p. 27), with all-to-all routing: 2(3n − 1)
+/// vertical routing qubits along a row of n logical qubits, each factory
+/// taking 5 logical qubits of the row.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PaperLayout;
+
+impl LayoutPolicy for PaperLayout {
+    fn vertical_routing_qubits(&self, logical_qubits: u64, factories: u64) -> u64 {
+        2 * (3 * (logical_qubits + factories * 5) - 1)
+    }
+
+    fn description(&self) -> String {
+        "all-to-all routing (arXiv:2302.06639)".into()
+    }
+}
+
+/// Vertical routing qubits along a row of `columns` logical qubits, as in
+/// arXiv:2302.06639 (p. 27).
+fn routing_band(columns: u64) -> u64 {
+    (6 * columns).saturating_sub(2)
+}
+
+/// Placement of the factories with respect to the compute row, with
+/// all-to-all routing.
+///
+/// Each factory stays next to the routing of the compute row whatever the
+/// placement, so the duration of the CCX gates is unchanged; only the length
+/// (and number) of the vertical routing bands changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FactoryPlacement {
+    /// A single row of factories along the compute row, as in
+    /// arXiv:2302.06639: exactly the routing of [`PaperLayout`], one band
+    /// along the n logical qubits and the 5 columns of each factory.
+    ///
+    /// With 10 logical qubits and 2 factories: 2(3 × 20 − 1) = 118.
+    #[default]
+    OneRow,
+    /// Two rows of factories flanking the compute row, ⌈f/2⌉ and ⌊f/2⌋
+    /// factories, each row with its own routing band, as long as the longer
+    /// of the compute row and the factory row (never less than the band of
+    /// the compute row alone). Fewer routing qubits when the factories make
+    /// most of the row, since they are no longer lined up after the logical
+    /// qubits.
+    ///
+    /// With 10 logical qubits and 2 factories: two bands of 2(3 × 10 − 1) =
+    /// 58, so 116; with 6 factories, two bands of 2(3 × 15 − 1) = 88, so 176
+    /// instead of 238 in one row.
+    TwoRows,
+    /// Factories interleaved between groups of logical qubits in a single
+    /// row: the routing band of [`FactoryPlacement::OneRow`], with 2 more
+    /// routing qubits per factory for its interface on both sides.
+    ///
+    /// With 10 logical qubits and 2 factories: 118 + 2 × 2 = 122.
+    Interleaved,
+}
+
+impl LayoutPolicy for FactoryPlacement {
+    fn vertical_routing_qubits(&self, logical_qubits: u64, factories: u64) -> u64 {
+        match self {
+            Self::OneRow => PaperLayout.vertical_routing_qubits(logical_qubits, factories),
+            Self::TwoRows => {
+                let rows = [factories.div_ceil(2), factories / 2];
+                rows.iter()
+                    .filter(|&&row| row > 0)
+                    .map(|row| routing_band(logical_qubits.max(5 * row)))
+                    .sum::<u64>()
+                    .max(routing_band(logical_qubits))
+            }
+            Self::Interleaved => {
+                PaperLayout.vertical_routing_qubits(logical_qubits, factories) + 2 * factories
+            }
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Self::OneRow => PaperLayout.description(),
+            Self::TwoRows => {
+                "all-to-all routing, factories in two rows flanking the compute row".into()
+            }
+            Self::Interleaved => {
+                "all-to-all routing, factories interleaved with the logical qubits".into()
+            }
+        }
+    }
+}
+
+impl FromStr for FactoryPlacement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "one-row" => Ok(Self::OneRow),
+            "two-rows" => Ok(Self::TwoRows),
+            "interleaved" => Ok(Self::Interleaved),
+            _ => Err(format!(
+                "unknown factory placement {s} (expected one-row, two-rows or interleaved)"
+            )),
+        }
+    }
+}
+
+impl Display for FactoryPlacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::OneRow => "one-row",
+            Self::TwoRows => "two-rows",
+            Self::Interleaved => "interleaved",
+        })
+    }
+}
+
+/// Compact layout without all-to-all routing: no vertical routing qubit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompactLayout;
+
+impl LayoutPolicy for CompactLayout {
+    fn vertical_routing_qubits(&self, _logical_qubits: u64, _factories: u64) -> u64 {
+        0
+    }
+
+    fn description(&self) -> String {
+        "compact, without vertical routing".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompactLayout, FactoryPlacement, LayoutPolicy, PaperLayout};
+
+    #[test]
+    fn one_row_is_the_paper_layout() {
+        for (logical_qubits, factories) in [(1, 0), (10, 1), (10, 2), (100, 7), (3, 40)] {
+            assert_eq!(
+                FactoryPlacement::OneRow.vertical_routing_qubits(logical_qubits, factories),
+                PaperLayout.vertical_routing_qubits(logical_qubits, factories)
+            );
+        }
+        assert_eq!(
+            FactoryPlacement::OneRow.description(),
+            PaperLayout.description()
+        );
+        assert_eq!(FactoryPlacement::default(), FactoryPlacement::OneRow);
+    }
+
+    #[test]
+    fn two_rows_match_the_worked_examples() {
+        let placement = FactoryPlacement::TwoRows;
+        assert_eq!(placement.vertical_routing_qubits(10, 2), 116);
+        assert_eq!(placement.vertical_routing_qubits(10, 6), 176);
+        assert_eq!(FactoryPlacement::OneRow.vertical_routing_qubits(10, 6), 238);
+        // A single factory: one band along the compute row.
+        assert_eq!(placement.vertical_routing_qubits(10, 1), 58);
+        // No factory: still the band of the compute row.
+        assert_eq!(placement.vertical_routing_qubits(10, 0), 58);
+        // An odd number of factories: the longer row has the extra one.
+        assert_eq!(placement.vertical_routing_qubits(2, 3), 58 + 28);
+    }
+
+    #[test]
+    fn interleaved_adds_two_qubits_per_factory() {
+        let placement = FactoryPlacement::Interleaved;
+        assert_eq!(placement.vertical_routing_qubits(10, 2), 122);
+        assert_eq!(
+            placement.vertical_routing_qubits(10, 0),
+            FactoryPlacement::OneRow.vertical_routing_qubits(10, 0)
+        );
+    }
+
+    #[test]
+    fn placements_are_parsed_back() {
+        for placement in [
+            FactoryPlacement::OneRow,
+            FactoryPlacement::TwoRows,
+            FactoryPlacement::Interleaved,
+        ] {
+            assert_eq!(placement.to_string().parse(), Ok(placement));
+        }
+        assert!("three-rows".parse::<FactoryPlacement>().is_err());
+    }
+
+    #[test]
+    fn compact_layout_has_no_vertical_routing() {
+        assert_eq!(CompactLayout.vertical_routing_qubits(100, 7), 0);
+    }
+}
@@ -18,7 +18,7 @@
 //! ### Assumes:
 //! - architecture as described in
 //!   [arXiv:2302.06639](https://arxiv.org/abs/2302.06639)
-//! - 1/κ₂ = 100 ns
+//! - 1/κ₂ = 100 ns (by default, possibly different in the factories)
 //! - κ₁/κ₂ = 1e-5
 //! - no saturation of bit-flip
 //! - simplified gate counting, when translating from Q# (no consequences for
@@ -26,26 +26,35 @@
 //!   * 1-qubit Clifford gates are free
 //!   * CX, CY, CZ are count as CX
 //!   * no parallelism considered
+//!   * a stricter conversion of the gates which are not bias-preserving can be
+//!     chosen with [`counter::gate_set::GateSet::bias_preserving`]
 //!
 //! ### Takes:
 //! - specification of the algorithmic required resources, either entered
-//!   directly, either deduced from a Q# file (see `example/from_qsharp.rs`).
+//!   directly, either deduced from a Q# file (see `example/from_qsharp.rs`,
+//!   requires the `qsharp` feature, enabled by default).
 //!   * number of logical qubits
 //!   * number of logical CX
 //!   * number of logical CCX
 //! - error budget:
 //!   * maximum total topological error probability
 //!   * maximum total error probability from magic states preparations
-//!   * maximum total error probability from rotations (unused)
+//!   * maximum total error probability from rotations (covering the synthesis
+//!     of the rotations, see [`budget::budget_for_counts`])
 //!
 //! ### Provides:
 //! - number of physical cat qubits
 //! - runtime
-//! - total error probability
+//! - number of logical cycles and logical clock rate
+//! - total error probability, split into phase-flip and bit-flip contributions
 //! - Code parameters:
 //!     * repetition code distance
 //!     * average number of photons |α|² in each cat
 //! - fraction of qubits assigned to the magic state factory
+//! - optionally, expected restarts and wall-clock time when heralded errors
+//!   lead to restarting the algorithm (see [`restart`])
+//! - assumptions extrapolated outside of the validity ranges of the models
+//!   (see [`validity`])
 //!
 //! *Author: Mathias Soeken*
 
@@ -53,10 +62,41 @@ pub use code::RepetitionCode;
 pub use counter::LogicalCounts;
 pub use estimates::AliceAndBobEstimates;
 pub use factories::ToffoliBuilder;
-pub use qubit::CatQubit;
+pub use qubit::{CatQubit, CxImplementation};
+pub use report::OutputFormat;
+pub use units::TimeUnit;
 
+pub mod advice;
+pub mod budget;
+pub mod budget_sweep;
+pub mod cancel;
+pub mod checkpoint;
 pub mod code;
 pub mod counter;
+pub mod diff;
 pub mod estimates;
 pub mod factories;
+pub mod frontier;
+pub mod geometry;
+pub mod layout;
+pub mod models;
+pub mod optimize;
+pub mod phases;
+pub mod pool;
+pub mod provenance;
+pub mod qre;
 pub mod qubit;
+pub mod random;
+pub mod report;
+pub mod restart;
+pub mod roadmap;
+mod scan;
+pub mod schema;
+pub mod sweep;
+pub mod syndrome;
+pub mod testing;
+pub mod units;
+pub mod utilization;
+pub mod validity;
+pub mod window;
+pub mod wiring;
@@ -5,48 +5,1103 @@
 //! computer with repetition code. The command-line is self documented, please
 //! use it with subcommand `help` to learn its usage.
 
-use clap::{Args, Parser, Subcommand};
-use std::rc::Rc;
+use anyhow::Context;
+use clap::{
+    error::ErrorKind, parser::ValueSource, Arg, Args, Command, CommandFactory, Parser, Subcommand,
+};
+use indicatif::ProgressBar;
+use interactive::Session;
+use num_traits::ToPrimitive;
+use std::{
+    ffi::OsString,
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    rc::Rc,
+    time::Duration,
+};
+use tracing::Level;
+use tracing_subscriber::fmt::format::FmtSpan;
 
 use qsharp_alice_bob_resource_estimator::{
-    AliceAndBobEstimates, CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+    advice::Advice,
+    budget,
+    cancel::CancellationToken,
+    checkpoint::{Checkpoint, OpenMode},
+    code::{
+        bit_flip::CxBitFlipTable, ordering::ParameterOrdering, search::SearchStrategy,
+        trace::SearchTrace, MAX_ALPHA_SQ, MAX_DISTANCE,
+    },
+    counter::{
+        gate_set::{self, GateCost, GateSet},
+        pyliqtr::TConversion,
+        rotations::RotationSynthesis,
+        routing::RoutingOverhead,
+        segmented::SegmentedCounts,
+        CountingError,
+    },
+    diff::{BaselineCheck, Diff},
+    factories::cascade::DistillationStage,
+    frontier::{self, Estimation, EstimationResult},
+    geometry::ChipGeometry,
+    layout::{CompactLayout, FactoryPlacement, LayoutPolicy},
+    models::Model,
+    pool,
+    provenance::Provenance,
+    qre::QreParameters,
+    qubit::{calibration::CalibrationPolicy, crosstalk::Crosstalk},
+    random,
+    report::html_report,
+    report::ReportSection,
+    restart::{AlgorithmRetry, HeraldedRestart},
+    roadmap::{Projection, Roadmap},
+    schema::{EstimateRecord, FailureDocument, FailureRecord, ResultDocument},
+    sweep::{ParameterSweep, SweepWorkers},
+    units,
+    validity::{self, ValidityRange},
+    wiring::WiringModel,
+    AliceAndBobEstimates, CatQubit, CxImplementation, LogicalCounts, OutputFormat, RepetitionCode,
+    TimeUnit, ToffoliBuilder,
 };
 use resource_estimator::estimates::{ErrorBudget, PhysicalResourceEstimation};
 
 /// Resource estimator for Alice & Bob's architecture (cats + repetition code).
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, after_help = "Environment variables AB_RE_K1_K2, AB_RE_THERMAL_PHOTONS, AB_RE_INVERSE_K2, AB_RE_MAX_ALPHA_SQ, AB_RE_ERROR_TOTAL, AB_RE_MAGIC_FRACTION and AB_RE_SEED set the corresponding options when they are not given on the command line.")]
+#[allow(clippy::struct_excessive_bools)]
 struct Cli {
+    /// Log the search of code parameters and factories, and the counting, on
+    /// stderr (-v for the main steps and their durations, -vv for the
+    /// candidates, -vvv for every evaluated code parameter).
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
     /// Show the frontier of good parameter sets instead of a single result.
     #[arg(short, long)]
     frontier: bool,
 
+    /// Show the frontier of good parameter sets over the physical qubits, the
+    /// runtime and the total error, the error budget being scaled down to 1%
+    /// of its value.
+    #[arg(long, conflicts_with = "frontier")]
+    error_frontier: bool,
+
+    /// Unit used to display the runtime (auto, ns, us, ms, s, min, h, days,
+    /// years).
+    #[arg(long, default_value_t = TimeUnit::Auto)]
+    units: TimeUnit,
+
+    /// Output format (text, markdown, latex or json).
+    #[arg(long, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Also write a self-contained HTML report (headline numbers, assumptions,
+    /// error breakdown, frontier plot) to this file.
+    #[arg(long, value_name = "FILE")]
+    report: Option<PathBuf>,
+
+    /// Also write every (distance, |α|²) evaluated by the searches of code
+    /// parameters, with its logical error rate, to this CSV file.
+    #[arg(long, value_name = "FILE")]
+    search_trace: Option<PathBuf>,
+
+    /// Registered revision of the physical models (fits, schedule, factories)
+    /// the assumptions start from, to reproduce the estimates of older
+    /// versions (gouzien2023) [default: the latest one].
+    #[arg(long, value_name = "NAME", value_parser = Model::by_name)]
+    model: Option<Model>,
+
+    /// Ratio κ₁/κ₂ between the one and two photon loss rates of the cat
+    /// qubits [default: 1e-5, or the one of the model or of the QRE
+    /// parameters].
+    #[arg(long, value_name = "RATIO", value_parser = parse_positive, conflicts_with = "calibration")]
+    k1_k2: Option<f64>,
+
+    /// Average number of thermal photons nₜₕ in the cavities.
+    #[arg(long, value_name = "N_TH", value_parser = parse_non_negative, default_value_t = 0.0)]
+    thermal_photons: f64,
+
+    /// Calibration of the cavities of a device (CSV, or JSON with a .json
+    /// extension), setting κ₁/κ₂ and nₜₕ.
+    #[arg(long, value_name = "FILE", conflicts_with = "thermal_photons")]
+    calibration: Option<PathBuf>,
+
+    /// Aggregation of the rates of the calibrated cavities (median, mean or
+    /// worst-case).
+    #[arg(
+        long,
+        value_name = "POLICY",
+        default_value = "median",
+        requires = "calibration"
+    )]
+    calibration_policy: CalibrationPolicy,
+
+    /// Parameters of the Azure Quantum Resource Estimator (JSON with
+    /// qubitParams, qecScheme and errorBudget), mapped to the cat qubits, the
+    /// repetition code and the error budget where meaningful.
+    #[arg(long, value_name = "FILE", value_parser = parse_qre_parameters, conflicts_with = "calibration")]
+    qre_params: Option<QreParameters>,
+
+    /// Leakage probability per physical qubit and per code round.
+    #[arg(long, value_name = "PROBA", value_parser = budget::parse_probability, default_value_t = 0.0)]
+    leakage_rate: f64,
+
+    /// Explicit error probability of the ancilla measurements (on top of the
+    /// ones included in the repetition code fit).
+    #[arg(long, value_name = "PROBA", value_parser = budget::parse_probability, default_value_t = 0.0)]
+    measurement_error: f64,
+
+    /// Inflate the physical error rates (κ₁/κ₂, leakage and measurement
+    /// errors) by crosstalk, by a factor 1 + PENALTY × AGGRESSORS, the
+    /// aggressors being neighbouring qubits or simultaneous gates (default:
+    /// 2, the neighbours in the repetition code).
+    #[arg(long, value_name = "PENALTY[,AGGRESSORS]")]
+    crosstalk: Option<Crosstalk>,
+
+    /// Implementation of the CX gates, in both the computation part and the
+    /// factories (fast or adiabatic) [default: fast in the computation part,
+    /// adiabatic in the factories].
+    #[arg(long, value_name = "CX")]
+    cx: Option<CxImplementation>,
+
+    /// Constants of the fit of the logical phase-flip probability of the
+    /// repetition code [default: 5.6e-2 0.86 0.013].
+    #[arg(long, num_args = 3, value_names = ["PREFACTOR", "ALPHA_EXPONENT", "THRESHOLD"])]
+    phase_flip_fit: Option<Vec<f64>>,
+
+    /// Bit-flip probability of the CX gates measured at several |α|² (CSV with
+    /// the |α|² and the probability on each line), replacing 0.5·exp(-2|α|²).
+    #[arg(long, value_name = "FILE", value_parser = parse_cx_bit_flip_table)]
+    cx_bit_flip_table: Option<CxBitFlipTable>,
+
+    /// Order of the code parameters reaching the required error rate: qubits
+    /// (then time), time (then qubits), volume or weighted:W (qubits^W ×
+    /// time^(1-W)).
+    #[arg(long, value_name = "ORDERING", default_value = "qubits")]
+    parameter_ordering: ParameterOrdering,
+
+    /// Order in which the search visits the code parameters: rectangular
+    /// (every |α|² at each distance), pruned or diagonal (skipping the |α|²
+    /// past the minimum of the logical error rate).
+    #[arg(long, value_name = "STRATEGY", default_value = "rectangular")]
+    search_strategy: SearchStrategy,
+
+    /// Time scale 1/κ₂ in nanoseconds, setting the speed of the gates
+    /// [default: 100, or the one of the model].
+    #[arg(long, value_name = "NS", value_parser = parse_positive)]
+    inverse_k2: Option<f64>,
+
+    /// Time scale 1/κ₂ in nanoseconds in the magic state factories, if their
+    /// κ₂ differs (κ₁ being the same, their κ₁/κ₂ is scaled accordingly).
+    #[arg(long, value_name = "NS", value_parser = parse_positive)]
+    factory_inverse_k2: Option<f64>,
+
+    /// Maximum average number of photons |α|² reachable with the pump power.
+    #[arg(long, value_name = "ALPHA_SQ", value_parser = parse_positive)]
+    max_alpha_sq: Option<f64>,
+
+    /// Fraction of the errors which are heralded, the algorithm being
+    /// restarted when one is detected. The error budget then applies to the
+    /// undetected errors of the accepted run.
+    #[arg(long, value_name = "FRACTION")]
+    heralded_fraction: Option<f64>,
+
+    /// Also report percentiles of the wall-clock time with restarts, sampled
+    /// from this number of runs (requires --heralded-fraction).
+    #[arg(long, value_name = "N", requires = "heralded_fraction")]
+    restart_samples: Option<usize>,
+
+    /// Intrinsic success probability of the algorithm, which is repeated
+    /// until it succeeds: also report the expected runs and total runtime.
+    #[arg(long, value_name = "PROBA")]
+    success_probability: Option<AlgorithmRetry>,
+
+    /// Seed of the random number generator of the sampled estimates and
+    /// measurement outcomes, reported with them [default: random].
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Count Q# programs this number of times with random measurement
+    /// outcomes (drawn from the seed), report the distribution of the counts,
+    /// and estimate the sample at the 90th percentile of the logical depth.
+    #[arg(long, value_name = "N")]
+    outcome_samples: Option<usize>,
+
+    #[command(flatten)]
+    layout: LayoutOptions,
+
+    /// Also consider factories followed by a second distillation stage, to
+    /// reach magic state errors below the precomputed ones.
+    #[arg(long)]
+    cascade: bool,
+
+    /// Exclude the factories whose acceptance probability is below this
+    /// floor, as their preparation time is unpredictable (e.g. 0.03 excludes
+    /// the distance 9 factories).
+    #[arg(long, value_name = "PROBA", value_parser = budget::parse_probability, default_value_t = 0.0)]
+    acceptance_floor: f64,
+
+    /// Reject the estimates relying on models extrapolated outside of their
+    /// validity ranges, instead of flagging them [default: only with the
+    /// `strict` feature].
+    #[arg(long)]
+    strict: bool,
+
+    /// Print the resolved assumptions (qubits, code models, factories, error
+    /// budget, constraints) and exit without counting nor estimating.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Configuration file (TOML, see `config init`), setting the options
+    /// which are not given on the command line.
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
     #[command(flatten)]
     budget: Budget,
 
+    #[command(flatten)]
+    limits: CountingLimits,
+
+    #[command(flatten)]
+    cost_model: CostModel,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Args)]
-#[group(required = false, multiple = false)]
+#[allow(clippy::struct_field_names)]
 struct Budget {
-    /// Overall error budget (equally split between topological and magic state
-    /// errors) [default: 0.333].
-    #[arg(long, value_name = "ERROR_PROBA")]
+    /// Overall error budget (split between topological and magic state errors
+    /// according to --magic-fraction) [default: 0.333].
+    #[arg(long, value_name = "ERROR_PROBA", value_parser = budget::parse_probability, conflicts_with = "error_budget")]
     error_total: Option<f64>,
 
-    /// Detailed error budget
-    #[arg(long, num_args = 3, value_names = ["TOPOLOGICAL_ERROR", "MAGIC_ERROR", "ROTATION_ERROR"])]
+    /// Fraction of the overall error budget given to the magic state errors,
+    /// the topological errors getting the rest.
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        default_value_t = 0.5,
+        conflicts_with = "error_budget"
+    )]
+    magic_fraction: f64,
+
+    /// Fraction of the overall error budget given to the synthesis of the
+    /// rotations (by default, their synthesis error is taken from the
+    /// topological errors).
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        default_value_t = 0.0,
+        conflicts_with = "error_budget"
+    )]
+    rotation_fraction: f64,
+
+    /// Detailed error budget (the rotation error must cover the synthesis of
+    /// the rotations, see --rotation-precision; with 0, it is taken from the
+    /// topological error)
+    #[arg(long, num_args = 3, value_names = ["TOPOLOGICAL_ERROR", "MAGIC_ERROR", "ROTATION_ERROR"], value_parser = budget::parse_probability)]
     error_budget: Option<Vec<f64>>,
+
+    /// Target overall failure probability, split between the topological and
+    /// magic state errors so as to minimize the space-time volume of the
+    /// estimate (the subcommands advise, diff and interactive split it
+    /// equally).
+    #[arg(long, value_name = "ERROR_PROBA", value_parser = budget::parse_probability, conflicts_with_all = ["error_total", "error_budget", "magic_fraction", "rotation_fraction"])]
+    error_target: Option<f64>,
+}
+
+#[derive(Args, Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)]
+struct LayoutOptions {
+    /// Also report the combination of factory types reaching the magic state
+    /// rate with the fewest qubits, without changing the estimate.
+    #[arg(long)]
+    mixed_factories: bool,
+
+    /// Also report the factory type and copies producing the consumed Toffoli
+    /// states within the runtime with the fewest qubits, when the factories
+    /// produce states in excess (the estimate keeps its factories).
+    #[arg(long)]
+    right_size_factories: bool,
+
+    /// Lay out the qubits without the vertical routing qubits of the
+    /// all-to-all routing of arXiv:2302.06639.
+    #[arg(long)]
+    compact_layout: bool,
+
+    /// Placement of the factories with respect to the compute row: one-row
+    /// (arXiv:2302.06639), two-rows flanking it, or interleaved with the
+    /// logical qubits; changes the vertical routing qubits.
+    #[arg(
+        long,
+        value_name = "PLACEMENT",
+        default_value = "one-row",
+        conflicts_with = "compact_layout"
+    )]
+    factory_placement: FactoryPlacement,
+
+    /// Also report the fraction of the runtime during which the compute,
+    /// routing and factory qubits are active.
+    #[arg(long)]
+    utilization: bool,
+
+    /// Also report the Toffoli states per second required by each phase (each
+    /// Q# file run in sequence), its bottleneck, and the factories sized for
+    /// the phase with the highest rate.
+    #[arg(long)]
+    phases: bool,
+
+    /// Also report the dimensions of the chip, with physical qubits (cat qubit
+    /// and resonator) spaced by this pitch.
+    #[arg(long, value_name = "MICROMETERS")]
+    qubit_pitch: Option<ChipGeometry>,
+
+    /// Also report the control and readout lines, with pump and drive lines
+    /// per physical qubit and qubits per readout line (default: 1,1,8).
+    #[arg(long, value_name = "PUMPS,DRIVES,MULTIPLEXING", num_args = 0..=1, default_missing_value = "default")]
+    wiring: Option<WiringModel>,
+
+    /// Also report the syndrome data sent to the decoder and its bandwidth,
+    /// with this number of bits per measurement (default: 1).
+    #[arg(long, value_name = "BITS", num_args = 0..=1, default_missing_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+    syndrome_bits: Option<u32>,
+}
+
+impl LayoutOptions {
+    /// Estimates with the layout, factory fleet, utilization, demand of the
+    /// `phases`, chip dimensions, wiring and syndrome data of the command line.
+    fn apply(
+        self,
+        estimates: AliceAndBobEstimates,
+        builder: &ToffoliBuilder,
+        qubit: &CatQubit,
+        phases: &SegmentedCounts,
+    ) -> AliceAndBobEstimates {
+        let estimates = if self.compact_layout {
+            estimates.with_layout(CompactLayout)
+        } else if self.factory_placement == FactoryPlacement::OneRow {
+            estimates
+        } else {
+            estimates.with_layout(self.factory_placement)
+        };
+        let estimates = if self.mixed_factories {
+            estimates.with_mixed_fleet(builder, qubit)
+        } else {
+            estimates
+        };
+        let estimates = if self.right_size_factories {
+            estimates.with_factory_right_sizing(builder, qubit)
+        } else {
+            estimates
+        };
+        let estimates = if self.utilization {
+            estimates.with_utilization()
+        } else {
+            estimates
+        };
+        let estimates = if self.phases {
+            estimates.with_phases(phases)
+        } else {
+            estimates
+        };
+        let estimates = match self.qubit_pitch {
+            Some(geometry) => estimates.with_chip_geometry(geometry),
+            None => estimates,
+        };
+        let estimates = match self.wiring {
+            Some(wiring) => estimates.with_wiring(&wiring),
+            None => estimates,
+        };
+        match self.syndrome_bits {
+            Some(bits) => estimates.with_syndrome_data(bits),
+            None => estimates,
+        }
+    }
+
+    /// Description of the layout, for the dry run.
+    fn description(self) -> String {
+        if self.compact_layout {
+            CompactLayout.description()
+        } else {
+            self.factory_placement.description()
+        }
+    }
+}
+
+#[derive(Args, Clone, Copy)]
+struct CostModel {
+    /// Cost of a SWAP gate: free (absorbed in the relabelling of the qubits),
+    /// 3cx, or a number of CX [default: 3cx]
+    #[arg(long, value_name = "COST", value_parser = gate_set::parse_swap_cost)]
+    swap_cost: Option<GateCost>,
+
+    /// Factor applied to the duration of the CX gates for the routing between
+    /// distant logical qubits [default: 1, or the one of saved counts].
+    #[arg(long, value_name = "FACTOR")]
+    routing_factor: Option<f64>,
+
+    /// Count no "horizontal" routing qubit in the logical qubits (instead of
+    /// one per pair of qubits plus one, as in arXiv:2302.06639), e.g. to
+    /// compare with counts already including the routing.
+    #[arg(long)]
+    no_routing_qubits: bool,
+
+    /// Uncompute the logical ANDs (CCX on a target in |0⟩) of Q# programs by
+    /// measurement, without magic state.
+    #[arg(long)]
+    measurement_uncompute: bool,
+
+    /// With --measurement-uncompute, only uncompute the logical ANDs by
+    /// measurement, and not the mirrored CCX of `within … apply` blocks, for
+    /// conservative estimates.
+    #[arg(long, requires = "measurement_uncompute")]
+    no_mirrored_uncompute: bool,
+
+    /// Duration of a measurement, in logical cycles [default: 0.2, or the one
+    /// of saved counts].
+    #[arg(long, value_name = "CYCLES")]
+    measurement_cycles: Option<f64>,
+
+    /// Classical latency between a measurement and a gate depending on its
+    /// outcome, in logical cycles [default: 0, or the one of saved counts].
+    #[arg(long, value_name = "CYCLES")]
+    feed_forward_cycles: Option<f64>,
+
+    /// Precision of the synthesis of the non-Clifford rotations of Q#
+    /// programs into T gates, charged to the rotation error budget for each
+    /// rotation.
+    #[arg(long, value_name = "EPSILON", default_value_t = 1e-10)]
+    rotation_precision: f64,
+
+    /// Conversion of the T gates of the rotations into CCX (and, toffoli,
+    /// catalyzed, or a number of T gates per CCX).
+    #[arg(long, value_name = "CONVERSION", default_value_t = TConversion::and())]
+    rotation_t_conversion: TConversion,
+}
+
+impl CostModel {
+    /// Gate set with the SWAP cost, uncomputation and rotation synthesis of
+    /// the command line.
+    fn gate_set(self, gate_set: GateSet) -> Result<GateSet, anyhow::Error> {
+        let gate_set = match self.swap_cost {
+            Some(swap) => gate_set.with_swap(swap),
+            None => gate_set,
+        };
+        let gate_set = match (self.measurement_uncompute, self.no_mirrored_uncompute) {
+            (true, true) => gate_set
+                .with_measurement_uncompute()
+                .without_mirrored_uncompute(),
+            (true, false) => gate_set.with_measurement_uncompute(),
+            (false, _) => gate_set,
+        };
+        Ok(gate_set.with_rotation_synthesis(self.rotation_synthesis(self.rotation_t_conversion)?))
+    }
+
+    /// Synthesis of the rotations with the precision of the command line, the
+    /// T gates being converted with `conversion`.
+    fn rotation_synthesis(
+        self,
+        conversion: TConversion,
+    ) -> Result<RotationSynthesis, anyhow::Error> {
+        RotationSynthesis::for_precision(self.rotation_precision, conversion)
+            .map_err(anyhow::Error::msg)
+    }
+
+    /// Counts with the routing overhead, routing factor and measurement timing
+    /// of the command line, the ones not given being kept (e.g. the ones of
+    /// saved counts).
+    fn apply(self, counts: LogicalCounts) -> Result<LogicalCounts, anyhow::Error> {
+        let counts = if self.no_routing_qubits {
+            counts.with_routing_overhead(RoutingOverhead::None)
+        } else {
+            counts
+        };
+        let gate_set = counts.gate_set();
+        let routing_factor = self.routing_factor.unwrap_or(gate_set.routing_factor);
+        let measurement_cycles = self
+            .measurement_cycles
+            .unwrap_or(gate_set.measurement_cycles);
+        let feed_forward_cycles = self
+            .feed_forward_cycles
+            .unwrap_or(gate_set.feed_forward_cycles);
+        counts
+            .with_routing_factor(routing_factor)
+            .and_then(|counts| {
+                counts.with_measurement_timing(measurement_cycles, feed_forward_cycles)
+            })
+            .map_err(anyhow::Error::msg)
+    }
+}
+
+/// Limits of the counting of Q# programs, for programs which don't terminate.
+#[derive(Args, Clone, Copy)]
+struct CountingLimits {
+    /// Stop counting a Q# program with an error after this number of gates
+    /// (e.g. 1e12), the repeated and cached blocks counting once.
+    #[arg(long, value_name = "GATES", value_parser = parse_gate_count)]
+    max_gates: Option<u64>,
+
+    /// Stop counting a Q# program with an error after this wall-clock time
+    /// (e.g. "10 min").
+    #[arg(long, value_name = "DURATION")]
+    count_timeout: Option<units::TimeSpan>,
+}
+
+impl CountingLimits {
+    /// Counter with the limits of the command line.
+    fn apply(self, counter: LogicalCounts) -> LogicalCounts {
+        let counter = match self.max_gates {
+            Some(max_gates) => counter.with_gate_limit(max_gates),
+            None => counter,
+        };
+        match self.count_timeout {
+            Some(timeout) => counter.with_time_limit(Duration::from_nanos(timeout.as_ns())),
+            None => counter,
+        }
+    }
+}
+
+impl Cli {
+    /// Qubits of the model with the time scale 1/κ₂ of the command line, to
+    /// which the QRE parameters are mapped.
+    fn model_qubit(&self) -> CatQubit {
+        let qubit = self.model().cat_qubit();
+        match self.inverse_k2 {
+            Some(inverse_k2) => qubit.with_inverse_k2_ns(inverse_k2),
+            None => qubit,
+        }
+    }
+
+    /// Qubits with the parameters of the command line.
+    fn qubit(&self) -> Result<CatQubit, anyhow::Error> {
+        let qubit = match &self.calibration {
+            Some(calibration) => {
+                CatQubit::from_calibration_with_policy(calibration, self.calibration_policy)
+                    .map_err(anyhow::Error::msg)?
+            }
+            None => match &self.qre_params {
+                Some(parameters) => {
+                    let ignored = parameters.ignored();
+                    if !ignored.is_empty() {
+                        tracing::warn!(
+                            ignored = ignored.join(", "),
+                            "QRE parameters without cat qubit equivalent are ignored"
+                        );
+                    }
+                    parameters
+                        .cat_qubit(self.model_qubit())
+                        .map_err(anyhow::Error::msg)?
+                }
+                None => self.model().cat_qubit(),
+            }
+            .with_thermal_photons(self.thermal_photons),
+        }
+        .with_leakage_rate(self.leakage_rate)
+        .with_measurement_error(self.measurement_error);
+        let qubit = match self.k1_k2 {
+            Some(k1_k2) => qubit.with_k1_k2(k1_k2),
+            None => qubit,
+        };
+        let qubit = match self.crosstalk {
+            Some(crosstalk) => qubit.with_crosstalk(crosstalk),
+            None => qubit,
+        };
+        let qubit = match self.inverse_k2 {
+            Some(inverse_k2) => qubit.with_inverse_k2_ns(inverse_k2),
+            None => qubit,
+        };
+        let qubit = match self.factory_inverse_k2 {
+            Some(factory_inverse_k2) => qubit.with_factory_inverse_k2_ns(factory_inverse_k2),
+            None => qubit,
+        };
+        let qubit = match self.max_alpha_sq {
+            Some(max_alpha_sq) => qubit
+                .with_max_alpha_sq(max_alpha_sq)
+                .map_err(anyhow::Error::msg)?,
+            None => qubit,
+        };
+        Ok(match self.cx {
+            Some(cx) => qubit.with_cx_implementation(cx),
+            None => qubit,
+        })
+    }
+
+    /// Repetition code with the phase-flip fit, ordering and search strategy
+    /// of the command line (after the ones of the QRE parameters), recording
+    /// its searches if a trace is asked for.
+    fn code(&self, cancellation: &CancellationToken) -> Result<RepetitionCode, anyhow::Error> {
+        let code = self
+            .model()
+            .repetition_code(RepetitionCode::new())
+            .map_err(anyhow::Error::msg)?
+            .with_cancellation(cancellation.clone())
+            .with_parameter_ordering(self.parameter_ordering)
+            .with_search_strategy(self.search_strategy);
+        let code = match &self.qre_params {
+            Some(parameters) => parameters
+                .repetition_code(code, self.model_qubit().inverse_k2_ns())
+                .map_err(anyhow::Error::msg)?,
+            None => code,
+        };
+        let code = match self.phase_flip_fit.as_deref() {
+            Some(&[prefactor, alpha_exponent, threshold]) => code
+                .with_fit(prefactor, alpha_exponent, threshold)
+                .map_err(anyhow::Error::msg)?,
+            _ => code,
+        };
+        let code = match &self.cx_bit_flip_table {
+            Some(table) => code.with_cx_bit_flip_table(table.clone()),
+            None => code,
+        };
+        Ok(match self.search_trace {
+            Some(_) => code.with_search_trace(SearchTrace::new()),
+            None => code,
+        })
+    }
+
+    /// Estimates with the restarts on heralded errors, the retries of the
+    /// algorithm and the sampled runtime asked for.
+    fn repeated(
+        &self,
+        estimates: AliceAndBobEstimates,
+        restart: Option<HeraldedRestart>,
+        seed: u64,
+    ) -> AliceAndBobEstimates {
+        let estimates = match restart {
+            Some(restart) => estimates.with_restart(restart),
+            None => estimates,
+        };
+        let estimates = match self.success_probability {
+            Some(retry) => estimates.with_retry(retry),
+            None => estimates,
+        };
+        match self.restart_samples {
+            Some(samples) => estimates.with_sampled_runtime(samples, seed),
+            None => estimates,
+        }
+    }
+
+    /// Registered model the assumptions start from.
+    fn model(&self) -> Model {
+        self.model.unwrap_or_else(Model::latest)
+    }
+
+    /// Error budget of each run, and restart strategy if errors are heralded.
+    fn budget_per_run(&self) -> Result<(ErrorBudget, Option<HeraldedRestart>), anyhow::Error> {
+        let default = self.qre_params.as_ref().and_then(QreParameters::budget);
+        let budget = self
+            .budget
+            .error_budget(default.transpose().map_err(anyhow::Error::msg)?)
+            .map_err(anyhow::Error::msg)?;
+        let restart = self
+            .heralded_fraction
+            .map(HeraldedRestart::new)
+            .transpose()
+            .map_err(anyhow::Error::msg)?;
+        let budget = match restart {
+            Some(restart) => restart.budget_per_run(&budget),
+            None => budget,
+        };
+        Ok((budget, restart))
+    }
+
+    /// Factories with the options of the command line.
+    fn builder(&self) -> Result<ToffoliBuilder, anyhow::Error> {
+        let builder = ToffoliBuilder::default();
+        self.model()
+            .check_factories(&builder)
+            .map_err(anyhow::Error::msg)?;
+        let builder = if self.cascade {
+            builder.with_cascade(DistillationStage::default())
+        } else {
+            builder
+        };
+        builder
+            .with_acceptance_floor(self.acceptance_floor)
+            .map_err(anyhow::Error::msg)
+    }
+
+    /// Seed of the sampled estimates (drawn at random if not given), and the
+    /// counting of Q# programs, with the sampling of the measurement outcomes
+    /// and the limits asked for.
+    fn seed_and_counting(&self, cancellation: &CancellationToken) -> (u64, QsharpCounting) {
+        let seed = self.seed.unwrap_or_else(random::random_seed);
+        tracing::info!(seed, "seed of the sampled estimates");
+        let outcomes = self
+            .outcome_samples
+            .map(|samples| OutcomeSampling { samples, seed });
+        let counting = QsharpCounting {
+            outcomes,
+            limits: self.limits,
+            cancellation: cancellation.clone(),
+        };
+        (seed, counting)
+    }
+}
+
+/// Positive finite number.
+fn parse_positive(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+        _ => Err(format!("'{s}' is not a positive number")),
+    }
+}
+
+/// Non-negative finite number.
+fn parse_non_negative(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok(value),
+        _ => Err(format!("'{s}' is not a non-negative number")),
+    }
+}
+
+/// Bit-flip probabilities of the CX gates of a CSV file.
+fn parse_cx_bit_flip_table(filename: &str) -> Result<CxBitFlipTable, String> {
+    CxBitFlipTable::from_file(filename)
+}
+
+/// Number of gates, possibly in scientific notation (e.g. 1e12).
+fn parse_gate_count(s: &str) -> Result<u64, String> {
+    s.parse().or_else(|_| {
+        s.parse::<f64>()
+            .ok()
+            .filter(|gates| gates.fract() == 0.0)
+            .and_then(|gates| gates.to_u64())
+            .ok_or_else(|| format!("{s} is not a number of gates"))
+    })
+}
+
+/// QRE parameters of a JSON file.
+fn parse_qre_parameters(filename: &str) -> Result<QreParameters, String> {
+    QreParameters::from_file(filename)
+}
+
+/// Options which can be set by environment variables (e.g. in containers or
+/// CI jobs), with the id of the option and of the other options setting the
+/// same value. An option given on the command line or in the configuration
+/// file, or conflicting with one given there, takes precedence over its
+/// variable, which takes precedence over the default.
+///
+/// `AB_RE_K1_K2` also yields to the κ₁/κ₂ mapped from `--qre-params` (which
+/// `--k1-k2` overrides when given explicitly), whereas `AB_RE_ERROR_TOTAL`,
+/// as `--error-total`, replaces the error budget of the QRE parameters.
+const ENVIRONMENT: [(&str, &str, &[&str]); 7] = [
+    ("AB_RE_K1_K2", "k1_k2", &["qre_params"]),
+    ("AB_RE_THERMAL_PHOTONS", "thermal_photons", &[]),
+    ("AB_RE_INVERSE_K2", "inverse_k2", &[]),
+    ("AB_RE_MAX_ALPHA_SQ", "max_alpha_sq", &[]),
+    ("AB_RE_ERROR_TOTAL", "error_total", &[]),
+    ("AB_RE_MAGIC_FRACTION", "magic_fraction", &[]),
+    ("AB_RE_SEED", "seed", &[]),
+];
+
+/// Command line, with the options set by the configuration file or by the
+/// [`ENVIRONMENT`] variables inserted before the ones given.
+fn parse_cli() -> Cli {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let args = with_defaults(&Cli::command(), &args, |variable| {
+        std::env::var_os(variable)
+    })
+    .unwrap_or_else(|err| err.exit());
+    Cli::parse_from(args)
+}
+
+/// Arguments `args` of `command`, with the options set by the configuration
+/// file or by the [`ENVIRONMENT`] variables (read with `variable`) inserted
+/// before the ones given.
+fn with_defaults(
+    command: &Command,
+    args: &[OsString],
+    variable: impl Fn(&str) -> Option<OsString>,
+) -> Result<Vec<OsString>, clap::Error> {
+    let matches = command.clone().try_get_matches_from(args)?;
+    // options set by order of precedence
+    let mut set: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .collect();
+    let configured = match matches.get_one::<PathBuf>("config") {
+        Some(path) => config::read(command, path)
+            .map_err(|err| command.clone().error(ErrorKind::InvalidValue, err))?,
+        None => vec![],
+    };
+    let environment = ENVIRONMENT
+        .iter()
+        .filter_map(|(variable_name, id, shadowing)| {
+            let value = variable(variable_name)?;
+            let arg = command
+                .get_arguments()
+                .find(|arg| arg.get_id() == *id)
+                .expect("environment variables set known options");
+            let mut option = OsString::from(format!(
+                "--{}=",
+                arg.get_long()
+                    .expect("environment variables set long options")
+            ));
+            option.push(value);
+            Some((arg, vec![option], *shadowing))
+        });
+    let conflicts = |arg: &Arg, other: &Arg| {
+        command
+            .get_arg_conflicts_with(arg)
+            .iter()
+            .any(|conflict| conflict.get_id() == other.get_id())
+    };
+    let mut options = vec![];
+    let configured = configured
+        .into_iter()
+        .map(|(arg, arguments)| (arg, arguments, &[] as &[&str]));
+    for (arg, arguments, shadowing) in configured.chain(environment) {
+        if set.iter().any(|other| {
+            other.get_id() == arg.get_id()
+                || shadowing.contains(&other.get_id().as_str())
+                || conflicts(arg, other)
+                || conflicts(other, arg)
+        }) {
+            continue;
+        }
+        set.push(arg);
+        options.extend(arguments);
+    }
+    let (program, given) = args.split_first().expect("the program name is given");
+    Ok(std::iter::once(program)
+        .chain(&options)
+        .chain(given)
+        .cloned()
+        .collect())
+}
+
+/// Log on stderr at the level chosen with `--verbose`.
+fn init_tracing(verbose: u8) {
+    let level = match verbose {
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+impl Budget {
+    /// Error budget given on the command line, `default` (e.g. the one of
+    /// the QRE parameters) if none is given.
+    fn error_budget(&self, default: Option<ErrorBudget>) -> Result<ErrorBudget, String> {
+        if let Some(target) = self.error_target {
+            return budget::split_error_budget(target);
+        }
+        if let (None, None, Some(default)) = (self.error_total, &self.error_budget, default) {
+            return Ok(default);
+        }
+        match (self.error_total, self.error_budget.as_deref()) {
+            (total, None) => budget::fractional_error_budget(
+                total.unwrap_or(0.333),
+                self.magic_fraction,
+                self.rotation_fraction,
+            ),
+            (None, Some(&[logical, magic_states, rotations])) => {
+                budget::error_budget(logical, magic_states, rotations)
+            }
+            (None, Some(_)) => Err("--error-budget takes 3 probabilities".into()),
+            (Some(_), Some(_)) => {
+                Err("give either --error-total or --error-budget, not both".into())
+            }
+        }
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Read a Q# file
+    #[command(flatten)]
+    Input(Input),
+    /// Adjust the assumptions and re-estimate from a prompt, without counting
+    /// the resources again
+    Interactive {
+        /// Path to a Q# file to count the resources from
+        filename: Option<String>,
+        /// Conversion of the non-native gates (simplified or bias-preserving)
+        #[arg(long, default_value_t = GateSet::simplified())]
+        gate_set: GateSet,
+    },
+    /// Suggest the changes of the algorithm which would reduce the resources
+    /// the most, from the sensitivity of the estimate to its inputs
+    Advise {
+        #[command(subcommand)]
+        input: Input,
+    },
+    /// Compare two versions of an algorithm, given as saved estimates
+    /// (`--output json`) or as logical counts in JSON (estimated with the
+    /// assumptions of the command line)
+    Diff {
+        /// Path to the JSON file of the version before the change
+        before: PathBuf,
+        /// Path to the JSON file of the version after the change
+        after: PathBuf,
+    },
+    /// Project the physical qubits and runtime of an algorithm along a
+    /// schedule of κ₁/κ₂ improvements
+    Roadmap(RoadmapArgs),
+    /// Estimate an algorithm for each combination of parameters of a CSV file
+    /// (κ₁/κ₂, error budget, constraints, preset), appending the results to
+    /// its rows
+    Sweep(SweepArgs),
+    /// Estimate an algorithm and compare the estimate with a saved baseline
+    /// (`--output json`), failing if the physical qubits, runtime or total
+    /// error increased beyond the tolerance
+    CompareBaseline(CompareBaselineArgs),
+    /// Manage the configuration files (`--config`)
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Write a configuration file with every option commented out at its
+    /// default, with its description
+    Init {
+        /// Path of the file [default: the standard output]
+        #[arg(long, value_name = "FILE")]
+        to: Option<PathBuf>,
+        /// Overwrite the file if it exists
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+impl ConfigCommand {
+    fn run(&self) -> Result<(), anyhow::Error> {
+        match self {
+            Self::Init { to, force } => config::init(&Cli::command(), to.as_deref(), *force),
+        }
+    }
+}
+
+#[derive(Args)]
+struct RoadmapArgs {
+    /// Path to the TOML schedule, with a `[[milestone]]` table per year giving
+    /// its `year` and `k1_k2`
+    schedule: PathBuf,
+    /// Also report the first year at which the algorithm fits in this number
+    /// of physical qubits
+    #[arg(long, value_name = "QUBITS")]
+    max_qubits: Option<u64>,
+    #[command(subcommand)]
+    input: Input,
+}
+
+#[derive(Args)]
+struct CompareBaselineArgs {
+    /// Path to the saved estimates of the baseline (the first one is
+    /// compared)
+    baseline: PathBuf,
+    /// Relative increase of the physical qubits, runtime or total error
+    /// tolerated before failing (e.g. 0.05 for 5%)
+    #[arg(long, value_name = "FRACTION", value_parser = parse_non_negative, default_value_t = 0.0)]
+    tolerance: f64,
+    #[command(subcommand)]
+    input: Input,
+}
+
+impl CompareBaselineArgs {
+    /// Compare the first estimate with the baseline, failing on regressions.
+    fn check(&self, results: &[AliceAndBobEstimates]) -> Result<(), anyhow::Error> {
+        let Saved::Estimate(baseline) = Saved::read(&self.baseline)? else {
+            anyhow::bail!("{} is not saved estimates", self.baseline.display());
+        };
+        let estimate = results.first().context("no estimate to compare")?;
+        let check = BaselineCheck::new(&baseline, &estimate.into(), self.tolerance);
+        print!("{check}");
+        let regressions = check.regressions();
+        if regressions.is_empty() {
+            return Ok(());
+        }
+        let quantities: Vec<_> = regressions.iter().map(|(quantity, _)| *quantity).collect();
+        Err(anyhow::anyhow!(
+            "regression from {}: {}",
+            self.baseline.display(),
+            quantities.join(", ")
+        )
+        .context(Failure::Regression))
+    }
+}
+
+#[derive(Args)]
+struct SweepArgs {
+    #[command(flatten)]
+    options: SweepOptions,
+    #[command(subcommand)]
+    input: Input,
+}
+
+#[derive(Args)]
+struct SweepOptions {
+    /// Path to the CSV file, with a header row naming the columns (`k1_k2`,
+    /// `thermal_photons`, `error_total`, `magic_fraction`, `max_qubits`,
+    /// `max_runtime`, `preset`, other columns being copied)
+    #[arg(long = "from", value_name = "FILE")]
+    from: PathBuf,
+    /// Write the results to this CSV file instead of the standard output
+    #[arg(long = "to", value_name = "FILE")]
+    to: Option<PathBuf>,
+    /// Record each estimated row in this file, to resume an interrupted sweep
+    #[arg(long, value_name = "FILE")]
+    checkpoint: Option<PathBuf>,
+    /// Skip the rows already recorded in the checkpoint, which must have
+    /// been recorded with the same options
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+    /// Overwrite the checkpoint if it exists
+    #[arg(long, requires = "checkpoint", conflicts_with = "resume")]
+    force: bool,
+    /// Number of rows estimated in parallel [default: available parallelism]
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+}
+
+/// Logical resources of the algorithm.
+#[derive(Subcommand)]
+enum Input {
+    /// Read Q# files, run in sequence
     File {
-        /// Path to the Q# file
+        /// Paths to the Q# files, whose entry points run in sequence
+        #[arg(required = true)]
+        filenames: Vec<String>,
+        /// Conversion of the non-native gates (simplified or bias-preserving)
+        #[arg(long, default_value_t = GateSet::simplified())]
+        gate_set: GateSet,
+    },
+    /// Import the leaf bloq counts exported from Qualtran as JSON
+    Qualtran {
+        /// Path to the JSON export
+        filename: String,
+        /// Conversion of the non-native bloqs (simplified or bias-preserving)
+        #[arg(long, default_value_t = GateSet::simplified())]
+        gate_set: GateSet,
+    },
+    /// Import a Stim circuit, its qubits being counted as logical qubits
+    Stim {
+        /// Path to the .stim circuit
+        filename: String,
+        /// Conversion of the non-native gates (simplified or bias-preserving)
+        #[arg(long, default_value_t = GateSet::simplified())]
+        gate_set: GateSet,
+    },
+    /// Import a resource summary of pyLIQTR (or `OpenQASM` tools) as JSON
+    Pyliqtr {
+        /// Path to the JSON summary
         filename: String,
+        /// Conversion of the T gates, including the ones of the rotations
+        /// synthesized with --rotation-precision, into CCX (and, toffoli,
+        /// catalyzed, or a number of T gates per CCX)
+        #[arg(long, default_value_t = TConversion::and())]
+        t_conversion: TConversion,
     },
     /// Compute from listed resources
     Resources {
@@ -56,41 +1111,1219 @@ enum Commands {
         cx: u64,
         /// Number of Toffoli gates
         ccx: u64,
+        /// Logical depth in logical cycles, e.g. the parallel depth given by
+        /// another compiler [default: CX and CCX gates run sequentially]
+        #[arg(long)]
+        depth: Option<u64>,
+        /// Number of measurements
+        #[arg(long, default_value_t = 0)]
+        measurements: u64,
+        /// Number of measurements followed by a gate waiting for their outcome
+        #[arg(long, default_value_t = 0)]
+        feed_forwards: u64,
     },
 }
 
-fn main() -> Result<(), anyhow::Error> {
-    let args = Cli::parse();
+impl Input {
+    /// Logical counts, counted from Q# if needed.
+    fn counts(
+        &self,
+        cost_model: CostModel,
+        counting: &QsharpCounting,
+    ) -> Result<LogicalCounts, anyhow::Error> {
+        self.phased_counts(cost_model, counting)
+            .map(|(counts, _)| counts)
+    }
+
+    /// Logical counts, with the counts of the phases run in sequence as
+    /// segments (each Q# file, or a single phase).
+    fn phased_counts(
+        &self,
+        cost_model: CostModel,
+        counting: &QsharpCounting,
+    ) -> Result<(LogicalCounts, SegmentedCounts), anyhow::Error> {
+        let single = |counts: Result<LogicalCounts, String>| {
+            counts
+                .map(|counts| vec![("program".to_string(), counts)])
+                .map_err(anyhow::Error::msg)
+        };
+        let phases = match self {
+            Self::File {
+                filenames,
+                gate_set,
+            } => {
+                let gate_set = cost_model.gate_set(gate_set.clone())?;
+                filenames
+                    .iter()
+                    .zip(phase_names(filenames))
+                    .map(|(filename, name)| {
+                        count_qsharp(filename.clone(), gate_set.clone(), counting.clone())
+                            .map(|counts| (name, counts))
+                    })
+                    .collect()
+            }
+            Self::Qualtran { filename, gate_set } => {
+                single(LogicalCounts::from_qualtran_with_gate_set(
+                    filename,
+                    cost_model.gate_set(gate_set.clone())?,
+                ))
+            }
+            Self::Stim { filename, gate_set } => single(LogicalCounts::from_stim_with_gate_set(
+                filename,
+                cost_model.gate_set(gate_set.clone())?,
+            )),
+            Self::Pyliqtr {
+                filename,
+                t_conversion,
+            } => single(LogicalCounts::from_pyliqtr(
+                filename,
+                *t_conversion,
+                cost_model.rotation_synthesis(*t_conversion)?,
+            )),
+            Self::Resources {
+                qubits,
+                cx,
+                ccx,
+                depth,
+                measurements,
+                feed_forwards,
+            } => {
+                let counts = LogicalCounts::new(*qubits, *cx, *ccx)
+                    .with_measurements(*measurements, *feed_forwards);
+                single(Ok(match depth {
+                    Some(depth) => counts.with_depth(*depth),
+                    None => counts,
+                }))
+            }
+        }
+        .map_err(Failure::compile)?
+        .into_iter()
+        .map(|(name, counts)| Ok((name, cost_model.apply(counts)?)))
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        let phases = SegmentedCounts::new(phases)
+            .and_then(|phases| Ok((phases.merged()?, phases)))
+            .map_err(anyhow::Error::msg)
+            .context(Failure::Compile)?;
+        Ok(phases)
+    }
+
+    /// Description of the input, and counts carrying its gate set (without
+    /// counting).
+    fn assumptions(
+        &self,
+        cost_model: CostModel,
+    ) -> Result<(ReportSection, LogicalCounts), anyhow::Error> {
+        let (section, counts) = match self {
+            Self::File {
+                filenames,
+                gate_set,
+            } => (
+                filenames
+                    .iter()
+                    .map(|filename| ("Q# file", filename.clone()))
+                    .collect(),
+                LogicalCounts::qsharp_counter(cost_model.gate_set(gate_set.clone())?),
+            ),
+            Self::Qualtran { filename, gate_set } => (
+                vec![("Qualtran export", filename.clone())],
+                LogicalCounts::qsharp_counter(cost_model.gate_set(gate_set.clone())?),
+            ),
+            Self::Stim { filename, gate_set } => (
+                vec![("Stim circuit", filename.clone())],
+                LogicalCounts::qsharp_counter(cost_model.gate_set(gate_set.clone())?),
+            ),
+            Self::Pyliqtr {
+                filename,
+                t_conversion,
+            } => (
+                vec![
+                    ("pyLIQTR summary", filename.clone()),
+                    (
+                        "T conversion",
+                        format!("{t_conversion} ({} T per CCX)", t_conversion.t_per_ccx()),
+                    ),
+                ],
+                LogicalCounts::qsharp_counter(
+                    GateSet::default()
+                        .with_rotation_synthesis(cost_model.rotation_synthesis(*t_conversion)?),
+                ),
+            ),
+            Self::Resources {
+                qubits,
+                cx,
+                ccx,
+                depth,
+                measurements,
+                feed_forwards,
+            } => {
+                let counts = LogicalCounts::new(*qubits, *cx, *ccx)
+                    .with_measurements(*measurements, *feed_forwards);
+                (
+                    vec![
+                        ("logical qubits", qubits.to_string()),
+                        ("logical CX", cx.to_string()),
+                        ("logical CCX", ccx.to_string()),
+                        ("measurements", measurements.to_string()),
+                        ("feed-forwards", feed_forwards.to_string()),
+                        (
+                            "logical depth",
+                            depth.map_or_else(|| "sequential".into(), |depth| depth.to_string()),
+                        ),
+                    ],
+                    match depth {
+                        Some(depth) => counts.with_depth(*depth),
+                        None => counts,
+                    },
+                )
+            }
+        };
+        Ok((section, cost_model.apply(counts)?))
+    }
+}
+
+mod config;
+mod interactive;
+
+/// Spinner on stderr (hidden if stderr is not a terminal).
+fn spinner(message: &'static str) -> ProgressBar {
+    let spinner = ProgressBar::new_spinner().with_message(message);
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// Input of the dry run, and its logical counts.
+fn dry_run_input(args: &Cli) -> Result<(ReportSection, LogicalCounts), anyhow::Error> {
+    Ok(match &args.command {
+        Commands::Input(input) | Commands::Advise { input } => {
+            input.assumptions(args.cost_model)?
+        }
+        Commands::CompareBaseline(CompareBaselineArgs {
+            baseline, input, ..
+        }) => {
+            let (mut input, counts) = input.assumptions(args.cost_model)?;
+            input.push(("baseline", baseline.display().to_string()));
+            (input, counts)
+        }
+        Commands::Roadmap(RoadmapArgs {
+            schedule, input, ..
+        }) => {
+            let (mut input, counts) = input.assumptions(args.cost_model)?;
+            input.push(("roadmap", schedule.display().to_string()));
+            (input, counts)
+        }
+        Commands::Sweep(SweepArgs { options, input }) => {
+            let (mut input, counts) = input.assumptions(args.cost_model)?;
+            input.push(("parameter sweep", options.from.display().to_string()));
+            (input, counts)
+        }
+        Commands::Interactive {
+            filename: Some(filename),
+            gate_set,
+        } => (
+            vec![("Q# file", filename.clone())],
+            args.cost_model.apply(LogicalCounts::qsharp_counter(
+                args.cost_model.gate_set(gate_set.clone())?,
+            ))?,
+        ),
+        Commands::Interactive { filename: None, .. } | Commands::Config(_) => {
+            (vec![("input", "none".into())], LogicalCounts::default())
+        }
+        Commands::Diff { before, after } => (
+            vec![
+                ("before", before.display().to_string()),
+                ("after", after.display().to_string()),
+            ],
+            args.cost_model.apply(LogicalCounts::default())?,
+        ),
+    })
+}
+
+/// Resolved assumptions of an estimation, in the text layout of the estimates.
+fn dry_run(
+    args: &Cli,
+    qubit: &CatQubit,
+    qec: &RepetitionCode,
+    builder: &ToffoliBuilder,
+    budget: &ErrorBudget,
+    restart: Option<HeraldedRestart>,
+) -> Result<String, anyhow::Error> {
+    let (input, counts) = dry_run_input(args)?;
+    let (leakage_prefactor, leakage_threshold) = qec.leakage_fit();
+    let (measurement_prefactor, measurement_threshold) = qec.measurement_fit();
+    let sections = [
+        input,
+        Provenance::new(qubit, qec, builder, &counts)
+            .with_model(&args.model())
+            .report_section(),
+        vec![
+            (
+                "leakage fit",
+                format!("{leakage_prefactor:e} · (p / {leakage_threshold})^((d+1)/2)"),
+            ),
+            (
+                "measurement fit",
+                format!("{measurement_prefactor:e} · (p / {measurement_threshold})^((d+1)/2)"),
+            ),
+            ("max distance", MAX_DISTANCE.to_string()),
+            ("max |α|² (search)", MAX_ALPHA_SQ.to_string()),
+        ],
+        builder
+            .factories()
+            .map(|factory| {
+                (
+                    "factory",
+                    format!(
+                        "{factory}, error {:.2e}, acceptance {}",
+                        factory.error_probability(),
+                        factory.acceptance_probability()
+                    ),
+                )
+            })
+            .collect(),
+        vec![
+            ("topological error", format!("{:.3e}", budget.logical())),
+            (
+                "magic state error",
+                format!("{:.3e}", budget.magic_states()),
+            ),
+            ("rotation error", format!("{:.3e}", budget.rotations())),
+            (
+                "error target",
+                args.budget.error_target.map_or_else(
+                    || "none".into(),
+                    |target| format!("{target:.3e} (split searched)"),
+                ),
+            ),
+            (
+                "heralded fraction",
+                restart.map_or_else(
+                    || "none".into(),
+                    |restart| restart.heralded_fraction().to_string(),
+                ),
+            ),
+            (
+                "success probability",
+                args.success_probability.map_or_else(
+                    || "1 (no retry)".into(),
+                    |retry| retry.success_probability().to_string(),
+                ),
+            ),
+            ("frontier", args.frontier.to_string()),
+            ("error frontier", args.error_frontier.to_string()),
+            ("mixed factories", args.layout.mixed_factories.to_string()),
+            ("layout", args.layout.description()),
+        ],
+    ];
+
+    let separator = "─────────────────────────────\n";
+    let line = |label: &str, value: &str| format!("{:<21}{value}\n", format!("{label}:"));
+    let mut text = format!("\n{separator}");
+    for section in sections {
+        for (label, value) in section {
+            text.push_str(&line(label, &value));
+        }
+        text.push_str(separator);
+    }
+    Ok(text)
+}
+
+/// Counts of the Q# file of the interactive session, if any.
+fn interactive_counts(
+    filename: Option<String>,
+    gate_set: GateSet,
+    cost_model: CostModel,
+    counting: QsharpCounting,
+) -> Result<Option<LogicalCounts>, anyhow::Error> {
+    filename
+        .map(|filename| {
+            cost_model.apply(count_qsharp(
+                filename,
+                cost_model.gate_set(gate_set)?,
+                QsharpCounting {
+                    outcomes: None,
+                    ..counting
+                },
+            )?)
+        })
+        .transpose()
+}
 
-    let qubit = CatQubit::new();
-    let qec = RepetitionCode::new();
-    let builder = ToffoliBuilder::default();
-    let budget = match (args.budget.error_total, args.budget.error_budget) {
-        (Some(proba), None) => ErrorBudget::new(proba * 0.5, proba * 0.5, 0.0),
-        (None, Some(vec)) => ErrorBudget::new(vec[0], vec[1], vec[2]),
-        // TODO: give default handling to clap.
-        (None, None) => ErrorBudget::new(0.333 * 0.5, 0.333 * 0.5, 0.0),
-        _ => unreachable!("Clap should have caught that!"),
+/// Error budget per run splitting the total error `target` between the
+/// topological and magic state errors so as to minimize the space-time volume
+/// (physical qubits × runtime) of the estimate.
+fn target_budget(
+    target: f64,
+    restart: Option<HeraldedRestart>,
+    qubit: &CatQubit,
+    qec: &RepetitionCode,
+    builder: &ToffoliBuilder,
+    counts: &LogicalCounts,
+) -> Result<ErrorBudget, anyhow::Error> {
+    let per_run = |budget: &ErrorBudget| match restart {
+        Some(restart) => restart.budget_per_run(budget),
+        None => ErrorBudget::new(budget.logical(), budget.magic_states(), budget.rotations()),
     };
+    let spinner = spinner("searching the error budget split");
+    let split = budget::optimal_error_budget(target, |budget| {
+        let result = PhysicalResourceEstimation::new(
+            qec.clone(),
+            Rc::new(qubit.clone()),
+            builder.clone(),
+            Rc::new(counts.clone()),
+            budget::budget_for_counts(&per_run(budget), counts).ok()?,
+        )
+        .estimate()
+        .ok()?;
+        Some(result.physical_qubits().to_f64()? * result.runtime().to_f64()?)
+    });
+    spinner.finish_and_clear();
+    let split = split.map_err(anyhow::Error::msg)?;
+    tracing::info!(
+        topological = split.logical(),
+        magic_states = split.magic_states(),
+        "error budget split"
+    );
+    Ok(per_run(&split))
+}
 
-    let count = match args.command {
-        Commands::File { filename } => {
-            LogicalCounts::from_qsharp(filename).map_err(anyhow::Error::msg)?
+/// Print the advice, showing a spinner while estimating the sensitivities.
+fn advise(advice: impl FnOnce() -> Result<Advice, String>) -> Result<(), anyhow::Error> {
+    let spinner = spinner("estimating the sensitivities");
+    let advice = advice();
+    spinner.finish_and_clear();
+    print!("{}", advice.map_err(anyhow::Error::msg)?);
+    Ok(())
+}
+
+/// Print the projection of an algorithm along the roadmap read from
+/// `schedule`.
+fn project(
+    schedule: &Path,
+    max_qubits: Option<u64>,
+    estimate: impl FnOnce(&Roadmap) -> Projection,
+) -> Result<(), anyhow::Error> {
+    let content = fs::read_to_string(schedule)
+        .with_context(|| format!("can't read {}", schedule.display()))?;
+    let roadmap = Roadmap::from_toml(&content)
+        .map_err(anyhow::Error::msg)
+        .with_context(|| format!("invalid roadmap {}", schedule.display()))?;
+    let spinner = spinner("estimating each milestone");
+    let projection = estimate(&roadmap);
+    spinner.finish_and_clear();
+    print!("{projection}");
+    if let Some(max_qubits) = max_qubits {
+        match projection.first_feasible(max_qubits) {
+            Some(point) => println!(
+                "fits in {max_qubits} physical qubits from {}",
+                point.milestone.year
+            ),
+            None => {
+                println!("does not fit in {max_qubits} physical qubits by the end of the roadmap");
+            }
         }
-        Commands::Resources { qubits, cx, ccx } => LogicalCounts::new(qubits, cx, ccx),
+    }
+    Ok(())
+}
+
+/// Estimate the rows of the parameter sweep read from `--from` in parallel,
+/// writing them with their results to `--to` (the standard output by
+/// default), and recording them in the checkpoint if any.
+fn sweep(
+    options: &SweepOptions,
+    code: &(dyn Fn() -> Result<RepetitionCode, String> + Sync),
+    qubit: &CatQubit,
+    builder: &ToffoliBuilder,
+    counts: &LogicalCounts,
+    budget: &ErrorBudget,
+) -> Result<(), anyhow::Error> {
+    let sweep = ParameterSweep::from_file(&options.from).map_err(anyhow::Error::msg)?;
+    let mut checkpoint = options
+        .checkpoint
+        .as_ref()
+        .map(|path| {
+            let mode = match (options.resume, options.force) {
+                (true, _) => OpenMode::Resume,
+                (false, true) => OpenMode::Overwrite,
+                (false, false) => OpenMode::Create,
+            };
+            // the rows are recorded with their cells, the other options
+            // must not change
+            let fingerprint = serde_json::json!({
+                "provenance": Provenance::new(qubit, &code()?, builder, counts),
+                "counts": counts,
+                "budget": [budget.logical(), budget.magic_states(), budget.rotations()],
+            });
+            Checkpoint::open(path, &fingerprint, mode)
+        })
+        .transpose()
+        .map_err(anyhow::Error::msg)?;
+    let workers = SweepWorkers {
+        jobs: options.jobs.unwrap_or_else(pool::available_jobs),
+        code,
     };
-    let estimation =
-        PhysicalResourceEstimation::new(qec, Rc::new(qubit), builder, Rc::new(count), budget);
+    let spinner = spinner("estimating each row");
+    let results = sweep
+        .run_parallel(workers, qubit, builder, counts, budget, checkpoint.as_mut())
+        .map(|results| results.to_string());
+    spinner.finish_and_clear();
+    let results = results.map_err(anyhow::Error::msg)?;
+    if let Some(to) = &options.to {
+        fs::write(to, results).with_context(|| format!("can't write {}", to.display()))
+    } else {
+        print!("{results}");
+        Ok(())
+    }
+}
+
+/// Version of an algorithm saved as JSON.
+enum Saved {
+    /// First estimate of a [`ResultDocument`].
+    Estimate(Box<EstimateRecord>),
+    /// Logical counts, to estimate.
+    Counts(Box<LogicalCounts>),
+}
+
+impl Saved {
+    fn read(filename: &Path) -> Result<Self, anyhow::Error> {
+        let content = fs::read_to_string(filename)
+            .with_context(|| format!("can't read {}", filename.display()))?;
+        if let Ok(document) = serde_json::from_str::<ResultDocument>(&content) {
+            let estimate = document
+                .estimates
+                .into_iter()
+                .next()
+                .with_context(|| format!("no estimate in {}", filename.display()))?;
+            return Ok(Self::Estimate(Box::new(estimate)));
+        }
+        serde_json::from_str(&content)
+            .map(Self::Counts)
+            .with_context(|| {
+                format!(
+                    "{} is neither saved estimates nor logical counts",
+                    filename.display()
+                )
+            })
+    }
+}
 
-    if args.frontier {
-        let results = estimation.build_frontier()?;
-        for r in results {
-            println!("{}", AliceAndBobEstimates::from(r));
+/// Compare two saved versions of an algorithm, estimating the logical counts.
+fn diff(
+    before: &Path,
+    after: &Path,
+    cost_model: CostModel,
+    estimate: impl Fn(&LogicalCounts, &LogicalCounts) -> Result<Diff, String>,
+) -> Result<Diff, anyhow::Error> {
+    match (Saved::read(before)?, Saved::read(after)?) {
+        (Saved::Estimate(before), Saved::Estimate(after)) => Ok(Diff::records(&before, &after)),
+        (Saved::Counts(before), Saved::Counts(after)) => {
+            let spinner = spinner("estimating both versions");
+            let diff = estimate(&cost_model.apply(*before)?, &cost_model.apply(*after)?);
+            spinner.finish_and_clear();
+            diff.map_err(anyhow::Error::msg)
         }
+        _ => anyhow::bail!("compare either two saved estimates or two logical counts"),
+    }
+}
+
+/// Warn about the extrapolated estimates and the ones over their error budget,
+/// and drop the extrapolated ones in strict mode (failing if none is left).
+fn check_validity(
+    results: Vec<AliceAndBobEstimates>,
+    strict: bool,
+) -> Result<Vec<AliceAndBobEstimates>, anyhow::Error> {
+    for estimates in &results {
+        for extrapolation in estimates.extrapolations() {
+            tracing::warn!(%extrapolation, "estimate relies on an extrapolated model");
+        }
+        if let Some(excess) = estimates.budget_excess() {
+            tracing::warn!(
+                total_error = excess.total_error,
+                budget = excess.budget,
+                excess_factor = excess.factor(),
+                corrected_budget = excess.corrected_budget(),
+                "{excess}"
+            );
+        }
+    }
+    if !strict {
+        return Ok(results);
+    }
+    let rejected = results
+        .iter()
+        .find_map(|estimates| validity::ensure_valid(estimates).err());
+    let valid: Vec<_> = results
+        .into_iter()
+        .filter(|estimates| validity::ensure_valid(estimates).is_ok())
+        .collect();
+    match rejected {
+        Some(err) if valid.is_empty() => {
+            Err(anyhow::anyhow!("rejected in strict mode: {err}")
+                .context(Failure::ConstraintViolation))
+        }
+        _ => Ok(valid),
+    }
+}
+
+/// Kind of failure, telling wrappers why the estimation failed by the exit
+/// code of the process. Errors are tagged with their kind as context, an
+/// untagged error being an internal error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Failure {
+    /// The command line is invalid, either when parsed (reported by clap) or
+    /// once its options are combined (e.g. κ₁/κ₂ out of range).
+    Usage,
+    /// The program can't be compiled or counted.
+    Compile,
+    /// The error budget is invalid or can't be met.
+    InfeasibleBudget,
+    /// A constraint is violated: a counting limit, or no estimate valid in
+    /// strict mode.
+    ConstraintViolation,
+    /// The estimate regressed from its baseline (`compare-baseline`).
+    Regression,
+    /// The estimation was cancelled by Ctrl-C.
+    Cancelled,
+    /// Any other error, e.g. an unreadable file.
+    Internal,
+}
+
+impl Failure {
+    /// Kind of failure of an error.
+    fn of(err: &anyhow::Error) -> Self {
+        err.downcast_ref().copied().unwrap_or(Self::Internal)
+    }
+
+    /// Tag an error of the input as a compile error, unless it is already
+    /// tagged (e.g. a cancelled counting).
+    fn compile(err: anyhow::Error) -> anyhow::Error {
+        match Self::of(&err) {
+            Self::Internal => err.context(Self::Compile),
+            _ => err,
+        }
+    }
+
+    /// Kind of failure of a counting from Q#.
+    fn of_counting(err: &CountingError) -> Self {
+        match err {
+            CountingError::Program(_) => Self::Compile,
+            CountingError::Cancelled(_) => Self::Cancelled,
+            CountingError::LimitExceeded(_) => Self::ConstraintViolation,
+        }
+    }
+
+    fn exit_code(self) -> u8 {
+        match self {
+            Self::Internal => 1,
+            Self::Usage => 2,
+            Self::Compile => 3,
+            Self::InfeasibleBudget => 4,
+            Self::ConstraintViolation => 5,
+            Self::Regression => 6,
+            Self::Cancelled => 130,
+        }
+    }
+
+    fn kind(self) -> &'static str {
+        match self {
+            Self::Usage => "usage_error",
+            Self::Compile => "compile_error",
+            Self::InfeasibleBudget => "infeasible_budget",
+            Self::ConstraintViolation => "constraint_violation",
+            Self::Regression => "regression",
+            Self::Cancelled => "cancelled",
+            Self::Internal => "internal_error",
+        }
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Usage => "invalid command line",
+            Self::Compile => "the program can't be counted",
+            Self::InfeasibleBudget => "infeasible error budget",
+            Self::ConstraintViolation => "constraint violated",
+            Self::Regression => "regression from the baseline",
+            Self::Cancelled => "cancelled",
+            Self::Internal => "internal error",
+        })
+    }
+}
+
+/// Print the error, as a [`FailureDocument`] with `--output json`, and
+/// return the exit code of its kind of failure.
+fn report_failure(err: &anyhow::Error, output: OutputFormat) -> ExitCode {
+    let failure = Failure::of(err);
+    let document = FailureDocument::new(FailureRecord {
+        kind: failure.kind().to_string(),
+        exit_code: failure.exit_code(),
+        message: format!("{err:#}"),
+    });
+    match serde_json::to_string_pretty(&document) {
+        Ok(json) if matches!(output, OutputFormat::Json) => println!("{json}"),
+        _ => eprintln!("Error: {err:?}"),
+    }
+    ExitCode::from(failure.exit_code())
+}
+
+/// First Ctrl-C stops the counting or the search cleanly, second one exits.
+fn cancel_on_ctrl_c(cancellation: CancellationToken) -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(move || {
+        if cancellation.is_cancelled() {
+            std::process::exit(130);
+        }
+        cancellation.cancel();
+    })
+}
+
+/// Sampling of the measurement outcomes of Q# programs.
+#[derive(Clone, Copy)]
+struct OutcomeSampling {
+    samples: usize,
+    seed: u64,
+}
+
+/// Counting of Q# programs.
+#[derive(Clone)]
+struct QsharpCounting {
+    /// Sampling of the measurement outcomes, if asked for.
+    outcomes: Option<OutcomeSampling>,
+    /// Limits of the counting.
+    limits: CountingLimits,
+    /// Cancellation of the counting (on Ctrl-C).
+    cancellation: CancellationToken,
+}
+
+/// Names of the phases of the Q# `filenames`: their stems, or the file names
+/// as given when several files have the same stem.
+fn phase_names(filenames: &[String]) -> Vec<String> {
+    let stem = |filename: &String| {
+        Path::new(filename)
+            .file_stem()
+            .map_or_else(|| filename.clone(), |stem| stem.to_string_lossy().into())
+    };
+    let stems: Vec<_> = filenames.iter().map(stem).collect();
+    stems
+        .iter()
+        .zip(filenames)
+        .map(|(name, filename)| {
+            if stems.iter().filter(|other| *other == name).count() > 1 {
+                filename.clone()
+            } else {
+                name.clone()
+            }
+        })
+        .collect()
+}
+
+/// Quantile of the logical depth of the sampled counts which is estimated.
+const OUTCOME_QUANTILE: f64 = 0.9;
+
+/// Count the logical resources of a Q# file, showing the progress, possibly
+/// sampling the measurement outcomes (the distribution being reported on
+/// stderr).
+fn count_qsharp(
+    filename: String,
+    gate_set: GateSet,
+    counting: QsharpCounting,
+) -> Result<LogicalCounts, anyhow::Error> {
+    let spinner = spinner("counting logical resources");
+    let progress = spinner.clone();
+    let counter = counting
+        .limits
+        .apply(LogicalCounts::qsharp_counter(gate_set))
+        .with_progress(move |c| {
+            progress.set_message(format!(
+                "counting logical resources: {} qubits, {} CX, {} CCX",
+                c.qubit_count(),
+                c.cx_count(),
+                c.ccx_count()
+            ));
+        })
+        .with_cancellation(counting.cancellation);
+    let count = match counting.outcomes {
+        Some(OutcomeSampling { samples, seed }) => counter
+            .sample_qsharp(filename, samples, seed)
+            .map(|sampled| {
+                spinner.suspend(|| eprint!("{sampled}"));
+                sampled.at_quantile(OUTCOME_QUANTILE).clone()
+            }),
+        None => counter.count_qsharp(filename),
+    };
+    spinner.finish_and_clear();
+    count.map_err(|err| {
+        let failure = Failure::of_counting(&err);
+        anyhow::Error::new(err).context(failure)
+    })
+}
+
+/// Print the estimates (or their comparison with the baseline), and write the
+/// HTML report and the trace of the searches if asked for.
+fn write(
+    results: &[AliceAndBobEstimates],
+    args: &Cli,
+    trace: Option<(&Path, &SearchTrace)>,
+) -> Result<(), anyhow::Error> {
+    let outcome = if let Commands::CompareBaseline(compare) = &args.command {
+        compare.check(results)
     } else {
-        let result: AliceAndBobEstimates = estimation.estimate()?.into();
-        println!("{result}");
+        print!("{}", args.output.render(results));
+        Ok(())
+    };
+    if let Some(path) = &args.report {
+        fs::write(path, html_report(results))
+            .with_context(|| format!("can't write the report to {}", path.display()))?;
+    }
+    if let Some((path, trace)) = trace {
+        fs::write(path, trace.to_csv())
+            .with_context(|| format!("can't write the search trace to {}", path.display()))?;
     }
+    outcome
+}
 
-    Ok(())
+/// Single estimate, frontier, or frontier over the error budgets of
+/// `estimation`.
+fn search(
+    estimation: &Estimation,
+    frontier: bool,
+    error_frontier: bool,
+) -> Result<Vec<EstimationResult>, resource_estimator::estimates::Error> {
+    let spinner = spinner(if frontier || error_frontier {
+        "building the frontier"
+    } else {
+        "searching code parameters and factories"
+    });
+    let _span = tracing::info_span!("estimate", frontier, error_frontier).entered();
+    let results = if error_frontier {
+        frontier::build_error_frontier(estimation)
+    } else if frontier {
+        estimation.build_frontier()
+    } else {
+        estimation.estimate().map(|r| vec![r])
+    };
+    spinner.finish_and_clear();
+    results
+}
+
+fn main() -> ExitCode {
+    let args = parse_cli();
+    init_tracing(args.verbose);
+    let result = match &args.command {
+        Commands::Config(command) => command.run(),
+        _ => run(&args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_failure(&err, args.output),
+    }
+}
+
+fn run(args: &Cli) -> Result<(), anyhow::Error> {
+    let cancellation = CancellationToken::new();
+    if !matches!(args.command, Commands::Interactive { .. }) {
+        cancel_on_ctrl_c(cancellation.clone())?;
+    }
+
+    let qubit = args.qubit().context(Failure::Usage)?;
+    let qec = args.code(&cancellation).context(Failure::Usage)?;
+    let builder = args.builder().context(Failure::Usage)?;
+    let (budget, restart) = args.budget_per_run().context(Failure::InfeasibleBudget)?;
+    if args.dry_run {
+        print!(
+            "{}",
+            dry_run(args, &qubit, &qec, &builder, &budget, restart)?
+        );
+        return Ok(());
+    }
+
+    let cost_model = args.cost_model;
+    let (seed, counting) = args.seed_and_counting(&cancellation);
+    let (count, phases) = match &args.command {
+        Commands::Input(input) | Commands::CompareBaseline(CompareBaselineArgs { input, .. }) => {
+            input.phased_counts(cost_model, &counting)?
+        }
+        Commands::Interactive { filename, gate_set } => {
+            let counts =
+                interactive_counts(filename.clone(), gate_set.clone(), cost_model, counting)?;
+            return Session::new(qubit, qec, builder, budget, counts, args.units, args.output)
+                .run();
+        }
+        Commands::Advise { input } => {
+            let counts = input.counts(cost_model, &counting)?;
+            return advise(|| Advice::new(&qubit, &qec, &builder, &counts, &budget));
+        }
+        Commands::Diff { before, after } => {
+            let diff = diff(before, after, cost_model, |before, after| {
+                Diff::new(&qubit, &qec, &builder, &budget, before, after)
+            });
+            return diff.map(|diff| print!("{diff}"));
+        }
+        Commands::Roadmap(roadmap) => {
+            let counts = roadmap.input.counts(cost_model, &counting)?;
+            return project(&roadmap.schedule, roadmap.max_qubits, |roadmap| {
+                roadmap.project(&qubit, &qec, &builder, &counts, &budget)
+            });
+        }
+        Commands::Sweep(SweepArgs { options, input }) => {
+            let counts = input.counts(cost_model, &counting)?;
+            let code = || args.code(&cancellation).map_err(|err| format!("{err:#}"));
+            return sweep(options, &code, &qubit, &builder, &counts, &budget);
+        }
+        Commands::Config(_) => unreachable!("configuration commands are run by main"),
+    };
+    let budget = match args.budget.error_target {
+        Some(target) => target_budget(target, restart, &qubit, &qec, &builder, &count),
+        None => Ok(budget),
+    }
+    .and_then(|budget| budget::budget_for_counts(&budget, &count).map_err(anyhow::Error::msg))
+    .context(Failure::InfeasibleBudget)?;
+    builder
+        .check_budget(&count, &budget)
+        .map_err(anyhow::Error::msg)
+        .context(Failure::InfeasibleBudget)?;
+    let provenance = Provenance::new(&qubit, &qec, &builder, &count).with_model(&args.model());
+    let qubit = Rc::new(qubit);
+    let estimation = PhysicalResourceEstimation::new(
+        qec.clone(),
+        qubit.clone(),
+        builder.clone(),
+        Rc::new(count),
+        budget,
+    );
+
+    let wrap = |r| {
+        let estimates = AliceAndBobEstimates::new(r, &qec)
+            .with_time_unit(args.units)
+            .with_provenance(provenance.clone())
+            .with_validity_check(&ValidityRange::default(), &qubit);
+        args.repeated(
+            args.layout.apply(estimates, &builder, &qubit, &phases),
+            restart,
+            seed,
+        )
+    };
+    let results = search(&estimation, args.frontier, args.error_frontier);
+    if cancellation.is_cancelled() {
+        return Err(anyhow::anyhow!("estimation cancelled").context(Failure::Cancelled));
+    }
+    let results = results.context(Failure::InfeasibleBudget)?;
+    let results: Vec<_> = results.into_iter().map(wrap).collect();
+    let results = check_validity(
+        if args.error_frontier {
+            frontier::pareto_front(results)
+        } else {
+            results
+        },
+        args.strict || validity::STRICT,
+    )?;
+    let trace = args.search_trace.as_deref().zip(qec.search_trace());
+    write(&results, args, trace)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::OsString, path::PathBuf};
+
+    use clap::{error::ErrorKind, CommandFactory, Parser};
+
+    use super::{
+        config, parse_gate_count, parse_non_negative, phase_names, units, with_defaults, Cli,
+        CountingError, Failure,
+    };
+
+    #[test]
+    fn failures_have_their_exit_code_and_kind() {
+        for (failure, exit_code, kind) in [
+            (Failure::Internal, 1, "internal_error"),
+            (Failure::Usage, 2, "usage_error"),
+            (Failure::Compile, 3, "compile_error"),
+            (Failure::InfeasibleBudget, 4, "infeasible_budget"),
+            (Failure::ConstraintViolation, 5, "constraint_violation"),
+            (Failure::Regression, 6, "regression"),
+            (Failure::Cancelled, 130, "cancelled"),
+        ] {
+            assert_eq!(failure.exit_code(), exit_code, "{failure:?}");
+            assert_eq!(failure.kind(), kind, "{failure:?}");
+            let err = anyhow::anyhow!("failed").context(failure);
+            assert_eq!(Failure::of(&err), failure);
+            // the kind is kept through further context
+            assert_eq!(Failure::of(&err.context("while estimating")), failure);
+        }
+        assert_eq!(Failure::of(&anyhow::anyhow!("failed")), Failure::Internal);
+    }
+
+    #[test]
+    fn compile_errors_keep_their_kind() {
+        let err = Failure::compile(anyhow::anyhow!("syntax error"));
+        assert_eq!(Failure::of(&err), Failure::Compile);
+        let err = Failure::compile(anyhow::anyhow!("stopped").context(Failure::Cancelled));
+        assert_eq!(Failure::of(&err), Failure::Cancelled);
+    }
+
+    #[test]
+    fn counting_errors_are_typed() {
+        for (err, failure) in [
+            (
+                CountingError::Program("no entry point".into()),
+                Failure::Compile,
+            ),
+            (
+                CountingError::Cancelled("cancelled".into()),
+                Failure::Cancelled,
+            ),
+            (
+                CountingError::LimitExceeded("counting exceeded 1e9 gates".into()),
+                Failure::ConstraintViolation,
+            ),
+        ] {
+            assert_eq!(Failure::of_counting(&err), failure, "{err}");
+        }
+    }
+
+    #[test]
+    fn phases_are_named_after_their_files() {
+        let filenames = ["a/modexp.qs", "qft.qs", "b/x.qs", "c/x.qs"].map(String::from);
+        assert_eq!(
+            phase_names(&filenames),
+            ["modexp", "qft", "b/x.qs", "c/x.qs"]
+        );
+    }
+
+    #[test]
+    fn tolerances_may_exceed_one() {
+        assert_eq!(parse_non_negative("0"), Ok(0.0));
+        assert_eq!(parse_non_negative("2.5"), Ok(2.5));
+        for text in ["-0.1", "inf", "NaN", "5%"] {
+            assert!(parse_non_negative(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn gate_counts_may_use_scientific_notation() {
+        assert_eq!(parse_gate_count("1000"), Ok(1000));
+        assert_eq!(parse_gate_count("1e12"), Ok(1_000_000_000_000));
+        for text in ["1.5", "-1", "1e30", "many"] {
+            assert!(parse_gate_count(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn count_timeouts_are_durations() {
+        let resources = ["resources", "10", "100", "100"];
+        let cli = Cli::try_parse_from(
+            ["estimator", "--count-timeout=10 min", "--max-gates=1e9"]
+                .into_iter()
+                .chain(resources),
+        );
+        assert!(
+            cli.is_ok_and(|cli| cli.limits.max_gates == Some(1_000_000_000)
+                && cli.limits.count_timeout.map(units::TimeSpan::as_ns) == Some(600_000_000_000))
+        );
+        let err = Cli::command()
+            .try_get_matches_from(["estimator", "--count-timeout=soon"])
+            .err();
+        assert!(err.is_some_and(|err| err.kind() == ErrorKind::ValueValidation));
+    }
+
+    #[test]
+    fn negative_thermal_photons_are_rejected() {
+        let err = Cli::command()
+            .try_get_matches_from(["estimator", "--thermal-photons=-0.5"])
+            .err();
+        assert!(err.is_some_and(|err| err.kind() == ErrorKind::ValueValidation));
+    }
+
+    #[test]
+    fn max_alpha_sq_is_positive() {
+        for max_alpha_sq in ["0", "-4", "NaN"] {
+            let err = Cli::command()
+                .try_get_matches_from(["estimator", &format!("--max-alpha-sq={max_alpha_sq}")])
+                .err();
+            assert!(
+                err.is_some_and(|err| err.kind() == ErrorKind::ValueValidation),
+                "{max_alpha_sq}"
+            );
+        }
+    }
+
+    #[test]
+    fn leakage_rates_are_probabilities() {
+        for rate in ["-1e-3", "1.5"] {
+            let err = Cli::command()
+                .try_get_matches_from(["estimator", &format!("--leakage-rate={rate}")])
+                .err();
+            assert!(
+                err.is_some_and(|err| err.kind() == ErrorKind::ValueValidation),
+                "{rate}"
+            );
+        }
+    }
+
+    #[test]
+    fn measurement_errors_are_probabilities() {
+        for error in ["-1e-3", "1.5"] {
+            let err = Cli::command()
+                .try_get_matches_from(["estimator", &format!("--measurement-error={error}")])
+                .err();
+            assert!(
+                err.is_some_and(|err| err.kind() == ErrorKind::ValueValidation),
+                "{error}"
+            );
+        }
+    }
+
+    #[test]
+    fn error_targets_exclude_the_other_budget_options() {
+        let resources = ["resources", "10", "100", "100"];
+        let cli = Cli::try_parse_from(
+            ["estimator", "--error-target=1e-3"]
+                .into_iter()
+                .chain(resources),
+        );
+        assert!(cli.is_ok_and(|cli| cli.budget.error_target == Some(1e-3)));
+        for option in [
+            "--error-total=1e-3",
+            "--magic-fraction=0.5",
+            "--rotation-fraction=0.1",
+        ] {
+            let err = Cli::command()
+                .try_get_matches_from(
+                    ["estimator", "--error-target=1e-3", option]
+                        .into_iter()
+                        .chain(resources),
+                )
+                .err();
+            assert!(
+                err.is_some_and(|err| err.kind() == ErrorKind::ArgumentConflict),
+                "{option}"
+            );
+        }
+        for target in ["-1e-3", "1.5"] {
+            let err = Cli::command()
+                .try_get_matches_from(["estimator", &format!("--error-target={target}")])
+                .err();
+            assert!(
+                err.is_some_and(|err| err.kind() == ErrorKind::ValueValidation),
+                "{target}"
+            );
+        }
+    }
+
+    /// Command line of a `diff` with the options `args` and the environment
+    /// variables `environment`.
+    fn parsed(args: &[&str], environment: &[(&str, &str)]) -> Cli {
+        let args: Vec<OsString> = ["estimator"]
+            .iter()
+            .chain(args)
+            .chain(&["diff", "before.json", "after.json"])
+            .map(OsString::from)
+            .collect();
+        let args = with_defaults(&Cli::command(), &args, |variable| {
+            environment
+                .iter()
+                .find(|(name, _)| *name == variable)
+                .map(|(_, value)| OsString::from(value))
+        })
+        .expect("valid command line");
+        Cli::try_parse_from(args).expect("valid command line")
+    }
+
+    /// Temporary file with `content`, removed by the caller.
+    fn temporary(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{name}", std::process::id()));
+        std::fs::write(&path, content).expect("temporary file");
+        path
+    }
+
+    #[test]
+    fn command_line_takes_precedence_over_the_environment() {
+        let environment = [("AB_RE_K1_K2", "1e-3"), ("AB_RE_SEED", "7")];
+        let cli = parsed(&[], &environment);
+        assert_eq!((cli.k1_k2, cli.seed), (Some(1e-3), Some(7)));
+        let cli = parsed(&["--k1-k2", "1e-4"], &environment);
+        assert_eq!((cli.k1_k2, cli.seed), (Some(1e-4), Some(7)));
+        assert_eq!(parsed(&[], &[]).k1_k2, None);
+    }
+
+    #[test]
+    fn conflicting_options_take_precedence_over_the_environment() {
+        let environment = [("AB_RE_ERROR_TOTAL", "1e-2")];
+        assert_eq!(parsed(&[], &environment).budget.error_total, Some(1e-2));
+        let cli = parsed(&["--error-budget", "1e-3", "1e-3", "0"], &environment);
+        assert_eq!(cli.budget.error_total, None);
+    }
+
+    #[test]
+    fn qre_parameters_take_precedence_over_the_environment() {
+        let path = temporary(
+            "qre.json",
+            r#"{"qubitParams": {"name": "qubit_gate_ns_e3"}}"#,
+        );
+        let qre_params = path.to_string_lossy().into_owned();
+        let environment = [("AB_RE_K1_K2", "1e-3")];
+        let from_environment = parsed(&["--qre-params", &qre_params], &environment);
+        let explicit = parsed(&["--qre-params", &qre_params, "--k1-k2", "1e-4"], &[]);
+        std::fs::remove_file(&path).expect("temporary file");
+        assert_eq!(from_environment.k1_k2, None);
+        assert_eq!(explicit.k1_k2, Some(1e-4));
+    }
+
+    #[test]
+    fn configuration_file_sits_between_the_command_line_and_the_environment() {
+        let path = temporary(
+            "config.toml",
+            "k1-k2 = 2e-4\nerror-budget = [1e-3, 1e-3, 0]\n",
+        );
+        let config = path.to_string_lossy().into_owned();
+        let environment = [("AB_RE_K1_K2", "1e-3"), ("AB_RE_ERROR_TOTAL", "1e-2")];
+        let configured = parsed(&["--config", &config], &environment);
+        let given = parsed(&["--config", &config, "--k1-k2", "1e-4"], &environment);
+        std::fs::remove_file(&path).expect("temporary file");
+        assert_eq!(configured.k1_k2, Some(2e-4));
+        assert_eq!(configured.budget.error_total, None);
+        assert_eq!(
+            configured.budget.error_budget.as_deref(),
+            Some([1e-3, 1e-3, 0.0].as_slice())
+        );
+        assert_eq!(given.k1_k2, Some(1e-4));
+    }
+
+    #[test]
+    fn invalid_configuration_files_are_reported() {
+        let path = temporary("invalid.toml", "no-such-option = 1\n");
+        let args: Vec<OsString> = ["estimator", "--config", &path.to_string_lossy()]
+            .iter()
+            .chain(&["diff", "before.json", "after.json"])
+            .map(OsString::from)
+            .collect();
+        let err = with_defaults(&Cli::command(), &args, |_| None).err();
+        std::fs::remove_file(&path).expect("temporary file");
+        assert!(err.is_some_and(|err| err.kind() == ErrorKind::InvalidValue));
+    }
+
+    #[test]
+    fn configuration_template_is_read_back() {
+        let command = Cli::command();
+        let uncommented: String = config::template(&command)
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(option)
+                    if option.split_once(" = ").is_some_and(|(key, _)| {
+                        command
+                            .get_arguments()
+                            .any(|arg| arg.get_long() == Some(key))
+                    }) =>
+                {
+                    format!("{option}\n")
+                }
+                _ => format!("{line}\n"),
+            })
+            .collect();
+        let path = std::env::temp_dir().join(format!("template-{}.toml", std::process::id()));
+        std::fs::write(&path, uncommented).expect("temporary file");
+        let options = config::read(&command, &path);
+        std::fs::remove_file(&path).expect("temporary file");
+        let options = options.expect("valid template");
+        assert!(options.iter().any(|(arg, _)| arg.get_id() == "k1_k2"));
+    }
 }
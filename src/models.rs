@@ -0,0 +1,202 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Versioned registry of the physical models.
+//!
+//! The fits and precomputed factories of the estimator are revised as new
+//! simulations and papers come out, which changes the estimates. A [`Model`]
+//! pins the constants of a revision of the models (qubits, repetition code),
+//! so that the estimates of a study can be reproduced with newer versions of
+//! this crate by selecting its model by name ([`Model::by_name`], `--model` on
+//! the command line). The precomputed factories are not stored in the model,
+//! only the hash of their table: a model whose factories are no longer the
+//! ones of the crate is rejected ([`Model::check_factories`]) rather than
+//! silently estimated with other factories.
+//!
+//! Revisions are appended to [`MODELS`] and never modified once released: a
+//! new fit gets a new name, and the defaults of the crate follow the latest
+//! revision. The costs of the logical gates are not part of the models, they
+//! are recorded with the gate set of the counts.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    code::{
+        phase_flip::PowerLawFit,
+        schedule::{CycleSchedule, RoundsPerCycle},
+    },
+    CatQubit, CxImplementation, RepetitionCode, ToffoliBuilder,
+};
+
+/// Revision of the physical models, with all of its constants.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Model {
+    /// Name selecting the model, e.g. `gouzien2023`.
+    pub name: &'static str,
+    /// Where the constants come from.
+    pub reference: &'static str,
+    /// Ratio κ₁/κ₂ of the qubits, unless given.
+    pub k1_k2: f64,
+    /// Time scale 1/κ₂ in nanoseconds, unless given.
+    pub inverse_k2_ns: f64,
+    /// CX implementation in the computation part.
+    pub compute_cx: CxImplementation,
+    /// CX implementation in the magic state factories.
+    pub factory_cx: CxImplementation,
+    /// Fit of the logical phase-flip probability of the repetition code.
+    pub phase_flip_fit: PowerLawFit,
+    /// Prefactor and threshold of the fit of the logical error due to
+    /// leakage.
+    pub leakage_fit: (f64, f64),
+    /// Prefactor and threshold of the fit of the logical error due to the
+    /// ancilla measurement errors.
+    pub measurement_fit: (f64, f64),
+    /// Schedule of the repetition code cycles.
+    pub schedule: CycleSchedule,
+    /// Hash of the table of precomputed factories
+    /// ([`ToffoliBuilder::table_hash`]).
+    pub factory_table_hash: &'static str,
+}
+
+/// Models of [arXiv:2302.06639](https://arxiv.org/abs/2302.06639): κ₁/κ₂ =
+/// 1e-5 (p. 2), phase-flip fit of p. 29, Fig. 26, schedule of p. 28 and
+/// factories of Table III, p. 35.
+pub const GOUZIEN_2023: Model = Model {
+    name: "gouzien2023",
+    reference: "arXiv:2302.06639",
+    k1_k2: 1e-5,
+    inverse_k2_ns: 100.0,
+    compute_cx: CxImplementation::Fast,
+    factory_cx: CxImplementation::Adiabatic,
+    phase_flip_fit: PowerLawFit {
+        prefactor: 5.6e-2,
+        alpha_exponent: 0.86,
+        threshold: 0.013,
+    },
+    leakage_fit: (5.6e-2, 0.013),
+    measurement_fit: (5.6e-2, 0.1),
+    schedule: CycleSchedule {
+        other_steps: 3.0,
+        rounds_per_cycle: RoundsPerCycle::Distance,
+    },
+    factory_table_hash: "cb9154baa49190b3",
+};
+
+/// Registered models, from the oldest to the latest.
+pub const MODELS: [Model; 1] = [GOUZIEN_2023];
+
+impl Model {
+    /// Latest revision of the models, the defaults of the crate.
+    #[must_use]
+    pub fn latest() -> Self {
+        MODELS[MODELS.len() - 1]
+    }
+
+    /// Registered model named `name`.
+    pub fn by_name(name: &str) -> Result<Self, String> {
+        MODELS
+            .iter()
+            .find(|model| model.name == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = MODELS.iter().map(|model| model.name).collect();
+                format!("unknown model {name} (expected {})", names.join(", "))
+            })
+    }
+
+    /// Cat qubits of the model.
+    pub fn cat_qubit(&self) -> CatQubit {
+        CatQubit::new()
+            .with_k1_k2(self.k1_k2)
+            .with_inverse_k2_ns(self.inverse_k2_ns)
+            .with_cx_implementation(self.compute_cx)
+            .with_factory_cx_implementation(self.factory_cx)
+    }
+
+    /// `code` with the fits and schedule of the model, its search options
+    /// being kept.
+    pub fn repetition_code(&self, code: RepetitionCode) -> Result<RepetitionCode, String> {
+        let (leakage_prefactor, leakage_threshold) = self.leakage_fit;
+        let (measurement_prefactor, measurement_threshold) = self.measurement_fit;
+        code.with_phase_flip_model(self.phase_flip_fit)
+            .with_leakage_fit(leakage_prefactor, leakage_threshold)
+            .with_measurement_fit(measurement_prefactor, measurement_threshold)
+            .with_schedule(self.schedule)
+    }
+
+    /// Check that `builder` has the precomputed factories of the model
+    /// (before any cascade is added).
+    pub fn check_factories(&self, builder: &ToffoliBuilder) -> Result<(), String> {
+        let hash = builder.table_hash();
+        if hash == self.factory_table_hash {
+            Ok(())
+        } else {
+            Err(format!(
+                "the factories of model {} (table {}) are not the ones of this version (table {hash})",
+                self.name, self.factory_table_hash
+            ))
+        }
+    }
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::by_name(s)
+    }
+}
+
+impl Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.reference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Model, GOUZIEN_2023, MODELS};
+    use crate::{CatQubit, RepetitionCode, ToffoliBuilder};
+
+    #[test]
+    fn latest_model_is_the_default() {
+        let model = Model::latest();
+        assert!(model.cat_qubit() == CatQubit::new());
+        let (code, default) = (
+            model
+                .repetition_code(RepetitionCode::new())
+                .expect("valid model"),
+            RepetitionCode::new(),
+        );
+        assert_eq!(
+            code.phase_flip_model_description(),
+            default.phase_flip_model_description()
+        );
+        assert_eq!(code.leakage_fit(), default.leakage_fit());
+        assert_eq!(code.measurement_fit(), default.measurement_fit());
+        assert_eq!(code.schedule(), default.schedule());
+        assert_eq!(model.check_factories(&ToffoliBuilder::default()), Ok(()));
+    }
+
+    #[test]
+    fn models_are_found_by_name() {
+        assert_eq!("gouzien2023".parse(), Ok(GOUZIEN_2023));
+        assert!(Model::by_name("unknown").is_err_and(|err| err.contains("gouzien2023")));
+        for (index, model) in MODELS.iter().enumerate() {
+            assert!(
+                MODELS[..index].iter().all(|other| other.name != model.name),
+                "{} registered twice",
+                model.name
+            );
+        }
+    }
+
+    #[test]
+    fn other_factories_are_rejected() {
+        let model = Model {
+            factory_table_hash: "0000000000000000",
+            ..Model::latest()
+        };
+        assert!(model.check_factories(&ToffoliBuilder::default()).is_err());
+    }
+}
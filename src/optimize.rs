@@ -0,0 +1,346 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Search of the joint parameters minimizing an objective.
+//!
+//! Estimating every combination of parameters (e.g. error budget splits times
+//! factory parameters) quickly becomes intractable. [`GridRefine`] evaluates
+//! the [`Objective`] on a coarse grid of the [`SearchSpace`], then refines the
+//! best points of the grid by moving to better neighbours, one step along one
+//! dimension at a time, until none is better. Each point is evaluated at most
+//! once.
+//!
+//! The result is the optimum when the objective has a single minimum along
+//! each dimension; otherwise, it is a local optimum, refined from several
+//! starting points (see [`GridRefine::with_starts`]).
+
+use std::collections::HashMap;
+
+/// Cost to minimize over the points of a [`SearchSpace`].
+///
+/// Implemented by closures taking the values of the point along each
+/// dimension.
+pub trait Objective {
+    /// Cost of the point with these values along each dimension, [`None`] if
+    /// the point is infeasible.
+    fn cost(&mut self, point: &[f64]) -> Option<f64>;
+}
+
+impl<F: FnMut(&[f64]) -> Option<f64>> Objective for F {
+    fn cost(&mut self, point: &[f64]) -> Option<f64> {
+        self(point)
+    }
+}
+
+/// One dimension of a [`SearchSpace`]: its values, in increasing order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dimension {
+    /// Name of the parameter, for the traces.
+    pub name: &'static str,
+    /// Values of the parameter, in increasing order.
+    pub values: Vec<f64>,
+}
+
+impl Dimension {
+    /// Dimension `name` taking the `values`, in increasing order.
+    pub fn new(name: &'static str, values: impl IntoIterator<Item = f64>) -> Self {
+        Self {
+            name,
+            values: values.into_iter().collect(),
+        }
+    }
+}
+
+/// Cartesian product of dimensions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchSpace {
+    /// Dimensions of the space.
+    pub dimensions: Vec<Dimension>,
+}
+
+impl SearchSpace {
+    /// Space of these dimensions.
+    #[must_use]
+    pub fn new(dimensions: Vec<Dimension>) -> Self {
+        Self { dimensions }
+    }
+
+    /// Number of points of the space.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.dimensions
+            .iter()
+            .map(|dimension| dimension.values.len())
+            .product()
+    }
+
+    /// Values of the point of these indices along each dimension.
+    fn values(&self, indices: &[usize]) -> Vec<f64> {
+        self.dimensions
+            .iter()
+            .zip(indices)
+            .map(|(dimension, &index)| dimension.values[index])
+            .collect()
+    }
+
+    /// Indices of the grid taking every `stride`-th value (and the last one)
+    /// along each dimension.
+    fn grid(&self, stride: usize) -> Vec<Vec<usize>> {
+        self.dimensions
+            .iter()
+            .fold(vec![vec![]], |points, dimension| {
+                let Some(last) = dimension.values.len().checked_sub(1) else {
+                    return vec![];
+                };
+                let mut indices: Vec<usize> = (0..=last).step_by(stride).collect();
+                if indices.last() != Some(&last) {
+                    indices.push(last);
+                }
+                points
+                    .iter()
+                    .flat_map(|point| {
+                        indices.iter().map(move |&index| {
+                            let mut point = point.clone();
+                            point.push(index);
+                            point
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    /// Indices of the points one step away from `indices` along one
+    /// dimension.
+    fn neighbours(&self, indices: &[usize]) -> Vec<Vec<usize>> {
+        let mut neighbours = vec![];
+        for (axis, dimension) in self.dimensions.iter().enumerate() {
+            let index = indices[axis];
+            for neighbour in [index.checked_sub(1), Some(index + 1)]
+                .into_iter()
+                .flatten()
+                .filter(|&neighbour| neighbour < dimension.values.len())
+            {
+                let mut point = indices.to_vec();
+                point[axis] = neighbour;
+                neighbours.push(point);
+            }
+        }
+        neighbours
+    }
+}
+
+/// Best point found by an optimizer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Optimum {
+    /// Values of the point along each dimension.
+    pub point: Vec<f64>,
+    /// Cost of the point.
+    pub cost: f64,
+    /// Number of evaluations of the objective.
+    pub evaluations: usize,
+}
+
+/// Coarse grid followed by a local refinement of its best points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridRefine {
+    stride: usize,
+    starts: usize,
+}
+
+impl Default for GridRefine {
+    /// Every 4th value of each dimension, the best point of the grid being
+    /// refined.
+    fn default() -> Self {
+        Self {
+            stride: 4,
+            starts: 1,
+        }
+    }
+}
+
+impl GridRefine {
+    /// Grid taking every `stride`-th value of each dimension (1 evaluates the
+    /// whole space).
+    pub fn new(stride: usize) -> Result<Self, String> {
+        if stride == 0 {
+            return Err("the stride of the grid must be at least 1".into());
+        }
+        Ok(Self {
+            stride,
+            ..Self::default()
+        })
+    }
+
+    #[must_use]
+    /// Refine the `starts` best points of the grid (at least 1), to escape
+    /// local optima.
+    pub fn with_starts(mut self, starts: usize) -> Self {
+        self.starts = starts.max(1);
+        self
+    }
+
+    /// Point of `space` minimizing `objective`, [`None`] if no point of the
+    /// grid is feasible.
+    pub fn minimize(&self, space: &SearchSpace, mut objective: impl Objective) -> Option<Optimum> {
+        let mut costs: HashMap<Vec<usize>, Option<f64>> = HashMap::new();
+        let mut evaluate = |indices: &Vec<usize>| -> Option<f64> {
+            *costs.entry(indices.clone()).or_insert_with(|| {
+                let cost = objective.cost(&space.values(indices));
+                tracing::trace!(?indices, ?cost, "evaluated point");
+                cost
+            })
+        };
+
+        let mut grid: Vec<(Vec<usize>, f64)> = space
+            .grid(self.stride)
+            .into_iter()
+            .filter_map(|indices| evaluate(&indices).map(|cost| (indices, cost)))
+            .collect();
+        grid.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let mut best: Option<(Vec<usize>, f64)> = None;
+        for (mut indices, mut cost) in grid.into_iter().take(self.starts) {
+            // move to the best neighbour until none improves the cost
+            loop {
+                let next = space
+                    .neighbours(&indices)
+                    .into_iter()
+                    .filter_map(|neighbour| evaluate(&neighbour).map(|cost| (neighbour, cost)))
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+                match next {
+                    Some((neighbour, neighbour_cost)) if neighbour_cost < cost => {
+                        indices = neighbour;
+                        cost = neighbour_cost;
+                    }
+                    _ => break,
+                }
+            }
+            if best.as_ref().is_none_or(|&(_, best_cost)| cost < best_cost) {
+                best = Some((indices, cost));
+            }
+        }
+
+        let evaluations = costs.len();
+        best.map(|(indices, cost)| Optimum {
+            point: space.values(&indices),
+            cost,
+            evaluations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dimension, GridRefine, SearchSpace};
+
+    type Cost = fn(&[f64]) -> Option<f64>;
+
+    /// One dimension taking the values 0, 1, ..., `len` − 1.
+    fn line(len: u32) -> SearchSpace {
+        SearchSpace::new(vec![Dimension::new("x", (0..len).map(f64::from))])
+    }
+
+    #[test]
+    fn refinement_stops_on_plateaus() {
+        let space = line(20);
+        // flat everywhere but at 14, between two points of the stride-4 grid
+        let step = |point: &[f64]| {
+            Some(if (point[0] - 14.0).abs() < 0.5 {
+                1.0
+            } else {
+                10.0
+            })
+        };
+
+        let coarse = GridRefine::default().minimize(&space, step);
+        assert_eq!(coarse.map(|optimum| optimum.cost), Some(10.0));
+
+        let exhaustive = GridRefine::new(1).and_then(|search| {
+            search
+                .minimize(&space, step)
+                .ok_or_else(|| "no optimum".to_string())
+        });
+        assert_eq!(exhaustive.map(|optimum| optimum.point), Ok(vec![14.0]));
+    }
+
+    #[test]
+    fn infeasible_grid_misses_narrow_bands() {
+        let space = line(20);
+        let narrow = |point: &[f64]| ((point[0] - 13.0).abs() < 0.5).then_some(1.0);
+
+        assert!(GridRefine::default().minimize(&space, narrow).is_none());
+        let exhaustive = GridRefine::new(1)
+            .ok()
+            .and_then(|search| search.minimize(&space, narrow));
+        assert_eq!(exhaustive.map(|optimum| optimum.point), Some(vec![13.0]));
+    }
+
+    #[test]
+    fn stride_1_is_an_exhaustive_scan() {
+        let space = SearchSpace::new(vec![
+            Dimension::new("x", (0..7).map(f64::from)),
+            Dimension::new("y", (0..5).map(f64::from)),
+        ]);
+        let objectives: [Cost; 3] = [
+            |point| Some((point[0] - 4.0).abs() + (point[1] - 1.0).abs()),
+            |point| Some(((point[0] * 3.0 + point[1] * 7.0) % 5.0).floor()),
+            |point| (point[0] + point[1] > 6.0).then(|| 20.0 - point[0] * point[1]),
+        ];
+        for objective in objectives {
+            // first cheapest point, in the order of the dimensions
+            let mut scan: Option<(Vec<f64>, f64)> = None;
+            for x in 0..7 {
+                for y in 0..5 {
+                    let point = vec![f64::from(x), f64::from(y)];
+                    if let Some(cost) = objective(&point) {
+                        if scan.as_ref().is_none_or(|(_, best)| cost < *best) {
+                            scan = Some((point, cost));
+                        }
+                    }
+                }
+            }
+
+            let optimum = GridRefine::new(1)
+                .ok()
+                .and_then(|search| search.minimize(&space, objective));
+            assert_eq!(
+                optimum
+                    .as_ref()
+                    .map(|optimum| (optimum.point.clone(), optimum.cost)),
+                scan
+            );
+            assert_eq!(optimum.map(|optimum| optimum.evaluations), Some(35));
+        }
+    }
+
+    #[test]
+    fn grid_keeps_the_last_value() {
+        assert_eq!(
+            line(6).grid(4),
+            vec![vec![0], vec![4], vec![5]],
+            "every 4th value, then the last one"
+        );
+        assert!(SearchSpace::new(vec![Dimension::new("x", [])])
+            .grid(1)
+            .is_empty());
+    }
+
+    #[test]
+    fn neighbours_stay_in_the_space() {
+        let space = SearchSpace::new(vec![
+            Dimension::new("x", [0.0, 1.0, 2.0]),
+            Dimension::new("y", [0.0, 1.0]),
+        ]);
+        assert_eq!(space.size(), 6);
+        assert_eq!(space.neighbours(&[0, 0]), vec![vec![1, 0], vec![0, 1]]);
+        assert_eq!(
+            space.neighbours(&[1, 1]),
+            vec![vec![0, 1], vec![2, 1], vec![1, 0]]
+        );
+    }
+
+    #[test]
+    fn stride_0_is_rejected() {
+        assert!(GridRefine::new(0).is_err());
+    }
+}
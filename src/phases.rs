@@ -0,0 +1,308 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Magic state demand of the phases of an algorithm.
+//!
+//! Algorithms often run distinct phases (e.g. a modular exponentiation, then
+//! a QFT) consuming Toffoli states at very different rates, while the
+//! factories of the estimates are sized for the average rate over the whole
+//! runtime: the phases consuming faster than the average wait for their
+//! states. A [`PhaseAnalysis`] gives, for each phase run in sequence, the
+//! states per second it requires, the copies of the factory of the estimate
+//! needed to keep up with it and its bottleneck, then the factories sized for
+//! the phase with the highest rate and the runtime with the stalls of the
+//! factories of the estimate.
+//!
+//! The analysis is a report only: the estimate keeps its factories sized for
+//! the average rate and its runtime without stalls, the peak-sized factories
+//! and the runtime with stalls telling what sizing for the phases would
+//! change.
+//!
+//! The phases are the segments of a [`SegmentedCounts`], each one running for
+//! its logical depth at the cycle time of the estimate.
+
+use std::fmt::Display;
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::{ErrorBudget, Factory, Overhead};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    counter::segmented::SegmentedCounts,
+    report::ReportSection,
+    units::{format_thousands, Rounding, TimeSpan, TimeUnit},
+    AliceAndBobEstimates,
+};
+
+/// What limits the progress of a phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bottleneck {
+    /// The factories keep up, the logical depth sets the duration.
+    Computation,
+    /// The computation waits for the Toffoli states.
+    Factories,
+}
+
+impl Display for Bottleneck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Computation => "computation",
+            Self::Factories => "factories",
+        })
+    }
+}
+
+/// Demand of Toffoli states of one phase.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PhaseDemand {
+    /// Name of the phase, e.g. its Q# file.
+    pub name: String,
+    /// Logical depth of the phase.
+    pub logical_cycles: u64,
+    /// Duration of the phase without waiting for the states.
+    pub duration: TimeSpan,
+    /// Toffoli states consumed by the phase.
+    pub toffoli_states: u64,
+    /// States consumed per second.
+    pub required_rate: f64,
+    /// Copies of the factory of the estimate keeping up with the phase.
+    pub copies: u64,
+    /// What limits the phase with the copies of the estimate.
+    pub bottleneck: Bottleneck,
+}
+
+/// Toffoli factories of an estimate.
+struct Supply {
+    /// Copies of the factory.
+    copies: u64,
+    /// States per nanosecond of a copy.
+    copy_rate: f64,
+    /// Physical qubits of a copy.
+    qubits_per_copy: u64,
+}
+
+/// Demand of the phases of an estimate, compared with its factories.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PhaseAnalysis {
+    /// Phases, in the order they run.
+    pub phases: Vec<PhaseDemand>,
+    /// Copies of the factory of the estimate, sized for the average rate.
+    pub copies: u64,
+    /// Copies keeping up with every phase, sized for the highest rate (not
+    /// applied to the estimate).
+    pub peak_copies: u64,
+    /// Physical qubits of the copies sized for the highest rate.
+    pub peak_physical_qubits: u64,
+    /// Physical qubits of the copies of the estimate.
+    pub physical_qubits: u64,
+    /// Runtime of the phases with the copies of the estimate, the phases
+    /// limited by the factories waiting for their states.
+    pub runtime: TimeSpan,
+    /// Fraction of the logical qubits allocated over the runtime (see
+    /// [`SegmentedCounts::occupancy`]).
+    pub occupancy: f64,
+}
+
+impl PhaseAnalysis {
+    /// Demand of the `segments`, run in sequence, with the factories and
+    /// cycle time of `estimates`.
+    ///
+    /// Returns [`None`] if the estimate has no factories.
+    #[must_use]
+    pub fn new(estimates: &AliceAndBobEstimates, segments: &SegmentedCounts) -> Option<Self> {
+        let part = estimates.toffoli_factory_part()?;
+        let factory = part.factory();
+        let preparation = factory.preparation_time().as_ns_f64();
+        // states per nanosecond of a copy
+        let copy_rate = factory.num_output_states().to_f64()? / preparation;
+        let supply = Supply {
+            copies: part.copies(),
+            copy_rate,
+            qubits_per_copy: factory.physical_qubits(),
+        };
+        Self::with_supply(segments, estimates.cycle_time().as_ns_f64(), &supply)
+    }
+
+    /// Demand of the `segments`, run in sequence with `cycle_time` (in
+    /// nanoseconds) and the factories of `supply`.
+    fn with_supply(segments: &SegmentedCounts, cycle_time: f64, supply: &Supply) -> Option<Self> {
+        let copy_rate = supply.copy_rate;
+        if !copy_rate.is_finite() || copy_rate <= 0.0 {
+            return None;
+        }
+        let rate = supply.copies.to_f64()? * copy_rate;
+        // the logical depth does not depend on the error budget
+        let budget = ErrorBudget::new(0.0, 0.0, 0.0);
+
+        let mut runtime = TimeSpan::default();
+        let phases: Vec<_> = segments
+            .segments()
+            .iter()
+            .map(|segment| {
+                let counts = &segment.counts;
+                let logical_cycles = counts.logical_depth(&budget);
+                let duration = logical_cycles.to_f64().unwrap_or(f64::MAX) * cycle_time;
+                let toffoli_states = counts.num_magic_states(&budget, 0);
+                let states = toffoli_states.to_f64().unwrap_or(f64::MAX);
+                let required = if toffoli_states == 0 {
+                    0.0
+                } else {
+                    states / duration
+                };
+                let copies = (required / copy_rate).ceil().to_u64().unwrap_or(u64::MAX);
+                let (bottleneck, stalled) = if copies > supply.copies {
+                    (Bottleneck::Factories, states / rate)
+                } else {
+                    (Bottleneck::Computation, duration)
+                };
+                runtime = runtime
+                    + TimeSpan::from_ns_f64(stalled, Rounding::Up)
+                        .unwrap_or(TimeSpan::from_ns(u64::MAX));
+                PhaseDemand {
+                    name: segment.name.clone(),
+                    logical_cycles,
+                    duration: TimeSpan::from_ns_f64(duration, Rounding::Up)
+                        .unwrap_or(TimeSpan::from_ns(u64::MAX)),
+                    toffoli_states,
+                    required_rate: required * TimeUnit::Seconds.nanoseconds(),
+                    copies,
+                    bottleneck,
+                }
+            })
+            .collect();
+        let peak_copies = phases
+            .iter()
+            .map(|phase| phase.copies)
+            .max()
+            .unwrap_or_default();
+        Some(Self {
+            peak_copies,
+            peak_physical_qubits: peak_copies.saturating_mul(supply.qubits_per_copy),
+            physical_qubits: supply.copies.saturating_mul(supply.qubits_per_copy),
+            copies: supply.copies,
+            runtime,
+            occupancy: segments.occupancy(),
+            phases,
+        })
+    }
+
+    /// Phase with the highest rate of Toffoli states, if any.
+    #[must_use]
+    pub fn peak(&self) -> Option<&PhaseDemand> {
+        self.phases
+            .iter()
+            .max_by(|a, b| a.required_rate.total_cmp(&b.required_rate))
+    }
+
+    #[must_use]
+    /// Demand of the phases as (label, formatted value) pairs, the durations
+    /// in `unit`.
+    pub fn report_section(&self, unit: TimeUnit) -> ReportSection {
+        let mut section: ReportSection = self
+            .phases
+            .iter()
+            .map(|phase| {
+                (
+                    "phase",
+                    format!(
+                        "{}: {} Toffoli states in {}, {:.1} states/s, {} factories ({}-bound)",
+                        phase.name,
+                        format_thousands(phase.toffoli_states),
+                        phase.duration.format(unit),
+                        phase.required_rate,
+                        phase.copies,
+                        phase.bottleneck
+                    ),
+                )
+            })
+            .collect();
+        if let Some(peak) = self.peak() {
+            section.push(("peak phase", peak.name.clone()));
+        }
+        section.extend([
+            (
+                "peak-sized factories",
+                format!(
+                    "{} ({} physical qubits, {:+} vs average sizing)",
+                    self.peak_copies,
+                    format_thousands(self.peak_physical_qubits),
+                    i128::from(self.peak_physical_qubits) - i128::from(self.physical_qubits)
+                ),
+            ),
+            ("runtime with stalls", self.runtime.format(unit)),
+            ("qubit occupancy", format!("{:.1}%", 100.0 * self.occupancy)),
+        ]);
+        section
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bottleneck, PhaseAnalysis, Supply};
+    use crate::{
+        counter::segmented::SegmentedCounts, testing::close, units::TimeSpan, LogicalCounts,
+    };
+
+    /// Phases of 100 logical cycles, with 1000 and 10 Toffoli states.
+    fn analysis(copies: u64) -> PhaseAnalysis {
+        let phase = |states| LogicalCounts::new(10, 0, states).with_depth(100);
+        let segments = SegmentedCounts::new([("hungry", phase(1000)), ("light", phase(10))])
+            .expect("same gate sets");
+        // cycles of 1 µs, a copy producing a state per µs
+        let supply = Supply {
+            copies,
+            copy_rate: 1e-3,
+            qubits_per_copy: 50,
+        };
+        PhaseAnalysis::with_supply(&segments, 1000.0, &supply).expect("factories")
+    }
+
+    #[test]
+    fn phases_are_factory_or_computation_bound() {
+        let analysis = analysis(4);
+        let [hungry, light] = analysis.phases.as_slice() else {
+            panic!("two phases");
+        };
+        // 1000 states in 100 µs need 10 copies
+        assert_eq!(hungry.duration, TimeSpan::from_ns(100_000));
+        assert!(close(hungry.required_rate, 1e7));
+        assert_eq!(hungry.copies, 10);
+        assert_eq!(hungry.bottleneck, Bottleneck::Factories);
+        assert_eq!(light.copies, 1);
+        assert_eq!(light.bottleneck, Bottleneck::Computation);
+        assert_eq!(
+            analysis.peak().map(|phase| phase.name.as_str()),
+            Some("hungry")
+        );
+        assert_eq!(analysis.peak_copies, 10);
+        assert_eq!(analysis.peak_physical_qubits, 500);
+        assert_eq!(analysis.physical_qubits, 200);
+    }
+
+    #[test]
+    fn factory_bound_phases_stall() {
+        // 1000 states at 4 per µs, then 100 µs
+        assert_eq!(analysis(4).runtime, TimeSpan::from_ns(250_000 + 100_000));
+        // enough copies for both phases
+        assert_eq!(analysis(10).runtime, TimeSpan::from_ns(200_000));
+        assert!(analysis(10)
+            .phases
+            .iter()
+            .all(|phase| phase.bottleneck == Bottleneck::Computation));
+    }
+
+    #[test]
+    fn factories_must_produce_states() {
+        let segments =
+            SegmentedCounts::new([("a", LogicalCounts::new(1, 0, 1))]).expect("one segment");
+        for copy_rate in [0.0, f64::INFINITY] {
+            let supply = Supply {
+                copies: 1,
+                copy_rate,
+                qubits_per_copy: 1,
+            };
+            assert!(PhaseAnalysis::with_supply(&segments, 1.0, &supply).is_none());
+        }
+    }
+}
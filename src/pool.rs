@@ -0,0 +1,191 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Worker pool for independent estimation tasks.
+//!
+//! The estimates of a batch or sweep are independent of each other, but the
+//! repetition code caches its error rates in a structure shared by its clones
+//! which can't cross threads. [`for_each`] thus runs each worker with its own
+//! context (e.g. its own code, built by the worker), the workers taking the
+//! next task as soon as they are done. If a worker can't build its context,
+//! no other task is started and the error is returned. Each task only depends on its input
+//! and the context, so that its result doesn't depend on the number of
+//! workers nor on the order in which the tasks are completed.
+
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+/// Number of workers used by default, the available parallelism of the
+/// machine (1 if unknown).
+#[must_use]
+pub fn available_jobs() -> NonZeroUsize {
+    thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)
+}
+
+/// Run `task` on each of the `items` with `jobs` workers, each one with the
+/// context built by `context`, and call `done` on the calling thread with the
+/// index of each item and its result, as soon as it is completed.
+///
+/// With a single job, the tasks run on the calling thread, in order.
+///
+/// # Errors
+///
+/// Returns the error of the first context which couldn't be built, the tasks
+/// already started being still completed.
+pub fn for_each<T, C, E, R>(
+    items: &[T],
+    jobs: NonZeroUsize,
+    context: impl Fn() -> Result<C, E> + Sync,
+    task: impl Fn(&C, &T) -> R + Sync,
+    mut done: impl FnMut(usize, R),
+) -> Result<(), E>
+where
+    T: Sync,
+    E: Send,
+    R: Send,
+{
+    let workers = jobs.get().min(items.len());
+    if workers <= 1 {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let context = context()?;
+        for (index, item) in items.iter().enumerate() {
+            done(index, task(&context, item));
+        }
+        return Ok(());
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for worker in 0..workers {
+            let sender = sender.clone();
+            let (next, context, task) = (&next, &context, &task);
+            scope.spawn(move || {
+                let _span = tracing::debug_span!("worker", worker).entered();
+                let context = match context() {
+                    Ok(context) => context,
+                    Err(err) => {
+                        // the other workers stop after their current task
+                        next.store(items.len(), Ordering::Relaxed);
+                        let _ = sender.send(Err(err));
+                        return;
+                    }
+                };
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    if sender.send(Ok((index, task(&context, item)))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        // the receiver ends once every worker has dropped its sender
+        drop(sender);
+        let mut failure = None;
+        for message in receiver {
+            match message {
+                Ok((index, result)) => done(index, result),
+                Err(err) => failure = failure.or(Some(err)),
+            }
+        }
+        failure.map_or(Ok(()), Err)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::for_each;
+    use std::{
+        num::NonZeroUsize,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    fn jobs(jobs: usize) -> NonZeroUsize {
+        NonZeroUsize::new(jobs).expect("positive")
+    }
+
+    fn squares(jobs: NonZeroUsize) -> Vec<Option<u64>> {
+        let items: Vec<u64> = (0..50).collect();
+        let mut results = vec![None; items.len()];
+        for_each(
+            &items,
+            jobs,
+            || Ok::<_, String>(10),
+            |offset, item| item * item + offset,
+            |index, result| {
+                assert!(results[index].replace(result).is_none(), "done twice");
+            },
+        )
+        .expect("contexts are built");
+        results
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_number_of_jobs() {
+        let expected: Vec<_> = (0..50).map(|item| Some(item * item + 10)).collect();
+        for count in [1, 2, 7, 64] {
+            assert_eq!(squares(jobs(count)), expected, "{count} jobs");
+        }
+    }
+
+    #[test]
+    fn single_job_runs_in_order() {
+        let mut order = Vec::new();
+        for_each(
+            &["a", "b", "c"],
+            jobs(1),
+            || Ok::<_, String>(()),
+            |(), item| item.to_uppercase(),
+            |index, result| order.push((index, result)),
+        )
+        .expect("context is built");
+        assert_eq!(order, [(0, "A".into()), (1, "B".into()), (2, "C".into())]);
+    }
+
+    #[test]
+    fn context_errors_are_returned() {
+        let items: Vec<u32> = (0..20).collect();
+        for count in [1, 4] {
+            let built = AtomicUsize::new(0);
+            let mut completed = 0;
+            let result = for_each(
+                &items,
+                jobs(count),
+                || match built.fetch_add(1, Ordering::Relaxed) {
+                    0 => Err("no context".to_string()),
+                    _ => Ok(()),
+                },
+                |(), item| item + 1,
+                |_, _| completed += 1,
+            );
+            assert_eq!(result, Err("no context".to_string()), "{count} jobs");
+            assert!(completed < items.len(), "{count} jobs");
+        }
+    }
+
+    #[test]
+    fn no_context_is_built_without_items() {
+        let items: [u32; 0] = [];
+        for count in [1, 4] {
+            let result = for_each(
+                &items,
+                jobs(count),
+                || Err("no context".to_string()),
+                |(), item| *item,
+                |_, _| unreachable!("no items"),
+            );
+            assert_eq!(result, Ok(()));
+        }
+    }
+}
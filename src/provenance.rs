@@ -0,0 +1,163 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Assumptions behind an estimate.
+//!
+//! A [`Provenance`] records everything needed to reproduce an estimate from
+//! the same logical counts: the qubit parameters, the models of the code, the
+//! factory table and the gate cost model, with the version of the crate. It is
+//! attached to the estimates with
+//! [`AliceAndBobEstimates::with_provenance`](crate::AliceAndBobEstimates::with_provenance),
+//! and is part of all the outputs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    code::{ordering::ParameterOrdering, schedule::CycleSchedule},
+    counter::gate_set::GateSet,
+    models::Model,
+    qubit::{crosstalk::Crosstalk, CxImplementation},
+    report::ReportSection,
+    CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+
+/// Assumptions used to compute an estimate.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Version of this crate.
+    pub crate_version: String,
+    /// κ₁/κ₂ of the qubits.
+    pub k1_k2: f64,
+    /// 1/κ₂ in nanoseconds.
+    pub inverse_k2_ns: f64,
+    /// 1/κ₂ in the factories in nanoseconds, if different.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory_inverse_k2_ns: Option<f64>,
+    /// Average number of thermal photons.
+    pub thermal_photons: f64,
+    /// Leakage probability per physical qubit and per round.
+    pub leakage_rate: f64,
+    /// Explicit error probability of the ancilla measurements.
+    pub measurement_error: f64,
+    /// Crosstalk inflating the physical error rates, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crosstalk: Option<Crosstalk>,
+    /// Maximum reachable |α|², if any.
+    pub max_alpha_sq: Option<f64>,
+    /// CX implementation in the computation part.
+    pub compute_cx: CxImplementation,
+    /// CX implementation in the factories.
+    pub factory_cx: CxImplementation,
+    /// Model of the logical phase-flip probability.
+    pub phase_flip_model: String,
+    /// Bit-flip probability of the CX gates.
+    #[serde(default)]
+    pub cx_bit_flip: String,
+    /// Schedule of the repetition code cycles.
+    pub schedule: CycleSchedule,
+    /// Order of the code parameters.
+    #[serde(default)]
+    pub parameter_ordering: ParameterOrdering,
+    /// Hash of the table of factories.
+    pub factory_table_hash: String,
+    /// Minimum acceptance probability of the factories.
+    #[serde(default)]
+    pub acceptance_floor: f64,
+    /// Costs of the non-native gates when counting from Q#.
+    pub gate_set: GateSet,
+    /// Registered model the assumptions start from, if any (see
+    /// [`models`](crate::models)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl Provenance {
+    #[must_use]
+    /// Record the assumptions of an estimation.
+    pub fn new(
+        qubit: &CatQubit,
+        code: &RepetitionCode,
+        builder: &ToffoliBuilder,
+        counts: &LogicalCounts,
+    ) -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").into(),
+            k1_k2: qubit.k1_k2(),
+            inverse_k2_ns: qubit.inverse_k2_ns(),
+            #[allow(clippy::float_cmp)]
+            factory_inverse_k2_ns: (qubit.factory_inverse_k2_ns() != qubit.inverse_k2_ns())
+                .then_some(qubit.factory_inverse_k2_ns()),
+            thermal_photons: qubit.thermal_photons(),
+            leakage_rate: qubit.leakage_rate(),
+            measurement_error: qubit.measurement_error(),
+            crosstalk: qubit.crosstalk(),
+            max_alpha_sq: qubit.max_alpha_sq(),
+            compute_cx: qubit.compute_cx(),
+            factory_cx: qubit.factory_cx(),
+            phase_flip_model: code.phase_flip_model_description(),
+            cx_bit_flip: code.cx_bit_flip_description(),
+            schedule: code.schedule(),
+            parameter_ordering: code.parameter_ordering(),
+            factory_table_hash: builder.table_hash(),
+            acceptance_floor: builder.acceptance_floor(),
+            gate_set: counts.gate_set().clone(),
+            model: None,
+        }
+    }
+
+    #[must_use]
+    /// Record the registered model the assumptions start from.
+    pub fn with_model(mut self, model: &Model) -> Self {
+        self.model = Some(model.name.into());
+        self
+    }
+
+    #[must_use]
+    /// Assumptions as (label, formatted value) pairs.
+    pub fn report_section(&self) -> ReportSection {
+        let mut section = vec![];
+        if let Some(model) = &self.model {
+            section.push(("model", model.clone()));
+        }
+        section.extend([
+            ("κ₁/κ₂", format!("{:e}", self.k1_k2)),
+            (
+                "1/κ₂",
+                match self.factory_inverse_k2_ns {
+                    Some(factory) => {
+                        format!("{} ns ({factory} ns in factories)", self.inverse_k2_ns)
+                    }
+                    None => format!("{} ns", self.inverse_k2_ns),
+                },
+            ),
+            ("thermal photons", self.thermal_photons.to_string()),
+            ("leakage rate", format!("{:e}", self.leakage_rate)),
+            ("measurement error", format!("{:e}", self.measurement_error)),
+            (
+                "crosstalk",
+                self.crosstalk.map_or_else(
+                    || "none".into(),
+                    |crosstalk| format!("×{} ({crosstalk})", crosstalk.factor()),
+                ),
+            ),
+            (
+                "max |α|²",
+                self.max_alpha_sq
+                    .map_or_else(|| "none".into(), |max| max.to_string()),
+            ),
+            (
+                "CX (compute/fact.)",
+                format!("{}/{}", self.compute_cx, self.factory_cx),
+            ),
+            ("phase-flip model", self.phase_flip_model.clone()),
+            ("CX bit flips", self.cx_bit_flip.clone()),
+            ("schedule", format!("{:?}", self.schedule.rounds_per_cycle)),
+            ("parameter ordering", self.parameter_ordering.to_string()),
+            ("factory table", self.factory_table_hash.clone()),
+            ("acceptance floor", self.acceptance_floor.to_string()),
+            ("gate set", self.gate_set.to_string()),
+            ("version", self.crate_version.clone()),
+        ]);
+        section
+    }
+}
@@ -0,0 +1,389 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Parameters of the Azure Quantum Resource Estimator.
+//!
+//! The [Azure Quantum Resource Estimator](https://learn.microsoft.com/azure/quantum/overview-resource-estimator)
+//! (QRE) describes the physical qubits with `qubitParams` and the error
+//! correction with `qecScheme`, in JSON:
+//!
+//! ```json
+//! {
+//!   "qubitParams": {"name": "qubit_gate_ns_e3", "twoQubitGateErrorRate": 5e-4},
+//!   "qecScheme": {"crossingPrefactor": 0.05, "errorCorrectionThreshold": 0.012},
+//!   "errorBudget": 1e-3
+//! }
+//! ```
+//!
+//! The gate-based qubits of the QRE are not cat qubits, so the parameters are
+//! only mapped where they are meaningful:
+//! - the time of a repetition code round is the one of an ancilla preparation
+//!   (`oneQubitGateTime`), 2 CX (`twoQubitGateTime`) and a measurement
+//!   (`oneQubitMeasurementTime`), which sets the [`CycleSchedule`] and thus
+//!   the logical cycle time, the CX gates taking the time scale 1/κ₂ of the
+//!   qubits (100 ns by default, see [`CatQubit::with_inverse_k2_ns`]);
+//! - `twoQubitGateErrorRate` is read as phase flips accumulating during the
+//!   CX gates at the rate κ₁, i.e. κ₁/κ₂ = p / (κ₂ · `twoQubitGateTime`);
+//! - `crossingPrefactor` and `errorCorrectionThreshold`, if given explicitly,
+//!   replace the prefactor and threshold of the phase-flip fit of the
+//!   repetition code, which has the same form as the one of the QRE;
+//! - `errorBudget` is the error budget, either a total or its parts.
+//!
+//! The other parameters (T gates, which are replaced by Toffoli factories,
+//! idle and single-qubit errors, folded in the repetition code fit, the
+//! predefined surface and Floquet codes, ...) are listed by
+//! [`QreParameters::ignored`].
+
+use std::{fs::read_to_string, path::Path};
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::ErrorBudget;
+use serde::Deserialize;
+
+use crate::{
+    budget,
+    code::{
+        phase_flip::PowerLawFit,
+        schedule::{CycleSchedule, RoundsPerCycle},
+    },
+    units::TimeSpan,
+    CatQubit, RepetitionCode,
+};
+
+/// Parameters of the Azure Quantum Resource Estimator.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QreParameters {
+    /// Physical qubits.
+    #[serde(default)]
+    pub qubit_params: QubitParams,
+    /// Error correction scheme.
+    #[serde(default)]
+    pub qec_scheme: QecScheme,
+    /// Error budget.
+    #[serde(default)]
+    pub error_budget: Option<QreErrorBudget>,
+}
+
+/// `qubitParams` of the QRE, the missing values being the ones of the
+/// predefined model `name`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct QubitParams {
+    pub name: Option<String>,
+    pub instruction_set: Option<String>,
+    pub one_qubit_measurement_time: Option<String>,
+    pub one_qubit_gate_time: Option<String>,
+    pub two_qubit_gate_time: Option<String>,
+    pub t_gate_time: Option<String>,
+    pub one_qubit_measurement_error_rate: Option<f64>,
+    pub one_qubit_gate_error_rate: Option<f64>,
+    pub two_qubit_gate_error_rate: Option<f64>,
+    pub t_gate_error_rate: Option<f64>,
+    pub idle_error_rate: Option<f64>,
+}
+
+/// `qecScheme` of the QRE.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct QecScheme {
+    pub name: Option<String>,
+    pub crossing_prefactor: Option<f64>,
+    pub error_correction_threshold: Option<f64>,
+    pub logical_cycle_time: Option<String>,
+    pub physical_qubits_per_logical_qubit: Option<String>,
+    pub max_code_distance: Option<u64>,
+}
+
+/// `errorBudget` of the QRE, a total or its parts.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum QreErrorBudget {
+    /// Total error budget, split equally between the topological and magic
+    /// state errors.
+    Total(f64),
+    /// Error budget of each part.
+    Parts {
+        /// Topological errors.
+        logical: f64,
+        /// Magic state errors.
+        tstates: f64,
+        /// Rotation synthesis errors.
+        #[serde(default)]
+        rotations: f64,
+    },
+}
+
+/// Times and two-qubit error rate of a predefined gate-based model of the QRE:
+/// measurement, single- and two-qubit gates (ns), and two-qubit error rate.
+fn predefined(name: &str) -> Result<(u64, u64, u64, f64), String> {
+    match name {
+        "qubit_gate_ns_e3" => Ok((100, 50, 50, 1e-3)),
+        "qubit_gate_ns_e4" => Ok((100, 50, 50, 1e-4)),
+        "qubit_gate_us_e3" => Ok((100_000, 100_000, 100_000, 1e-3)),
+        "qubit_gate_us_e4" => Ok((100_000, 100_000, 100_000, 1e-4)),
+        "qubit_maj_ns_e4" | "qubit_maj_ns_e6" => Err(format!(
+            "{name} describes Majorana qubits, which have no cat qubit equivalent"
+        )),
+        _ => Err(format!(
+            "unknown qubit model '{name}' (expected qubit_gate_ns_e3, qubit_gate_ns_e4, \
+             qubit_gate_us_e3 or qubit_gate_us_e4)"
+        )),
+    }
+}
+
+impl QreParameters {
+    /// Read the parameters from a JSON file.
+    pub fn from_file(filename: impl AsRef<Path>) -> Result<Self, String> {
+        let filename = filename.as_ref();
+        let content = read_to_string(filename)
+            .map_err(|err| format!("can't read {}: {err}", filename.display()))?;
+        Self::from_json(&content)
+    }
+
+    /// Read the parameters from JSON, checking that they can be mapped.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let parameters: Self =
+            serde_json::from_str(json).map_err(|err| format!("invalid QRE parameters: {err}"))?;
+        let qubit = parameters.cat_qubit(CatQubit::new())?;
+        parameters.schedule(qubit.inverse_k2_ns())?;
+        parameters.budget().transpose()?;
+        Ok(parameters)
+    }
+
+    /// Cat qubits `qubit` with the κ₁/κ₂ of the two-qubit gate error rate at
+    /// their time scale 1/κ₂.
+    pub fn cat_qubit(&self, qubit: CatQubit) -> Result<CatQubit, String> {
+        let (_, _, two_qubit_gate_time) = self.gate_times()?;
+        let error_rate = match self.qubit_params.two_qubit_gate_error_rate {
+            Some(error_rate) => error_rate,
+            None => self.predefined()?.3,
+        };
+        if !(error_rate > 0.0 && error_rate < 1.0) {
+            return Err(format!(
+                "two-qubit gate error rate {error_rate} is not a probability"
+            ));
+        }
+        if two_qubit_gate_time.as_ns() == 0 {
+            return Err("two-qubit gate time is 0".into());
+        }
+        let inverse_k2_ns = qubit.inverse_k2_ns();
+        Ok(qubit.with_k1_k2(error_rate * inverse_k2_ns / two_qubit_gate_time.as_ns_f64()))
+    }
+
+    /// Schedule of the repetition code rounds with the gate times, the CX
+    /// gates taking the time scale 1/κ₂ (`inverse_k2_ns`, in nanoseconds) and
+    /// the rest of the round the other steps.
+    pub fn schedule(&self, inverse_k2_ns: f64) -> Result<CycleSchedule, String> {
+        if !(inverse_k2_ns.is_finite() && inverse_k2_ns > 0.0) {
+            return Err(format!("1/κ₂ = {inverse_k2_ns} ns is not positive"));
+        }
+        let (measurement, one_qubit_gate, two_qubit_gate) = self.gate_times()?;
+        let round = one_qubit_gate + two_qubit_gate * CycleSchedule::CX_LAYERS + measurement;
+        let other_steps = round.as_ns_f64() / inverse_k2_ns
+            - CycleSchedule::CX_LAYERS.to_f64().unwrap_or_default();
+        if other_steps < 0.0 {
+            return Err(format!(
+                "a round of {round} (preparation, CX gates and measurement) is shorter than \
+                 its {} CX gates of 1/κ₂ = {inverse_k2_ns} ns each",
+                CycleSchedule::CX_LAYERS
+            ));
+        }
+        CycleSchedule::new(other_steps, RoundsPerCycle::Distance)
+    }
+
+    /// Repetition code `code` with the schedule of the gate times for the
+    /// time scale 1/κ₂ `inverse_k2_ns` and, if given, the crossing prefactor
+    /// and threshold of the QRE.
+    pub fn repetition_code(
+        &self,
+        code: RepetitionCode,
+        inverse_k2_ns: f64,
+    ) -> Result<RepetitionCode, String> {
+        let code = code.with_schedule(self.schedule(inverse_k2_ns)?)?;
+        let qec = &self.qec_scheme;
+        if qec.crossing_prefactor.is_none() && qec.error_correction_threshold.is_none() {
+            return Ok(code);
+        }
+        let default = PowerLawFit::default();
+        code.with_fit(
+            qec.crossing_prefactor.unwrap_or(default.prefactor),
+            default.alpha_exponent,
+            qec.error_correction_threshold.unwrap_or(default.threshold),
+        )
+    }
+
+    /// Error budget, if given.
+    #[must_use]
+    pub fn budget(&self) -> Option<Result<ErrorBudget, String>> {
+        Some(match self.error_budget? {
+            QreErrorBudget::Total(total) => budget::split_error_budget(total),
+            QreErrorBudget::Parts {
+                logical,
+                tstates,
+                rotations,
+            } => budget::error_budget(logical, tstates, rotations),
+        })
+    }
+
+    /// Parameters given but not mapped to the cat qubit model.
+    #[must_use]
+    pub fn ignored(&self) -> Vec<&'static str> {
+        let qubit = &self.qubit_params;
+        let qec = &self.qec_scheme;
+        [
+            ("tGateTime", qubit.t_gate_time.is_some()),
+            ("tGateErrorRate", qubit.t_gate_error_rate.is_some()),
+            ("idleErrorRate", qubit.idle_error_rate.is_some()),
+            (
+                "oneQubitGateErrorRate",
+                qubit.one_qubit_gate_error_rate.is_some(),
+            ),
+            (
+                "oneQubitMeasurementErrorRate",
+                qubit.one_qubit_measurement_error_rate.is_some(),
+            ),
+            ("qecScheme.name", qec.name.is_some()),
+            ("logicalCycleTime", qec.logical_cycle_time.is_some()),
+            (
+                "physicalQubitsPerLogicalQubit",
+                qec.physical_qubits_per_logical_qubit.is_some(),
+            ),
+            ("maxCodeDistance", qec.max_code_distance.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, given)| given.then_some(name))
+        .collect()
+    }
+
+    /// Measurement, single- and two-qubit gate times.
+    fn gate_times(&self) -> Result<(TimeSpan, TimeSpan, TimeSpan), String> {
+        let qubit = &self.qubit_params;
+        if let Some(instruction_set) = &qubit.instruction_set {
+            if !instruction_set.eq_ignore_ascii_case("GateBased") {
+                return Err(format!(
+                    "instruction set {instruction_set} is not supported (expected GateBased)"
+                ));
+            }
+        }
+        let time = |time: &Option<String>, default: fn(&Self) -> Result<u64, String>| match time {
+            Some(time) => time.parse(),
+            None => default(self).map(TimeSpan::from_ns),
+        };
+        Ok((
+            time(&qubit.one_qubit_measurement_time, |p| Ok(p.predefined()?.0))?,
+            time(&qubit.one_qubit_gate_time, |p| Ok(p.predefined()?.1))?,
+            time(&qubit.two_qubit_gate_time, |p| Ok(p.predefined()?.2))?,
+        ))
+    }
+
+    /// Values of the predefined model, the one of `qubit_gate_ns_e3` if none
+    /// is named.
+    fn predefined(&self) -> Result<(u64, u64, u64, f64), String> {
+        predefined(
+            self.qubit_params
+                .name
+                .as_deref()
+                .unwrap_or("qubit_gate_ns_e3"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QreErrorBudget, QreParameters};
+    use crate::{code::schedule::RoundsPerCycle, testing::close, CatQubit};
+
+    #[test]
+    fn two_qubit_error_rate_sets_k1_k2() {
+        let parameters = QreParameters::from_json(
+            r#"{"qubitParams": {"name": "qubit_gate_ns_e3", "twoQubitGateErrorRate": 5e-4}}"#,
+        )
+        .expect("valid parameters");
+        // 5·10⁻⁴ over a CX of 50 ns, at 1/κ₂ = 100 ns
+        let qubit = parameters.cat_qubit(CatQubit::new()).expect("valid rate");
+        assert!(close(qubit.k1_k2(), 1e-3), "{}", qubit.k1_k2());
+        // the time scale of the qubits is kept
+        let qubit = parameters
+            .cat_qubit(CatQubit::new().with_inverse_k2_ns(50.0))
+            .expect("valid rate");
+        assert!(close(qubit.k1_k2(), 5e-4), "{}", qubit.k1_k2());
+        assert!(close(qubit.inverse_k2_ns(), 50.0));
+    }
+
+    #[test]
+    fn gate_times_set_the_schedule() {
+        let parameters = QreParameters::from_json(
+            r#"{"qubitParams": {"name": "qubit_gate_us_e4", "oneQubitGateTime": "200 ns",
+                "twoQubitGateTime": "150 ns", "oneQubitMeasurementTime": "1 us"}}"#,
+        )
+        .expect("valid parameters");
+        // a round of 200 + 2·150 + 1000 ns, i.e. 15 steps of 100 ns, 2 of them CX
+        let schedule = parameters.schedule(100.0).expect("valid times");
+        assert!(
+            close(schedule.other_steps, 13.0),
+            "{}",
+            schedule.other_steps
+        );
+        assert_eq!(schedule.rounds_per_cycle, RoundsPerCycle::Distance);
+        let schedule = parameters.schedule(500.0).expect("valid times");
+        assert!(close(schedule.other_steps, 1.0), "{}", schedule.other_steps);
+        // CX gates shorter than 1/κ₂
+        assert!(parameters.schedule(1000.0).is_err());
+        assert!(parameters.schedule(0.0).is_err());
+    }
+
+    #[test]
+    fn predefined_models_are_mapped() {
+        let parameters =
+            QreParameters::from_json(r#"{"qubitParams": {"name": "qubit_gate_ns_e4"}}"#)
+                .expect("valid parameters");
+        let qubit = parameters.cat_qubit(CatQubit::new()).expect("valid rate");
+        assert!(close(qubit.k1_k2(), 2e-4), "{}", qubit.k1_k2());
+        // a round of 50 + 2·50 + 100 ns
+        let schedule = parameters.schedule(100.0).expect("valid times");
+        assert!(close(schedule.other_steps, 0.5), "{}", schedule.other_steps);
+        for json in [
+            r#"{"qubitParams": {"name": "qubit_maj_ns_e4"}}"#,
+            r#"{"qubitParams": {"name": "qubit_gate_ps_e3"}}"#,
+            r#"{"qubitParams": {"instructionSet": "Majorana"}}"#,
+            r#"{"qubitParams": {"twoQubitGateErrorRate": 1.5}}"#,
+            r#"{"qubitParams": {"twoQubitGateTime": "0 ns"}}"#,
+            r#"{"qubitParams": {"twoQubitGateTime": "fast"}}"#,
+            r#"{"errorBudget": 2}"#,
+        ] {
+            assert!(QreParameters::from_json(json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn error_budget_is_a_total_or_its_parts() {
+        let total = QreParameters::from_json(r#"{"errorBudget": 1e-3}"#).expect("valid budget");
+        assert_eq!(total.error_budget, Some(QreErrorBudget::Total(1e-3)));
+        let budget = total.budget().expect("given").expect("valid budget");
+        assert!(close(budget.logical(), 5e-4) && close(budget.magic_states(), 5e-4));
+
+        let parts = QreParameters::from_json(
+            r#"{"errorBudget": {"logical": 1e-3, "tstates": 2e-3, "rotations": 0}}"#,
+        )
+        .expect("valid budget");
+        let budget = parts.budget().expect("given").expect("valid budget");
+        assert!(close(budget.logical(), 1e-3) && close(budget.magic_states(), 2e-3));
+        assert!(QreParameters::default().budget().is_none());
+    }
+
+    #[test]
+    fn unmapped_parameters_are_listed() {
+        let parameters = QreParameters::from_json(
+            r#"{"qubitParams": {"tGateTime": "50 ns", "idleErrorRate": 1e-5},
+                "qecScheme": {"crossingPrefactor": 0.05, "maxCodeDistance": 25}}"#,
+        )
+        .expect("valid parameters");
+        assert_eq!(
+            parameters.ignored(),
+            ["tGateTime", "idleErrorRate", "maxCodeDistance"]
+        );
+    }
+}
@@ -6,25 +6,136 @@
 //!
 //! Cat qubits are characterized by:
 //! - the physical error rate κ₁/κ₂
+//! - the thermal population nₜₕ of the cavity
+//! - the leakage probability out of the cat-qubit manifold (photon jumps to
+//!   states not stabilized by the two-photon dissipation), per code round
+//! - optionally, the maximum |α|² the pump can sustain
+//! - the explicit error probability of the ancilla measurements, on top of the
+//!   measurement errors already included in the repetition code fit
+//! - the implementation of the CX gates ([`CxImplementation`]), in the
+//!   computation part and in the magic state factories
 //! - their average number of photons |α|²
+//! - the time scale 1/κ₂, possibly different in the magic state factories
 //!
 //! Default value (and only one compatible with the magic state factories
 //! precomputations) is κ₁/κ₂=1e-5, while |α|² is considered as an error
 //! correction code parameter and not handled in this module (resource estimator
 //! will optimized on it).
+//!
+//! Leakage is not part of the model of
+//! [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) and is disabled by
+//! default; see [`crate::RepetitionCode`] for its effect on the logical error.
+//!
+//! Thermal photons (nₜₕ > 0) raise the phase-flip rate, which scales as
+//! κ₁(1 + 2nₜₕ)|α|². This is accounted for through an effective κ₁/κ₂.
+//!
+//! The two-photon dissipation κ₂ sets the speed of the gates: durations are
+//! proportional to 1/κ₂ (100 ns by default, as in arXiv:2302.06639). The
+//! factories may use modes with a different κ₂; κ₁ being the same, their
+//! κ₁/κ₂ is scaled accordingly (see [`CatQubit::factory_k1_k2`]).
+//!
+//! The ratio κ₁/κ₂ and nₜₕ can be obtained from the calibration of a device
+//! (see [`calibration`]).
+//!
+//! Crosstalk can inflate all the physical error rates by a configurable factor
+//! (see [`crosstalk`]).
+
+pub mod calibration;
+pub mod crosstalk;
+
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+use crosstalk::Crosstalk;
+
+/// Default time scale 1/κ₂, in nanoseconds, used to precompute the
+/// factories.
+pub const REFERENCE_INVERSE_K2_NS: f64 = 100.0;
+
+/// Implementation of the CX gates between cat qubits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CxImplementation {
+    /// Gate of duration 1/κ₂, as in the computation part of
+    /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 28).
+    Fast,
+    /// Adiabatic gate of duration 89.2/(κ₂|α|²), as in the magic state
+    /// factories of [arXiv:2302.06639](https://arxiv.org/abs/2302.06639)
+    /// (p. 32).
+    Adiabatic,
+}
+
+impl CxImplementation {
+    /// Duration of the gate, in units of 1/κ₂.
+    #[must_use]
+    pub fn duration(self, alpha_sq: f64) -> f64 {
+        match self {
+            Self::Fast => 1.0,
+            // The more accurate # of time steps 89.2 was taken from the Github
+            // code (vs 89 in arXiv:2302.06639 (p. 32))
+            Self::Adiabatic => 89.2 / alpha_sq,
+        }
+    }
+}
+
+impl Display for CxImplementation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fast => write!(f, "fast"),
+            Self::Adiabatic => write!(f, "adiabatic"),
+        }
+    }
+}
+
+impl FromStr for CxImplementation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fast" => Ok(Self::Fast),
+            "adiabatic" => Ok(Self::Adiabatic),
+            _ => Err(format!(
+                "unknown CX implementation '{s}' (expected fast or adiabatic)"
+            )),
+        }
+    }
+}
 
 /// Struct for cat qubits, stores κ₁/κ₂, the ratio between the one and two
 /// photon loss rates, as it defines the intrinsic physical error rate.
+#[derive(Clone, PartialEq)]
 #[must_use]
 pub struct CatQubit {
     pub(crate) k1_k2: f64,
+    pub(crate) n_th: f64,
+    pub(crate) leakage_rate: f64,
+    pub(crate) max_alpha_sq: Option<f64>,
+    pub(crate) measurement_error: f64,
+    pub(crate) compute_cx: CxImplementation,
+    pub(crate) factory_cx: CxImplementation,
+    pub(crate) inverse_k2_ns: f64,
+    pub(crate) factory_inverse_k2_ns: f64,
+    pub(crate) crosstalk: Option<Crosstalk>,
 }
 
 impl Default for CatQubit {
     /// Set κ₁/κ₂ to a default value of 1e-5, as in
-    /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 2).
+    /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 2), without
+    /// thermal photons, with fast CX gates in the computation part and
+    /// adiabatic ones in the factories, and 1/κ₂ = 100 ns everywhere.
     fn default() -> Self {
-        Self { k1_k2: 1e-5 }
+        Self {
+            k1_k2: 1e-5,
+            n_th: 0.0,
+            leakage_rate: 0.0,
+            max_alpha_sq: None,
+            measurement_error: 0.0,
+            compute_cx: CxImplementation::Fast,
+            factory_cx: CxImplementation::Adiabatic,
+            inverse_k2_ns: REFERENCE_INVERSE_K2_NS,
+            factory_inverse_k2_ns: REFERENCE_INVERSE_K2_NS,
+            crosstalk: None,
+        }
     }
 }
 
@@ -35,4 +146,218 @@ impl CatQubit {
         // changing this.
         Self::default()
     }
+
+    /// Set the ratio κ₁/κ₂ between the one and two photon loss rates.
+    ///
+    /// The performances of the factories have been precomputed for κ₁/κ₂ =
+    /// 1e-5, they are extrapolated for other values (see
+    /// [`ToffoliFactory::for_qubit`](crate::factories::ToffoliFactory::for_qubit)).
+    pub fn with_k1_k2(mut self, k1_k2: f64) -> Self {
+        self.k1_k2 = k1_k2;
+        self
+    }
+
+    /// Ratio κ₁/κ₂ between the one and two photon loss rates.
+    #[must_use]
+    pub fn k1_k2(&self) -> f64 {
+        self.k1_k2
+    }
+
+    /// Set the average number of thermal photons nₜₕ in the cavity.
+    pub fn with_thermal_photons(mut self, n_th: f64) -> Self {
+        self.n_th = n_th;
+        self
+    }
+
+    /// Average number of thermal photons nₜₕ in the cavity.
+    #[must_use]
+    pub fn thermal_photons(&self) -> f64 {
+        self.n_th
+    }
+
+    /// Set the leakage probability per physical qubit and per round of the
+    /// error correction code (0 by default, i.e. no leakage).
+    pub fn with_leakage_rate(mut self, leakage_rate: f64) -> Self {
+        self.leakage_rate = leakage_rate;
+        self
+    }
+
+    /// Leakage probability per physical qubit and per round of the error
+    /// correction code.
+    #[must_use]
+    pub fn leakage_rate(&self) -> f64 {
+        self.leakage_rate
+    }
+
+    /// Set the explicit error probability of an ancilla measurement (0 by
+    /// default, measurement errors being folded into the repetition code
+    /// fit).
+    pub fn with_measurement_error(mut self, measurement_error: f64) -> Self {
+        self.measurement_error = measurement_error;
+        self
+    }
+
+    /// Explicit error probability of an ancilla measurement.
+    #[must_use]
+    pub fn measurement_error(&self) -> f64 {
+        self.measurement_error
+    }
+
+    /// Use the same CX implementation in the computation part and in the
+    /// magic state factories.
+    pub fn with_cx_implementation(mut self, cx: CxImplementation) -> Self {
+        self.compute_cx = cx;
+        self.factory_cx = cx;
+        self
+    }
+
+    /// Set the CX implementation in the magic state factories only (after
+    /// [`CatQubit::with_cx_implementation`]).
+    pub fn with_factory_cx_implementation(mut self, cx: CxImplementation) -> Self {
+        self.factory_cx = cx;
+        self
+    }
+
+    /// CX implementation in the computation part.
+    #[must_use]
+    pub fn compute_cx(&self) -> CxImplementation {
+        self.compute_cx
+    }
+
+    /// CX implementation in the magic state factories.
+    #[must_use]
+    pub fn factory_cx(&self) -> CxImplementation {
+        self.factory_cx
+    }
+
+    /// Set the time scale 1/κ₂, in nanoseconds, in the computation part and
+    /// in the magic state factories.
+    pub fn with_inverse_k2_ns(mut self, inverse_k2_ns: f64) -> Self {
+        self.inverse_k2_ns = inverse_k2_ns;
+        self.factory_inverse_k2_ns = inverse_k2_ns;
+        self
+    }
+
+    /// Set the time scale 1/κ₂, in nanoseconds, in the magic state factories
+    /// only (after [`CatQubit::with_inverse_k2_ns`]).
+    pub fn with_factory_inverse_k2_ns(mut self, inverse_k2_ns: f64) -> Self {
+        self.factory_inverse_k2_ns = inverse_k2_ns;
+        self
+    }
+
+    /// Time scale 1/κ₂ in the computation part, in nanoseconds.
+    #[must_use]
+    pub fn inverse_k2_ns(&self) -> f64 {
+        self.inverse_k2_ns
+    }
+
+    /// Time scale 1/κ₂ in the magic state factories, in nanoseconds.
+    #[must_use]
+    pub fn factory_inverse_k2_ns(&self) -> f64 {
+        self.factory_inverse_k2_ns
+    }
+
+    /// Set the maximum average number of photons |α|² reachable with the
+    /// available pump power, which must be positive. Code parameters and
+    /// factories above it are discarded.
+    pub fn with_max_alpha_sq(mut self, max_alpha_sq: f64) -> Result<Self, String> {
+        if !(max_alpha_sq.is_finite() && max_alpha_sq > 0.0) {
+            return Err(format!("{max_alpha_sq} is not a positive |α|²"));
+        }
+        self.max_alpha_sq = Some(max_alpha_sq);
+        Ok(self)
+    }
+
+    /// Remove the cap on the average number of photons |α|².
+    pub fn without_max_alpha_sq(mut self) -> Self {
+        self.max_alpha_sq = None;
+        self
+    }
+
+    /// Maximum average number of photons |α|², if any.
+    #[must_use]
+    pub fn max_alpha_sq(&self) -> Option<f64> {
+        self.max_alpha_sq
+    }
+
+    /// Inflate the physical error rates by crosstalk (none by default).
+    pub fn with_crosstalk(mut self, crosstalk: Crosstalk) -> Self {
+        self.crosstalk = Some(crosstalk);
+        self
+    }
+
+    /// Crosstalk inflating the physical error rates, if any.
+    #[must_use]
+    pub fn crosstalk(&self) -> Option<Crosstalk> {
+        self.crosstalk
+    }
+
+    /// Factor multiplying the physical error rates (1 without crosstalk).
+    #[must_use]
+    pub fn crosstalk_factor(&self) -> f64 {
+        self.crosstalk.map_or(1.0, |crosstalk| crosstalk.factor())
+    }
+
+    /// Leakage probability per round with the crosstalk penalty, at most 1.
+    #[must_use]
+    pub fn effective_leakage_rate(&self) -> f64 {
+        (self.leakage_rate * self.crosstalk_factor()).min(1.0)
+    }
+
+    /// Ancilla measurement error probability with the crosstalk penalty, at
+    /// most 1.
+    #[must_use]
+    pub fn effective_measurement_error(&self) -> f64 {
+        (self.measurement_error * self.crosstalk_factor()).min(1.0)
+    }
+
+    /// Whether an average number of photons |α|² is reachable.
+    #[must_use]
+    pub fn supports_alpha_sq(&self, alpha_sq: f64) -> bool {
+        self.max_alpha_sq.is_none_or(|max| alpha_sq <= max)
+    }
+
+    /// Effective κ₁/κ₂ entering the phase-flip error rate, i.e.
+    /// κ₁(1 + 2nₜₕ)/κ₂ (thermal excitations add to photon losses), with the
+    /// crosstalk penalty.
+    #[must_use]
+    pub fn effective_k1_k2(&self) -> f64 {
+        self.k1_k2 * (1.0 + 2.0 * self.n_th) * self.crosstalk_factor()
+    }
+
+    /// Effective κ₁/κ₂ in the magic state factories: κ₁ being the same as in
+    /// the computation part, it scales with their 1/κ₂.
+    #[must_use]
+    pub fn factory_k1_k2(&self) -> f64 {
+        self.effective_k1_k2() * self.factory_inverse_k2_ns / self.inverse_k2_ns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crosstalk::Crosstalk, CatQubit};
+
+    #[test]
+    fn crosstalk_inflates_the_probabilities_up_to_1() {
+        let crosstalk = Crosstalk::new(0.5, 2.0).expect("valid crosstalk");
+        let qubit = CatQubit::new()
+            .with_leakage_rate(1e-3)
+            .with_measurement_error(0.4)
+            .with_crosstalk(crosstalk);
+        assert!((qubit.crosstalk_factor() - 2.0).abs() < 1e-12);
+        assert!((qubit.effective_leakage_rate() - 2e-3).abs() < 1e-15);
+        assert!((qubit.effective_measurement_error() - 0.8).abs() < 1e-12);
+        let qubit = qubit.with_crosstalk(Crosstalk::new(1.0, 10.0).expect("valid crosstalk"));
+        assert!((qubit.effective_measurement_error() - 1.0).abs() < f64::EPSILON);
+        assert!((CatQubit::new().crosstalk_factor() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn max_alpha_sq_is_positive() {
+        for max_alpha_sq in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(CatQubit::new().with_max_alpha_sq(max_alpha_sq).is_err());
+        }
+        let qubit = CatQubit::new().with_max_alpha_sq(10.0).expect("valid |α|²");
+        assert_eq!(qubit.max_alpha_sq(), Some(10.0));
+    }
 }
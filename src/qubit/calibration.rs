@@ -0,0 +1,460 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Cat qubits from the calibration of a device.
+//!
+//! A calibration file lists the rates measured on each cavity, either as CSV
+//! with a header row (empty cells for missing values, `#` for comments):
+//!
+//! ```csv
+//! name,kappa_1,kappa_2,t1_us,t_phi_us,n_th
+//! c0,,1e7,10,200,0.01
+//! c1,95,1.1e7,,150,
+//! ```
+//!
+//! or as JSON, with the same fields:
+//!
+//! ```json
+//! {"cavities": [{"name": "c0", "kappa_2": 1e7, "t1_us": 10, "n_th": 0.01}]}
+//! ```
+//!
+//! κ₁ and κ₂ are in s⁻¹, T1 and Tφ in µs; κ₁ can be given directly or through
+//! T1 (κ₁ = 1/T1). The ratios κ₁/κ₂ and the thermal populations nₜₕ of the
+//! cavities are aggregated with a [`CalibrationPolicy`] into the parameters of
+//! a [`CatQubit`]. The model keeps the time scale 1/κ₂ = 100 ns of
+//! [arXiv:2302.06639](https://arxiv.org/abs/2302.06639), so κ₂ only enters
+//! through κ₁/κ₂. Pure dephasing only induces bit flips, exponentially
+//! suppressed in |α|² and neglected by the model: Tφ is checked but not used.
+
+use std::{fmt::Display, fs::read_to_string, path::Path, str::FromStr};
+
+use serde::Deserialize;
+
+use super::CatQubit;
+
+/// Rates measured on one cavity.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct CavityCalibration {
+    /// Name of the cavity, to locate the errors.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Single-photon loss rate κ₁, in s⁻¹.
+    #[serde(default)]
+    pub kappa_1: Option<f64>,
+    /// Two-photon dissipation rate κ₂, in s⁻¹.
+    pub kappa_2: f64,
+    /// Energy relaxation time T1, in µs, if κ₁ is not given.
+    #[serde(default)]
+    pub t1_us: Option<f64>,
+    /// Pure dephasing time Tφ, in µs.
+    #[serde(default)]
+    pub t_phi_us: Option<f64>,
+    /// Average number of thermal photons nₜₕ.
+    #[serde(default)]
+    pub n_th: Option<f64>,
+}
+
+impl CavityCalibration {
+    /// Single-photon loss rate κ₁, in s⁻¹, measured or derived from T1.
+    pub fn kappa_1(&self) -> Result<f64, String> {
+        let kappa_1 = match (self.kappa_1, self.t1_us) {
+            (Some(kappa_1), _) => kappa_1,
+            (None, Some(t1_us)) if t1_us > 0.0 => 1e6 / t1_us,
+            (None, Some(t1_us)) => {
+                return Err(format!("{}: T1 {t1_us} µs is not positive", self.label()))
+            }
+            (None, None) => return Err(format!("{}: neither κ₁ nor T1 is given", self.label())),
+        };
+        if kappa_1.is_finite() && kappa_1 >= 0.0 {
+            Ok(kappa_1)
+        } else {
+            Err(format!("{}: κ₁ {kappa_1} s⁻¹ is not valid", self.label()))
+        }
+    }
+
+    /// Ratio κ₁/κ₂ of the cavity.
+    pub fn k1_k2(&self) -> Result<f64, String> {
+        if !(self.kappa_2.is_finite() && self.kappa_2 > 0.0) {
+            return Err(format!(
+                "{}: κ₂ {} s⁻¹ is not positive",
+                self.label(),
+                self.kappa_2
+            ));
+        }
+        if let Some(t_phi_us) = self.t_phi_us {
+            if t_phi_us.is_nan() || t_phi_us <= 0.0 {
+                return Err(format!(
+                    "{}: Tφ {t_phi_us} µs is not positive",
+                    self.label()
+                ));
+            }
+        }
+        Ok(self.kappa_1()? / self.kappa_2)
+    }
+
+    /// Thermal population of the cavity, 0 if not measured.
+    pub fn thermal_photons(&self) -> Result<f64, String> {
+        match self.n_th {
+            Some(n_th) if !(n_th.is_finite() && n_th >= 0.0) => Err(format!(
+                "{}: nₜₕ {n_th} is not a number of photons",
+                self.label()
+            )),
+            n_th => Ok(n_th.unwrap_or(0.0)),
+        }
+    }
+
+    /// Name of the cavity in the errors.
+    fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("cavity {name}"),
+            None => String::from("cavity"),
+        }
+    }
+}
+
+/// Aggregation of the rates of the cavities into the ones of the model.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CalibrationPolicy {
+    /// Median over the cavities.
+    #[default]
+    Median,
+    /// Mean over the cavities.
+    Mean,
+    /// Worst cavity (largest κ₁/κ₂ and nₜₕ), every qubit being assumed as bad.
+    WorstCase,
+}
+
+impl CalibrationPolicy {
+    /// Aggregate `values`, which must not be empty.
+    fn aggregate(self, mut values: Vec<f64>) -> f64 {
+        values.sort_by(f64::total_cmp);
+        let count = values.len();
+        match self {
+            Self::Median if count.is_multiple_of(2) => {
+                0.5 * (values[count / 2 - 1] + values[count / 2])
+            }
+            Self::Median => values[count / 2],
+            #[allow(clippy::cast_precision_loss)]
+            Self::Mean => values.iter().sum::<f64>() / count as f64,
+            Self::WorstCase => values[count - 1],
+        }
+    }
+}
+
+impl Display for CalibrationPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Median => write!(f, "median"),
+            Self::Mean => write!(f, "mean"),
+            Self::WorstCase => write!(f, "worst-case"),
+        }
+    }
+}
+
+impl FromStr for CalibrationPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "median" => Ok(Self::Median),
+            "mean" => Ok(Self::Mean),
+            "worst-case" => Ok(Self::WorstCase),
+            _ => Err(format!(
+                "unknown calibration policy '{s}' (expected median, mean or worst-case)"
+            )),
+        }
+    }
+}
+
+/// Calibration of the cavities of a device.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Calibration {
+    /// Rates measured on each cavity.
+    pub cavities: Vec<CavityCalibration>,
+}
+
+impl Calibration {
+    /// Read a calibration file, as JSON if its extension is `.json` and as
+    /// CSV otherwise.
+    pub fn from_file(filename: impl AsRef<Path>) -> Result<Self, String> {
+        let filename = filename.as_ref();
+        let content = read_to_string(filename)
+            .map_err(|err| format!("can't read {}: {err}", filename.display()))?;
+        let is_json = filename
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        if is_json {
+            Self::from_json(&content)
+        } else {
+            Self::from_csv(&content)
+        }
+    }
+
+    /// Calibration as JSON, either an object with a `cavities` array or the
+    /// array itself.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Export {
+            Object(Calibration),
+            Array(Vec<CavityCalibration>),
+        }
+
+        let calibration = match serde_json::from_str(json)
+            .map_err(|err| format!("invalid calibration: {err}"))?
+        {
+            Export::Object(calibration) => calibration,
+            Export::Array(cavities) => Self { cavities },
+        };
+        calibration.checked()
+    }
+
+    /// Calibration as CSV, with a header row naming the columns.
+    pub fn from_csv(csv: &str) -> Result<Self, String> {
+        let mut rows = csv
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        let (_, header) = rows.next().ok_or("empty calibration")?;
+        let columns: Vec<_> = header.split(',').map(str::trim).collect();
+        if let Some(unknown) = columns.iter().find(|column| {
+            !["name", "kappa_1", "kappa_2", "t1_us", "t_phi_us", "n_th"].contains(column)
+        }) {
+            return Err(format!("unknown calibration column '{unknown}'"));
+        }
+
+        let cavities = rows
+            .map(|(line, row)| {
+                let cells: Vec<_> = row.split(',').map(str::trim).collect();
+                if cells.len() != columns.len() {
+                    return Err(format!(
+                        "line {line}: {} cells for {} columns",
+                        cells.len(),
+                        columns.len()
+                    ));
+                }
+                let mut cavity = CavityCalibration::default();
+                let mut kappa_2 = None;
+                for (&column, &cell) in columns.iter().zip(&cells) {
+                    if cell.is_empty() {
+                        continue;
+                    }
+                    if column == "name" {
+                        cavity.name = Some(cell.to_string());
+                        continue;
+                    }
+                    let value = cell
+                        .parse()
+                        .map_err(|_| format!("line {line}: '{cell}' is not a {column}"))?;
+                    match column {
+                        "kappa_1" => cavity.kappa_1 = Some(value),
+                        "kappa_2" => kappa_2 = Some(value),
+                        "t1_us" => cavity.t1_us = Some(value),
+                        "t_phi_us" => cavity.t_phi_us = Some(value),
+                        _ => cavity.n_th = Some(value),
+                    }
+                }
+                cavity.kappa_2 = kappa_2.ok_or_else(|| format!("line {line}: κ₂ is not given"))?;
+                Ok(cavity)
+            })
+            .collect::<Result<_, String>>()?;
+        Self { cavities }.checked()
+    }
+
+    /// Ratio κ₁/κ₂ of the model, aggregated with `policy`.
+    pub fn k1_k2(&self, policy: CalibrationPolicy) -> Result<f64, String> {
+        self.aggregate(policy, CavityCalibration::k1_k2)
+    }
+
+    /// Thermal population nₜₕ of the model, aggregated with `policy`.
+    pub fn thermal_photons(&self, policy: CalibrationPolicy) -> Result<f64, String> {
+        self.aggregate(policy, CavityCalibration::thermal_photons)
+    }
+
+    /// Aggregation with `policy` of a quantity of the cavities.
+    fn aggregate(
+        &self,
+        policy: CalibrationPolicy,
+        quantity: impl Fn(&CavityCalibration) -> Result<f64, String>,
+    ) -> Result<f64, String> {
+        let values = self
+            .cavities
+            .iter()
+            .map(quantity)
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.is_empty() {
+            Err("no cavity in the calibration".into())
+        } else {
+            Ok(policy.aggregate(values))
+        }
+    }
+
+    /// Calibration, if its cavities are valid.
+    fn checked(self) -> Result<Self, String> {
+        self.k1_k2(CalibrationPolicy::default())?;
+        self.thermal_photons(CalibrationPolicy::default())?;
+        Ok(self)
+    }
+}
+
+impl CatQubit {
+    /// Cat qubits with the median rates of the cavities of a calibration file
+    /// (see [`Calibration::from_file`] for the format).
+    pub fn from_calibration(filename: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_calibration_with_policy(filename, CalibrationPolicy::default())
+    }
+
+    /// Cat qubits with the rates of the cavities of a calibration file,
+    /// aggregated with `policy`.
+    pub fn from_calibration_with_policy(
+        filename: impl AsRef<Path>,
+        policy: CalibrationPolicy,
+    ) -> Result<Self, String> {
+        Self::from_calibration_data(&Calibration::from_file(filename)?, policy)
+    }
+
+    /// Cat qubits with the rates of the cavities of `calibration`, aggregated
+    /// with `policy`.
+    pub fn from_calibration_data(
+        calibration: &Calibration,
+        policy: CalibrationPolicy,
+    ) -> Result<Self, String> {
+        Ok(Self::new()
+            .with_k1_k2(calibration.k1_k2(policy)?)
+            .with_thermal_photons(calibration.thermal_photons(policy)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Calibration, CalibrationPolicy, CavityCalibration};
+    use crate::testing::close;
+
+    /// Calibration of cavities with the given κ₁/κ₂ (κ₂ = 10⁷ s⁻¹) and nₜₕ.
+    fn calibration(cavities: &[(f64, f64)]) -> Calibration {
+        Calibration {
+            cavities: cavities
+                .iter()
+                .map(|&(k1_k2, n_th)| CavityCalibration {
+                    kappa_1: Some(k1_k2 * 1e7),
+                    kappa_2: 1e7,
+                    n_th: Some(n_th),
+                    ..CavityCalibration::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn rates_are_aggregated_with_the_policy() {
+        let odd = calibration(&[(3e-4, 0.0), (1e-4, 0.02), (2e-4, 0.01)]);
+        let even = calibration(&[(4e-4, 0.0), (1e-4, 0.02), (2e-4, 0.01), (1e-4, 0.01)]);
+        for (calibration, policy, k1_k2, n_th) in [
+            (&odd, CalibrationPolicy::Median, 2e-4, 0.01),
+            (&odd, CalibrationPolicy::Mean, 2e-4, 0.01),
+            (&odd, CalibrationPolicy::WorstCase, 3e-4, 0.02),
+            (&even, CalibrationPolicy::Median, 1.5e-4, 0.01),
+            (&even, CalibrationPolicy::Mean, 2e-4, 0.01),
+            (&even, CalibrationPolicy::WorstCase, 4e-4, 0.02),
+        ] {
+            let aggregated = calibration.k1_k2(policy).expect("valid cavities");
+            assert!(close(aggregated, k1_k2), "{policy}: {aggregated}");
+            let aggregated = calibration.thermal_photons(policy).expect("valid cavities");
+            assert!(close(aggregated, n_th), "{policy}: {aggregated}");
+        }
+        assert!(Calibration::default()
+            .k1_k2(CalibrationPolicy::Median)
+            .is_err());
+    }
+
+    #[test]
+    fn kappa_1_is_derived_from_t1() {
+        let cavity = CavityCalibration {
+            kappa_2: 1e7,
+            t1_us: Some(10.0),
+            ..CavityCalibration::default()
+        };
+        assert!(close(cavity.kappa_1().expect("T1 given"), 1e5));
+        assert!(close(cavity.k1_k2().expect("T1 given"), 1e-2));
+        // κ₁ takes precedence
+        let measured = CavityCalibration {
+            kappa_1: Some(50.0),
+            ..cavity.clone()
+        };
+        assert!(close(measured.kappa_1().expect("κ₁ given"), 50.0));
+        for t1_us in [None, Some(0.0), Some(-1.0)] {
+            let cavity = CavityCalibration {
+                t1_us,
+                ..cavity.clone()
+            };
+            assert!(cavity.kappa_1().is_err(), "{t1_us:?}");
+        }
+    }
+
+    #[test]
+    fn csv_cells_are_parsed_by_column() {
+        let calibration = Calibration::from_csv(
+            "# device A\nname,kappa_1,kappa_2,t1_us,t_phi_us,n_th\nc0,,1e7,10,200,0.01\n\
+             c1,95,1e7,,150,\n",
+        )
+        .expect("valid calibration");
+        assert_eq!(calibration.cavities.len(), 2);
+        assert_eq!(calibration.cavities[0].name.as_deref(), Some("c0"));
+        assert_eq!(calibration.cavities[0].kappa_1, None);
+        assert_eq!(calibration.cavities[1].kappa_1, Some(95.0));
+        // empty cells keep the defaults
+        assert_eq!(calibration.cavities[1].n_th, None);
+        let thermal = calibration
+            .thermal_photons(CalibrationPolicy::WorstCase)
+            .expect("valid cavities");
+        assert!(close(thermal, 0.01));
+    }
+
+    #[test]
+    fn invalid_csv_is_rejected() {
+        for csv in [
+            "",
+            "name,kappa_2,gain\nc0,1e7,3\n",
+            // non-numeric cells
+            "name,kappa_1,kappa_2\nc0,fast,1e7\n",
+            "kappa_1,kappa_2\n10,1e7,3\n",
+            // missing κ₂ column, or empty κ₂ cell
+            "name,kappa_1\nc0,100\n",
+            "kappa_1,kappa_2\n100,\n",
+            "kappa_1,kappa_2\n100,0\n",
+            "kappa_1,kappa_2\n-100,1e7\n",
+            "kappa_1,kappa_2,n_th\n100,1e7,-0.1\n",
+            "kappa_1,kappa_2,t_phi_us\n100,1e7,0\n",
+            "kappa_2\n1e7\n",
+        ] {
+            assert!(Calibration::from_csv(csv).is_err(), "{csv:?}");
+        }
+    }
+
+    #[test]
+    fn json_is_an_object_or_an_array() {
+        let object = Calibration::from_json(
+            r#"{"cavities": [{"name": "c0", "kappa_2": 1e7, "t1_us": 10, "n_th": 0.01}]}"#,
+        )
+        .expect("valid calibration");
+        let array = Calibration::from_json(
+            r#"[{"name": "c0", "kappa_2": 1e7, "t1_us": 10, "n_th": 0.01}]"#,
+        )
+        .expect("valid calibration");
+        assert_eq!(object, array);
+        assert!(Calibration::from_json(r#"[{"name": "c0", "t1_us": 10}]"#).is_err());
+    }
+
+    #[test]
+    fn policies_are_parsed() {
+        for policy in [
+            CalibrationPolicy::Median,
+            CalibrationPolicy::Mean,
+            CalibrationPolicy::WorstCase,
+        ] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        assert!("best".parse::<CalibrationPolicy>().is_err());
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Inflation of the physical error rates by crosstalk.
+//!
+//! The models of [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) treat
+//! the cat qubits as independent. A [`Crosstalk`] bounds the impact of
+//! correlated errors with a simple linear model: each aggressor (a
+//! neighbouring qubit for a penalty on the qubit density, or a gate executed
+//! simultaneously nearby for a penalty on the parallelism) adds a fixed
+//! fraction to the physical error rates, which are multiplied by
+//!
+//! ```text
+//! 1 + penalty × aggressors
+//! ```
+//!
+//! The same factor applies to κ₁/κ₂ (phase flips), to the leakage rate and to
+//! the ancilla measurement errors. It is not derived from a physical model of
+//! the couplings, but lets a study check how sensitive its numbers are to
+//! crosstalk.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Linear penalty on the physical error rates.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "CrosstalkFields")]
+pub struct Crosstalk {
+    /// Relative increase of the error rates per aggressor.
+    penalty: f64,
+    /// Number of aggressors per qubit (neighbours, or simultaneous gates).
+    aggressors: f64,
+}
+
+/// Fields of a serialized [`Crosstalk`], validated when deserialized.
+#[derive(Deserialize)]
+struct CrosstalkFields {
+    penalty: f64,
+    aggressors: f64,
+}
+
+impl TryFrom<CrosstalkFields> for Crosstalk {
+    type Error = String;
+
+    fn try_from(fields: CrosstalkFields) -> Result<Self, Self::Error> {
+        Self::new(fields.penalty, fields.aggressors)
+    }
+}
+
+impl Crosstalk {
+    /// Penalty per aggressor and number of aggressors, which must be
+    /// non-negative.
+    pub fn new(penalty: f64, aggressors: f64) -> Result<Self, String> {
+        for (name, value) in [("penalty", penalty), ("aggressors", aggressors)] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(format!(
+                    "crosstalk {name} {value} must be a non-negative number"
+                ));
+            }
+        }
+        Ok(Self {
+            penalty,
+            aggressors,
+        })
+    }
+
+    /// Relative increase of the error rates per aggressor.
+    #[must_use]
+    pub fn penalty(&self) -> f64 {
+        self.penalty
+    }
+
+    /// Number of aggressors per qubit.
+    #[must_use]
+    pub fn aggressors(&self) -> f64 {
+        self.aggressors
+    }
+
+    /// Factor multiplying the physical error rates.
+    #[must_use]
+    pub fn factor(&self) -> f64 {
+        1.0 + self.penalty * self.aggressors
+    }
+}
+
+impl Display for Crosstalk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.penalty, self.aggressors)
+    }
+}
+
+impl FromStr for Crosstalk {
+    type Err = String;
+
+    /// Parse `PENALTY,AGGRESSORS` (e.g. `0.05,2`), or `PENALTY` with 2
+    /// aggressors, the neighbours of a qubit in the row of the repetition
+    /// code.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{s}' is not a crosstalk model (expected PENALTY[,AGGRESSORS])");
+        match s.split(',').collect::<Vec<_>>()[..] {
+            [penalty] => Self::new(penalty.trim().parse().map_err(|_| invalid())?, 2.0),
+            [penalty, aggressors] => Self::new(
+                penalty.trim().parse().map_err(|_| invalid())?,
+                aggressors.trim().parse().map_err(|_| invalid())?,
+            ),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Crosstalk;
+
+    #[test]
+    fn penalty_grows_with_the_aggressors() {
+        let crosstalk = Crosstalk::new(0.05, 4.0).expect("valid crosstalk");
+        assert!((crosstalk.factor() - 1.2).abs() < 1e-12);
+        let none = Crosstalk::new(0.0, 4.0).expect("valid crosstalk");
+        assert!((none.factor() - 1.0).abs() < f64::EPSILON);
+        for (penalty, aggressors) in [
+            (-0.1, 2.0),
+            (0.1, -1.0),
+            (f64::NAN, 2.0),
+            (0.1, f64::INFINITY),
+        ] {
+            assert!(Crosstalk::new(penalty, aggressors).is_err());
+        }
+    }
+
+    #[test]
+    fn crosstalk_is_parsed() {
+        assert_eq!("0.05,3".parse(), Crosstalk::new(0.05, 3.0));
+        // the neighbours in the row by default
+        assert_eq!(" 0.05 ".parse(), Crosstalk::new(0.05, 2.0));
+        let crosstalk: Crosstalk = "0.1,2.5".parse().expect("valid crosstalk");
+        assert_eq!(crosstalk.to_string().parse(), Ok(crosstalk));
+        for text in ["", "x", "0.1,2,3", "0.1,", "-0.1", "0.1,-2"] {
+            assert!(text.parse::<Crosstalk>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn deserialized_crosstalk_is_validated() {
+        let crosstalk: Crosstalk =
+            serde_json::from_str(r#"{"penalty": 0.05, "aggressors": 2}"#).expect("valid");
+        assert_eq!(Ok(crosstalk), Crosstalk::new(0.05, 2.0));
+        assert_eq!(
+            serde_json::to_string(&crosstalk).ok().as_deref(),
+            Some(r#"{"penalty":0.05,"aggressors":2.0}"#)
+        );
+        assert!(serde_json::from_str::<Crosstalk>(r#"{"penalty": -1, "aggressors": 2}"#).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Reproducible randomness for the stochastic estimates.
+//!
+//! The stochastic parts of the library (for now, the sampling of the
+//! wall-clock time with restarts, see [`crate::restart`]) draw their random
+//! numbers from a [`StochasticRng`] built from an explicit seed, never from a
+//! global generator. It is a `ChaCha8` generator, whose output for a given seed
+//! is portable and stable across versions, so the seed reported with an
+//! estimate reproduces it exactly (e.g. in a paper or in CI).
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Generator used by the stochastic estimates.
+pub type StochasticRng = ChaCha8Rng;
+
+/// Generator with the given seed.
+#[must_use]
+pub fn seeded(seed: u64) -> StochasticRng {
+    StochasticRng::seed_from_u64(seed)
+}
+
+/// Fresh seed from the operating system, for runs without an explicit seed
+/// (it should be reported with the results).
+#[must_use]
+pub fn random_seed() -> u64 {
+    rand::random()
+}
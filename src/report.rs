@@ -0,0 +1,178 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Rendering of the estimates in formats other than plain text.
+//!
+//! The JSON output follows a versioned schema, see [`crate::schema`].
+//!
+//! Markdown and LaTeX tables are meant to be pasted into reports and papers. A
+//! single estimate is rendered as a two-column (quantity, value) table, while
+//! several estimates (e.g. a frontier) are rendered with one row per estimate.
+//! All the formats contain the quantities given by
+//! [`AliceAndBobEstimates::report_sections`].
+//!
+//! A self-contained HTML report, with charts, is given by [`html_report`].
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{schema::ResultDocument, AliceAndBobEstimates};
+
+pub use html::html_report;
+
+mod html;
+
+/// Section of a report: list of (label, formatted value) pairs.
+pub type ReportSection = Vec<(&'static str, String)>;
+
+/// Output format of the estimates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable text, as given by [`Display`].
+    #[default]
+    Text,
+    /// Markdown table.
+    Markdown,
+    /// LaTeX `tabular` environment.
+    Latex,
+    /// JSON document, see [`crate::schema`].
+    Json,
+}
+
+impl OutputFormat {
+    /// Render estimates in this format.
+    #[must_use]
+    pub fn render(self, estimates: &[AliceAndBobEstimates]) -> String {
+        match self {
+            Self::Text => estimates.iter().fold(String::new(), |mut text, estimate| {
+                text.push_str(&estimate.to_string());
+                text.push('\n');
+                text
+            }),
+            Self::Markdown => markdown_table(estimates),
+            Self::Latex => latex_table(estimates),
+            Self::Json => {
+                let mut json = serde_json::to_string_pretty(&ResultDocument::new(estimates))
+                    .expect("can't serialize estimates");
+                json.push('\n');
+                json
+            }
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Markdown => write!(f, "markdown"),
+            Self::Latex => write!(f, "latex"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "latex" | "tex" => Ok(Self::Latex),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "unknown output format '{s}' (expected text, markdown, latex or json)"
+            )),
+        }
+    }
+}
+
+/// Labels and values of an estimate, sections flattened.
+fn rows(estimate: &AliceAndBobEstimates) -> Vec<(&'static str, String)> {
+    estimate.report_sections().into_iter().flatten().collect()
+}
+
+/// Render estimates as a Markdown table.
+#[must_use]
+pub fn markdown_table(estimates: &[AliceAndBobEstimates]) -> String {
+    let escape = |cell: &str| cell.replace('|', "\\|");
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+
+    let mut table = String::new();
+    if let [estimate] = estimates {
+        table.push_str(&line(vec!["Quantity".into(), "Value".into()]));
+        table.push_str("|---|---:|\n");
+        for (label, value) in rows(estimate) {
+            table.push_str(&line(vec![escape(label), escape(&value)]));
+        }
+    } else if let Some(first) = estimates.first() {
+        let labels: Vec<_> = rows(first).into_iter().map(|(label, _)| label).collect();
+        table.push_str(&line(labels.iter().map(|label| escape(label)).collect()));
+        table.push('|');
+        table.push_str(&"---:|".repeat(labels.len()));
+        table.push('\n');
+        for estimate in estimates {
+            table.push_str(&line(
+                rows(estimate)
+                    .into_iter()
+                    .map(|(_, value)| escape(&value))
+                    .collect(),
+            ));
+        }
+    }
+    table
+}
+
+/// Escape LaTeX special characters.
+fn latex_escape(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+    for c in cell.chars() {
+        match c {
+            '#' | '%' | '&' | '_' | '$' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '|' => escaped.push_str("\\textbar{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render estimates as a LaTeX `tabular` environment.
+#[must_use]
+pub fn latex_table(estimates: &[AliceAndBobEstimates]) -> String {
+    let line = |cells: Vec<String>| format!("  {} \\\\\n", cells.join(" & "));
+
+    let mut table = String::new();
+    if let [estimate] = estimates {
+        table.push_str("\\begin{tabular}{lr}\n  \\hline\n");
+        table.push_str(&line(vec!["Quantity".into(), "Value".into()]));
+        for section in estimate.report_sections() {
+            table.push_str("  \\hline\n");
+            for (label, value) in section {
+                table.push_str(&line(vec![latex_escape(label), latex_escape(&value)]));
+            }
+        }
+        table.push_str("  \\hline\n\\end{tabular}\n");
+    } else if let Some(first) = estimates.first() {
+        let labels: Vec<_> = rows(first).into_iter().map(|(label, _)| label).collect();
+        table.push_str("\\begin{tabular}{");
+        table.push_str(&"r".repeat(labels.len()));
+        table.push_str("}\n  \\hline\n");
+        table.push_str(&line(
+            labels.iter().map(|label| latex_escape(label)).collect(),
+        ));
+        table.push_str("  \\hline\n");
+        for estimate in estimates {
+            table.push_str(&line(
+                rows(estimate)
+                    .into_iter()
+                    .map(|(_, value)| latex_escape(&value))
+                    .collect(),
+            ));
+        }
+        table.push_str("  \\hline\n\\end{tabular}\n");
+    }
+    table
+}
@@ -0,0 +1,255 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Self-contained HTML report of the estimates.
+//!
+//! The report gathers what is usually assembled by hand for reviews: the
+//! headline numbers, the detailed quantities, the assumptions (if a
+//! [`Provenance`](crate::provenance::Provenance) is attached), a chart of the
+//! error contributions and, for several estimates, a plot of the frontier.
+//! Charts are inline SVG and the style is embedded, so the file can be shared
+//! as is.
+
+use std::fmt::{Result, Write};
+
+use num_traits::ToPrimitive;
+
+use super::rows;
+use crate::AliceAndBobEstimates;
+
+/// Width of the charts, in pixels.
+const WIDTH: f64 = 640.0;
+
+/// Style of the report.
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 60em; margin: 2em auto; color: #222; }
+h1, h2 { font-weight: normal; }
+.headline { display: flex; flex-wrap: wrap; gap: 1em; }
+.headline div { border: 1px solid #ccc; border-radius: 4px; padding: 0.5em 1em; }
+.headline .value { font-size: 1.5em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border-bottom: 1px solid #ddd; padding: 0.25em 0.75em; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+svg text { font-size: 12px; }";
+
+/// Escape HTML special characters.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// HTML table with a header row.
+fn table<S: AsRef<str>>(html: &mut String, header: &[&str], body: &[Vec<S>]) -> Result {
+    writeln!(html, "<table>")?;
+    write!(html, "<tr>")?;
+    for cell in header {
+        write!(html, "<th>{}</th>", escape(cell))?;
+    }
+    writeln!(html, "</tr>")?;
+    for cells in body {
+        write!(html, "<tr>")?;
+        for cell in cells {
+            write!(html, "<td>{}</td>", escape(cell.as_ref()))?;
+        }
+        writeln!(html, "</tr>")?;
+    }
+    writeln!(html, "</table>")
+}
+
+/// Headline numbers of an estimate, as cards.
+fn headline(html: &mut String, estimate: &AliceAndBobEstimates) -> Result {
+    writeln!(html, "<div class=\"headline\">")?;
+    for (label, value) in estimate.report_sections().swap_remove(0) {
+        writeln!(
+            html,
+            "<div>{}<br><span class=\"value\">{}</span></div>",
+            escape(label),
+            escape(&value)
+        )?;
+    }
+    writeln!(html, "</div>")
+}
+
+/// Horizontal bar chart of the error contributions of an estimate.
+fn error_chart(html: &mut String, estimate: &AliceAndBobEstimates) -> Result {
+    let computation = estimate.computation_error_contributions();
+    let factories = estimate.factory_error_contributions();
+    let bars = [
+        ("Z (compute)", computation.phase_flip),
+        ("X (compute)", computation.bit_flip),
+        ("Z (factories)", factories.phase_flip),
+        ("X (factories)", factories.bit_flip),
+    ];
+    let max = bars.iter().map(|(_, error)| *error).fold(0.0, f64::max);
+    let (label_width, bar_height) = (110.0, 24.0);
+    let bar_width = WIDTH - label_width - 90.0;
+
+    writeln!(
+        html,
+        "<svg width=\"{WIDTH}\" height=\"{}\" role=\"img\">",
+        bar_height * 4.0 + 8.0
+    )?;
+    let mut y = 4.0;
+    for (label, error) in bars {
+        let width = if max > 0.0 {
+            error / max * bar_width
+        } else {
+            0.0
+        };
+        writeln!(
+            html,
+            "<text x=\"0\" y=\"{:.1}\">{label}</text>\
+             <rect x=\"{label_width}\" y=\"{y:.1}\" width=\"{width:.1}\" height=\"{:.1}\" \
+             fill=\"#4a7ebb\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\">{error:.3e}</text>",
+            y + 16.0,
+            bar_height - 6.0,
+            label_width + width + 6.0,
+            y + 16.0,
+        )?;
+        y += bar_height;
+    }
+    writeln!(html, "</svg>")
+}
+
+/// Scatter plot of the runtime and physical qubits of the estimates, on
+/// logarithmic scales.
+fn frontier_plot(html: &mut String, estimates: &[AliceAndBobEstimates]) -> Result {
+    let points: Vec<_> = estimates
+        .iter()
+        .filter_map(|estimate| {
+            Some((
+                estimate.runtime().to_f64()?.log10(),
+                estimate.physical_qubits().to_f64()?.log10(),
+            ))
+        })
+        .collect();
+    let range = |values: &mut dyn Iterator<Item = f64>| {
+        let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+        // avoid a null range with a single point or equal values
+        (min.floor(), max.ceil().max(min.floor() + 1.0))
+    };
+    let (x_min, x_max) = range(&mut points.iter().map(|(x, _)| *x));
+    let (y_min, y_max) = range(&mut points.iter().map(|(_, y)| *y));
+    let (height, margin) = (360.0, 50.0);
+    let to_x = |x: f64| margin + (x - x_min) / (x_max - x_min) * (WIDTH - 2.0 * margin);
+    let to_y = |y: f64| height - margin - (y - y_min) / (y_max - y_min) * (height - 2.0 * margin);
+
+    writeln!(
+        html,
+        "<svg width=\"{WIDTH}\" height=\"{height}\" role=\"img\">"
+    )?;
+    writeln!(
+        html,
+        "<line x1=\"{margin}\" y1=\"{0}\" x2=\"{1}\" y2=\"{0}\" stroke=\"#222\"/>\
+         <line x1=\"{margin}\" y1=\"{margin}\" x2=\"{margin}\" y2=\"{0}\" stroke=\"#222\"/>\
+         <text x=\"{1}\" y=\"{2}\" text-anchor=\"end\">runtime (ns)</text>\
+         <text x=\"4\" y=\"{3}\">physical qubits</text>",
+        height - margin,
+        WIDTH - margin,
+        height - 10.0,
+        margin - 10.0,
+    )?;
+    for (x, y) in points {
+        writeln!(
+            html,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"4\" fill=\"#4a7ebb\">\
+             <title>{:.3e} ns, {:.3e} qubits</title></circle>",
+            to_x(x),
+            to_y(y),
+            10_f64.powf(x),
+            10_f64.powf(y),
+        )?;
+    }
+    for tick in [x_min, x_max] {
+        writeln!(
+            html,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">1e{tick}</text>",
+            to_x(tick),
+            height - margin + 16.0
+        )?;
+    }
+    for tick in [y_min, y_max] {
+        writeln!(
+            html,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">1e{tick}</text>",
+            margin - 4.0,
+            to_y(tick) + 4.0
+        )?;
+    }
+    writeln!(html, "</svg>")
+}
+
+/// Write the report of `estimates` into `html`.
+fn report(html: &mut String, estimates: &[AliceAndBobEstimates]) -> Result {
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Resource estimate</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n\
+         <h1>Resource estimate</h1>"
+    )?;
+    let Some(first) = estimates.first() else {
+        return writeln!(html, "<p>No estimate.</p>\n</body>\n</html>");
+    };
+
+    if estimates.len() > 1 {
+        writeln!(
+            html,
+            "<p>Frontier of {} estimates, the headline numbers and error breakdown are the ones \
+             of the first.</p>",
+            estimates.len()
+        )?;
+    }
+    headline(html, first)?;
+
+    writeln!(html, "<h2>Estimate</h2>")?;
+    if let [estimate] = estimates {
+        let mut sections = estimate.report_sections();
+        if estimate.provenance().is_some() {
+            // reported with the assumptions
+            sections.pop();
+        }
+        let body: Vec<_> = sections
+            .into_iter()
+            .flatten()
+            .map(|(label, value)| vec![label.to_string(), value])
+            .collect();
+        table(html, &["Quantity", "Value"], &body)?;
+    } else {
+        let labels: Vec<_> = rows(first).into_iter().map(|(label, _)| label).collect();
+        let body: Vec<Vec<_>> = estimates
+            .iter()
+            .map(|estimate| rows(estimate).into_iter().map(|(_, value)| value).collect())
+            .collect();
+        table(html, &labels, &body)?;
+        writeln!(html, "<h2>Frontier</h2>")?;
+        frontier_plot(html, estimates)?;
+    }
+
+    writeln!(html, "<h2>Error breakdown</h2>")?;
+    error_chart(html, first)?;
+
+    if let Some(provenance) = first.provenance() {
+        writeln!(html, "<h2>Assumptions</h2>")?;
+        let body: Vec<_> = provenance
+            .report_section()
+            .into_iter()
+            .map(|(label, value)| vec![label.to_string(), value])
+            .collect();
+        table(html, &["Assumption", "Value"], &body)?;
+    }
+
+    writeln!(html, "</body>\n</html>")
+}
+
+/// Render estimates as a self-contained HTML report.
+#[must_use]
+pub fn html_report(estimates: &[AliceAndBobEstimates]) -> String {
+    let mut html = String::new();
+    report(&mut html, estimates).expect("can't write to a String");
+    html
+}
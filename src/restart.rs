@@ -0,0 +1,304 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Restart-on-failure strategy for heralded errors.
+//!
+//! By default, the error budget ensures that a single run of the algorithm
+//! succeeds with high probability. With cat qubits, part of the errors can be
+//! heralded (e.g. detected leakage or erasures): the whole algorithm can then
+//! be discarded and restarted when such an error is detected, and only the
+//! undetected errors corrupt the result.
+//!
+//! With a total error probability p per run, of which a fraction h is
+//! heralded:
+//! - a run is restarted with probability h·p, so the expected number of
+//!   restarts is h·p / (1 - h·p),
+//! - failures being detected at a uniformly random time, an aborted run lasts
+//!   half of the runtime on average,
+//! - the probability that the accepted run is corrupted is
+//!   (1 - h)·p / (1 - h·p).
+//!
+//! The expected wall-clock time hides a long tail when restarts are likely;
+//! [`HeraldedRestart::sample_runtimes`] samples it to give its percentiles,
+//! reproducibly from a seed (see [`crate::random`]).
+//!
+//! Independently of the errors, some algorithms only succeed with a given
+//! probability s (e.g. the post-processing of Shor's algorithm may fail), and
+//! are repeated until they do. [`AlgorithmRetry`] gives the expected number of
+//! runs 1 / (s·(1 - p)) for an error probability p of the accepted run, and
+//! the expected total runtime over the retries, which is how the expected
+//! times of arXiv:2302.06639 (Table IV) are meant to be read.
+
+use std::str::FromStr;
+
+use num_traits::ToPrimitive;
+use rand::Rng;
+use resource_estimator::estimates::ErrorBudget;
+use serde::{Deserialize, Serialize};
+
+use crate::{budget, random, AliceAndBobEstimates};
+
+/// Discard and restart the whole algorithm when a heralded error is detected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeraldedRestart {
+    heralded_fraction: f64,
+}
+
+impl HeraldedRestart {
+    /// Restart strategy where a fraction `heralded_fraction` (between 0 and 1)
+    /// of the errors are detected.
+    pub fn new(heralded_fraction: f64) -> Result<Self, String> {
+        if (0.0..1.0).contains(&heralded_fraction) {
+            Ok(Self { heralded_fraction })
+        } else {
+            Err(format!(
+                "heralded fraction {heralded_fraction} must be in [0, 1) (with only heralded \
+                 errors, any error budget can be reached by restarting)"
+            ))
+        }
+    }
+
+    #[must_use]
+    /// Fraction of the errors which are detected.
+    pub fn heralded_fraction(&self) -> f64 {
+        self.heralded_fraction
+    }
+
+    #[must_use]
+    /// Total error probability allowed per run so that the accepted run is
+    /// corrupted with probability `target_error`.
+    pub fn error_per_run(&self, target_error: f64) -> f64 {
+        let h = self.heralded_fraction;
+        target_error / (1.0 - h + h * target_error)
+    }
+
+    #[must_use]
+    /// Error budget allowed per run so that the accepted run fits in
+    /// `budget` (all its parts are scaled by the same factor).
+    pub fn budget_per_run(&self, budget: &ErrorBudget) -> ErrorBudget {
+        let total = budget.logical() + budget.magic_states() + budget.rotations();
+        let factor = self.error_per_run(total) / total;
+        ErrorBudget::new(
+            budget.logical() * factor,
+            budget.magic_states() * factor,
+            budget.rotations() * factor,
+        )
+    }
+
+    #[must_use]
+    /// Expected number of restarts, for an error probability `error` per run
+    /// (infinite when every run is aborted).
+    pub fn expected_restarts(&self, error: f64) -> f64 {
+        let detected = self.heralded_fraction * error;
+        if detected >= 1.0 {
+            return f64::INFINITY;
+        }
+        detected / (1.0 - detected)
+    }
+
+    #[must_use]
+    /// Probability that the accepted run is corrupted by an undetected error,
+    /// for an error probability `error` per run.
+    pub fn undetected_error(&self, error: f64) -> f64 {
+        (1.0 - self.heralded_fraction) * error / (1.0 - self.heralded_fraction * error)
+    }
+
+    #[must_use]
+    /// Expected wall-clock time (in ns) until a run is accepted.
+    pub fn expected_runtime(&self, estimates: &AliceAndBobEstimates) -> f64 {
+        let runtime = estimates.runtime_duration().as_ns_f64();
+        runtime * (1.0 + 0.5 * self.expected_restarts(estimates.total_error()))
+    }
+
+    /// Sample a wall-clock time (in ns) until a run is accepted: each run is
+    /// aborted with probability h·p, after a uniformly random fraction of the
+    /// runtime. Infinite when every run is aborted (h·p ≥ 1).
+    pub fn sample_runtime(&self, estimates: &AliceAndBobEstimates, rng: &mut impl Rng) -> f64 {
+        sample_wall_clock(
+            estimates.runtime_duration().as_ns_f64(),
+            self.heralded_fraction * estimates.total_error(),
+            rng,
+        )
+    }
+
+    #[must_use]
+    /// Percentiles of `samples` wall-clock times sampled with a generator
+    /// seeded with `seed`.
+    pub fn sample_runtimes(
+        &self,
+        estimates: &AliceAndBobEstimates,
+        samples: usize,
+        seed: u64,
+    ) -> SampledRuntime {
+        sample_percentiles(
+            estimates.runtime_duration().as_ns_f64(),
+            self.heralded_fraction * estimates.total_error(),
+            samples,
+            seed,
+        )
+    }
+}
+
+/// Wall-clock time (in ns) of runs of `runtime` ns, each one aborted with
+/// probability `detected`.
+fn sample_wall_clock(runtime: f64, detected: f64, rng: &mut impl Rng) -> f64 {
+    if detected.is_nan() || detected >= 1.0 {
+        return f64::INFINITY;
+    }
+    let mut wall_clock = runtime;
+    while rng.gen_bool(detected.max(0.0)) {
+        wall_clock += rng.gen::<f64>() * runtime;
+    }
+    wall_clock
+}
+
+/// Percentiles of `samples` wall-clock times sampled by [`sample_wall_clock`]
+/// with a generator seeded with `seed`.
+fn sample_percentiles(runtime: f64, detected: f64, samples: usize, seed: u64) -> SampledRuntime {
+    let mut rng = random::seeded(seed);
+    let mut runtimes: Vec<_> = (0..samples.max(1))
+        .map(|_| sample_wall_clock(runtime, detected, &mut rng))
+        .collect();
+    runtimes.sort_by(f64::total_cmp);
+    let last = runtimes.len() - 1;
+    let percentile = |q: f64| {
+        let index = (q * last.to_f64().unwrap_or(f64::MAX)).round();
+        runtimes[index.to_usize().unwrap_or(last).min(last)]
+    };
+    SampledRuntime {
+        seed,
+        samples: runtimes.len(),
+        median_ns: percentile(0.5),
+        p90_ns: percentile(0.9),
+        p99_ns: percentile(0.99),
+    }
+}
+
+/// Percentiles of the wall-clock time with restarts, sampled by
+/// [`HeraldedRestart::sample_runtimes`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SampledRuntime {
+    /// Seed of the generator.
+    pub seed: u64,
+    /// Number of samples.
+    pub samples: usize,
+    /// Median wall-clock time in nanoseconds.
+    pub median_ns: f64,
+    /// 90th percentile of the wall-clock time in nanoseconds.
+    pub p90_ns: f64,
+    /// 99th percentile of the wall-clock time in nanoseconds.
+    pub p99_ns: f64,
+}
+
+/// Repeat the whole algorithm until it succeeds, a run succeeding with an
+/// intrinsic (algorithmic) probability on top of the errors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AlgorithmRetry {
+    success_probability: f64,
+}
+
+impl AlgorithmRetry {
+    /// Retry strategy for an algorithm succeeding with probability
+    /// `success_probability` (in (0, 1]) without errors.
+    pub fn new(success_probability: f64) -> Result<Self, String> {
+        if success_probability > 0.0 && success_probability <= 1.0 {
+            Ok(Self {
+                success_probability,
+            })
+        } else {
+            Err(format!(
+                "success probability {success_probability} must be in (0, 1]"
+            ))
+        }
+    }
+
+    #[must_use]
+    /// Intrinsic success probability of the algorithm.
+    pub fn success_probability(&self) -> f64 {
+        self.success_probability
+    }
+
+    #[must_use]
+    /// Expected number of runs until a successful one, the accepted runs
+    /// being corrupted with probability `error`.
+    pub fn expected_runs(&self, error: f64) -> f64 {
+        1.0 / (self.success_probability * (1.0 - error))
+    }
+
+    #[must_use]
+    /// Expected runs and total runtime of `estimates`, on top of its heralded
+    /// restarts if any (see [`AliceAndBobEstimates::with_restart`]).
+    pub fn estimate(&self, estimates: &AliceAndBobEstimates) -> RetryEstimate {
+        let total_error = estimates.total_error();
+        let (runtime, error) = match estimates.restart() {
+            Some(restart) => (
+                restart.expected_runtime(estimates),
+                restart.undetected_error(total_error),
+            ),
+            None => (estimates.runtime_duration().as_ns_f64(), total_error),
+        };
+        let expected_runs = self.expected_runs(error);
+        RetryEstimate {
+            success_probability: self.success_probability,
+            expected_runs,
+            expected_runtime_ns: expected_runs * runtime,
+        }
+    }
+}
+
+impl FromStr for AlgorithmRetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(budget::parse_probability(s)?)
+    }
+}
+
+/// Expected costs of repeating the algorithm until it succeeds, computed by
+/// [`AlgorithmRetry::estimate`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RetryEstimate {
+    /// Intrinsic success probability of the algorithm.
+    pub success_probability: f64,
+    /// Expected number of runs, the successful one included.
+    pub expected_runs: f64,
+    /// Expected total runtime over the runs in nanoseconds.
+    pub expected_runtime_ns: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{random, sample_percentiles, sample_wall_clock, HeraldedRestart};
+
+    #[test]
+    fn samples_are_reproducible_from_the_seed() {
+        let first = sample_percentiles(1000.0, 0.3, 500, 42);
+        assert_eq!(first, sample_percentiles(1000.0, 0.3, 500, 42));
+        assert_eq!(first.seed, 42);
+        assert_eq!(first.samples, 500);
+        assert!(first.median_ns >= 1000.0);
+        assert!(first.median_ns <= first.p90_ns && first.p90_ns <= first.p99_ns);
+        assert_ne!(first, sample_percentiles(1000.0, 0.3, 500, 43));
+    }
+
+    #[test]
+    fn runs_without_restart_last_the_runtime() {
+        let mut rng = random::seeded(0);
+        for _ in 0..100 {
+            assert!((sample_wall_clock(1000.0, 0.0, &mut rng) - 1000.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn certain_restarts_never_end() {
+        let mut rng = random::seeded(0);
+        assert!(sample_wall_clock(1000.0, 1.0, &mut rng).is_infinite());
+        assert!(sample_wall_clock(1000.0, 1.5, &mut rng).is_infinite());
+        assert!(sample_percentiles(1000.0, 1.0, 10, 0)
+            .median_ns
+            .is_infinite());
+        let restart = HeraldedRestart::new(0.5).expect("valid fraction");
+        assert!(restart.expected_restarts(2.0).is_infinite());
+        assert!((restart.expected_restarts(1.0) - 1.0).abs() < 1e-12);
+    }
+}
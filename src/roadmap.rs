@@ -0,0 +1,224 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Projection of the resources of an algorithm along a hardware roadmap.
+//!
+//! A [`Roadmap`] is a schedule of projected κ₁/κ₂ ratios, read from TOML:
+//!
+//! ```toml
+//! [[milestone]]
+//! year = 2026
+//! k1_k2 = 1e-4
+//!
+//! [[milestone]]
+//! year = 2030
+//! k1_k2 = 1e-5
+//! ```
+//!
+//! [`Roadmap::project`] estimates a fixed algorithm with the qubits of each
+//! milestone, all other assumptions unchanged, giving the physical qubits and
+//! runtime over time (e.g. when an algorithm breaking ECC-256 becomes
+//! feasible with a given number of qubits).
+
+use std::fmt::Display;
+
+use resource_estimator::estimates::ErrorBudget;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    scan::{self, Resources},
+    units::format_thousands,
+    CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+
+/// Projected hardware at a given year.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Milestone {
+    /// Year of the milestone.
+    pub year: u32,
+    /// Projected κ₁/κ₂ ratio.
+    pub k1_k2: f64,
+}
+
+/// Schedule of projected hardware improvements.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Roadmap {
+    /// Milestones, sorted by year.
+    #[serde(rename = "milestone", default)]
+    pub milestones: Vec<Milestone>,
+}
+
+impl Roadmap {
+    /// Roadmap read from TOML, with a `[[milestone]]` table per year.
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let mut roadmap: Self = toml::from_str(content).map_err(|err| err.to_string())?;
+        if roadmap.milestones.is_empty() {
+            return Err("the roadmap has no milestone".into());
+        }
+        if let Some(milestone) = roadmap
+            .milestones
+            .iter()
+            .find(|milestone| !(milestone.k1_k2.is_finite() && milestone.k1_k2 > 0.0))
+        {
+            return Err(format!(
+                "κ₁/κ₂ {} of {} must be positive",
+                milestone.k1_k2, milestone.year
+            ));
+        }
+        roadmap.milestones.sort_by_key(|milestone| milestone.year);
+        Ok(roadmap)
+    }
+
+    /// Estimates of `counts` with `qubit` improved to the κ₁/κ₂ of each
+    /// milestone.
+    #[must_use]
+    pub fn project(
+        &self,
+        qubit: &CatQubit,
+        code: &RepetitionCode,
+        builder: &ToffoliBuilder,
+        counts: &LogicalCounts,
+        budget: &ErrorBudget,
+    ) -> Projection {
+        let points = self
+            .milestones
+            .iter()
+            .map(|milestone| {
+                let qubit = qubit.clone().with_k1_k2(milestone.k1_k2);
+                let resources = scan::resources(&qubit, code, builder, counts.clone(), budget);
+                ProjectedPoint {
+                    milestone: *milestone,
+                    resources,
+                }
+            })
+            .collect();
+        Projection { points }
+    }
+}
+
+/// Resources of the algorithm at one milestone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectedPoint {
+    /// Milestone of the roadmap.
+    pub milestone: Milestone,
+    /// Physical qubits and runtime, or why the algorithm can't be
+    /// estimated with the hardware of the milestone.
+    pub resources: Resources,
+}
+
+/// Resources of the algorithm along a roadmap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Projection {
+    /// Resources at each milestone, by year.
+    pub points: Vec<ProjectedPoint>,
+}
+
+impl Projection {
+    /// First milestone at which the algorithm fits in `physical_qubits`
+    /// physical qubits, if any.
+    #[must_use]
+    pub fn first_feasible(&self, physical_qubits: u64) -> Option<&ProjectedPoint> {
+        self.points.iter().find(|point| {
+            point
+                .resources
+                .as_ref()
+                .is_ok_and(|(qubits, _)| *qubits <= physical_qubits)
+        })
+    }
+}
+
+impl Display for Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        scan::write_table(
+            f,
+            &format!("{:<8}{:>10}", "year", "κ₁/κ₂"),
+            &[("physical qubits", 18), ("runtime", 16)],
+            self.points.iter().map(|point| {
+                let Milestone { year, k1_k2 } = point.milestone;
+                let cells = point
+                    .resources
+                    .as_ref()
+                    .map(|(qubits, runtime)| vec![format_thousands(*qubits), runtime.to_string()]);
+                (format!("{year:<8}{k1_k2:>10.1e}"), cells)
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Milestone, ProjectedPoint, Projection, Roadmap};
+    use crate::units::TimeSpan;
+
+    #[test]
+    fn milestones_are_read_by_year() {
+        let roadmap = Roadmap::from_toml(
+            "[[milestone]]\nyear = 2030\nk1_k2 = 1e-5\n\n[[milestone]]\nyear = 2026\nk1_k2 = 1e-4\n",
+        )
+        .expect("valid roadmap");
+        assert_eq!(
+            roadmap.milestones,
+            [
+                Milestone {
+                    year: 2026,
+                    k1_k2: 1e-4
+                },
+                Milestone {
+                    year: 2030,
+                    k1_k2: 1e-5
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_roadmaps_are_rejected() {
+        for toml in [
+            "",
+            "[[milestone]]\nyear = 2026\n",
+            "[[milestone]]\nyear = 2026\nk1_k2 = 0.0\n",
+            "[[milestone]]\nyear = 2026\nk1_k2 = -1e-4\n",
+            "[[milestone]]\nyear = 2026\nk1_k2 = nan\n",
+            "[[milestone]]\nyear = -1\nk1_k2 = 1e-4\n",
+        ] {
+            assert!(Roadmap::from_toml(toml).is_err(), "{toml}");
+        }
+    }
+
+    fn projection() -> Projection {
+        let point = |year, resources| ProjectedPoint {
+            milestone: Milestone { year, k1_k2: 1e-4 },
+            resources,
+        };
+        Projection {
+            points: vec![
+                point(2026, Err("budget too small".into())),
+                point(2028, Ok((2_000_000, TimeSpan::from_ns(3_600_000_000_000)))),
+                point(2030, Ok((200_000, TimeSpan::from_ns(3_600_000_000_000)))),
+            ],
+        }
+    }
+
+    #[test]
+    fn first_feasible_milestone_fits_the_qubits() {
+        let projection = projection();
+        let year = |qubits| {
+            projection
+                .first_feasible(qubits)
+                .map(|point| point.milestone.year)
+        };
+        assert_eq!(year(10_000_000), Some(2028));
+        assert_eq!(year(1_000_000), Some(2030));
+        assert_eq!(year(1_000), None);
+    }
+
+    #[test]
+    fn unreachable_milestones_are_listed() {
+        let table = projection().to_string();
+        assert!(
+            table.contains("2026        1.0e-4  unreachable: budget too small"),
+            "{table}"
+        );
+        assert!(table.contains("2,000,000"), "{table}");
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Estimates of variants of one algorithm, and their tables.
+//!
+//! Budget sweeps, window sweeps, roadmap projections and parameter sweeps
+//! estimate the same algorithm with one input changed at a time (the error
+//! budget, the counts, the qubits...). A variant which can't be estimated has
+//! an error instead of estimates and doesn't stop the others: [`estimate`]
+//! gives the estimates of a variant, and [`write_table`] the table of the
+//! variants, the unreachable ones with their error.
+
+use std::{fmt::Formatter, rc::Rc};
+
+use resource_estimator::estimates::{ErrorBudget, PhysicalResourceEstimation};
+
+use crate::{
+    budget, units::TimeSpan, AliceAndBobEstimates, CatQubit, LogicalCounts, RepetitionCode,
+    ToffoliBuilder,
+};
+
+/// Physical qubits and runtime of a variant, or why it can't be estimated.
+pub(crate) type Resources = Result<(u64, TimeSpan), String>;
+
+/// Maximum resources of the estimates of a variant.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Limits {
+    /// Maximum number of physical qubits.
+    pub max_qubits: Option<u64>,
+    /// Maximum runtime.
+    pub max_runtime: Option<TimeSpan>,
+}
+
+/// Estimate with the given inputs.
+pub(crate) fn estimate(
+    qubit: &CatQubit,
+    code: &RepetitionCode,
+    builder: &ToffoliBuilder,
+    counts: LogicalCounts,
+    budget: &ErrorBudget,
+) -> Result<AliceAndBobEstimates, String> {
+    estimate_within(qubit, code, builder, counts, budget, Limits::default())
+}
+
+/// Estimate with the given inputs, within the `limits`, the rotation
+/// synthesis being charged to the budget.
+pub(crate) fn estimate_within(
+    qubit: &CatQubit,
+    code: &RepetitionCode,
+    builder: &ToffoliBuilder,
+    counts: LogicalCounts,
+    budget: &ErrorBudget,
+    limits: Limits,
+) -> Result<AliceAndBobEstimates, String> {
+    let budget = budget::budget_for_counts(budget, &counts)?;
+    builder.check_budget(&counts, &budget)?;
+    let mut estimation = PhysicalResourceEstimation::new(
+        code.clone(),
+        Rc::new(qubit.clone()),
+        builder.clone(),
+        Rc::new(counts),
+        budget,
+    );
+    if let Some(max_qubits) = limits.max_qubits {
+        estimation.set_max_physical_qubits(max_qubits);
+    }
+    if let Some(max_runtime) = limits.max_runtime {
+        estimation.set_max_duration(max_runtime.as_ns());
+    }
+    estimation
+        .estimate()
+        .map(|result| AliceAndBobEstimates::new(result, code))
+        .map_err(|err| err.to_string())
+}
+
+/// Physical qubits and runtime of the estimates with the given inputs.
+pub(crate) fn resources(
+    qubit: &CatQubit,
+    code: &RepetitionCode,
+    builder: &ToffoliBuilder,
+    counts: LogicalCounts,
+    budget: &ErrorBudget,
+) -> Resources {
+    estimate(qubit, code, builder, counts, budget)
+        .map(|estimates| (estimates.physical_qubits(), estimates.runtime_duration()))
+}
+
+/// Write a table of variants: the `header` then the `columns` (right-aligned
+/// to their width), and for each row its label then its cells, or why it
+/// can't be estimated. The labels are formatted by the caller, as wide as the
+/// header.
+pub(crate) fn write_table<'a>(
+    f: &mut Formatter<'_>,
+    header: &str,
+    columns: &[(&str, usize)],
+    rows: impl IntoIterator<Item = (String, Result<Vec<String>, &'a String>)>,
+) -> std::fmt::Result {
+    write!(f, "  {header}")?;
+    for (column, width) in columns {
+        write!(f, "{column:>width$}")?;
+    }
+    writeln!(f)?;
+    for (label, cells) in rows {
+        write!(f, "  {label}")?;
+        match cells {
+            Ok(cells) => {
+                for (cell, (_, width)) in cells.iter().zip(columns) {
+                    write!(f, "{cell:>width$}")?;
+                }
+            }
+            Err(err) => write!(f, "  unreachable: {err}")?,
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Display;
+
+    use super::write_table;
+
+    struct Table(Vec<(u32, Result<u64, String>)>);
+
+    impl Display for Table {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write_table(
+                f,
+                &format!("{:<6}", "row"),
+                &[("value", 8), ("double", 8)],
+                self.0.iter().map(|(row, value)| {
+                    (
+                        format!("{row:<6}"),
+                        value
+                            .as_ref()
+                            .map(|value| vec![value.to_string(), (2 * value).to_string()]),
+                    )
+                }),
+            )
+        }
+    }
+
+    #[test]
+    fn unreachable_rows_give_their_error() {
+        let table = Table(vec![(1, Ok(10)), (2, Err("budget too small".into()))]);
+        assert_eq!(
+            table.to_string(),
+            "  row      value  double\n  1           10      20\n  2       unreachable: budget too small\n"
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Versioned JSON schema of the estimation results.
+//!
+//! The JSON output (`--output json`) is a [`ResultDocument`]: a
+//! `schema_version` field and the list of estimates, each of them being an
+//! [`EstimateRecord`], or a [`FailureDocument`] if the estimation fails.
+//! Durations are given in nanoseconds, frequencies in Hz, and error
+//! probabilities as plain numbers.
+//!
+//! Adding an optional field keeps the version unchanged, so consumers must
+//! ignore unknown fields. Renaming, removing or changing the meaning of a
+//! field increments [`SCHEMA_VERSION`].
+
+use resource_estimator::estimates::Overhead;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    budget::{BudgetConsumption, BudgetExcess},
+    code::CodeParameter,
+    estimates::ErrorContributions,
+    factories::{
+        sizing::{Overproduction, RightSizing},
+        ToffoliFactory,
+    },
+    geometry::ChipDimensions,
+    phases::PhaseAnalysis,
+    provenance::Provenance,
+    restart::{RetryEstimate, SampledRuntime},
+    syndrome::SyndromeData,
+    utilization::Utilization,
+    wiring::WiringCount,
+    AliceAndBobEstimates, LogicalCounts,
+};
+
+/// Version of the schema of [`ResultDocument`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Root of the JSON output.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ResultDocument {
+    /// Version of the schema, see [`SCHEMA_VERSION`].
+    pub schema_version: u32,
+    /// Estimates (a single one, or the frontier).
+    pub estimates: Vec<EstimateRecord>,
+}
+
+impl ResultDocument {
+    #[must_use]
+    /// Document with the current schema version.
+    pub fn new(estimates: &[AliceAndBobEstimates]) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            estimates: estimates.iter().map(EstimateRecord::from).collect(),
+        }
+    }
+}
+
+/// Root of the JSON output when the estimation fails.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FailureDocument {
+    /// Version of the schema, see [`SCHEMA_VERSION`].
+    pub schema_version: u32,
+    /// Why the estimation failed.
+    pub error: FailureRecord,
+}
+
+impl FailureDocument {
+    #[must_use]
+    /// Document with the current schema version.
+    pub fn new(error: FailureRecord) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            error,
+        }
+    }
+}
+
+/// Failure of an estimation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FailureRecord {
+    /// Kind of failure, e.g. `compile_error` or `infeasible_budget`.
+    pub kind: String,
+    /// Exit code of the process for this kind of failure.
+    pub exit_code: u8,
+    /// Error message, with its causes.
+    pub message: String,
+}
+
+/// Factories of an estimate.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FactoryRecord {
+    /// Parameters and performances of the factory.
+    pub factory: ToffoliFactory,
+    /// Number of copies running in parallel.
+    pub copies: u64,
+    /// Number of runs of each copy.
+    pub runs: u64,
+    /// Toffoli states produced compared with the consumed ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overproduction: Option<Overproduction>,
+}
+
+/// Error probabilities of an estimate.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ErrorRecord {
+    /// Total error probability.
+    pub total: f64,
+    /// Error of the computation part.
+    pub computation: ErrorContributions,
+    /// Error of the magic states.
+    pub factories: ErrorContributions,
+    /// Part of each component of the error budget used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetConsumption>,
+    /// Excess of the total error over the error budget, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excess: Option<BudgetExcess>,
+}
+
+/// Expected costs with the restart-on-failure strategy.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct RestartRecord {
+    /// Fraction of the errors which are heralded.
+    pub heralded_fraction: f64,
+    /// Expected number of restarts.
+    pub expected_restarts: f64,
+    /// Expected wall-clock time in nanoseconds.
+    pub expected_runtime_ns: f64,
+    /// Probability that the accepted run is corrupted.
+    pub undetected_error: f64,
+    /// Sampled percentiles of the wall-clock time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampled_runtime: Option<SampledRuntime>,
+}
+
+/// A single estimate.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EstimateRecord {
+    /// Number of physical qubits, routing qubits included.
+    pub physical_qubits: u64,
+    /// Number of physical qubits of the factories.
+    pub physical_qubits_for_factories: u64,
+    /// Physical qubits with the factory interfaces at the main code distance
+    /// (exact layout, the other counts approximate them at the factory
+    /// distance).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout_physical_qubits: Option<u64>,
+    /// Runtime in nanoseconds.
+    pub runtime_ns: u64,
+    /// Number of logical cycles.
+    pub logical_cycles: u64,
+    /// Duration of a logical cycle in nanoseconds.
+    pub cycle_time_ns: u64,
+    /// Logical clock rate in Hz.
+    pub logical_clock_rate_hz: f64,
+    /// Physical CX gates executed over the whole computation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physical_cx: Option<u64>,
+    /// Physical measurements executed over the whole computation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physical_measurements: Option<u64>,
+    /// Number of logical qubits, routing qubits included.
+    pub logical_qubits: u64,
+    /// Parameters of the repetition code of the computation part.
+    pub code_parameter: CodeParameter,
+    /// Factories, if any magic state is needed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factories: Option<FactoryRecord>,
+    /// Error probabilities.
+    pub errors: ErrorRecord,
+    /// Restart strategy, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<RestartRecord>,
+    /// Expected costs of repeating the algorithm until it succeeds, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryEstimate>,
+    /// Assumptions used to compute the estimate, if recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Assumptions extrapolated outside of the validity ranges of the models.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extrapolations: Vec<String>,
+    /// Logical counts of the algorithm, to compare estimates (see
+    /// [`Diff::records`](crate::diff::Diff::records)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counts: Option<LogicalCounts>,
+    /// Factories right-sized to the consumed Toffoli states, if computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub right_sizing: Option<RightSizing>,
+    /// Utilization of the regions of the layout, if computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utilization: Option<Utilization>,
+    /// Demand of Toffoli states of the phases of the algorithm, if computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phases: Option<PhaseAnalysis>,
+    /// Dimensions of the chip, if computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chip: Option<ChipDimensions>,
+    /// Control and readout lines, if computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wiring: Option<WiringCount>,
+    /// Syndrome data sent to the decoder, if computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub syndrome: Option<SyndromeData>,
+}
+
+impl From<&AliceAndBobEstimates> for EstimateRecord {
+    fn from(estimates: &AliceAndBobEstimates) -> Self {
+        let total_error = estimates.total_error();
+
+        Self {
+            physical_qubits: estimates.physical_qubits(),
+            physical_qubits_for_factories: estimates.physical_qubits_for_factories(),
+            layout_physical_qubits: Some(estimates.layout_physical_qubits()),
+            runtime_ns: estimates.runtime(),
+            logical_cycles: estimates.logical_cycles(),
+            cycle_time_ns: estimates.cycle_time_ns(),
+            logical_clock_rate_hz: estimates.logical_clock_rate(),
+            physical_cx: Some(estimates.physical_cx_count()),
+            physical_measurements: Some(estimates.physical_measurement_count()),
+            logical_qubits: estimates.layout_overhead().logical_qubits(),
+            code_parameter: estimates.logical_patch().code_parameter().clone(),
+            factories: estimates.toffoli_factory_part().map(|part| FactoryRecord {
+                factory: part.factory().clone(),
+                copies: part.copies(),
+                runs: part.runs(),
+                overproduction: estimates.toffoli_overproduction(),
+            }),
+            errors: ErrorRecord {
+                total: total_error,
+                computation: estimates.computation_error_contributions(),
+                factories: estimates.factory_error_contributions(),
+                budget: Some(estimates.budget_consumption()),
+                excess: estimates.budget_excess(),
+            },
+            restart: estimates.restart().map(|restart| RestartRecord {
+                heralded_fraction: restart.heralded_fraction(),
+                expected_restarts: restart.expected_restarts(total_error),
+                expected_runtime_ns: restart.expected_runtime(estimates),
+                undetected_error: restart.undetected_error(total_error),
+                sampled_runtime: estimates.sampled_runtime(),
+            }),
+            retry: estimates.retry(),
+            provenance: estimates.provenance().cloned(),
+            extrapolations: estimates
+                .extrapolations()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            counts: Some(estimates.layout_overhead().clone()),
+            right_sizing: estimates.factory_right_sizing().cloned(),
+            utilization: estimates.utilization().cloned(),
+            phases: estimates.phases().cloned(),
+            chip: estimates.chip_dimensions(),
+            wiring: estimates.wiring(),
+            syndrome: estimates.syndrome_data(),
+        }
+    }
+}
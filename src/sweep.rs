@@ -0,0 +1,591 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Parameter sweeps read from CSV, for design-of-experiments studies.
+//!
+//! Each row of a [`ParameterSweep`] is a full combination of parameters,
+//! overriding the ones of the command line, in the columns (all optional, in
+//! any order, empty cells keeping the default):
+//!
+//! - `k1_k2`: ratio κ₁/κ₂ of the cat qubits;
+//! - `thermal_photons`: average number of thermal photons nₜₕ;
+//! - `error_total`: overall error budget;
+//! - `magic_fraction`: fraction of the budget given to the magic state errors
+//!   (0.5 by default);
+//! - `max_qubits`: maximum number of physical qubits;
+//! - `max_runtime`: maximum runtime, e.g. `3 h` or `250 ms`;
+//! - `preset`: predefined qubit model of the Azure Quantum Resource Estimator
+//!   (see [`crate::qre`]), setting κ₁/κ₂ and the schedule of the code.
+//!
+//! ```csv
+//! k1_k2,error_total,max_qubits,preset
+//! 1e-4,0.01,,
+//! 1e-5,0.001,200000,
+//! ,0.01,,qubit_gate_ns_e4
+//! ```
+//!
+//! Cells containing commas are quoted, with doubled quotes inside. Other
+//! columns (e.g. labels) are copied untouched. [`ParameterSweep::run`]
+//! estimates the same logical counts for each row, and [`SweepResults`]
+//! writes the rows back with the result columns appended. Long sweeps can
+//! record each row in a [`Checkpoint`] as it is estimated, and skip the
+//! recorded rows when resumed, and the rows can be estimated by several
+//! workers in parallel ([`ParameterSweep::run_parallel`]).
+
+use std::{collections::HashMap, fmt::Display, fs::read_to_string, num::NonZeroUsize, path::Path};
+
+use resource_estimator::estimates::ErrorBudget;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    budget,
+    checkpoint::Checkpoint,
+    pool,
+    qre::{QreParameters, QubitParams},
+    scan::{self, Limits},
+    units::TimeSpan,
+    AliceAndBobEstimates, CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+
+/// Columns appended to the rows by [`SweepResults`].
+pub const RESULT_COLUMNS: [&str; 5] = [
+    "physical_qubits",
+    "runtime_ns",
+    "code_parameter",
+    "total_error",
+    "error",
+];
+
+/// Parameters of one row of a sweep, [`None`] keeping the default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SweepParameters {
+    /// Ratio κ₁/κ₂ of the cat qubits.
+    pub k1_k2: Option<f64>,
+    /// Average number of thermal photons.
+    pub thermal_photons: Option<f64>,
+    /// Overall error budget.
+    pub error_total: Option<f64>,
+    /// Fraction of the overall error budget given to the magic state errors.
+    pub magic_fraction: Option<f64>,
+    /// Maximum number of physical qubits.
+    pub max_qubits: Option<u64>,
+    /// Maximum runtime.
+    pub max_runtime: Option<TimeSpan>,
+    /// Predefined qubit model of the Azure Quantum Resource Estimator.
+    pub preset: Option<String>,
+}
+
+impl SweepParameters {
+    /// Qubits and code of the row, from the defaults `qubit` and `code`.
+    fn qubit_and_code(
+        &self,
+        qubit: &CatQubit,
+        code: &RepetitionCode,
+    ) -> Result<(CatQubit, RepetitionCode), String> {
+        let (qubit, code) = match &self.preset {
+            Some(preset) => {
+                let parameters = QreParameters {
+                    qubit_params: QubitParams {
+                        name: Some(preset.clone()),
+                        ..QubitParams::default()
+                    },
+                    ..QreParameters::default()
+                };
+                (
+                    parameters.cat_qubit(qubit.clone())?,
+                    parameters.repetition_code(code.clone(), qubit.inverse_k2_ns())?,
+                )
+            }
+            None => (qubit.clone(), code.clone()),
+        };
+        let qubit = match self.k1_k2 {
+            Some(k1_k2) => qubit.with_k1_k2(k1_k2),
+            None => qubit,
+        };
+        let qubit = match self.thermal_photons {
+            Some(n_th) => qubit.with_thermal_photons(n_th),
+            None => qubit,
+        };
+        Ok((qubit, code))
+    }
+
+    /// Error budget of the row, the total of `default` being kept if none is
+    /// given.
+    fn budget(&self, default: &ErrorBudget) -> Result<ErrorBudget, String> {
+        if self.error_total.is_none() && self.magic_fraction.is_none() {
+            return Ok(ErrorBudget::new(
+                default.logical(),
+                default.magic_states(),
+                default.rotations(),
+            ));
+        }
+        let total = self
+            .error_total
+            .unwrap_or_else(|| default.logical() + default.magic_states() + default.rotations());
+        budget::fractional_error_budget(total, self.magic_fraction.unwrap_or(0.5), 0.0)
+    }
+}
+
+/// Rows of a parameter sweep, with the cells of the CSV.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParameterSweep {
+    /// Columns of the CSV.
+    pub columns: Vec<String>,
+    /// Cells and parameters of each row.
+    pub rows: Vec<(Vec<String>, SweepParameters)>,
+}
+
+impl ParameterSweep {
+    /// Sweep read from a CSV file (see [`ParameterSweep::from_csv`]).
+    pub fn from_file(filename: impl AsRef<Path>) -> Result<Self, String> {
+        let filename = filename.as_ref();
+        let content = read_to_string(filename)
+            .map_err(|err| format!("can't read {}: {err}", filename.display()))?;
+        Self::from_csv(&content)
+    }
+
+    /// Sweep as CSV, with a header row naming the columns (`#` for comments).
+    pub fn from_csv(csv: &str) -> Result<Self, String> {
+        let mut rows = csv
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        let (_, header) = rows.next().ok_or("empty parameter sweep")?;
+        let columns = csv_row(header).map_err(|err| format!("header: {err}"))?;
+        if let Some(result) = columns
+            .iter()
+            .find(|column| RESULT_COLUMNS.contains(&column.as_str()))
+        {
+            return Err(format!("column '{result}' is reserved for the results"));
+        }
+
+        let rows = rows
+            .map(|(line, row)| {
+                let cells = csv_row(row).map_err(|err| format!("line {line}: {err}"))?;
+                if cells.len() != columns.len() {
+                    return Err(format!(
+                        "line {line}: {} cells for {} columns",
+                        cells.len(),
+                        columns.len()
+                    ));
+                }
+                let parameters = Self::parameters(&columns, &cells)
+                    .map_err(|err| format!("line {line}: {err}"))?;
+                Ok((cells, parameters))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if rows.is_empty() {
+            return Err("no row in the parameter sweep".into());
+        }
+        Ok(Self { columns, rows })
+    }
+
+    /// Parameters of the cells of a row.
+    fn parameters(columns: &[String], cells: &[String]) -> Result<SweepParameters, String> {
+        fn parse<T: std::str::FromStr>(column: &str, cell: &str) -> Result<T, String> {
+            cell.parse()
+                .map_err(|_| format!("'{cell}' is not a valid {column}"))
+        }
+
+        let mut parameters = SweepParameters::default();
+        for (column, cell) in columns.iter().zip(cells) {
+            if cell.is_empty() {
+                continue;
+            }
+            match column.as_str() {
+                "k1_k2" => {
+                    let k1_k2: f64 = parse(column, cell)?;
+                    if !(k1_k2.is_finite() && k1_k2 > 0.0) {
+                        return Err(format!("k1_k2 {cell} must be a positive number"));
+                    }
+                    parameters.k1_k2 = Some(k1_k2);
+                }
+                "thermal_photons" => {
+                    let n_th: f64 = parse(column, cell)?;
+                    if !(n_th.is_finite() && n_th >= 0.0) {
+                        return Err(format!(
+                            "thermal_photons {cell} must be a non-negative number"
+                        ));
+                    }
+                    parameters.thermal_photons = Some(n_th);
+                }
+                "error_total" => {
+                    parameters.error_total = Some(budget::parse_probability(cell)?);
+                }
+                "magic_fraction" => parameters.magic_fraction = Some(parse(column, cell)?),
+                "max_qubits" => parameters.max_qubits = Some(parse(column, cell)?),
+                "max_runtime" => parameters.max_runtime = Some(parse(column, cell)?),
+                "preset" => parameters.preset = Some(cell.clone()),
+                _ => {}
+            }
+        }
+        Ok(parameters)
+    }
+
+    /// Estimates of `counts` for each row, the parameters of the row
+    /// overriding `qubit`, `code` and `budget`.
+    ///
+    /// A row which can't be estimated (e.g. invalid parameters or unmet
+    /// constraints) has an error instead of estimates, and doesn't stop the
+    /// sweep.
+    #[must_use]
+    pub fn run(
+        &self,
+        qubit: &CatQubit,
+        code: &RepetitionCode,
+        builder: &ToffoliBuilder,
+        counts: &LogicalCounts,
+        budget: &ErrorBudget,
+    ) -> SweepResults<'_> {
+        let outcomes = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(index, (_, parameters))| {
+                outcome(index, parameters, qubit, code, builder, counts, budget)
+            })
+            .collect();
+        SweepResults {
+            sweep: self,
+            outcomes,
+        }
+    }
+
+    /// Estimates of each row as with [`ParameterSweep::run`], by the
+    /// `workers` in parallel and, with a `checkpoint`, skipping the rows
+    /// completed by its interrupted run and recording the others in it as
+    /// soon as they are estimated.
+    pub fn run_parallel(
+        &self,
+        workers: SweepWorkers<'_>,
+        qubit: &CatQubit,
+        builder: &ToffoliBuilder,
+        counts: &LogicalCounts,
+        budget: &ErrorBudget,
+        mut checkpoint: Option<&mut Checkpoint<SweepPoint>>,
+    ) -> Result<SweepResults<'_>, String> {
+        let mut completed: HashMap<_, _> = checkpoint
+            .as_mut()
+            .map(|checkpoint| checkpoint.take_completed())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|point| ((point.row, point.cells), point.outcome))
+            .collect();
+        let mut outcomes: Vec<_> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(index, (cells, _))| completed.remove(&(index, cells.clone())))
+            .collect();
+        let pending: Vec<_> = (0..self.rows.len())
+            .filter(|&index| outcomes[index].is_none())
+            .collect();
+        tracing::debug!(
+            completed = self.rows.len() - pending.len(),
+            "sweep rows already estimated"
+        );
+
+        let mut recorded = Ok(());
+        pool::for_each(
+            &pending,
+            workers.jobs,
+            workers.code,
+            |code, &index| {
+                let parameters = &self.rows[index].1;
+                outcome(index, parameters, qubit, code, builder, counts, budget)
+            },
+            |pending_index, outcome| {
+                let index = pending[pending_index];
+                if let (Some(checkpoint), Ok(())) = (checkpoint.as_mut(), &recorded) {
+                    recorded = checkpoint.record(&SweepPoint {
+                        row: index,
+                        cells: self.rows[index].0.clone(),
+                        outcome: outcome.clone(),
+                    });
+                }
+                outcomes[index] = Some(outcome);
+            },
+        )?;
+        recorded?;
+        Ok(SweepResults {
+            sweep: self,
+            outcomes: outcomes.into_iter().flatten().collect(),
+        })
+    }
+}
+
+/// Workers estimating the rows of a sweep in parallel.
+#[derive(Clone, Copy)]
+pub struct SweepWorkers<'a> {
+    /// Number of workers.
+    pub jobs: NonZeroUsize,
+    /// Repetition code of the command line, built by each worker as the codes
+    /// can't be shared between threads.
+    pub code: &'a (dyn Fn() -> Result<RepetitionCode, String> + Sync),
+}
+
+/// Results of one row of a sweep.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SweepOutcome {
+    /// Number of physical qubits.
+    pub physical_qubits: u64,
+    /// Runtime in nanoseconds.
+    pub runtime_ns: u64,
+    /// Parameters of the repetition code of the computation part.
+    pub code_parameter: String,
+    /// Total error probability.
+    pub total_error: f64,
+}
+
+impl From<&AliceAndBobEstimates> for SweepOutcome {
+    fn from(estimates: &AliceAndBobEstimates) -> Self {
+        Self {
+            physical_qubits: estimates.physical_qubits(),
+            runtime_ns: estimates.runtime(),
+            code_parameter: estimates.logical_patch().code_parameter().to_string(),
+            total_error: estimates.total_error(),
+        }
+    }
+}
+
+/// Row of a sweep completed, as recorded in a [`Checkpoint`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SweepPoint {
+    /// Index of the row (from 0).
+    pub row: usize,
+    /// Cells of the row, so that the rows of another CSV are not skipped.
+    pub cells: Vec<String>,
+    /// Results of the row, or why it can't be estimated.
+    pub outcome: Result<SweepOutcome, String>,
+}
+
+/// Results of the row `index`.
+fn outcome(
+    index: usize,
+    parameters: &SweepParameters,
+    qubit: &CatQubit,
+    code: &RepetitionCode,
+    builder: &ToffoliBuilder,
+    counts: &LogicalCounts,
+    budget: &ErrorBudget,
+) -> Result<SweepOutcome, String> {
+    let estimate = estimate(parameters, qubit, code, builder, counts, budget);
+    tracing::debug!(
+        row = index + 1,
+        ok = estimate.is_ok(),
+        "sweep row estimated"
+    );
+    estimate
+        .as_ref()
+        .map(SweepOutcome::from)
+        .map_err(Clone::clone)
+}
+
+/// Estimate of one row of a sweep.
+fn estimate(
+    parameters: &SweepParameters,
+    qubit: &CatQubit,
+    code: &RepetitionCode,
+    builder: &ToffoliBuilder,
+    counts: &LogicalCounts,
+    budget: &ErrorBudget,
+) -> Result<AliceAndBobEstimates, String> {
+    let (qubit, code) = parameters.qubit_and_code(qubit, code)?;
+    let budget = parameters.budget(budget)?;
+    let limits = Limits {
+        max_qubits: parameters.max_qubits,
+        max_runtime: parameters.max_runtime,
+    };
+    scan::estimate_within(&qubit, &code, builder, counts.clone(), &budget, limits)
+}
+
+/// Results of each row of a [`ParameterSweep`].
+pub struct SweepResults<'a> {
+    /// Sweep of the estimates.
+    pub sweep: &'a ParameterSweep,
+    /// Results of each row, or why the row can't be estimated.
+    pub outcomes: Vec<Result<SweepOutcome, String>>,
+}
+
+/// Cells of a row of a CSV, trimmed, a quoted cell possibly containing
+/// commas and doubled quotes (as written by [`csv_cell`]).
+fn csv_row(row: &str) -> Result<Vec<String>, String> {
+    let mut cells = Vec::new();
+    let mut chars = row.chars().peekable();
+    loop {
+        while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+        let mut cell = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next() {
+                    Some('"') if chars.next_if_eq(&'"').is_some() => cell.push('"'),
+                    Some('"') => break,
+                    Some(c) => cell.push(c),
+                    None => return Err("unterminated quoted cell".into()),
+                }
+            }
+            while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+            if chars.peek().is_some_and(|c| *c != ',') {
+                return Err(format!("unexpected text after the quoted cell '{cell}'"));
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                if c == '"' {
+                    return Err("quote inside an unquoted cell".into());
+                }
+                cell.push(c);
+            }
+            cell.truncate(cell.trim_end().len());
+        }
+        cells.push(cell);
+        if chars.next().is_none() {
+            return Ok(cells);
+        }
+    }
+}
+
+/// Cell of a CSV, quoted if needed.
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+impl Display for SweepResults<'_> {
+    /// Rows of the sweep as CSV, with the [`RESULT_COLUMNS`] appended.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header: Vec<_> = self
+            .sweep
+            .columns
+            .iter()
+            .map(String::as_str)
+            .chain(RESULT_COLUMNS)
+            .map(csv_cell)
+            .collect();
+        writeln!(f, "{}", header.join(","))?;
+        for ((cells, _), outcome) in self.sweep.rows.iter().zip(&self.outcomes) {
+            let results = match outcome {
+                Ok(outcome) => [
+                    outcome.physical_qubits.to_string(),
+                    outcome.runtime_ns.to_string(),
+                    outcome.code_parameter.clone(),
+                    format!("{:e}", outcome.total_error),
+                    String::new(),
+                ],
+                Err(err) => [
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    err.clone(),
+                ],
+            };
+            let row: Vec<_> = cells
+                .iter()
+                .chain(&results)
+                .map(|cell| csv_cell(cell))
+                .collect();
+            writeln!(f, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_row, ParameterSweep, SweepOutcome, SweepParameters, SweepResults};
+    use crate::units::TimeSpan;
+
+    #[test]
+    fn rows_are_parsed_by_column_name() {
+        let sweep = ParameterSweep::from_csv(
+            "# study\nlabel, max_runtime ,k1_k2,thermal_photons\na,3 h,1e-4,0.5\n\nb,,,\n",
+        )
+        .expect("valid sweep");
+        assert_eq!(
+            sweep.columns,
+            ["label", "max_runtime", "k1_k2", "thermal_photons"]
+        );
+        assert_eq!(sweep.rows.len(), 2);
+        let (cells, parameters) = &sweep.rows[0];
+        assert_eq!(cells, &["a", "3 h", "1e-4", "0.5"]);
+        assert_eq!(
+            parameters,
+            &SweepParameters {
+                k1_k2: Some(1e-4),
+                thermal_photons: Some(0.5),
+                max_runtime: Some(TimeSpan::from_ns(3 * 3_600_000_000_000)),
+                ..SweepParameters::default()
+            }
+        );
+        // empty cells keep the defaults
+        assert_eq!(sweep.rows[1].1, SweepParameters::default());
+    }
+
+    #[test]
+    fn quoted_cells_may_contain_commas() {
+        assert_eq!(
+            csv_row(r#""a, ""b""" , c,"",d"#).expect("valid row"),
+            ["a, \"b\"", "c", "", "d"]
+        );
+        let sweep = ParameterSweep::from_csv("label,k1_k2\n\"x, y\",1e-5").expect("valid sweep");
+        assert_eq!(sweep.rows[0].0, ["x, y", "1e-5"]);
+        assert_eq!(sweep.rows[0].1.k1_k2, Some(1e-5));
+        for row in [r#""a"#, r#"a"b,c"#, r#""a" b,c"#] {
+            assert!(csv_row(row).is_err(), "{row}");
+        }
+    }
+
+    #[test]
+    fn invalid_rows_are_rejected() {
+        for csv in [
+            "",
+            "# no header\n",
+            "k1_k2\n",
+            "k1_k2,error\n1e-4,\n",
+            "k1_k2,label\n1e-4\n",
+            "k1_k2\nsmall\n",
+            "k1_k2\n0\n",
+            "k1_k2\n-1e-4\n",
+            "k1_k2\ninf\n",
+            "thermal_photons\n-0.1\n",
+            "thermal_photons\nNaN\n",
+            "error_total\n2\n",
+            "label\n\"a\n",
+        ] {
+            assert!(ParameterSweep::from_csv(csv).is_err(), "{csv:?}");
+        }
+        assert!(ParameterSweep::from_csv("thermal_photons\n0\n").is_ok());
+    }
+
+    #[test]
+    fn results_are_appended_to_the_rows() {
+        let sweep =
+            ParameterSweep::from_csv("label,k1_k2\n\"a,b\",1e-4\nc,1e-5\n").expect("valid sweep");
+        let results = SweepResults {
+            sweep: &sweep,
+            outcomes: vec![
+                Ok(SweepOutcome {
+                    physical_qubits: 1234,
+                    runtime_ns: 5000,
+                    code_parameter: "d=5".into(),
+                    total_error: 0.01,
+                }),
+                Err("no valid code, try 1, 2".into()),
+            ],
+        };
+        assert_eq!(
+            results.to_string(),
+            "label,k1_k2,physical_qubits,runtime_ns,code_parameter,total_error,error\n\
+             \"a,b\",1e-4,1234,5000,d=5,1e-2,\n\
+             c,1e-5,,,,,\"no valid code, try 1, 2\"\n"
+        );
+        // the output is read back as a CSV
+        let written = results.to_string();
+        let row = written.lines().nth(2).expect("two rows");
+        assert_eq!(csv_row(row).expect("valid row").len(), 7);
+    }
+}
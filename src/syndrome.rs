@@ -0,0 +1,132 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Volume of the syndrome data sent to the decoder.
+//!
+//! Each ancilla measurement produces a syndrome bit (or more, with soft
+//! information), so the total data generated during the run follows from the
+//! physical measurements (see
+//! [`AliceAndBobEstimates::physical_measurement_count`]). A repetition code
+//! block of distance d produces d − 1 syndrome measurements per round, which
+//! gives the real-time bandwidth the classical pipeline must sustain for each
+//! block.
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::{report::ReportSection, AliceAndBobEstimates};
+
+/// Syndrome data of an estimate.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyndromeData {
+    /// Bits sent per measurement (1 for hard decisions).
+    pub bits_per_measurement: u32,
+    /// Syndrome data generated during the whole run, in bytes.
+    pub total_bytes: f64,
+    /// Bandwidth of the syndrome data of one repetition code block, in
+    /// bits per second.
+    pub block_bandwidth_bps: f64,
+    /// Bandwidth of the syndrome data of the whole processor, in bits per
+    /// second.
+    pub total_bandwidth_bps: f64,
+}
+
+impl SyndromeData {
+    /// Syndrome data of `estimates`, with `bits_per_measurement` bits per
+    /// measurement.
+    #[must_use]
+    pub fn new(estimates: &AliceAndBobEstimates, bits_per_measurement: u32) -> Self {
+        let float = |value: u64| value.to_f64().unwrap_or(f64::MAX);
+        let bits = f64::from(bits_per_measurement);
+        let total_bits = float(estimates.physical_measurement_count()) * bits;
+        let runtime_s = estimates.runtime_duration().as_seconds();
+
+        // a block measures its d − 1 ancillas at each round
+        let distance = estimates.logical_patch().code_parameter().distance();
+        let block_bits = float(
+            (distance - 1)
+                .saturating_mul(estimates.rounds())
+                .saturating_mul(estimates.logical_cycles()),
+        ) * bits;
+
+        Self {
+            bits_per_measurement,
+            total_bytes: total_bits / 8.0,
+            block_bandwidth_bps: rate(block_bits, runtime_s),
+            total_bandwidth_bps: rate(total_bits, runtime_s),
+        }
+    }
+
+    #[must_use]
+    /// Syndrome data as (label, formatted value) pairs.
+    pub fn report_section(&self) -> ReportSection {
+        vec![
+            ("syndrome data", format_bytes(self.total_bytes)),
+            (
+                "syndrome bandwidth",
+                format!(
+                    "{} per block, {} in total",
+                    format_bandwidth(self.block_bandwidth_bps),
+                    format_bandwidth(self.total_bandwidth_bps)
+                ),
+            ),
+        ]
+    }
+}
+
+/// `amount / seconds`, 0 for an empty duration.
+fn rate(amount: f64, seconds: f64) -> f64 {
+    if seconds > 0.0 {
+        amount / seconds
+    } else {
+        0.0
+    }
+}
+
+/// Format `value` in `unit`, with the largest decimal prefix keeping it at
+/// least 1.
+fn format_decimal(value: f64, unit: &str) -> String {
+    let prefixes = ["", "k", "M", "G", "T", "P", "E"];
+    let mut scaled = value;
+    let mut prefix = prefixes[0];
+    for next in &prefixes[1..] {
+        if scaled < 1000.0 {
+            break;
+        }
+        scaled /= 1000.0;
+        prefix = next;
+    }
+    format!("{scaled:.2} {prefix}{unit}")
+}
+
+/// Format a number of bytes (kB, MB, GB, TB, ...).
+#[must_use]
+pub fn format_bytes(bytes: f64) -> String {
+    format_decimal(bytes, "B")
+}
+
+/// Format a bandwidth in bits per second (kbit/s, Mbit/s, ...).
+#[must_use]
+pub fn format_bandwidth(bps: f64) -> String {
+    format_decimal(bps, "bit/s")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_bandwidth, format_bytes, rate};
+
+    #[test]
+    fn amounts_take_the_largest_prefix() {
+        assert_eq!(format_bytes(0.0), "0.00 B");
+        assert_eq!(format_bytes(999.0), "999.00 B");
+        assert_eq!(format_bytes(1500.0), "1.50 kB");
+        assert_eq!(format_bandwidth(2.5e9), "2.50 Gbit/s");
+        assert_eq!(format_bytes(3e21), "3000.00 EB");
+    }
+
+    #[test]
+    fn empty_durations_have_no_rate() {
+        assert!((rate(10.0, 2.0) - 5.0).abs() < f64::EPSILON);
+        assert!(rate(10.0, 0.0).abs() < f64::EPSILON);
+    }
+}
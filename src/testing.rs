@@ -0,0 +1,181 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Canonical inputs and reference outputs, to check integrations.
+//!
+//! Downstream crates embedding the estimator can estimate the inputs of this
+//! module (the elliptic curve discrete logarithm of arXiv:2302.06639, and the
+//! ripple-carry adder of `qsharp/Adder.qs`) and compare the results with the
+//! [`Reference`] values, e.g. after upgrading the crate:
+//!
+//! ```no_run
+//! use std::rc::Rc;
+//!
+//! use qsharp_alice_bob_resource_estimator::{
+//!     testing, AliceAndBobEstimates, CatQubit, RepetitionCode, ToffoliBuilder,
+//! };
+//! use resource_estimator::estimates::PhysicalResourceEstimation;
+//!
+//! let estimation = PhysicalResourceEstimation::new(
+//!     RepetitionCode::new(),
+//!     Rc::new(CatQubit::new()),
+//!     ToffoliBuilder::default(),
+//!     Rc::new(testing::ecc_256_counts()),
+//!     testing::ecc_256_budget(),
+//! );
+//! let estimates = AliceAndBobEstimates::estimate(&estimation).unwrap();
+//! testing::ECC_256_REFERENCE.check(&estimates).unwrap();
+//! ```
+//!
+//! [`estimate`] does the same with the default qubits, code and factories.
+//!
+//! The reference of the elliptic curve discrete logarithm is the one of the
+//! article (126 133 cat qubits, 9 hours, with κ₁/κ₂ = 1e-5 and 1/κ₂ = 100 ns);
+//! its tolerance covers the differences between the optimization of the
+//! article and the one of the estimator.
+
+use std::rc::Rc;
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::{ErrorBudget, PhysicalResourceEstimation};
+
+use crate::{
+    restart::AlgorithmRetry, window::ecc_counts, AliceAndBobEstimates, CatQubit, LogicalCounts,
+    RepetitionCode, ToffoliBuilder,
+};
+
+/// Bit size of the elliptic curve of [`ecc_256_counts`].
+pub const ECC_BIT_SIZE: u64 = 256;
+
+/// Window size of the modular exponentiation of [`ecc_256_counts`], value `w_e`
+/// of arXiv:2302.06639 (Table IV, p. 37).
+pub const ECC_WINDOW_SIZE: u64 = 18;
+
+/// Logical counts of the 256-bit elliptic curve discrete logarithm of
+/// arXiv:2302.06639.
+pub fn ecc_256_counts() -> LogicalCounts {
+    ecc_counts(ECC_BIT_SIZE, ECC_WINDOW_SIZE)
+}
+
+/// Error budget of [`ECC_256_REFERENCE`]: a total error of 1/3, split equally
+/// between the topological and magic state errors.
+#[must_use]
+pub fn ecc_256_budget() -> ErrorBudget {
+    ErrorBudget::new(0.333 * 0.5, 0.333 * 0.5, 0.0)
+}
+
+/// Intrinsic success probability of the elliptic curve discrete logarithm of
+/// arXiv:2302.06639: a run without error yields the logarithm after the
+/// classical post-processing, so only the errors cause retries.
+pub const ECC_SUCCESS_PROBABILITY: f64 = 1.0;
+
+/// Retry strategy of the elliptic curve discrete logarithm, whose expected
+/// runs over the retries (1 / (1 − p) for a total error p) give the expected
+/// times of arXiv:2302.06639 (Table IV).
+#[must_use]
+pub fn ecc_retry() -> AlgorithmRetry {
+    AlgorithmRetry::new(ECC_SUCCESS_PROBABILITY).expect("valid success probability")
+}
+
+/// Path of the Q# ripple-carry adder of 128 bits shipped with the crate.
+#[must_use]
+pub fn adder_path() -> String {
+    format!("{}/qsharp/Adder.qs", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Logical counts of the Q# ripple-carry adder of [`adder_path`].
+#[cfg(feature = "qsharp")]
+pub fn adder_counts() -> Result<LogicalCounts, String> {
+    LogicalCounts::from_qsharp(adder_path())
+}
+
+/// Error budget of the adder example: a total error of 1e-3, split equally
+/// between the topological and magic state errors.
+#[must_use]
+pub fn adder_budget() -> ErrorBudget {
+    ErrorBudget::new(0.001 * 0.5, 0.001 * 0.5, 0.0)
+}
+
+/// Estimate of `counts` within `budget`, with the default cat qubits,
+/// repetition code and factories (the ones of the references).
+pub fn estimate(
+    counts: LogicalCounts,
+    budget: ErrorBudget,
+) -> Result<AliceAndBobEstimates, String> {
+    let estimation = PhysicalResourceEstimation::new(
+        RepetitionCode::new(),
+        Rc::new(CatQubit::new()),
+        ToffoliBuilder::default(),
+        Rc::new(counts),
+        budget,
+    );
+    AliceAndBobEstimates::estimate(&estimation)
+}
+
+/// Whether `value` is `expected` up to the rounding errors of floating-point
+/// computations (a relative difference of 1e-12).
+#[must_use]
+pub fn close(value: f64, expected: f64) -> bool {
+    (value - expected).abs() <= 1e-12 * expected.abs()
+}
+
+/// Expected resources of an estimate, with a relative tolerance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reference {
+    /// Name of the reference, for the error messages.
+    pub name: &'static str,
+    /// Expected number of physical qubits.
+    pub physical_qubits: u64,
+    /// Expected runtime, in ns.
+    pub runtime_ns: u64,
+    /// Accepted relative deviation from the expected values (e.g. 0.25 for
+    /// ±25%).
+    pub relative_tolerance: f64,
+}
+
+/// Resources of the 256-bit elliptic curve discrete logarithm with the default
+/// qubits and code, arXiv:2302.06639 (abstract, and Table IV p. 37).
+pub const ECC_256_REFERENCE: Reference = Reference {
+    name: "ECC-256",
+    physical_qubits: 126_133,
+    runtime_ns: 9 * 3_600 * 1_000_000_000,
+    relative_tolerance: 0.25,
+};
+
+impl Reference {
+    /// Check that the physical qubits and runtime of `estimates` are within
+    /// the tolerance of the reference.
+    pub fn check(&self, estimates: &AliceAndBobEstimates) -> Result<(), String> {
+        let quantities = [
+            (
+                "physical qubits",
+                self.physical_qubits,
+                estimates.physical_qubits(),
+            ),
+            ("runtime (ns)", self.runtime_ns, estimates.runtime()),
+        ];
+        let deviations: Vec<String> = quantities
+            .into_iter()
+            .filter_map(|(quantity, expected, actual)| {
+                let deviation =
+                    (actual.to_f64()? - expected.to_f64()?).abs() / expected.to_f64()?;
+                (deviation > self.relative_tolerance).then(|| {
+                    format!(
+                        "{quantity} {actual} deviates by {:.1}% from {expected}",
+                        100.0 * deviation
+                    )
+                })
+            })
+            .collect();
+        if deviations.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{}: {} (tolerance {:.0}%)",
+                self.name,
+                deviations.join(", "),
+                100.0 * self.relative_tolerance
+            ))
+        }
+    }
+}
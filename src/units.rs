@@ -0,0 +1,323 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Units used to display the estimates.
+//!
+//! Durations are handled in nanoseconds by the resource estimator, which is
+//! unreadable both for short circuits and for computations running for years.
+//! [`TimeUnit`] allows to either choose automatically the most suitable unit,
+//! or to force a specific one.
+//!
+//! [`TimeSpan`] wraps the nanoseconds, so that the durations of the code, the
+//! factories and the estimates can't be mixed with other integers, and gives
+//! them in seconds or hours. Durations computed in f64 are rounded to whole
+//! nanoseconds with an explicit [`Rounding`] (to the nearest by default, as
+//! the resource estimator).
+
+use std::{
+    fmt::Display,
+    ops::{Add, Mul},
+    str::FromStr,
+};
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+/// Unit in which durations are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Largest unit in which the duration is at least 1.
+    #[default]
+    Auto,
+    /// Nanoseconds.
+    Nanoseconds,
+    /// Microseconds.
+    Microseconds,
+    /// Milliseconds.
+    Milliseconds,
+    /// Seconds.
+    Seconds,
+    /// Minutes.
+    Minutes,
+    /// Hours.
+    Hours,
+    /// Days.
+    Days,
+    /// Julian years (365.25 days).
+    Years,
+}
+
+impl TimeUnit {
+    /// Units from the smallest to the largest, [`TimeUnit::Auto`] excluded.
+    const ALL: [Self; 8] = [
+        Self::Nanoseconds,
+        Self::Microseconds,
+        Self::Milliseconds,
+        Self::Seconds,
+        Self::Minutes,
+        Self::Hours,
+        Self::Days,
+        Self::Years,
+    ];
+
+    /// Duration of the unit in nanoseconds ([`TimeUnit::Auto`] counts as 1).
+    #[must_use]
+    pub fn nanoseconds(self) -> f64 {
+        match self {
+            Self::Auto | Self::Nanoseconds => 1.0,
+            Self::Microseconds => 1e3,
+            Self::Milliseconds => 1e6,
+            Self::Seconds => 1e9,
+            Self::Minutes => 60e9,
+            Self::Hours => 3600e9,
+            Self::Days => 86_400e9,
+            Self::Years => 365.25 * 86_400e9,
+        }
+    }
+
+    /// Short symbol of the unit.
+    #[must_use]
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Nanoseconds => "ns",
+            Self::Microseconds => "µs",
+            Self::Milliseconds => "ms",
+            Self::Seconds => "s",
+            Self::Minutes => "min",
+            Self::Hours => "hrs",
+            Self::Days => "days",
+            Self::Years => "years",
+        }
+    }
+
+    /// Resolve [`TimeUnit::Auto`] into the largest unit in which the duration
+    /// (in nanoseconds) is at least 1. Other units are returned unchanged.
+    #[must_use]
+    pub fn resolve(self, nanoseconds: f64) -> Self {
+        match self {
+            Self::Auto => Self::ALL
+                .into_iter()
+                .rev()
+                .find(|unit| nanoseconds >= unit.nanoseconds())
+                .unwrap_or(Self::Nanoseconds),
+            unit => unit,
+        }
+    }
+
+    /// Format a duration given in nanoseconds in this unit, with two decimals.
+    #[must_use]
+    pub fn format(self, nanoseconds: f64) -> String {
+        let unit = self.resolve(nanoseconds);
+        format!("{:.2} {}", nanoseconds / unit.nanoseconds(), unit.symbol())
+    }
+}
+
+impl Display for TimeUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Microseconds => write!(f, "us"),
+            Self::Hours => write!(f, "h"),
+            unit => write!(f, "{}", unit.symbol()),
+        }
+    }
+}
+
+impl FromStr for TimeUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "ns" => Ok(Self::Nanoseconds),
+            "us" | "µs" => Ok(Self::Microseconds),
+            "ms" => Ok(Self::Milliseconds),
+            "s" => Ok(Self::Seconds),
+            "min" => Ok(Self::Minutes),
+            "h" | "hrs" => Ok(Self::Hours),
+            "days" => Ok(Self::Days),
+            "years" => Ok(Self::Years),
+            _ => Err(format!(
+                "unknown time unit '{s}' (expected auto, ns, us, ms, s, min, h, days or years)"
+            )),
+        }
+    }
+}
+
+/// Rounding of the durations computed in f64 to whole nanoseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// To the nearest nanosecond, half away from zero.
+    #[default]
+    Nearest,
+    /// To the next nanosecond (e.g. for deadlines).
+    Up,
+    /// To the previous nanosecond.
+    Down,
+}
+
+impl Rounding {
+    /// `nanoseconds` rounded with this policy.
+    #[must_use]
+    pub fn apply(self, nanoseconds: f64) -> f64 {
+        match self {
+            Self::Nearest => nanoseconds.round(),
+            Self::Up => nanoseconds.ceil(),
+            Self::Down => nanoseconds.floor(),
+        }
+    }
+}
+
+/// Duration in whole nanoseconds, named apart from [`std::time::Duration`]
+/// (which the timeouts of the command line use).
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct TimeSpan(u64);
+
+impl TimeSpan {
+    /// Duration of `nanoseconds` ns.
+    #[must_use]
+    pub const fn from_ns(nanoseconds: u64) -> Self {
+        Self(nanoseconds)
+    }
+
+    /// Duration of `nanoseconds` ns rounded with `rounding`, [`None`] if it is
+    /// negative, NaN or too long.
+    #[must_use]
+    pub fn from_ns_f64(nanoseconds: f64, rounding: Rounding) -> Option<Self> {
+        u64::from_f64(rounding.apply(nanoseconds)).map(Self)
+    }
+
+    /// Duration in nanoseconds.
+    #[must_use]
+    pub const fn as_ns(self) -> u64 {
+        self.0
+    }
+
+    /// Duration in nanoseconds, as f64.
+    #[must_use]
+    pub fn as_ns_f64(self) -> f64 {
+        self.0.to_f64().unwrap_or(f64::MAX)
+    }
+
+    /// Duration in seconds.
+    #[must_use]
+    pub fn as_seconds(self) -> f64 {
+        self.as_ns_f64() / TimeUnit::Seconds.nanoseconds()
+    }
+
+    /// Duration in hours.
+    #[must_use]
+    pub fn as_hours(self) -> f64 {
+        self.as_ns_f64() / TimeUnit::Hours.nanoseconds()
+    }
+
+    /// Format the duration in `unit`, with two decimals.
+    #[must_use]
+    pub fn format(self, unit: TimeUnit) -> String {
+        unit.format(self.as_ns_f64())
+    }
+}
+
+impl Add for TimeSpan {
+    type Output = Self;
+
+    /// Sum of the durations, saturating at the longest duration.
+    fn add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
+impl Mul<u64> for TimeSpan {
+    type Output = Self;
+
+    /// Duration repeated `times` times, saturating at the longest duration.
+    fn mul(self, times: u64) -> Self {
+        Self(self.0.saturating_mul(times))
+    }
+}
+
+impl FromStr for TimeSpan {
+    type Err = String;
+
+    /// Parse a value followed by a time unit (e.g. `50 ns` or `1.5us`),
+    /// rounded to the nearest nanosecond.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let value = s.trim_end_matches(char::is_alphabetic);
+        let unit: TimeUnit = s[value.len()..].parse()?;
+        if unit == TimeUnit::Auto {
+            return Err(format!("duration '{s}' has no time unit"));
+        }
+        value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(|value| Self::from_ns_f64(value * unit.nanoseconds(), Rounding::Nearest))
+            .ok_or_else(|| format!("'{s}' is not a duration"))
+    }
+}
+
+impl Display for TimeSpan {
+    /// Duration in the most suitable unit.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format(TimeUnit::Auto))
+    }
+}
+
+/// Format an integer with a comma as thousands separator.
+#[must_use]
+pub fn format_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_thousands, TimeSpan};
+
+    #[test]
+    fn durations_are_parsed_with_their_unit() {
+        for (text, ns) in [
+            ("50 ns", 50),
+            ("50ns", 50),
+            ("1.5us", 1_500),
+            ("1.5 µs", 1_500),
+            ("  2 ms ", 2_000_000),
+            ("1e-3 s", 1_000_000),
+            ("3 h", 3 * 3_600_000_000_000),
+            ("0.4 ns", 0),
+            ("0.5 ns", 1),
+        ] {
+            assert_eq!(text.parse(), Ok(TimeSpan::from_ns(ns)), "{text}");
+        }
+        for text in [
+            "50",
+            "ns",
+            "50 auto",
+            "-5 ns",
+            "NaN s",
+            "5 fortnights",
+            "1e30 years",
+        ] {
+            assert!(text.parse::<TimeSpan>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn thousands_are_separated() {
+        assert_eq!(format_thousands(0), "0");
+        assert_eq!(format_thousands(999), "999");
+        assert_eq!(format_thousands(1_000), "1,000");
+        assert_eq!(format_thousands(1_234_567), "1,234,567");
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Utilization of the regions of the layout.
+//!
+//! A [`Utilization`] estimates, for each region of the layout (compute qubits,
+//! "horizontal" routing qubits, factories), the fraction of the runtime during
+//! which its logical qubits are active, which shows imbalances of the
+//! architecture (e.g. factories idle most of the time, or compute qubits
+//! waiting for magic states).
+//!
+//! The activity is derived from the counts, gates being assumed to be
+//! sequential as in the logical depth:
+//! - a CX keeps 2 compute qubits busy for 2.2 cycles (times the routing
+//!   factor), a CCX 3 for 10.1 cycles, and a measurement 1 for its duration;
+//! - each CX and CCX goes through one routing qubit for its duration;
+//! - a factory is active while producing the Toffoli states consumed by the
+//!   computation (retries included).
+
+use std::fmt::Display;
+
+use num_traits::ToPrimitive;
+use resource_estimator::estimates::{FactoryPart, Overhead};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    counter::{LogicalCounts, CCX_CYCLES, CX_CYCLES},
+    report::ReportSection,
+    AliceAndBobEstimates,
+};
+
+/// Logical qubits of a factory, its routing qubit included (see
+/// [`crate::factories::ToffoliFactory`]).
+const FACTORY_LOGICAL_QUBITS: u64 = 5;
+
+/// Activity of one region of the layout.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RegionUtilization {
+    /// Region of the layout (compute, routing or factories).
+    pub region: String,
+    /// Logical qubits of the region.
+    pub logical_qubits: u64,
+    /// Fraction of the runtime during which the qubits of the region are
+    /// active, between 0 and 1.
+    pub active_fraction: f64,
+}
+
+impl RegionUtilization {
+    /// Fraction of the runtime during which the qubits of the region are idle.
+    #[must_use]
+    pub fn idle_fraction(&self) -> f64 {
+        1.0 - self.active_fraction
+    }
+}
+
+/// Activity of the regions of the layout of an estimate.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Utilization {
+    /// Regions of the layout: compute, routing, then factories.
+    pub regions: Vec<RegionUtilization>,
+}
+
+/// Fraction `busy / available` capped to 1, 0 if nothing is available.
+fn fraction(busy: f64, available: f64) -> f64 {
+    if available > 0.0 {
+        (busy / available).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+impl Utilization {
+    /// Utilization of the regions of the layout of `estimates`.
+    #[must_use]
+    pub fn new(estimates: &AliceAndBobEstimates) -> Self {
+        Self::from_counts(
+            estimates.layout_overhead(),
+            estimates.logical_cycles(),
+            estimates
+                .toffoli_factory_part()
+                .map_or(0, FactoryPart::copies),
+            estimates.factory_duty_cycle().unwrap_or_default(),
+        )
+    }
+
+    /// Utilization of the regions of the layout of `counts` running for
+    /// `cycles` logical cycles, with `copies` factories active a fraction
+    /// `duty_cycle` of the runtime.
+    #[allow(clippy::similar_names)]
+    fn from_counts(counts: &LogicalCounts, cycles: u64, copies: u64, duty_cycle: f64) -> Self {
+        let gate_set = counts.gate_set();
+        let float = |value: u64| value.to_f64().unwrap_or(f64::MAX);
+        let cycles = float(cycles);
+
+        let cx_cycles = float(counts.cx_count()) * CX_CYCLES * gate_set.routing_factor;
+        let ccx_cycles = float(counts.ccx_count()) * CCX_CYCLES;
+        let measurement_cycles = float(counts.measurement_count()) * gate_set.measurement_cycles;
+        let compute_qubits = counts.qubit_count();
+        let compute_busy = 2.0 * cx_cycles + 3.0 * ccx_cycles + measurement_cycles;
+
+        let routing_qubits = counts.logical_qubits() - compute_qubits;
+        let routing_busy = cx_cycles + ccx_cycles;
+
+        Self {
+            regions: vec![
+                RegionUtilization {
+                    region: "compute".into(),
+                    logical_qubits: compute_qubits,
+                    active_fraction: fraction(compute_busy, float(compute_qubits) * cycles),
+                },
+                RegionUtilization {
+                    region: "routing".into(),
+                    logical_qubits: routing_qubits,
+                    active_fraction: fraction(routing_busy, float(routing_qubits) * cycles),
+                },
+                RegionUtilization {
+                    region: "factories".into(),
+                    logical_qubits: copies * FACTORY_LOGICAL_QUBITS,
+                    active_fraction: duty_cycle,
+                },
+            ],
+        }
+    }
+
+    #[must_use]
+    /// Activity of the regions as (label, formatted value) pairs.
+    pub fn report_section(&self) -> ReportSection {
+        self.regions
+            .iter()
+            .map(|region| {
+                (
+                    "active",
+                    format!(
+                        "{:.1}% ({}, {} logical qubits)",
+                        100.0 * region.active_fraction,
+                        region.region,
+                        region.logical_qubits
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Display for Utilization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "  {:<12}{:>16}{:>10}{:>10}",
+            "region", "logical qubits", "active", "idle"
+        )?;
+        for region in &self.regions {
+            writeln!(
+                f,
+                "  {:<12}{:>16}{:>9.1}%{:>9.1}%",
+                region.region,
+                region.logical_qubits,
+                100.0 * region.active_fraction,
+                100.0 * region.idle_fraction()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fraction, LogicalCounts, Utilization};
+
+    #[test]
+    fn regions_are_busy_for_their_gates() {
+        // 10 compute qubits and 6 routing qubits, busy for 2·220 + 3·101 and
+        // 220 + 101 cycles out of 1000.
+        let counts = LogicalCounts::new(10, 100, 10);
+        let utilization = Utilization::from_counts(&counts, 1000, 2, 0.5);
+        let regions: Vec<_> = utilization
+            .regions
+            .iter()
+            .map(|region| (region.region.as_str(), region.logical_qubits))
+            .collect();
+        assert_eq!(
+            regions,
+            [("compute", 10), ("routing", 6), ("factories", 10)]
+        );
+        let active: Vec<_> = utilization
+            .regions
+            .iter()
+            .map(|region| region.active_fraction)
+            .collect();
+        for (active, expected) in active.iter().zip([0.0743, 0.0535, 0.5]) {
+            assert!((active - expected).abs() < 1e-12, "{active} {expected}");
+        }
+        assert!((utilization.regions[2].idle_fraction() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn fractions_are_capped() {
+        assert!((fraction(3.0, 2.0) - 1.0).abs() < f64::EPSILON);
+        assert!(fraction(1.0, 0.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn regions_are_reported_in_order() {
+        let utilization = Utilization::from_counts(&LogicalCounts::new(10, 100, 10), 1000, 0, 0.0);
+        let text = utilization.to_string();
+        let rows: Vec<_> = text
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_whitespace().next())
+            .collect();
+        assert_eq!(rows, ["compute", "routing", "factories"]);
+        assert_eq!(utilization.report_section().len(), 3);
+    }
+}
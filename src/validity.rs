@@ -0,0 +1,244 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Validity ranges of the models.
+//!
+//! The formulas of the estimator are fits of simulations, and the factories
+//! have been precomputed for a single set of parameters:
+//! - the phase-flip fit of the repetition code
+//!   ([arXiv:2302.06639](https://arxiv.org/abs/2302.06639), p. 29, Fig. 26)
+//!   has been obtained for small distances, moderate |α|² and κ₁/κ₂ below the
+//!   threshold, without thermal photons,
+//! - the factories (p. 35, Table III) have been simulated for κ₁/κ₂ = 1e-5,
+//!   without thermal photons, with adiabatic CX gates, and are extrapolated
+//!   otherwise (see
+//!   [`ToffoliFactory::for_qubit`](crate::factories::ToffoliFactory::for_qubit)).
+//!
+//! A [`ValidityRange`] lists the [`Extrapolation`]s an estimate relies on. They
+//! are reported with the estimate, or rejected in strict mode (always enabled
+//! with the `strict` cargo feature).
+
+use std::fmt::Display;
+
+use crate::{
+    code::CodeParameter, factories::REFERENCE_K1_K2, AliceAndBobEstimates, CatQubit,
+    CxImplementation,
+};
+
+/// Whether estimates relying on extrapolations are rejected by default.
+pub const STRICT: bool = cfg!(feature = "strict");
+
+/// Assumption used outside of its validity range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Extrapolation {
+    /// Extrapolated assumption.
+    pub assumption: &'static str,
+    /// Value used by the estimate.
+    pub value: String,
+    /// Range covered by the fitted or simulated data.
+    pub range: String,
+}
+
+impl Display for Extrapolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} = {} outside of {}",
+            self.assumption, self.value, self.range
+        )
+    }
+}
+
+/// Ranges of the parameters covered by the fits and precomputations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidityRange {
+    /// Code distances of the phase-flip fit.
+    pub distance: (u64, u64),
+    /// Average numbers of photons |α|² of the phase-flip fit.
+    pub alpha_sq: (f64, f64),
+    /// κ₁/κ₂ of the phase-flip fit.
+    pub k1_k2: (f64, f64),
+    /// Relative tolerance on the κ₁/κ₂ of the factories.
+    pub factory_k1_k2_tolerance: f64,
+}
+
+impl Default for ValidityRange {
+    /// Approximate ranges of the simulations of
+    /// [arXiv:2302.06639](https://arxiv.org/abs/2302.06639) (p. 29, Fig. 26
+    /// and p. 35, Table III).
+    fn default() -> Self {
+        Self {
+            distance: (3, 25),
+            alpha_sq: (2.0, 30.0),
+            k1_k2: (1e-6, 1e-2),
+            factory_k1_k2_tolerance: 0.01,
+        }
+    }
+}
+
+impl ValidityRange {
+    /// Assumptions of `estimates` (obtained with `qubit`) outside of the
+    /// ranges.
+    #[must_use]
+    pub fn extrapolations(
+        &self,
+        estimates: &AliceAndBobEstimates,
+        qubit: &CatQubit,
+    ) -> Vec<Extrapolation> {
+        let mut extrapolations =
+            self.code_extrapolations(estimates.logical_patch().code_parameter(), qubit);
+        if estimates.toffoli_factory_part().is_some() {
+            extrapolations.extend(self.factory_extrapolations(qubit));
+        }
+        extrapolations
+    }
+
+    /// Assumptions of the phase-flip fit outside of the ranges, for the code
+    /// parameter `parameter` of `qubit`.
+    fn code_extrapolations(
+        &self,
+        parameter: &CodeParameter,
+        qubit: &CatQubit,
+    ) -> Vec<Extrapolation> {
+        let mut extrapolations = vec![];
+        let (min, max) = self.distance;
+        if !(min..=max).contains(&parameter.distance()) {
+            extrapolations.push(Extrapolation {
+                assumption: "code distance",
+                value: parameter.distance().to_string(),
+                range: format!("[{min}, {max}] (phase-flip fit)"),
+            });
+        }
+        let (min, max) = self.alpha_sq;
+        if !(min..=max).contains(&parameter.alpha_sq()) {
+            extrapolations.push(Extrapolation {
+                assumption: "|α|²",
+                value: parameter.alpha_sq().to_string(),
+                range: format!("[{min}, {max}] (phase-flip fit)"),
+            });
+        }
+        let (min, max) = self.k1_k2;
+        if !(min..=max).contains(&qubit.effective_k1_k2()) {
+            extrapolations.push(Extrapolation {
+                assumption: "effective κ₁/κ₂",
+                value: format!("{:e}", qubit.effective_k1_k2()),
+                range: format!("[{min:e}, {max:e}] (phase-flip fit)"),
+            });
+        }
+        extrapolations
+    }
+
+    /// Assumptions of the precomputed factories outside of the ranges, for
+    /// `qubit`.
+    fn factory_extrapolations(&self, qubit: &CatQubit) -> Vec<Extrapolation> {
+        let mut extrapolations = vec![];
+        let deviation = (qubit.effective_k1_k2() / REFERENCE_K1_K2 - 1.0).abs();
+        if deviation > self.factory_k1_k2_tolerance {
+            extrapolations.push(Extrapolation {
+                assumption: "factory κ₁/κ₂",
+                value: format!("{:e}", qubit.effective_k1_k2()),
+                range: format!("{REFERENCE_K1_K2:e} (precomputed factories)"),
+            });
+        }
+        if qubit.thermal_photons() > 0.0 {
+            extrapolations.push(Extrapolation {
+                assumption: "factory thermal photons",
+                value: qubit.thermal_photons().to_string(),
+                range: "0 (precomputed factories)".into(),
+            });
+        }
+        if qubit.factory_cx() != CxImplementation::Adiabatic {
+            extrapolations.push(Extrapolation {
+                assumption: "factory CX",
+                value: qubit.factory_cx().to_string(),
+                range: "adiabatic (precomputed factories)".into(),
+            });
+        }
+        extrapolations
+    }
+}
+
+/// Fail, listing the extrapolated assumptions, if `estimates` relies on any
+/// (as found by [`AliceAndBobEstimates::with_validity_check`]).
+pub fn ensure_valid(estimates: &AliceAndBobEstimates) -> Result<(), String> {
+    reject(estimates.extrapolations())
+}
+
+/// Fail, listing them, if there are `extrapolations`.
+fn reject(extrapolations: &[Extrapolation]) -> Result<(), String> {
+    if extrapolations.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "estimate relies on extrapolated models: {}",
+        extrapolations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reject, Extrapolation, ValidityRange};
+    use crate::{code::CodeParameter, CatQubit, CxImplementation};
+
+    fn assumptions(extrapolations: &[Extrapolation]) -> Vec<&'static str> {
+        extrapolations.iter().map(|e| e.assumption).collect()
+    }
+
+    #[test]
+    fn parameters_of_the_article_are_in_range() {
+        let range = ValidityRange::default();
+        let qubit = CatQubit::new();
+        let parameter = CodeParameter::new(11, 19.0);
+        assert!(range.code_extrapolations(&parameter, &qubit).is_empty());
+        assert!(range.factory_extrapolations(&qubit).is_empty());
+    }
+
+    #[test]
+    fn code_parameters_outside_of_the_fit_are_extrapolated() {
+        let range = ValidityRange::default();
+        let qubit = CatQubit::new().with_k1_k2(1e-7);
+        let extrapolations = range.code_extrapolations(&CodeParameter::new(31, 1.0), &qubit);
+        assert_eq!(
+            assumptions(&extrapolations),
+            ["code distance", "|α|²", "effective κ₁/κ₂"]
+        );
+        assert_eq!(
+            extrapolations[0].to_string(),
+            "code distance = 31 outside of [3, 25] (phase-flip fit)"
+        );
+    }
+
+    #[test]
+    fn factories_are_extrapolated_away_from_their_simulation() {
+        let range = ValidityRange::default();
+        let qubit = CatQubit::new()
+            .with_k1_k2(1e-4)
+            .with_thermal_photons(0.01)
+            .with_cx_implementation(CxImplementation::Fast);
+        assert_eq!(
+            assumptions(&range.factory_extrapolations(&qubit)),
+            ["factory κ₁/κ₂", "factory thermal photons", "factory CX"]
+        );
+    }
+
+    #[test]
+    fn extrapolations_are_rejected_together() {
+        assert_eq!(reject(&[]), Ok(()));
+        let extrapolation = |assumption| Extrapolation {
+            assumption,
+            value: "1".into(),
+            range: "0".into(),
+        };
+        assert_eq!(
+            reject(&[extrapolation("a"), extrapolation("b")]),
+            Err(
+                "estimate relies on extrapolated models: a = 1 outside of 0; b = 1 outside of 0"
+                    .into()
+            )
+        );
+    }
+}
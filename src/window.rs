@@ -0,0 +1,170 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Choice of the window size of the elliptic curve discrete logarithm.
+//!
+//! Larger windows divide the number of gates of the modular exponentiation
+//! (see [`ecc_counts`]) but add logical qubits, and the best
+//! trade-off depends on the qubits and the error budget. A [`WindowSweep`]
+//! estimates the whole algorithm for each window size of a range, and gives
+//! the window minimizing the runtime or the physical qubits
+//! ([`WindowObjective`]), with the results of the sweep as a table.
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+use resource_estimator::estimates::ErrorBudget;
+
+use crate::{
+    scan::{self, Resources},
+    units::format_thousands,
+    CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+
+/// Window sizes swept by default, around the ones of arXiv:2302.06639
+/// (Table IV, p. 37).
+pub const WINDOW_SIZES: RangeInclusive<u64> = 2..=30;
+
+/// Logical counts of the elliptic curve discrete logarithm of `bit_size` bits,
+/// with windows of `window_size` bits, as in arXiv:2302.06639.
+#[allow(clippy::similar_names)]
+pub fn ecc_counts(bit_size: u64, window_size: u64) -> LogicalCounts {
+    // Number of qubits for discrete log computation, arXiv:2302.06639 (p. 22, app C.11)
+    let qubit_count = 9 * bit_size + window_size + 4;
+    // Asymptotic gate counts, arXiv:2302.06639 (p. 21, app C.10)
+    let cx_count = (448 * bit_size.pow(3)).div_ceil(window_size);
+    let ccx_count = (348 * bit_size.pow(3)).div_ceil(window_size);
+
+    LogicalCounts::new(qubit_count, cx_count, ccx_count)
+}
+
+/// Quantity minimized by the choice of the window size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowObjective {
+    /// Shortest runtime.
+    #[default]
+    Runtime,
+    /// Fewest physical qubits.
+    Qubits,
+}
+
+/// Resources of the algorithm with one window size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowPoint {
+    /// Window size of the modular exponentiation.
+    pub window_size: u64,
+    /// Physical qubits and runtime in ns, or why the algorithm can't be
+    /// estimated with this window size.
+    pub resources: Resources,
+}
+
+/// Resources of the elliptic curve discrete logarithm for a range of window
+/// sizes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WindowSweep {
+    /// Bit size of the elliptic curve.
+    pub bit_size: u64,
+    /// Resources for each window size, in increasing order.
+    pub points: Vec<WindowPoint>,
+}
+
+impl WindowSweep {
+    /// Estimates of the discrete logarithm of `bit_size` bits for each of the
+    /// (nonzero) `window_sizes`.
+    #[must_use]
+    pub fn run(
+        bit_size: u64,
+        window_sizes: impl IntoIterator<Item = u64>,
+        qubit: &CatQubit,
+        code: &RepetitionCode,
+        builder: &ToffoliBuilder,
+        budget: &ErrorBudget,
+    ) -> Self {
+        let points = window_sizes
+            .into_iter()
+            .filter(|&window_size| window_size > 0)
+            .map(|window_size| {
+                let counts = ecc_counts(bit_size, window_size);
+                let resources = scan::resources(qubit, code, builder, counts, budget);
+                tracing::debug!(window_size, ?resources, "window size");
+                WindowPoint {
+                    window_size,
+                    resources,
+                }
+            })
+            .collect();
+        Self { bit_size, points }
+    }
+
+    /// Window size minimizing `objective`, the other quantity breaking ties,
+    /// [`None`] if no window size can be estimated.
+    #[must_use]
+    pub fn best(&self, objective: WindowObjective) -> Option<&WindowPoint> {
+        self.points
+            .iter()
+            .filter_map(|point| {
+                let &(qubits, runtime) = point.resources.as_ref().ok()?;
+                let key = match objective {
+                    WindowObjective::Runtime => (runtime.as_ns(), qubits),
+                    WindowObjective::Qubits => (qubits, runtime.as_ns()),
+                };
+                Some((key, point))
+            })
+            .min_by_key(|(key, _)| *key)
+            .map(|(_, point)| point)
+    }
+}
+
+impl Display for WindowSweep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        scan::write_table(
+            f,
+            &format!("{:<8}", "window"),
+            &[("physical qubits", 18), ("runtime", 16)],
+            self.points.iter().map(|point| {
+                let cells = point
+                    .resources
+                    .as_ref()
+                    .map(|(qubits, runtime)| vec![format_thousands(*qubits), runtime.to_string()]);
+                (format!("{:<8}", point.window_size), cells)
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ecc_counts, WindowObjective, WindowPoint, WindowSweep};
+    use crate::units::TimeSpan;
+
+    #[test]
+    fn windows_divide_the_gates() {
+        let (small, large) = (ecc_counts(256, 4), ecc_counts(256, 16));
+        assert_eq!(small.ccx_count(), 4 * large.ccx_count());
+        assert_eq!(large.qubit_count(), small.qubit_count() + 12);
+    }
+
+    #[test]
+    fn best_window_minimizes_the_objective() {
+        let point = |window_size, resources| WindowPoint {
+            window_size,
+            resources,
+        };
+        let sweep = WindowSweep {
+            bit_size: 256,
+            points: vec![
+                point(2, Err("too many gates".into())),
+                point(8, Ok((100_000, TimeSpan::from_ns(20)))),
+                point(16, Ok((150_000, TimeSpan::from_ns(10)))),
+                point(24, Ok((150_000, TimeSpan::from_ns(10)))),
+            ],
+        };
+        let best = |objective| sweep.best(objective).map(|point| point.window_size);
+        assert_eq!(best(WindowObjective::Qubits), Some(8));
+        // ties broken by the first window
+        assert_eq!(best(WindowObjective::Runtime), Some(16));
+        assert_eq!(WindowSweep::default().best(WindowObjective::Runtime), None);
+        assert!(sweep
+            .to_string()
+            .contains("2         unreachable: too many gates"));
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Control and readout lines of the processor.
+//!
+//! A [`WiringModel`] estimates the number of input/output lines going into
+//! the cryostat from the number of physical qubits: pump lines (two-photon
+//! dissipation of the cats) and drive lines (gates), each a number of lines
+//! per physical qubit, and readout lines, several qubits being multiplexed on
+//! each line. The default coefficients (one pump and one drive line per
+//! qubit, 8 qubits per readout line) are assumptions, to be adapted to the
+//! hardware.
+
+use std::{fmt::Display, str::FromStr};
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::report::ReportSection;
+
+/// Coefficients of the input/output lines per physical qubit.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WiringModel {
+    /// Pump lines per physical qubit.
+    pub pumps_per_qubit: f64,
+    /// Drive lines per physical qubit.
+    pub drives_per_qubit: f64,
+    /// Physical qubits read out through each readout line.
+    pub readout_multiplexing: u64,
+}
+
+impl WiringModel {
+    /// Model with the given coefficients, which must be non-negative (and the
+    /// multiplexing factor positive).
+    pub fn new(
+        pumps_per_qubit: f64,
+        drives_per_qubit: f64,
+        readout_multiplexing: u64,
+    ) -> Result<Self, String> {
+        for (name, value) in [("pump", pumps_per_qubit), ("drive", drives_per_qubit)] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(format!(
+                    "{name} lines per qubit {value} must be a non-negative number"
+                ));
+            }
+        }
+        if readout_multiplexing == 0 {
+            return Err("readout multiplexing must be at least 1 qubit per line".into());
+        }
+        Ok(Self {
+            pumps_per_qubit,
+            drives_per_qubit,
+            readout_multiplexing,
+        })
+    }
+
+    /// Lines needed by `physical_qubits` physical qubits.
+    #[must_use]
+    pub fn lines(&self, physical_qubits: u64) -> WiringCount {
+        let lines = |per_qubit: f64| {
+            (physical_qubits.to_f64().unwrap_or(f64::MAX) * per_qubit)
+                .ceil()
+                .to_u64()
+                .unwrap_or(u64::MAX)
+        };
+        WiringCount {
+            pump_lines: lines(self.pumps_per_qubit),
+            drive_lines: lines(self.drives_per_qubit),
+            readout_lines: physical_qubits.div_ceil(self.readout_multiplexing),
+        }
+    }
+}
+
+impl Default for WiringModel {
+    /// One pump and one drive line per qubit, 8 qubits per readout line.
+    fn default() -> Self {
+        Self {
+            pumps_per_qubit: 1.0,
+            drives_per_qubit: 1.0,
+            readout_multiplexing: 8,
+        }
+    }
+}
+
+impl Display for WiringModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{},{},{}",
+            self.pumps_per_qubit, self.drives_per_qubit, self.readout_multiplexing
+        )
+    }
+}
+
+impl FromStr for WiringModel {
+    type Err = String;
+
+    /// Parse `default`, or `PUMPS,DRIVES,MULTIPLEXING` (e.g. `1,1,8`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "default" {
+            return Ok(Self::default());
+        }
+        let invalid =
+            || format!("'{s}' is not a wiring model (expected PUMPS,DRIVES,MULTIPLEXING)");
+        let [pumps, drives, multiplexing] = s.split(',').collect::<Vec<_>>()[..] else {
+            return Err(invalid());
+        };
+        Self::new(
+            pumps.trim().parse().map_err(|_| invalid())?,
+            drives.trim().parse().map_err(|_| invalid())?,
+            multiplexing.trim().parse().map_err(|_| invalid())?,
+        )
+    }
+}
+
+/// Input/output lines of a processor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WiringCount {
+    /// Pump lines.
+    pub pump_lines: u64,
+    /// Drive lines.
+    pub drive_lines: u64,
+    /// Readout lines.
+    pub readout_lines: u64,
+}
+
+impl WiringCount {
+    /// Total number of lines.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.pump_lines + self.drive_lines + self.readout_lines
+    }
+
+    #[must_use]
+    /// Lines as (label, formatted value) pairs.
+    pub fn report_section(&self) -> ReportSection {
+        vec![(
+            "I/O lines",
+            format!(
+                "{} ({} pump, {} drive, {} readout)",
+                self.total(),
+                self.pump_lines,
+                self.drive_lines,
+                self.readout_lines
+            ),
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WiringCount, WiringModel};
+
+    #[test]
+    fn lines_are_rounded_up() {
+        let model = WiringModel::new(1.0, 0.5, 8).expect("valid coefficients");
+        let lines = model.lines(1001);
+        assert_eq!(
+            lines,
+            WiringCount {
+                pump_lines: 1001,
+                drive_lines: 501,
+                readout_lines: 126,
+            }
+        );
+        assert_eq!(lines.total(), 1628);
+    }
+
+    #[test]
+    fn models_are_parsed_back() {
+        for model in [
+            WiringModel::default(),
+            WiringModel::new(2.0, 0.5, 4).expect("valid coefficients"),
+        ] {
+            assert_eq!(model.to_string().parse::<WiringModel>(), Ok(model));
+        }
+        assert_eq!("default".parse::<WiringModel>(), Ok(WiringModel::default()));
+    }
+
+    #[test]
+    fn invalid_models_are_rejected() {
+        for model in ["1,1", "1,1,0", "-1,1,8", "1,NaN,8", "1,1,8,8", "one,1,8"] {
+            assert!(model.parse::<WiringModel>().is_err(), "{model}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Production statistics of the factories of an estimate: rates, duty cycle
+//! and runtime of the factories, consistent with each other.
+
+use qsharp_alice_bob_resource_estimator::{testing, LogicalCounts, OutputFormat};
+use resource_estimator::estimates::Factory;
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn factories_run_for_the_consumed_states() {
+    let estimates = testing::estimate(testing::ecc_256_counts(), testing::ecc_256_budget())
+        .expect("the input can be estimated");
+    let part = estimates.factory_parts()[0]
+        .as_ref()
+        .expect("the estimate has factories");
+    let preparation = part.factory().preparation_time().as_ns();
+
+    let total = estimates
+        .total_factory_runtime()
+        .expect("the estimate has factories");
+    assert_eq!(total.as_ns(), part.copies() * part.runs() * preparation);
+    assert_eq!(
+        estimates.states_per_factory(),
+        Some(part.runs() * part.factory().num_output_states())
+    );
+
+    let duty_cycle = estimates
+        .factory_duty_cycle()
+        .expect("the estimate has factories");
+    let busy = estimates.toffoli_states() as f64 * preparation as f64;
+    let available = part.copies() as f64 * estimates.runtime_duration().as_ns_f64();
+    assert!(
+        testing::close(duty_cycle, (busy / available).min(1.0)),
+        "{duty_cycle}"
+    );
+    assert!(duty_cycle > 0.0 && duty_cycle <= 1.0, "{duty_cycle}");
+}
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn magic_state_rate_matches_the_duty_cycle() {
+    let estimates = testing::estimate(testing::ecc_256_counts(), testing::ecc_256_budget())
+        .expect("the input can be estimated");
+    let copies = estimates.factory_parts()[0]
+        .as_ref()
+        .expect("the estimate has factories")
+        .copies();
+    let rate = estimates
+        .magic_state_rate()
+        .expect("the estimate consumes states");
+    let runtime = estimates.runtime_duration().as_seconds();
+    assert!(testing::close(
+        rate.required,
+        estimates.toffoli_states() as f64 / runtime
+    ));
+    let per_factory = estimates
+        .factory_rate()
+        .expect("the estimate has factories");
+    assert!(testing::close(rate.provided, copies as f64 * per_factory));
+    // the factories keep up, busy the inverse of the margin
+    assert!(rate.margin() >= 1.0, "{}", rate.margin());
+    let duty_cycle = estimates
+        .factory_duty_cycle()
+        .expect("the estimate has factories");
+    assert!(
+        testing::close(duty_cycle * rate.margin(), 1.0),
+        "{duty_cycle}"
+    );
+}
+
+#[test]
+fn utilization_of_the_factories_is_their_duty_cycle() {
+    let estimates = testing::estimate(testing::ecc_256_counts(), testing::ecc_256_budget())
+        .expect("the input can be estimated")
+        .with_utilization();
+    let utilization = estimates.utilization().expect("asked for");
+    let factories = &utilization.regions[2];
+    assert_eq!(factories.region, "factories");
+    assert!(testing::close(
+        factories.active_fraction,
+        estimates
+            .factory_duty_cycle()
+            .expect("the estimate has factories")
+    ));
+    assert!(utilization
+        .regions
+        .iter()
+        .all(|region| (0.0..=1.0).contains(&region.active_fraction)));
+}
+
+#[test]
+fn estimates_without_toffoli_have_no_production() {
+    let estimates = testing::estimate(
+        LogicalCounts::new(100, 10_000, 0),
+        testing::ecc_256_budget(),
+    )
+    .expect("the input can be estimated");
+    assert_eq!(estimates.toffoli_states(), 0);
+    assert!(estimates.magic_state_rate().is_none());
+    assert!(estimates.toffoli_overproduction().is_none());
+}
+
+#[test]
+fn estimates_without_toffoli_are_rendered() {
+    let estimates = testing::estimate(
+        LogicalCounts::new(100, 10_000, 0),
+        testing::ecc_256_budget(),
+    )
+    .expect("the input can be estimated");
+    assert!(estimates.to_string().contains("factories distance"));
+    for format in [
+        OutputFormat::Text,
+        OutputFormat::Markdown,
+        OutputFormat::Latex,
+        OutputFormat::Json,
+    ] {
+        let rendered = format.render(std::slice::from_ref(&estimates));
+        assert!(!rendered.is_empty(), "{format:?}");
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Frontier streamed point by point: each point is faster than the previous
+//! one, and the build resumes after an interrupted one.
+
+use std::rc::Rc;
+
+use qsharp_alice_bob_resource_estimator::{
+    frontier::{Estimation, FrontierPoints},
+    CatQubit, LogicalCounts, RepetitionCode, ToffoliBuilder,
+};
+use resource_estimator::estimates::{ErrorBudget, PhysicalResourceEstimation};
+
+fn estimation() -> Estimation {
+    PhysicalResourceEstimation::new(
+        RepetitionCode::new(),
+        Rc::new(CatQubit::new()),
+        ToffoliBuilder::default(),
+        Rc::new(LogicalCounts::new(10, 1000, 100)),
+        ErrorBudget::new(1e-3, 1e-3, 0.0),
+    )
+}
+
+/// (physical qubits, runtime) of the points of `points`.
+fn trade_offs(points: FrontierPoints) -> Vec<(u64, u64)> {
+    points
+        .map(|point| point.expect("the counts can be estimated"))
+        .map(|result| (result.physical_qubits(), result.runtime()))
+        .collect()
+}
+
+#[test]
+fn points_trade_qubits_for_runtime() {
+    let points = trade_offs(FrontierPoints::new(estimation()));
+    assert!(!points.is_empty());
+    for pair in points.windows(2) {
+        let [(qubits, runtime), (next_qubits, next_runtime)] = pair else {
+            unreachable!("windows of 2 points");
+        };
+        assert!(next_runtime < runtime, "{pair:?}");
+        assert!(next_qubits >= qubits, "{pair:?}");
+    }
+}
+
+#[test]
+fn resumed_frontiers_continue_after_the_last_point() {
+    let points = trade_offs(FrontierPoints::new(estimation()));
+    let (_, first_runtime) = points[0];
+    let resumed = trade_offs(FrontierPoints::resume_after(estimation(), 1, first_runtime));
+    assert_eq!(resumed, points[1..]);
+}
+
+#[test]
+fn impossible_estimations_yield_one_error() {
+    let mut estimation = estimation();
+    estimation.set_max_physical_qubits(1);
+    let mut points = FrontierPoints::new(estimation);
+    assert!(points.next().is_some_and(|point| point.is_err()));
+    assert!(points.next().is_none());
+}
@@ -0,0 +1,124 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Golden-reference tests: the canonical inputs of the `testing` module must
+//! reproduce the reference numbers.
+
+use qsharp_alice_bob_resource_estimator::{
+    schema::ResultDocument,
+    testing::{self, Reference},
+};
+use serde_json::{json, Value};
+
+#[test]
+fn ecc_256_matches_the_article() {
+    let estimates = testing::estimate(testing::ecc_256_counts(), testing::ecc_256_budget())
+        .expect("the input can be estimated");
+    assert_eq!(testing::ECC_256_REFERENCE.check(&estimates), Ok(()));
+}
+
+#[test]
+fn ecc_counts_follow_the_article() {
+    let counts = testing::ecc_256_counts();
+    assert_eq!(counts.qubit_count(), 9 * 256 + 18 + 4);
+    assert_eq!(counts.cx_count(), (448 * 256_u64.pow(3)).div_ceil(18));
+    assert_eq!(counts.ccx_count(), (348 * 256_u64.pow(3)).div_ceil(18));
+}
+
+#[test]
+fn reference_rejects_deviations_beyond_the_tolerance() {
+    let estimates = testing::estimate(testing::ecc_256_counts(), testing::ecc_256_budget())
+        .expect("the input can be estimated");
+    let strict = Reference {
+        physical_qubits: 2 * estimates.physical_qubits(),
+        ..testing::ECC_256_REFERENCE
+    };
+    let err = strict
+        .check(&estimates)
+        .expect_err("twice the physical qubits are out of the tolerance");
+    assert!(err.contains("physical qubits"), "{err}");
+}
+
+/// Replace the numbers of `value` by 0, to compare the structure of documents.
+fn redact(value: &mut Value) {
+    match value {
+        Value::Number(number) => *number = 0.into(),
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        Value::Object(fields) => fields.values_mut().for_each(redact),
+        Value::Null | Value::Bool(_) | Value::String(_) => {}
+    }
+}
+
+#[test]
+fn result_document_keeps_its_schema() {
+    let estimates = testing::estimate(testing::ecc_256_counts(), testing::ecc_256_budget())
+        .expect("the input can be estimated");
+    let mut document =
+        serde_json::to_value(ResultDocument::new(&[estimates])).expect("document is serialized");
+    redact(&mut document["estimates"]);
+
+    // changing this document requires to increment `SCHEMA_VERSION`, unless
+    // only optional fields are added
+    assert_eq!(
+        document,
+        json!({
+            "schema_version": 1,
+            "estimates": [{
+                "physical_qubits": 0,
+                "physical_qubits_for_factories": 0,
+                "layout_physical_qubits": 0,
+                "runtime_ns": 0,
+                "logical_cycles": 0,
+                "cycle_time_ns": 0,
+                "logical_clock_rate_hz": 0,
+                "physical_cx": 0,
+                "physical_measurements": 0,
+                "logical_qubits": 0,
+                "code_parameter": { "distance": 0, "alpha_sq": 0 },
+                "factories": {
+                    "factory": {
+                        "code_distance": 0,
+                        "alpha_sq": 0,
+                        "error_probability": 0,
+                        "acceptance_probability": 0,
+                        "steps": 0,
+                        "cx_implementation": "adiabatic"
+                    },
+                    "copies": 0,
+                    "runs": 0,
+                    "overproduction": { "produced": 0, "consumed": 0 }
+                },
+                "errors": {
+                    "total": 0,
+                    "computation": { "phase_flip": 0, "bit_flip": 0 },
+                    "factories": { "phase_flip": 0, "bit_flip": 0 },
+                    "budget": {
+                        "topological_budget": 0,
+                        "topological_error": 0,
+                        "magic_budget": 0,
+                        "magic_error": 0
+                    }
+                },
+                "counts": {
+                    "qubit_count": 0,
+                    "cx_count": 0,
+                    "ccx_count": 0,
+                    "and_count": 0,
+                    "measurement_count": 0,
+                    "feed_forward_count": 0,
+                    "rotation_t_count": 0
+                }
+            }]
+        })
+    );
+}
+
+#[cfg(feature = "qsharp")]
+#[test]
+fn adder_is_estimated() {
+    let counts = testing::adder_counts().expect("the bundled adder can be counted");
+    assert!(counts.ccx_count() > 0);
+    let estimates =
+        testing::estimate(counts, testing::adder_budget()).expect("the input can be estimated");
+    assert!(estimates.physical_qubits() > 0);
+}
@@ -0,0 +1,83 @@
+// Copyright (c) Alice & Bob.
+// Licensed under the MIT License.
+
+//! Physical operations of an estimate: CX gates and measurements of the
+//! repetition code patches over the whole computation, and the syndrome data
+//! they send to the decoder.
+
+use qsharp_alice_bob_resource_estimator::{
+    code::schedule::CycleSchedule, testing, AliceAndBobEstimates, LogicalCounts,
+};
+use resource_estimator::estimates::ErrorBudget;
+
+/// 4 qubits and 10 CX gates: 4 + 3 logical qubits with the horizontal
+/// routing, 22 logical cycles of 2.2 cycles per CX, and a required logical
+/// error rate of 1e-3 / (7 × 22) reached at d = 3 (|α|² = 8), i.e. 3 rounds of
+/// 500 ns per cycle.
+fn small_estimate() -> AliceAndBobEstimates {
+    testing::estimate(
+        LogicalCounts::new(4, 10, 0),
+        ErrorBudget::new(1e-3, 0.0, 0.0),
+    )
+    .expect("the input can be estimated")
+}
+
+#[test]
+fn small_computation_measures_its_patches_at_each_round() {
+    let estimates = small_estimate();
+    assert_eq!(estimates.logical_patch().code_parameter().distance(), 3);
+    assert_eq!(estimates.logical_cycles(), 22);
+    assert_eq!(estimates.rounds(), 3);
+
+    // 7 patches of 5 qubits, and 2(3 × 7 − 1) vertical routing qubits
+    assert_eq!(estimates.physical_qubits(), 75);
+    assert_eq!(estimates.compute_patches(), 15);
+    assert_eq!(estimates.factory_patches(), 0);
+
+    // 22 cycles × 3 rounds × 15 patches × 2 ancillas
+    assert_eq!(estimates.physical_measurement_count(), 1980);
+    assert_eq!(estimates.physical_cx_count(), 3960);
+}
+
+#[test]
+fn factories_are_counted_at_their_distance() {
+    let estimates = testing::estimate(testing::ecc_256_counts(), testing::ecc_256_budget())
+        .expect("the input can be estimated");
+    let copies = estimates
+        .factory_parts()
+        .first()
+        .and_then(Option::as_ref)
+        .expect("the reference input has factories")
+        .copies();
+
+    // 4 logical qubits and 1 routing qubit per factory
+    assert_eq!(estimates.factory_patches(), 5 * copies);
+    assert!(
+        estimates.physical_measurement_count()
+            > estimates.logical_cycles() * estimates.rounds() * estimates.compute_patches()
+    );
+}
+
+#[test]
+fn each_measurement_follows_the_cx_layers() {
+    let estimates = testing::estimate(testing::ecc_256_counts(), testing::ecc_256_budget())
+        .expect("the input can be estimated");
+    assert_eq!(
+        estimates.physical_cx_count(),
+        CycleSchedule::CX_LAYERS * estimates.physical_measurement_count()
+    );
+}
+
+#[test]
+fn syndrome_data_follows_the_measurements() {
+    let estimates = small_estimate().with_syndrome_data(2);
+    let syndrome = estimates
+        .syndrome_data()
+        .expect("the syndrome data is computed");
+
+    // 2 bits for each of the 1980 measurements, during 22 cycles of 1.5 µs
+    assert!(testing::close(syndrome.total_bytes, 495.0));
+    assert!(testing::close(syndrome.total_bandwidth_bps, 3960.0 / 33e-6));
+    // a block measures 2 ancillas at each of the 66 rounds
+    assert!(testing::close(syndrome.block_bandwidth_bps, 264.0 / 33e-6));
+}